@@ -0,0 +1,10 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS join_tickets (
+  id TEXT PRIMARY KEY,
+  session_id TEXT NOT NULL,
+  room_id uuid NOT NULL,
+  expiry_date TIMESTAMPTZ NOT NULL,
+
+  CONSTRAINT fk_session FOREIGN KEY(session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+  CONSTRAINT fk_room FOREIGN KEY(room_id) REFERENCES rooms(id) ON DELETE CASCADE
+);
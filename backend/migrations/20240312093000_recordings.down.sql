@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS recording_tracks;
+DROP TABLE IF EXISTS recordings;
+ALTER TABLE rooms DROP COLUMN IF EXISTS encrypted;
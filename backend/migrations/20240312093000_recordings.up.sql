@@ -0,0 +1,26 @@
+-- Add up migration script here
+ALTER TABLE rooms ADD COLUMN IF NOT EXISTS encrypted BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS recordings (
+  id uuid PRIMARY KEY,
+  room_id uuid NOT NULL,
+  started_by VARCHAR(50) NOT NULL,
+  started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  stopped_at TIMESTAMPTZ,
+
+  CONSTRAINT fk_room FOREIGN KEY(room_id) REFERENCES rooms(id) ON DELETE CASCADE
+);
+
+-- a room is recorded at most once at a time
+CREATE UNIQUE INDEX IF NOT EXISTS recordings_active_room ON recordings(room_id) WHERE stopped_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS recording_tracks (
+  id uuid PRIMARY KEY,
+  recording_id uuid NOT NULL,
+  username VARCHAR(50) NOT NULL,
+  kind VARCHAR(10) NOT NULL,
+  offset_ms BIGINT NOT NULL,
+  size BIGINT NOT NULL DEFAULT 0,
+
+  CONSTRAINT fk_recording FOREIGN KEY(recording_id) REFERENCES recordings(id) ON DELETE CASCADE
+);
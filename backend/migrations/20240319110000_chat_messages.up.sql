@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS chat_messages (
+  id uuid PRIMARY KEY,
+  room_id uuid NOT NULL,
+  username VARCHAR(50) NOT NULL,
+  text TEXT NOT NULL,
+  sent_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  CONSTRAINT fk_room FOREIGN KEY(room_id) REFERENCES rooms(id) ON DELETE CASCADE
+);
+
+-- the history of a room is read newest first
+CREATE INDEX IF NOT EXISTS chat_messages_room_sent_at ON chat_messages(room_id, sent_at DESC, id DESC);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE rooms DROP COLUMN IF EXISTS transport_started_at;
+ALTER TABLE rooms DROP COLUMN IF EXISTS count_in;
+ALTER TABLE rooms DROP COLUMN IF EXISTS beat_unit;
+ALTER TABLE rooms DROP COLUMN IF EXISTS beats_per_bar;
+ALTER TABLE rooms DROP COLUMN IF EXISTS tempo;
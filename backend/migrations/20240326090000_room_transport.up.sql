@@ -0,0 +1,7 @@
+-- Add up migration script here
+ALTER TABLE rooms ADD COLUMN IF NOT EXISTS tempo DOUBLE PRECISION NOT NULL DEFAULT 120;
+ALTER TABLE rooms ADD COLUMN IF NOT EXISTS beats_per_bar INTEGER NOT NULL DEFAULT 4;
+ALTER TABLE rooms ADD COLUMN IF NOT EXISTS beat_unit INTEGER NOT NULL DEFAULT 4;
+ALTER TABLE rooms ADD COLUMN IF NOT EXISTS count_in INTEGER NOT NULL DEFAULT 1;
+-- first downbeat after the count-in, NULL while the transport is stopped
+ALTER TABLE rooms ADD COLUMN IF NOT EXISTS transport_started_at TIMESTAMPTZ;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS rooms_name;
+DROP INDEX IF EXISTS rooms_created_at;
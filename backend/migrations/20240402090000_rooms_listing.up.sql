@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- rooms are listed newest first or by name
+CREATE INDEX IF NOT EXISTS rooms_created_at ON rooms(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS rooms_name ON rooms(lower(name), id);
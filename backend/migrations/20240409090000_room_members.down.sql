@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS room_invites;
+DROP TABLE IF EXISTS room_members;
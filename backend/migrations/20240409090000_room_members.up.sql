@@ -0,0 +1,32 @@
+-- Add up migration script here
+-- users let into a private room besides its owner
+CREATE TABLE IF NOT EXISTS room_members (
+  room_id uuid NOT NULL,
+  username VARCHAR(50) NOT NULL,
+  added_by VARCHAR(50) NOT NULL,
+  added_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY (room_id, username),
+  CONSTRAINT fk_room FOREIGN KEY(room_id) REFERENCES rooms(id) ON DELETE CASCADE,
+  CONSTRAINT fk_user FOREIGN KEY(username) REFERENCES users(username) ON DELETE CASCADE
+);
+
+-- the rooms a user is a member of are listed along with the public ones
+CREATE INDEX IF NOT EXISTS room_members_username ON room_members(username);
+
+-- links that make whoever opens them a member of the room
+CREATE TABLE IF NOT EXISTS room_invites (
+  id uuid PRIMARY KEY,
+  room_id uuid NOT NULL,
+  token VARCHAR(64) NOT NULL UNIQUE,
+  created_by VARCHAR(50) NOT NULL,
+  -- address the link was sent to, NULL for the links shared by the owner
+  email VARCHAR(255),
+  -- NULL when the link can be used any number of times
+  max_uses INTEGER,
+  uses INTEGER NOT NULL DEFAULT 0,
+  expires_at TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  CONSTRAINT fk_room FOREIGN KEY(room_id) REFERENCES rooms(id) ON DELETE CASCADE
+);
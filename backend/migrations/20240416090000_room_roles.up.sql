@@ -0,0 +1,13 @@
+-- Add up migration script here
+-- role of the participants of a room, the owner is the one of the room and anyone else without a
+-- role plays
+CREATE TABLE IF NOT EXISTS room_roles (
+  room_id uuid NOT NULL,
+  username VARCHAR(50) NOT NULL,
+  role VARCHAR(16) NOT NULL CHECK (role IN ('listener', 'musician', 'moderator')),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY (room_id, username),
+  CONSTRAINT fk_room FOREIGN KEY(room_id) REFERENCES rooms(id) ON DELETE CASCADE,
+  CONSTRAINT fk_user FOREIGN KEY(username) REFERENCES users(username) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS calendar_feeds;
+DROP TABLE IF EXISTS schedule_rsvps;
+DROP TABLE IF EXISTS room_schedules;
@@ -0,0 +1,44 @@
+-- Add up migration script here
+-- sessions planned in a room, a recurring one repeats with the same length until `repeat_until`
+CREATE TABLE IF NOT EXISTS room_schedules (
+  id uuid PRIMARY KEY,
+  room_id uuid NOT NULL,
+  title VARCHAR(100) NOT NULL,
+  starts_at TIMESTAMPTZ NOT NULL,
+  ends_at TIMESTAMPTZ NOT NULL,
+  -- NULL for a session that does not repeat
+  recurrence VARCHAR(8) CHECK (recurrence IN ('daily', 'weekly')),
+  repeat_until TIMESTAMPTZ,
+  -- the room is open only while one of its sessions with the flag is in progress
+  opens_room BOOLEAN NOT NULL DEFAULT FALSE,
+  -- start of the last occurrence the attendees were reminded of
+  reminded_for TIMESTAMPTZ,
+  created_by VARCHAR(50) NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  CHECK (ends_at > starts_at),
+  CONSTRAINT fk_room FOREIGN KEY(room_id) REFERENCES rooms(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS room_schedules_room_id ON room_schedules(room_id);
+
+-- whether the users will attend a planned session, all of its occurrences alike
+CREATE TABLE IF NOT EXISTS schedule_rsvps (
+  schedule_id uuid NOT NULL,
+  username VARCHAR(50) NOT NULL,
+  response VARCHAR(8) NOT NULL CHECK (response IN ('yes', 'no', 'maybe')),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY (schedule_id, username),
+  CONSTRAINT fk_schedule FOREIGN KEY(schedule_id) REFERENCES room_schedules(id) ON DELETE CASCADE,
+  CONSTRAINT fk_user FOREIGN KEY(username) REFERENCES users(username) ON DELETE CASCADE
+);
+
+-- secret of the calendar feed of a user, read by calendar apps without a session
+CREATE TABLE IF NOT EXISTS calendar_feeds (
+  username VARCHAR(50) PRIMARY KEY,
+  token VARCHAR(64) NOT NULL UNIQUE,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  CONSTRAINT fk_user FOREIGN KEY(username) REFERENCES users(username) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS sessions_user_id;
+
+ALTER TABLE sessions
+  DROP COLUMN IF EXISTS user_id,
+  DROP COLUMN IF EXISTS public_id,
+  DROP COLUMN IF EXISTS remember,
+  DROP COLUMN IF EXISTS user_agent,
+  DROP COLUMN IF EXISTS ip,
+  DROP COLUMN IF EXISTS created_at,
+  DROP COLUMN IF EXISTS last_seen_at;
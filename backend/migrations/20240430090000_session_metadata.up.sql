@@ -0,0 +1,27 @@
+-- Add up migration script here
+-- the sessions of the users that no longer exist cannot be resolved anyway
+DELETE FROM sessions s WHERE NOT EXISTS (
+  SELECT 1 FROM users u WHERE u.id::text = convert_from(s.data, 'UTF8')::jsonb ->> 'id'
+);
+
+ALTER TABLE sessions
+  ADD COLUMN IF NOT EXISTS user_id uuid REFERENCES users(id) ON DELETE CASCADE,
+  -- shown to the user in place of the token, which is the secret of the session
+  ADD COLUMN IF NOT EXISTS public_id uuid UNIQUE,
+  -- a remembered session outlives the browser and lasts longer without being used
+  ADD COLUMN IF NOT EXISTS remember BOOLEAN NOT NULL DEFAULT TRUE,
+  ADD COLUMN IF NOT EXISTS user_agent TEXT,
+  ADD COLUMN IF NOT EXISTS ip VARCHAR(45),
+  ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+UPDATE sessions SET
+  user_id = (convert_from(data, 'UTF8')::jsonb ->> 'id')::uuid,
+  public_id = md5(random()::text || id)::uuid
+WHERE user_id IS NULL OR public_id IS NULL;
+
+ALTER TABLE sessions
+  ALTER COLUMN user_id SET NOT NULL,
+  ALTER COLUMN public_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS sessions_user_id ON sessions(user_id);
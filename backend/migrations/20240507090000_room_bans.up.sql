@@ -0,0 +1,12 @@
+-- Add up migration script here
+-- users kicked out of a public room, kept out of it until the ban expires
+CREATE TABLE IF NOT EXISTS room_bans (
+  room_id uuid NOT NULL,
+  username VARCHAR(50) NOT NULL,
+  banned_by VARCHAR(50) NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL,
+
+  PRIMARY KEY (room_id, username),
+  CONSTRAINT fk_room FOREIGN KEY(room_id) REFERENCES rooms(id) ON DELETE CASCADE,
+  CONSTRAINT fk_user FOREIGN KEY(username) REFERENCES users(username) ON DELETE CASCADE
+);
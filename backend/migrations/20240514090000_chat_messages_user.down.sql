@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS chat_messages_username;
+
+ALTER TABLE chat_messages DROP CONSTRAINT IF EXISTS fk_user;
@@ -0,0 +1,20 @@
+-- Add up migration script here
+-- the relay used to send the messages with the spaces of the username replaced by `_`, they go
+-- back to the account when a single one matches
+UPDATE chat_messages c SET username = u.username
+FROM users u
+WHERE replace(u.username, ' ', '_') = c.username
+  AND NOT EXISTS (SELECT 1 FROM users o WHERE o.username = c.username)
+  AND (SELECT count(*) FROM users o WHERE replace(o.username, ' ', '_') = c.username) = 1;
+
+-- the messages of the accounts deleted before the constraint cannot be tied to anyone
+DELETE FROM chat_messages c WHERE NOT EXISTS (
+  SELECT 1 FROM users u WHERE u.username = c.username
+);
+
+ALTER TABLE chat_messages
+  DROP CONSTRAINT IF EXISTS fk_user,
+  ADD CONSTRAINT fk_user FOREIGN KEY(username) REFERENCES users(username) ON DELETE CASCADE;
+
+-- the messages of a user are exported and deleted along with its account
+CREATE INDEX IF NOT EXISTS chat_messages_username ON chat_messages(username);
@@ -1,6 +1,8 @@
 use std::env ;
+use std::net::IpAddr;
 
-use crate::service::email;
+use crate::service::{email, relay};
+use crate::web::limits;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -14,8 +16,20 @@ pub struct Config {
     pub session_key: String,
     pub listen_address: String,
     pub webtransport_address: String,
+    pub metrics_address: String,
     pub cert_path: String,
-    pub key_path: String
+    pub key_path: String,
+    pub relay_backend: String,
+    pub nats_url: Option<String>,
+    pub drain_period: u64,
+    pub recording_dir: String,
+    pub limit_datagrams: u32,
+    pub limit_streams: u32,
+    pub limit_bytes: u64,
+    pub max_stream_size: usize,
+    pub max_concurrent_streams: u32,
+    pub max_violations: u32,
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
 impl Config {
@@ -31,8 +45,41 @@ impl Config {
             smtp_from: env::var("RTJAM_SMTP_FROM")?,
             app_url: env::var("RTJAM_APP_URL")?,
             webtransport_address: env::var("RTJAM_WEBTRANSPORT_ADDRESS")?,
+            metrics_address: env::var("RTJAM_METRICS_ADDRESS")
+                .unwrap_or(String::from("127.0.0.1:9090")),
             cert_path: env::var("RTJAM_CERT_PATH")?,
             key_path: env::var("RTJAM_KEY_PATH")?,
+            relay_backend: env::var("RTJAM_RELAY_BACKEND").unwrap_or(String::from("nats")),
+            nats_url: env::var("RTJAM_NATS_URL").ok(),
+            drain_period: env::var("RTJAM_DRAIN_PERIOD")
+                .map(|period| period.parse::<u64>())
+                .unwrap_or(Ok(10))?,
+            recording_dir: env::var("RTJAM_RECORDING_DIR").unwrap_or(String::from("recordings")),
+            limit_datagrams: env::var("RTJAM_LIMIT_DATAGRAMS")
+                .map(|limit| limit.parse::<u32>())
+                .unwrap_or(Ok(500))?,
+            limit_streams: env::var("RTJAM_LIMIT_STREAMS")
+                .map(|limit| limit.parse::<u32>())
+                .unwrap_or(Ok(200))?,
+            limit_bytes: env::var("RTJAM_LIMIT_BYTES")
+                .map(|limit| limit.parse::<u64>())
+                .unwrap_or(Ok(4_000_000))?,
+            max_stream_size: env::var("RTJAM_MAX_STREAM_SIZE")
+                .map(|size| size.parse::<usize>())
+                .unwrap_or(Ok(1_000_000))?,
+            max_concurrent_streams: env::var("RTJAM_MAX_CONCURRENT_STREAMS")
+                .map(|streams| streams.parse::<u32>())
+                .unwrap_or(Ok(100))?,
+            max_violations: env::var("RTJAM_MAX_VIOLATIONS")
+                .map(|violations| violations.parse::<u32>())
+                .unwrap_or(Ok(100))?,
+            trusted_proxies: env::var("RTJAM_TRUSTED_PROXIES")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|proxy| !proxy.is_empty())
+                .map(|proxy| proxy.parse::<IpAddr>())
+                .collect::<Result<_, _>>()?,
         })
     }
 }
@@ -59,3 +106,41 @@ impl From<Config> for email::Config {
         }
     }
 }
+
+impl From<Config> for relay::Config {
+    fn from(
+        Config {
+            relay_backend,
+            nats_url,
+            ..
+        }: Config,
+    ) -> Self {
+        Self {
+            backend: relay_backend,
+            nats_url,
+        }
+    }
+}
+
+impl From<Config> for limits::Limits {
+    fn from(
+        Config {
+            limit_datagrams,
+            limit_streams,
+            limit_bytes,
+            max_stream_size,
+            max_concurrent_streams,
+            max_violations,
+            ..
+        }: Config,
+    ) -> Self {
+        Self {
+            datagrams: limit_datagrams,
+            streams: limit_streams,
+            bytes: limit_bytes,
+            max_stream_size,
+            max_concurrent_streams,
+            max_violations,
+        }
+    }
+}
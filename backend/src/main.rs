@@ -1,9 +1,10 @@
 mod config;
 mod log;
+mod metrics;
 mod service;
 mod web;
 
-use std::{future::IntoFuture, net::ToSocketAddrs};
+use std::net::{SocketAddr, ToSocketAddrs};
 
 use axum::{middleware, Router};
 use base64::{engine::general_purpose, Engine};
@@ -12,7 +13,7 @@ use service::{
     user::{auth, session},
 };
 use sqlx::{postgres::PgPoolOptions, PgPool};
-use tokio::{signal, task::AbortHandle};
+use tokio::{signal, sync::watch, task::AbortHandle};
 use tower_cookies::{CookieManagerLayer, Key};
 use tracing::info;
 use tracing_subscriber::EnvFilter;
@@ -24,9 +25,11 @@ use web::{
 };
 
 use crate::{
-    service::room,
+    service::{chat, invite, presence, recording, relay, room, schedule},
     web::{
-        routes_room,
+        limits::Limits,
+        routes_account, routes_chat, routes_member, routes_metrics, routes_recording, routes_room,
+        routes_schedule,
         webtransport::{self, Certs},
         SESSION_COOKIE_KEY,
     },
@@ -46,11 +49,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     sqlx::migrate!("../backend/migrations").run(&db).await?;
 
+    let relay = relay::connect(relay::Config::from(config.clone())).await?;
+    let limits = Limits::from(config.clone());
+
     let email_service = email::Service::new(email::Config::from(config.clone())).await?;
 
-    let auth_service = auth::Service::new(db.clone(), email_service);
+    let auth_service = auth::Service::new(db.clone(), email_service.clone());
     let session_service = session::Service::new(db.clone());
     let room_service = room::Service::new(db.clone());
+    let chat_service = chat::Service::new(db.clone());
+    let invite_service = invite::Service::new(db.clone());
+    let schedule_service = schedule::Service::new(db.clone(), email_service.clone());
 
     let key = general_purpose::STANDARD
         .decode(config.session_key)
@@ -72,14 +81,101 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             key: config.key_path.into(),
             cert: config.cert_path.into(),
         },
+        drain_period: tokio::time::Duration::from_secs(config.drain_period),
+    };
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        shutdown_signal(deletion_task.abort_handle()).await;
+        let _ = shutdown_tx.send(true);
+    });
+
+    let recording_service = recording::Service::new(
+        db.clone(),
+        relay.clone(),
+        config.recording_dir.clone().into(),
+        shutdown_rx.clone(),
+    );
+
+    let presence_service =
+        presence::Service::new(relay.clone(), tokio::time::Duration::from_secs(2));
+    tokio::spawn(presence_service.clone().continously_gossip());
+    tokio::spawn(
+        schedule_service
+            .clone()
+            .continously_follow_schedules(tokio::time::Duration::from_secs(60)),
+    );
+
+    let relay_state = webtransport::RelayState {
+        session_service: session_service.clone(),
+        room_service: room_service.clone(),
+        presence_service,
+        chat_service: chat_service.clone(),
+        relay,
+        limits,
+        shutdown: shutdown_rx.clone(),
     };
 
     let app = Router::new()
-        .nest("/api/rooms", routes_room::router(room_service))
+        .nest(
+            "/api/rooms",
+            routes_room::router(
+                room_service.clone(),
+                session_service.clone(),
+                recording_service.clone(),
+                relay_state.presence_service.clone(),
+                relay_state.relay.clone(),
+            )
+            .merge(routes_recording::router(
+                room_service.clone(),
+                recording_service.clone(),
+            ))
+            .merge(routes_member::router(
+                room_service.clone(),
+                invite_service.clone(),
+                auth_service.clone(),
+                email_service,
+            ))
+            .merge(routes_chat::router(room_service.clone(), chat_service.clone()))
+            .merge(routes_schedule::router(
+                room_service.clone(),
+                schedule_service.clone(),
+                config.app_url.clone(),
+            )),
+        )
+        .nest(
+            "/api/invites",
+            routes_member::accept_router(room_service.clone(), invite_service),
+        )
+        .nest(
+            "/api/account",
+            routes_account::router(
+                db.clone(),
+                auth_service.clone(),
+                session_service.clone(),
+                room_service,
+                recording_service,
+                chat_service,
+                schedule_service.clone(),
+            ),
+        )
+        .nest(
+            "/api/calendar",
+            routes_schedule::calendar_router(schedule_service.clone(), config.app_url.clone()),
+        )
         .layer(middleware::from_fn(mw_ctx_require))
+        // subscribed to by calendar apps, the token of the link stands for the session
+        .nest(
+            "/api/feeds",
+            routes_schedule::feed_router(schedule_service, config.app_url.clone()),
+        )
         .nest(
             "/api/auth",
-            routes_login::router(auth_service, session_service),
+            routes_login::router(
+                auth_service,
+                session_service,
+                config.trusted_proxies.clone(),
+            ),
         )
         .layer(middleware::map_response(mw_reponse_map))
         .layer(middleware::from_fn_with_state(
@@ -89,21 +185,42 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .layer(middleware::from_fn(mw_req_stamp_resolver))
         .layer(CookieManagerLayer::new());
 
+    // scraped by the monitoring without auth, so kept off the public listener
+    let metrics_app = routes_metrics::router(db.clone());
+
     let listener = tokio::net::TcpListener::bind(config.listen_address.clone()).await?;
     info!("listening on {}", config.listen_address);
-    tokio::select! {
-        res = axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(deletion_task.abort_handle())).into_future() => {
-            Ok(())
+    let metrics_listener = tokio::net::TcpListener::bind(config.metrics_address.clone()).await?;
+    info!("serving metrics on {}", config.metrics_address);
+    let metrics_shutdown_rx = shutdown_rx.clone();
+    // every server drains on shutdown, the first one failing brings the others down
+    tokio::try_join!(
+        async {
+            // the address of the client is stored with the sessions
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+                .with_graceful_shutdown(wait_for_shutdown(shutdown_rx))
+                .await
+                .map_err(Box::<dyn std::error::Error>::from)
         },
-        res = webtransport::start(opt).into_future() => {
-            res
-        }
-    }?;
+        async {
+            axum::serve(metrics_listener, metrics_app)
+                .with_graceful_shutdown(wait_for_shutdown(metrics_shutdown_rx))
+                .await
+                .map_err(Box::<dyn std::error::Error>::from)
+        },
+        webtransport::start(opt, relay_state)
+    )?;
 
     Ok(())
 }
 
+async fn wait_for_shutdown(mut shutdown: watch::Receiver<bool>) {
+    let _ = shutdown.wait_for(|shutdown| *shutdown).await;
+}
+
 async fn connect_to_db(dsn: &str) -> Result<PgPool, Box<dyn std::error::Error>> {
     let conn = PgPoolOptions::new()
         .max_connections(5)
@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use sqlx::PgPool;
+use tracing::error;
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Metrics of the process, exposed in the Prometheus text format on `/metrics`
+pub fn get() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::new)
+}
+
+pub struct Metrics {
+    registry: Registry,
+
+    // -- HTTP API
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+
+    // -- relay
+    sessions: IntGaugeVec,
+    rooms: IntGauge,
+    /// Open relay sessions per room, only the number of rooms is exported so that the ids of
+    /// private rooms do not leak to the monitoring
+    room_sessions: Mutex<HashMap<String, usize>>,
+    received_packets: IntCounter,
+    received_bytes: IntCounter,
+    sent_packets: IntCounterVec,
+    sent_bytes: IntCounterVec,
+    send_errors: IntCounterVec,
+    dropped_packets: IntCounterVec,
+    session_queued_packets: IntGaugeVec,
+    session_dropped_packets: IntCounterVec,
+    publish_failures: IntCounter,
+    malformed_packets: IntCounter,
+    spoofed_packets: IntCounter,
+    limited_packets: IntCounterVec,
+    limit_disconnects: IntCounter,
+
+    // -- database
+    db_pool_connections: IntGauge,
+    db_pool_idle_connections: IntGauge,
+    db_pool_max_connections: IntGauge,
+    db_pool_timeouts: IntCounter,
+}
+
+/// How a packet is delivered to a peer
+#[derive(Clone, Copy)]
+pub enum Delivery {
+    Datagram,
+    Stream,
+}
+
+impl Delivery {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Delivery::Datagram => "datagram",
+            Delivery::Stream => "stream",
+        }
+    }
+}
+
+/// Series of a lane of the outbound queue of a relay session, removed when dropped
+pub struct LaneSeries {
+    session: String,
+    lane: &'static str,
+    queued: IntGauge,
+    dropped: IntCounter,
+    total_dropped: IntCounter,
+}
+
+impl LaneSeries {
+    pub fn set_queued(&self, packets: usize) {
+        self.queued.set(packets as i64);
+    }
+
+    pub fn dropped(&self) {
+        self.dropped.inc();
+        self.total_dropped.inc();
+    }
+}
+
+/// Accounts a relay session until it is dropped
+pub struct SessionGuard {
+    transport: &'static str,
+    room_id: String,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        macro_rules! register {
+            ($metric:expr) => {{
+                let metric = $metric.expect("invalid metric");
+                registry
+                    .register(Box::new(metric.clone()))
+                    .expect("metric registered twice");
+                metric
+            }};
+        }
+
+        Self {
+            http_requests: register!(IntCounterVec::new(
+                Opts::new("rtjam_http_requests_total", "HTTP requests handled"),
+                &["method", "route", "status", "client_error"]
+            )),
+            http_request_duration: register!(HistogramVec::new(
+                HistogramOpts::new(
+                    "rtjam_http_request_duration_seconds",
+                    "Time taken to handle HTTP requests"
+                ),
+                &["method", "route"]
+            )),
+            sessions: register!(IntGaugeVec::new(
+                Opts::new("rtjam_relay_sessions", "Open relay sessions"),
+                &["transport"]
+            )),
+            rooms: register!(IntGauge::new(
+                "rtjam_relay_rooms",
+                "Rooms with at least an open relay session"
+            )),
+            room_sessions: Mutex::new(HashMap::new()),
+            received_packets: register!(IntCounter::new(
+                "rtjam_relay_received_packets_total",
+                "Packets received from the peers"
+            )),
+            received_bytes: register!(IntCounter::new(
+                "rtjam_relay_received_bytes_total",
+                "Bytes received from the peers"
+            )),
+            sent_packets: register!(IntCounterVec::new(
+                Opts::new(
+                    "rtjam_relay_sent_packets_total",
+                    "Packets forwarded to the peers"
+                ),
+                &["delivery"]
+            )),
+            sent_bytes: register!(IntCounterVec::new(
+                Opts::new(
+                    "rtjam_relay_sent_bytes_total",
+                    "Bytes forwarded to the peers"
+                ),
+                &["delivery"]
+            )),
+            send_errors: register!(IntCounterVec::new(
+                Opts::new(
+                    "rtjam_relay_send_errors_total",
+                    "Packets that could not be sent to the peers"
+                ),
+                &["delivery"]
+            )),
+            dropped_packets: register!(IntCounterVec::new(
+                Opts::new(
+                    "rtjam_relay_dropped_packets_total",
+                    "Packets dropped from the queue of a slow peer"
+                ),
+                &["lane"]
+            )),
+            session_queued_packets: register!(IntGaugeVec::new(
+                Opts::new(
+                    "rtjam_relay_session_queued_packets",
+                    "Packets waiting in the outbound queue of a relay session"
+                ),
+                &["session", "lane"]
+            )),
+            session_dropped_packets: register!(IntCounterVec::new(
+                Opts::new(
+                    "rtjam_relay_session_dropped_packets_total",
+                    "Packets dropped from the outbound queue of a relay session"
+                ),
+                &["session", "lane"]
+            )),
+            publish_failures: register!(IntCounter::new(
+                "rtjam_relay_publish_failures_total",
+                "Packets that could not be published on the relay bus"
+            )),
+            malformed_packets: register!(IntCounter::new(
+                "rtjam_relay_malformed_packets_total",
+                "Packets from the peers dropped because they could not be parsed"
+            )),
+            spoofed_packets: register!(IntCounter::new(
+                "rtjam_relay_spoofed_packets_total",
+                "Packets from the peers claiming another sender or sent as the relay"
+            )),
+            limited_packets: register!(IntCounterVec::new(
+                Opts::new(
+                    "rtjam_relay_limited_packets_total",
+                    "Packets from the peers dropped because they exceeded a limit of the session"
+                ),
+                &["delivery", "limit"]
+            )),
+            limit_disconnects: register!(IntCounter::new(
+                "rtjam_relay_limit_disconnects_total",
+                "Peers disconnected because they kept exceeding the limits of their session"
+            )),
+            db_pool_connections: register!(IntGauge::new(
+                "rtjam_db_pool_connections",
+                "Connections of the database pool"
+            )),
+            db_pool_idle_connections: register!(IntGauge::new(
+                "rtjam_db_pool_idle_connections",
+                "Idle connections of the database pool"
+            )),
+            db_pool_max_connections: register!(IntGauge::new(
+                "rtjam_db_pool_max_connections",
+                "Maximum number of connections of the database pool"
+            )),
+            db_pool_timeouts: register!(IntCounter::new(
+                "rtjam_db_pool_timeouts_total",
+                "Requests that could not get a database connection in time"
+            )),
+            registry,
+        }
+    }
+
+    pub fn observe_request(
+        &self,
+        method: &str,
+        route: &str,
+        status: u16,
+        client_error: &str,
+        duration_secs: f64,
+    ) {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string(), client_error])
+            .inc();
+        self.http_request_duration
+            .with_label_values(&[method, route])
+            .observe(duration_secs);
+    }
+
+    pub fn db_pool_timed_out(&self) {
+        self.db_pool_timeouts.inc();
+    }
+
+    pub fn session_opened(&self, transport: &'static str, room_id: &str) -> SessionGuard {
+        self.sessions.with_label_values(&[transport]).inc();
+        let mut room_sessions = self.room_sessions.lock().unwrap();
+        *room_sessions.entry(room_id.to_owned()).or_default() += 1;
+        self.rooms.set(room_sessions.len() as i64);
+        SessionGuard {
+            transport,
+            room_id: room_id.to_owned(),
+        }
+    }
+
+    pub fn packet_received(&self, bytes: usize) {
+        self.received_packets.inc();
+        self.received_bytes.inc_by(bytes as u64);
+    }
+
+    pub fn packet_sent(&self, delivery: Delivery, bytes: usize) {
+        self.sent_packets
+            .with_label_values(&[delivery.as_str()])
+            .inc();
+        self.sent_bytes
+            .with_label_values(&[delivery.as_str()])
+            .inc_by(bytes as u64);
+    }
+
+    pub fn send_failed(&self, delivery: Delivery) {
+        self.send_errors
+            .with_label_values(&[delivery.as_str()])
+            .inc();
+    }
+
+    /// Opens the series of the `lane` of the outbound queue of `session`, an opaque id logged
+    /// with the peer when the queue is created
+    pub fn queue_lane(&self, session: &str, lane: &'static str) -> LaneSeries {
+        LaneSeries {
+            session: session.to_owned(),
+            lane,
+            queued: self
+                .session_queued_packets
+                .with_label_values(&[session, lane]),
+            dropped: self
+                .session_dropped_packets
+                .with_label_values(&[session, lane]),
+            total_dropped: self.dropped_packets.with_label_values(&[lane]),
+        }
+    }
+
+    pub fn publish_failed(&self) {
+        self.publish_failures.inc();
+    }
+
+    pub fn packet_malformed(&self) {
+        self.malformed_packets.inc();
+    }
+
+    pub fn packet_spoofed(&self) {
+        self.spoofed_packets.inc();
+    }
+
+    pub fn packet_limited(&self, delivery: Delivery, limit: &str) {
+        self.limited_packets
+            .with_label_values(&[delivery.as_str(), limit])
+            .inc();
+    }
+
+    pub fn limit_disconnected(&self) {
+        self.limit_disconnects.inc();
+    }
+
+    /// Renders every metric, the gauges of the database pool are sampled now
+    pub fn render(&self, db: &PgPool) -> String {
+        self.db_pool_connections.set(db.size() as i64);
+        self.db_pool_idle_connections.set(db.num_idle() as i64);
+        self.db_pool_max_connections
+            .set(db.options().get_max_connections() as i64);
+
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            error!("Error encoding metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        let metrics = get();
+        metrics.sessions.with_label_values(&[self.transport]).dec();
+
+        let mut room_sessions = metrics.room_sessions.lock().unwrap();
+        if let Some(sessions) = room_sessions.get_mut(&self.room_id) {
+            *sessions -= 1;
+            if *sessions == 0 {
+                room_sessions.remove(&self.room_id);
+            }
+        }
+        metrics.rooms.set(room_sessions.len() as i64);
+    }
+}
+
+impl Drop for LaneSeries {
+    fn drop(&mut self) {
+        // sessions come and go, their series must not pile up
+        let metrics = get();
+        let labels = [self.session.as_str(), self.lane];
+        let _ = metrics.session_queued_packets.remove_label_values(&labels);
+        let _ = metrics.session_dropped_packets.remove_label_values(&labels);
+    }
+}
@@ -0,0 +1,113 @@
+use sqlx::{prelude::FromRow, PgConnection, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::error::Result;
+
+/// Longest message accepted, in characters
+pub const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Text chat of the rooms.
+///
+/// The messages are relayed live like any other packet, each one is also stored by the instance
+/// of its author so that late joiners and the REST API can read the history of the room.
+#[derive(Clone)]
+pub struct Service {
+    db: PgPool,
+}
+
+#[derive(FromRow)]
+pub struct Message {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub username: String,
+    pub text: String,
+    pub sent_at: OffsetDateTime,
+}
+
+impl Message {
+    /// A message sent now, the time is kept to the millisecond as it travels in the chat packets
+    pub fn new(room_id: Uuid, username: String, text: String) -> Self {
+        let now = OffsetDateTime::now_utc();
+        Self {
+            id: Uuid::new_v4(),
+            room_id,
+            username,
+            text,
+            sent_at: now
+                .replace_nanosecond(now.millisecond() as u32 * 1_000_000)
+                .unwrap_or(now),
+        }
+    }
+}
+
+impl Service {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, message: &Message) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO chat_messages (id, room_id, username, text, sent_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            message.id,
+            message.room_id,
+            message.username,
+            message.text,
+            message.sent_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns up to `limit` messages of the room, newest first, sent before the message
+    /// `before` when given
+    pub async fn page(
+        &self,
+        room_id: Uuid,
+        before: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<Message>> {
+        let messages = sqlx::query_as!(
+            Message,
+            r#"SELECT * FROM chat_messages
+            WHERE room_id = $1
+            AND ($2::uuid IS NULL OR (sent_at, id) < (SELECT sent_at, id FROM chat_messages WHERE id = $2))
+            ORDER BY sent_at DESC, id DESC
+            LIMIT $3
+            "#,
+            room_id,
+            before,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(messages)
+    }
+
+    /// Returns the messages sent by `username` in any room, oldest first
+    pub async fn sent_by(&self, username: &str) -> Result<Vec<Message>> {
+        let messages = sqlx::query_as!(
+            Message,
+            "SELECT * FROM chat_messages WHERE username = $1 ORDER BY sent_at, id",
+            username
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(messages)
+    }
+
+    /// Deletes the messages sent by `username` in any room, within the transaction of the caller
+    pub async fn delete_sent_by_in(&self, tx: &mut PgConnection, username: &str) -> Result<()> {
+        sqlx::query!("DELETE FROM chat_messages WHERE username = $1", username)
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -5,6 +5,8 @@ use lettre::{
     AsyncTransport, Message, Tokio1Executor,
 };
 
+use time::{OffsetDateTime, UtcOffset};
+
 use super::user::User;
 
 #[derive(Clone)]
@@ -127,6 +129,153 @@ impl Service {
 
         Ok(())
     }
+
+    /// Invites `to` to the room `room_name`, `path` is the page of the app that lets them in
+    pub async fn send_room_invitation(
+        &self,
+        to: &str,
+        name: &str,
+        inviter: &str,
+        room_name: &str,
+        path: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let link = format!("{}{}", self.app_url, path);
+        let subject = format!("{} invited you to play in {}", inviter, room_name);
+
+        let template = RoomInvitation {
+            subject: subject.clone(),
+            name: name.to_owned(),
+            message: format!(
+                "{} invited you to play in the room {}. Please, click the button below to join it",
+                inviter, room_name
+            ),
+            link,
+        }
+        .render()?;
+
+        let email = Message::builder()
+            .to(to.parse()?)
+            .reply_to(self.from.parse().unwrap())
+            .from(self.from.parse().unwrap())
+            .subject(subject)
+            .header(ContentType::TEXT_HTML)
+            .body(template)?;
+
+        self.transport.send(email).await?;
+
+        Ok(())
+    }
+
+    /// Reminds `to` of the session `title` planned in `room_name`, starting at `starts_at`
+    pub async fn send_session_reminder(
+        &self,
+        to: &str,
+        name: &str,
+        title: &str,
+        room_name: &str,
+        starts_at: OffsetDateTime,
+        path: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let link = format!("{}{}", self.app_url, path);
+        let subject = format!("{} starts soon in {}", title, room_name);
+
+        let template = SessionReminder {
+            subject: subject.clone(),
+            name: name.to_owned(),
+            message: format!(
+                "{} starts in the room {} at {}. Please, click the button below to join it",
+                title,
+                room_name,
+                format_utc(starts_at)
+            ),
+            link,
+        }
+        .render()?;
+
+        let email = Message::builder()
+            .to(to.parse()?)
+            .reply_to(self.from.parse().unwrap())
+            .from(self.from.parse().unwrap())
+            .subject(subject)
+            .header(ContentType::TEXT_HTML)
+            .body(template)?;
+
+        self.transport.send(email).await?;
+
+        Ok(())
+    }
+
+    /// Tells `to` that the session `title` planned in `room_name` will not take place
+    pub async fn send_session_cancelled(
+        &self,
+        to: &str,
+        name: &str,
+        title: &str,
+        room_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let subject = format!("{} was cancelled", title);
+
+        let template = SessionCancelled {
+            subject: subject.clone(),
+            name: name.to_owned(),
+            message: format!(
+                "{} in the room {} was cancelled, it will not take place anymore",
+                title, room_name
+            ),
+        }
+        .render()?;
+
+        let email = Message::builder()
+            .to(to.parse()?)
+            .reply_to(self.from.parse().unwrap())
+            .from(self.from.parse().unwrap())
+            .subject(subject)
+            .header(ContentType::TEXT_HTML)
+            .body(template)?;
+
+        self.transport.send(email).await?;
+
+        Ok(())
+    }
+}
+
+/// Formats a time as `2024-04-23 18:30 UTC`, the recipient may be anywhere
+fn format_utc(at: OffsetDateTime) -> String {
+    let at = at.to_offset(UtcOffset::UTC);
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02} UTC",
+        at.year(),
+        u8::from(at.month()),
+        at.day(),
+        at.hour(),
+        at.minute()
+    )
+}
+
+#[derive(Template)]
+#[template(path = "room-invitation.html")]
+struct RoomInvitation {
+    subject: String,
+    name: String,
+    message: String,
+    link: String,
+}
+
+#[derive(Template)]
+#[template(path = "session-reminder.html")]
+struct SessionReminder {
+    subject: String,
+    name: String,
+    message: String,
+    link: String,
+}
+
+#[derive(Template)]
+#[template(path = "session-cancelled.html")]
+struct SessionCancelled {
+    subject: String,
+    name: String,
+    message: String,
 }
 
 #[derive(Template)]
@@ -18,8 +18,23 @@ pub enum Error {
         sqlx::Error,
     ),
 
+    BannedFromRoom,
+
     InvalidCredentials,
+    InviteForAnotherEmail,
+
+    #[error(transparent)]
+    Io(
+        #[from]
+        #[serde_as(as = "DisplayFromStr")]
+        std::io::Error,
+    ),
+
     NoAuth,
+    RecordingFailed,
+    RecordingInProgress,
+
+    RoomEncrypted,
 
     #[error(transparent)]
     SerializationError(#[serde_as(as = "DisplayFromStr")] serde_json::Error),
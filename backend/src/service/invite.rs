@@ -0,0 +1,170 @@
+use sqlx::{prelude::FromRow, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::{
+    error::{Error, Result},
+    user::session,
+};
+
+/// A link that makes whoever opens it a member of a private room, until it expires or is used
+/// up
+#[derive(Debug, FromRow)]
+pub struct Invite {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub token: String,
+    pub created_by: String,
+    /// Address the link was sent to, `None` for the links shared by the owner
+    pub email: Option<String>,
+    /// `None` when the link can be used any number of times
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub expires_at: OffsetDateTime,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Clone)]
+pub struct Service {
+    db: PgPool,
+}
+
+impl Service {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(
+        &self,
+        room_id: Uuid,
+        created_by: &str,
+        email: Option<String>,
+        max_uses: Option<i32>,
+        expires_at: OffsetDateTime,
+    ) -> Result<Invite> {
+        let invite = sqlx::query_as!(
+            Invite,
+            r#"INSERT INTO room_invites (id, room_id, token, created_by, email, max_uses, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+            Uuid::new_v4(),
+            room_id,
+            session::Service::generate_token(),
+            created_by,
+            email,
+            max_uses,
+            expires_at
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(invite)
+    }
+
+    /// Returns the invites of the room that can still be used, newest first
+    pub async fn list(&self, room_id: Uuid) -> Result<Vec<Invite>> {
+        let invites = sqlx::query_as!(
+            Invite,
+            r#"SELECT * FROM room_invites
+            WHERE room_id = $1 AND expires_at > now() AND (max_uses IS NULL OR uses < max_uses)
+            ORDER BY created_at DESC
+            "#,
+            room_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(invites)
+    }
+
+    /// Returns whether the room had the invite
+    pub async fn delete(&self, room_id: Uuid, id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM room_invites WHERE room_id = $1 AND id = $2",
+            room_id,
+            id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Makes `username` a member of the room of the invite, returns the room or `None` if the
+    /// invite is unknown, expired or used up. A user that can already join the room does not use
+    /// the invite, an invite sent by email is only for the account with that address.
+    pub async fn accept(&self, token: &str, username: &str) -> Result<Option<Uuid>> {
+        let mut tx = self.db.begin().await?;
+
+        let invite = sqlx::query_as!(
+            Invite,
+            r#"SELECT * FROM room_invites
+            WHERE token = $1 AND expires_at > now() AND (max_uses IS NULL OR uses < max_uses)
+            FOR UPDATE
+            "#,
+            token
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(invite) = invite else {
+            return Ok(None);
+        };
+        if let Some(email) = &invite.email {
+            let user = sqlx::query!(
+                "SELECT 1 AS one FROM users WHERE username = $1 AND lower(email) = lower($2)",
+                username,
+                email
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+            if user.is_none() {
+                return Err(Error::InviteForAnotherEmail);
+            }
+        }
+        // a kicked peer waits for the ban to expire, whatever link it was sent
+        let ban = sqlx::query!(
+            r#"SELECT 1 AS one FROM room_bans
+            WHERE room_id = $1 AND username = $2 AND expires_at > now()
+            "#,
+            invite.room_id,
+            username
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if ban.is_some() {
+            return Err(Error::BannedFromRoom);
+        }
+
+        let joined = sqlx::query!(
+            r#"SELECT 1 AS one FROM rooms
+            WHERE id = $1 AND (owner = $2
+                OR EXISTS (SELECT 1 FROM room_members m WHERE m.room_id = $1 AND m.username = $2))
+            "#,
+            invite.room_id,
+            username
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if joined.is_none() {
+            sqlx::query!(
+                "INSERT INTO room_members (room_id, username, added_by) VALUES ($1, $2, $3)",
+                invite.room_id,
+                username,
+                invite.created_by
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!(
+                "UPDATE room_invites SET uses = uses + 1 WHERE id = $1",
+                invite.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(Some(invite.room_id))
+    }
+}
@@ -1,4 +1,10 @@
+pub mod chat;
 pub mod email;
 pub mod error;
+pub mod invite;
+pub mod presence;
+pub mod recording;
+pub mod relay;
 pub mod room;
+pub mod schedule;
 pub mod user;
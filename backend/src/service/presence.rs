@@ -0,0 +1,309 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::time::{Duration, Instant};
+use tracing::error;
+use uuid::Uuid;
+
+use super::relay::{self, Relay, Subscription};
+
+/// Subject on which the backend instances gossip the members of their rooms
+const PRESENCE_SUBJECT: &str = "relay.presence";
+
+/// Members announced by another instance are forgotten after this many missed announces
+const MISSED_ANNOUNCES: u32 = 3;
+
+/// Members announced by the other instances, by instance and room, with their expiration
+type RemoteMembers = HashMap<(Uuid, String), (BTreeSet<String>, Instant)>;
+
+/// Live occupancy of the rooms.
+///
+/// Every instance knows the members connected to itself and announces them on the relay bus at
+/// a fixed period, and whenever they change. Members announced by the other instances expire
+/// when their instance stops announcing them, so a crashed instance does not keep rooms full.
+///
+/// The count is eventually consistent: two peers joining the last free slot at the same time on
+/// different instances can both get in.
+#[derive(Clone)]
+pub struct Service {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    instance_id: Uuid,
+    relay: Arc<dyn Relay>,
+    // room -> username -> number of local sessions
+    local: Mutex<HashMap<String, HashMap<String, usize>>>,
+    remote: Mutex<RemoteMembers>,
+    period: Duration,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Announce {
+    instance_id: Uuid,
+    room_id: String,
+    members: Vec<String>,
+}
+
+/// Claim of a session on the room of its user
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct SessionClaim {
+    /// Milliseconds since the epoch, concurrent claims are settled in favour of the newest
+    opened_at: u64,
+    session_id: Uuid,
+}
+
+/// Keeps a peer counted in its room until dropped
+pub struct Membership {
+    service: Service,
+    room_id: String,
+    username: String,
+}
+
+impl Drop for Membership {
+    fn drop(&mut self) {
+        self.service.leave(&self.room_id, &self.username);
+    }
+}
+
+impl Service {
+    pub fn new(relay: Arc<dyn Relay>, period: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                instance_id: Uuid::new_v4(),
+                relay,
+                local: Mutex::new(HashMap::new()),
+                remote: Mutex::new(HashMap::new()),
+                period,
+            }),
+        }
+    }
+
+    /// Returns the members of a room across every instance
+    pub fn members(&self, room_id: &str) -> BTreeSet<String> {
+        let mut members = self.remote_members(room_id);
+        if let Some(users) = self.inner.local.lock().unwrap().get(room_id) {
+            members.extend(users.keys().cloned());
+        }
+        members
+    }
+
+    fn remote_members(&self, room_id: &str) -> BTreeSet<String> {
+        let now = Instant::now();
+        let mut members = BTreeSet::new();
+        for ((_, room), (users, expiration)) in self.inner.remote.lock().unwrap().iter() {
+            if room == room_id && *expiration > now {
+                members.extend(users.iter().cloned());
+            }
+        }
+        members
+    }
+
+    /// Counts the peer in the room, unless the room already has `capacity` other members taking
+    /// a seat, as told by `takes_seat`.
+    ///
+    /// A user that is already in the room is always let in, it does not take another slot.
+    pub fn join(
+        &self,
+        room_id: &str,
+        username: &str,
+        capacity: usize,
+        takes_seat: impl Fn(&str) -> bool,
+    ) -> Option<Membership> {
+        let mut members = self.remote_members(room_id);
+        {
+            let mut local = self.inner.local.lock().unwrap();
+            let users = local.entry(room_id.to_owned()).or_default();
+            members.extend(users.keys().cloned());
+            let seated = members.iter().filter(|member| takes_seat(member)).count();
+            if !members.contains(username) && seated >= capacity {
+                if users.is_empty() {
+                    local.remove(room_id);
+                }
+                return None;
+            }
+            *users.entry(username.to_owned()).or_default() += 1;
+        }
+        self.spawn_announce(room_id);
+
+        Some(Membership {
+            service: self.clone(),
+            room_id: room_id.to_owned(),
+            username: username.to_owned(),
+        })
+    }
+
+    fn leave(&self, room_id: &str, username: &str) {
+        {
+            let mut local = self.inner.local.lock().unwrap();
+            if let Some(users) = local.get_mut(room_id) {
+                if let Some(sessions) = users.get_mut(username) {
+                    *sessions -= 1;
+                    if *sessions == 0 {
+                        users.remove(username);
+                    }
+                }
+            }
+        }
+        self.spawn_announce(room_id);
+        // the last announce is already built, an empty room can be forgotten
+        let mut local = self.inner.local.lock().unwrap();
+        if local.get(room_id).is_some_and(|users| users.is_empty()) {
+            local.remove(room_id);
+        }
+    }
+
+    fn announce_of(&self, room_id: &str) -> Announce {
+        Announce {
+            instance_id: self.inner.instance_id,
+            room_id: room_id.to_owned(),
+            members: self
+                .inner
+                .local
+                .lock()
+                .unwrap()
+                .get(room_id)
+                .map(|users| users.keys().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn spawn_announce(&self, room_id: &str) {
+        let announce = self.announce_of(room_id);
+        let relay = self.inner.relay.clone();
+        tokio::spawn(async move { publish(&relay, &announce).await });
+    }
+
+    /// Announces the local members at every period and collects the announces of the other
+    /// instances
+    pub async fn continously_gossip(self) -> Result<()> {
+        let mut sub = self.inner.relay.subscribe(PRESENCE_SUBJECT).await?;
+        let mut interval = tokio::time::interval(self.inner.period);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let rooms = self
+                        .inner
+                        .local
+                        .lock()
+                        .unwrap()
+                        .keys()
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    for room_id in rooms {
+                        publish(&self.inner.relay, &self.announce_of(&room_id)).await;
+                    }
+                    let now = Instant::now();
+                    self.inner
+                        .remote
+                        .lock()
+                        .unwrap()
+                        .retain(|_, (_, expiration)| *expiration > now);
+                }
+                msg = sub.next() => {
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    match serde_json::from_slice::<Announce>(&msg.payload) {
+                        Ok(announce) if announce.instance_id != self.inner.instance_id => {
+                            self.on_announce(announce);
+                        }
+                        Ok(_) => {}
+                        Err(e) => error!("Error parsing presence announce: {}", e),
+                    }
+                }
+            }
+        }
+        self.inner.relay.unsubscribe(sub).await
+    }
+
+    fn on_announce(&self, Announce { instance_id, room_id, members }: Announce) {
+        let mut remote = self.inner.remote.lock().unwrap();
+        if members.is_empty() {
+            remote.remove(&(instance_id, room_id));
+        } else {
+            let expiration = Instant::now() + self.inner.period * MISSED_ANNOUNCES;
+            remote.insert(
+                (instance_id, room_id),
+                (members.into_iter().collect(), expiration),
+            );
+        }
+    }
+}
+
+/// Session of a user in a room, see [`claim`]
+pub struct Claim {
+    relay: Arc<dyn Relay>,
+    sub: Subscription,
+    claim: SessionClaim,
+    /// Whether the claim went around the bus: any claim received after it is from a newer session
+    settled: bool,
+}
+
+/// Claims the room for a new session of the user.
+///
+/// A user has a single session per room: the older sessions of the user in the room, on any
+/// instance, receive the claim and are replaced. Claims crossing each other on the bus are
+/// settled in favour of the newest one.
+pub async fn claim(relay: &Arc<dyn Relay>, room_id: &str, username: &str) -> Result<Claim> {
+    let subject = relay::session_subject(room_id, username);
+    let sub = relay.subscribe(&subject).await?;
+    let claim = SessionClaim {
+        opened_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default(),
+        session_id: Uuid::new_v4(),
+    };
+    relay
+        .publish(subject, serde_json::to_vec(&claim)?.into())
+        .await?;
+    Ok(Claim {
+        relay: relay.clone(),
+        sub,
+        claim,
+        settled: false,
+    })
+}
+
+impl Claim {
+    /// Resolves once a newer session of the same user claims the room
+    pub async fn replaced(&mut self) {
+        while let Some(msg) = self.sub.next().await {
+            match serde_json::from_slice::<SessionClaim>(&msg.payload) {
+                Ok(claim) if claim == self.claim => self.settled = true,
+                Ok(claim) if self.settled || claim > self.claim => return,
+                Ok(_) => {}
+                Err(e) => error!("Error parsing session claim: {}", e),
+            }
+        }
+        // the bus is gone, nothing can replace this session anymore
+        std::future::pending().await
+    }
+
+    pub async fn release(self) {
+        if let Err(e) = self.relay.unsubscribe(self.sub).await {
+            error!("Error releasing session claim: {}", e);
+        }
+    }
+}
+
+async fn publish(relay: &Arc<dyn Relay>, announce: &Announce) {
+    match serde_json::to_vec(announce) {
+        Ok(payload) => {
+            if let Err(e) = relay
+                .publish(PRESENCE_SUBJECT.to_owned(), payload.into())
+                .await
+            {
+                error!("Error publishing presence announce: {}", e);
+            }
+        }
+        Err(e) => error!("Error serializing presence announce: {}", e),
+    }
+}
@@ -0,0 +1,1055 @@
+use std::{collections::HashMap, io, path::PathBuf, sync::Arc};
+
+use bytes::Bytes;
+use common::protos::{
+    media_packet::{media_packet::MediaType, MediaPacket},
+    packet_wrapper::{packet_wrapper::PacketType, PacketWrapper},
+};
+use futures::StreamExt;
+use protobuf::Message;
+use sqlx::{prelude::FromRow, PgPool};
+use time::OffsetDateTime;
+use tokio::{
+    fs,
+    io::{AsyncWriteExt, BufWriter},
+    sync::watch,
+    time::Instant,
+};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use super::{
+    error::{Error, Result},
+    relay::{self, Relay},
+    room::Room,
+};
+
+/// Records the rooms on disk: every participant gets an Ogg/Opus file for its audio and a
+/// WebM/VP9 file for its video.
+///
+/// The recorder is just another subscriber of `room.{id}.*`, so it can only read media that is
+/// not end-to-end encrypted. Since the stop request may reach another instance than the one
+/// recording, it is delivered through the relay.
+#[derive(Clone)]
+pub struct Service {
+    db: PgPool,
+    relay: Arc<dyn Relay>,
+    dir: PathBuf,
+    shutdown: watch::Receiver<bool>,
+}
+
+#[derive(FromRow)]
+pub struct Recording {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub started_by: String,
+    pub started_at: OffsetDateTime,
+    pub stopped_at: Option<OffsetDateTime>,
+}
+
+#[derive(FromRow)]
+pub struct Track {
+    pub id: Uuid,
+    pub recording_id: Uuid,
+    pub username: String,
+    pub kind: String,
+    pub offset_ms: i64,
+    pub size: i64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Audio,
+    Video,
+}
+
+impl Kind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Audio => "audio",
+            Kind::Video => "video",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Kind::Audio => "ogg",
+            Kind::Video => "webm",
+        }
+    }
+}
+
+impl Track {
+    fn kind(&self) -> Kind {
+        if self.kind == Kind::Video.as_str() {
+            Kind::Video
+        } else {
+            Kind::Audio
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self.kind() {
+            Kind::Audio => "audio/ogg",
+            Kind::Video => "video/webm",
+        }
+    }
+
+    /// Name proposed to the browser when the track is downloaded
+    pub fn file_name(&self) -> String {
+        let username = self
+            .username
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        format!("{}-{}.{}", username, self.kind, self.kind().extension())
+    }
+}
+
+/// Subject on which the recorder of a recording waits for the stop request
+fn control_subject(recording_id: Uuid) -> String {
+    format!("recording.{}.stop", recording_id)
+}
+
+impl Service {
+    pub fn new(
+        db: PgPool,
+        relay: Arc<dyn Relay>,
+        dir: PathBuf,
+        shutdown: watch::Receiver<bool>,
+    ) -> Self {
+        Self {
+            db,
+            relay,
+            dir,
+            shutdown,
+        }
+    }
+
+    fn dir_of(&self, recording_id: Uuid) -> PathBuf {
+        self.dir.join(recording_id.to_string())
+    }
+
+    pub fn path_of(&self, track: &Track) -> PathBuf {
+        self.dir_of(track.recording_id)
+            .join(format!("{}.{}", track.id, track.kind().extension()))
+    }
+}
+
+impl Service {
+    /// Starts recording the room, the media of the participants is written until [`Self::stop`]
+    /// is called or the server shuts down
+    pub async fn start(&self, room: &Room, started_by: String) -> Result<Recording> {
+        if room.encrypted {
+            return Err(Error::RoomEncrypted);
+        }
+
+        let id = Uuid::new_v4();
+        let recording = sqlx::query_as!(
+            Recording,
+            r#"INSERT INTO recordings (id, room_id, started_by)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#,
+            id,
+            room.id,
+            started_by
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(e) if e.is_unique_violation() => Error::RecordingInProgress,
+            e => Error::from(e),
+        })?;
+
+        let subscriptions = async {
+            fs::create_dir_all(self.dir_of(id)).await?;
+            let relay_failed = |e: anyhow::Error| {
+                error!("Error subscribing recording {} to the relay: {}", id, e);
+                Error::RecordingFailed
+            };
+            let room_sub = self
+                .relay
+                .subscribe_room(&room.id.to_string(), &format!("recording.{}", id))
+                .await
+                .map_err(relay_failed)?;
+            let control_sub = self
+                .relay
+                .subscribe(&control_subject(id))
+                .await
+                .map_err(relay_failed)?;
+            Ok::<_, Error>((room_sub, control_sub))
+        };
+        let (room_sub, control_sub) = match subscriptions.await {
+            Ok(subscriptions) => subscriptions,
+            Err(e) => {
+                self.mark_stopped(id).await?;
+                return Err(e);
+            }
+        };
+
+        info!("Recording {} of room {} started", id, room.id);
+        tokio::spawn(self.clone().record(id, room_sub, control_sub));
+
+        Ok(recording)
+    }
+
+    /// Stops a recording, the files are closed shortly after by the instance recording it
+    pub async fn stop(&self, recording: Recording) -> Result<Recording> {
+        if recording.stopped_at.is_some() {
+            return Ok(recording);
+        }
+
+        // marking the recording first lets the owner stop it even if its recorder is gone
+        let recording = self.mark_stopped(recording.id).await?.unwrap_or(recording);
+        if let Err(e) = self
+            .relay
+            .publish(control_subject(recording.id), Bytes::new())
+            .await
+        {
+            error!("Error stopping recording {}: {}", recording.id, e);
+        }
+
+        Ok(recording)
+    }
+
+    pub async fn get(&self, room_id: Uuid, id: Uuid) -> Result<Option<Recording>> {
+        let recording = sqlx::query_as!(
+            Recording,
+            "SELECT * FROM recordings WHERE id = $1 AND room_id = $2",
+            id,
+            room_id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(recording)
+    }
+
+    pub async fn list(&self, room_id: Uuid) -> Result<Vec<Recording>> {
+        let recordings = sqlx::query_as!(
+            Recording,
+            "SELECT * FROM recordings WHERE room_id = $1 ORDER BY started_at DESC",
+            room_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(recordings)
+    }
+
+    pub async fn tracks(&self, recording_id: Uuid) -> Result<Vec<Track>> {
+        let tracks = sqlx::query_as!(
+            Track,
+            "SELECT * FROM recording_tracks WHERE recording_id = $1 ORDER BY username, kind",
+            recording_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(tracks)
+    }
+
+    pub async fn get_track(&self, recording_id: Uuid, id: Uuid) -> Result<Option<Track>> {
+        let track = sqlx::query_as!(
+            Track,
+            "SELECT * FROM recording_tracks WHERE id = $1 AND recording_id = $2",
+            id,
+            recording_id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(track)
+    }
+
+    /// Deletes a recording and its files, stopping it first if needed
+    pub async fn delete(&self, recording: Recording) -> Result<()> {
+        let recording = self.stop(recording).await?;
+
+        sqlx::query!("DELETE FROM recordings WHERE id = $1", recording.id)
+            .execute(&self.db)
+            .await?;
+
+        self.delete_files(recording.id).await
+    }
+
+    /// Deletes the files of a recording whose row is already gone
+    pub async fn delete_files(&self, id: Uuid) -> Result<()> {
+        match fs::remove_dir_all(self.dir_of(id)).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Deletes the recordings of a room, the rows go away with the room but the files do not
+    pub async fn delete_all(&self, room_id: Uuid) -> Result<()> {
+        for recording in self.list(room_id).await? {
+            self.delete(recording).await?;
+        }
+        Ok(())
+    }
+
+    async fn mark_stopped(&self, id: Uuid) -> Result<Option<Recording>> {
+        let recording = sqlx::query_as!(
+            Recording,
+            r#"UPDATE recordings SET stopped_at = now()
+            WHERE id = $1 AND stopped_at IS NULL
+            RETURNING *
+            "#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(recording)
+    }
+}
+
+// recorder
+impl Service {
+    async fn record(
+        self,
+        recording_id: Uuid,
+        mut room_sub: relay::Subscription,
+        mut control_sub: relay::Subscription,
+    ) {
+        let started_at = Instant::now();
+        let mut shutdown = self.shutdown.clone();
+        let mut tracks: HashMap<(String, Kind), TrackWriter> = HashMap::new();
+        let mut undecodable = 0u64;
+
+        loop {
+            let msg = tokio::select! {
+                msg = room_sub.next() => msg,
+                _ = control_sub.next() => None,
+                _ = shutdown.wait_for(|shutdown| *shutdown) => None,
+            };
+            let Some(msg) = msg else {
+                break;
+            };
+            let Some(username) = relay::username_of(&msg.subject) else {
+                continue;
+            };
+            let Ok(packet) = PacketWrapper::parse_from_bytes(&msg.payload) else {
+                continue;
+            };
+            if packet.packet_type != PacketType::MEDIA.into() {
+                continue;
+            }
+            let Ok(media) = MediaPacket::parse_from_bytes(&packet.data) else {
+                undecodable += 1;
+                continue;
+            };
+            let kind = match media.media_type.enum_value() {
+                Ok(MediaType::AUDIO) => Kind::Audio,
+                Ok(MediaType::VIDEO) => Kind::Video,
+                _ => continue,
+            };
+
+            let key = (username.to_owned(), kind);
+            if !tracks.contains_key(&key) {
+                // a video track must start with a key frame to be decodable
+                if kind == Kind::Video && media.frame_type != "key" {
+                    continue;
+                }
+                let offset = started_at.elapsed().as_millis() as i64;
+                match self
+                    .create_track(recording_id, username, kind, offset, &media)
+                    .await
+                {
+                    Ok(track) => {
+                        tracks.insert(key.clone(), track);
+                    }
+                    Err(e) => {
+                        error!("Error creating track of {}: {}", username, e);
+                        continue;
+                    }
+                }
+            }
+            if let Some(track) = tracks.get_mut(&key) {
+                if let Err(e) = track.write(&media).await {
+                    error!("Error writing track {}: {}", track.id, e);
+                }
+            }
+        }
+
+        for (_, track) in tracks {
+            let id = track.id;
+            match track.finish().await {
+                Ok(size) => {
+                    if let Err(e) = sqlx::query!(
+                        "UPDATE recording_tracks SET size = $2 WHERE id = $1",
+                        id,
+                        size as i64
+                    )
+                    .execute(&self.db)
+                    .await
+                    {
+                        error!("Error updating track {}: {}", id, e);
+                    }
+                }
+                Err(e) => error!("Error closing track {}: {}", id, e),
+            }
+        }
+        if undecodable > 0 {
+            warn!(
+                "Recording {} skipped {} encrypted or malformed packets",
+                recording_id, undecodable
+            );
+        }
+        if let Err(e) = self.mark_stopped(recording_id).await {
+            error!("Error stopping recording {}: {}", recording_id, e);
+        }
+        for sub in [room_sub, control_sub] {
+            if let Err(e) = self.relay.unsubscribe(sub).await {
+                error!("Error unsubscribing: {}", e);
+            }
+        }
+        info!("Recording {} stopped", recording_id);
+    }
+
+    async fn create_track(
+        &self,
+        recording_id: Uuid,
+        username: &str,
+        kind: Kind,
+        offset_ms: i64,
+        media: &MediaPacket,
+    ) -> Result<TrackWriter> {
+        let id = Uuid::new_v4();
+        let track = sqlx::query_as!(
+            Track,
+            r#"INSERT INTO recording_tracks (id, recording_id, username, kind, offset_ms)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+            id,
+            recording_id,
+            username,
+            kind.as_str(),
+            offset_ms
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        let file = fs::File::create(self.path_of(&track)).await?;
+        Ok(TrackWriter {
+            id,
+            file: BufWriter::new(file),
+            size: 0,
+            muxer: match kind {
+                Kind::Audio => Muxer::Audio(ogg::OpusWriter::new(id.as_u128() as u32)),
+                Kind::Video => Muxer::Video(webm::Vp9Writer::default()),
+            },
+            offset_us: offset_ms as f64 * 1000.0,
+            first_timestamp: media.timestamp,
+        })
+    }
+}
+
+enum Muxer {
+    Audio(ogg::OpusWriter),
+    Video(webm::Vp9Writer),
+}
+
+/// A file being written. The tracks of a participant are placed on the timeline of the recording
+/// by the time their first packet was received, after that `MediaPacket.timestamp` (the
+/// microseconds of the WebCodecs chunk) keeps the original timing.
+struct TrackWriter {
+    id: Uuid,
+    file: BufWriter<fs::File>,
+    size: u64,
+    muxer: Muxer,
+    offset_us: f64,
+    first_timestamp: f64,
+}
+
+impl TrackWriter {
+    async fn write(&mut self, media: &MediaPacket) -> io::Result<()> {
+        let time_us = self.offset_us + (media.timestamp - self.first_timestamp);
+        let bytes = match &mut self.muxer {
+            Muxer::Audio(writer) => writer.packet(time_us, &media.data),
+            Muxer::Video(writer) => writer.frame(time_us, &media.data, media.frame_type == "key"),
+        };
+        self.size += bytes.len() as u64;
+        self.file.write_all(&bytes).await
+    }
+
+    async fn finish(mut self) -> io::Result<u64> {
+        let bytes = match &mut self.muxer {
+            Muxer::Audio(writer) => writer.finish(),
+            Muxer::Video(writer) => writer.finish(),
+        };
+        self.size += bytes.len() as u64;
+        self.file.write_all(&bytes).await?;
+        self.file.flush().await?;
+        Ok(self.size)
+    }
+}
+
+/// Ogg encapsulation of Opus (RFC 7845), one packet per page
+mod ogg {
+    const SAMPLES_PER_MS: f64 = 48.0;
+
+    const FLAG_BEGIN_OF_STREAM: u8 = 0x02;
+    const FLAG_END_OF_STREAM: u8 = 0x04;
+
+    static CRC_TABLE: [u32; 256] = crc_table();
+
+    pub struct OpusWriter {
+        serial: u32,
+        sequence: u32,
+        granule: u64,
+        /// The last packet is held back to be flagged as the end of the stream
+        pending: Option<(Vec<u8>, u64)>,
+        headers_written: bool,
+    }
+
+    impl OpusWriter {
+        pub fn new(serial: u32) -> Self {
+            Self {
+                serial,
+                sequence: 0,
+                granule: 0,
+                pending: None,
+                headers_written: false,
+            }
+        }
+
+        /// Adds a packet starting at `time_us` on the timeline of the recording
+        pub fn packet(&mut self, time_us: f64, packet: &[u8]) -> Vec<u8> {
+            let mut out = Vec::new();
+            if !self.headers_written {
+                self.headers_written = true;
+                let channels = if packet.first().is_some_and(|toc| toc & 0x04 != 0) {
+                    2
+                } else {
+                    1
+                };
+                out.extend(self.page(&opus_head(channels), 0, FLAG_BEGIN_OF_STREAM));
+                out.extend(self.page(&opus_tags(), 0, 0));
+            }
+
+            // a late or reordered packet cannot go back in time
+            let start = ((time_us.max(0.0) / 1000.0) * SAMPLES_PER_MS) as u64;
+            self.granule = start.max(self.granule) + samples(packet);
+            if let Some((packet, granule)) = self.pending.replace((packet.to_vec(), self.granule)) {
+                out.extend(self.page(&packet, granule, 0));
+            }
+            out
+        }
+
+        pub fn finish(&mut self) -> Vec<u8> {
+            match self.pending.take() {
+                Some((packet, granule)) => self.page(&packet, granule, FLAG_END_OF_STREAM),
+                None => Vec::new(),
+            }
+        }
+
+        fn page(&mut self, packet: &[u8], granule: u64, flags: u8) -> Vec<u8> {
+            let mut lacing = vec![255u8; packet.len() / 255];
+            lacing.push((packet.len() % 255) as u8);
+
+            let mut page = Vec::with_capacity(27 + lacing.len() + packet.len());
+            page.extend_from_slice(b"OggS");
+            page.push(0);
+            page.push(flags);
+            page.extend_from_slice(&granule.to_le_bytes());
+            page.extend_from_slice(&self.serial.to_le_bytes());
+            page.extend_from_slice(&self.sequence.to_le_bytes());
+            page.extend_from_slice(&[0; 4]);
+            page.push(lacing.len() as u8);
+            page.extend_from_slice(&lacing);
+            page.extend_from_slice(packet);
+            let crc = crc(&page);
+            page[22..26].copy_from_slice(&crc.to_le_bytes());
+
+            self.sequence += 1;
+            page
+        }
+    }
+
+    fn opus_head(channels: u8) -> Vec<u8> {
+        let mut head = b"OpusHead".to_vec();
+        head.push(1);
+        head.push(channels);
+        head.extend_from_slice(&0u16.to_le_bytes());
+        head.extend_from_slice(&48_000u32.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes());
+        head.push(0);
+        head
+    }
+
+    fn opus_tags() -> Vec<u8> {
+        let vendor = b"rt-jam";
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor);
+        tags.extend_from_slice(&0u32.to_le_bytes());
+        tags
+    }
+
+    /// Number of 48kHz samples in a packet, read from its TOC byte
+    fn samples(packet: &[u8]) -> u64 {
+        let Some(&toc) = packet.first() else {
+            return 0;
+        };
+        let config = (toc >> 3) as usize;
+        let frame_size = match config {
+            // SILK: 10, 20, 40 and 60 ms
+            0..=11 => [480, 960, 1920, 2880][config % 4],
+            // hybrid: 10 and 20 ms
+            12..=15 => [480, 960][config % 2],
+            // CELT: 2.5, 5, 10 and 20 ms
+            _ => [120, 240, 480, 960][config % 4],
+        };
+        let frames = match toc & 0x03 {
+            0 => 1,
+            1 | 2 => 2,
+            _ => packet.get(1).map_or(0, |count| count & 0x3f) as u64,
+        };
+        frame_size * frames
+    }
+
+    fn crc(data: &[u8]) -> u32 {
+        data.iter().fold(0, |crc, byte| {
+            (crc << 8) ^ CRC_TABLE[((crc >> 24) as u8 ^ byte) as usize]
+        })
+    }
+
+    const fn crc_table() -> [u32; 256] {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut r = (i as u32) << 24;
+            let mut j = 0;
+            while j < 8 {
+                r = if r & 0x8000_0000 != 0 {
+                    (r << 1) ^ 0x04c1_1db7
+                } else {
+                    r << 1
+                };
+                j += 1;
+            }
+            table[i] = r;
+            i += 1;
+        }
+        table
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn page_of_a_real_file() {
+            // first page of a Vorbis file, written by libogg
+            let packet = [
+                0x01, 0x76, 0x6f, 0x72, 0x62, 0x69, 0x73, 0x00, 0x00, 0x00, 0x00, 0x02, 0x44, 0xac,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0xb5, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0xb8, 0x01,
+            ];
+            let mut writer = OpusWriter::new(0x5b90_a374);
+            let page = writer.page(&packet, 0, FLAG_BEGIN_OF_STREAM);
+            assert_eq!(
+                page[..28],
+                [
+                    0x4f, 0x67, 0x67, 0x53, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    0x00, 0x74, 0xa3, 0x90, 0x5b, 0x00, 0x00, 0x00, 0x00, 0x6d, 0x94, 0x4e, 0x3d,
+                    0x01, 0x1e,
+                ]
+            );
+            assert_eq!(page[28..], packet);
+            assert_eq!(crc(&[61, 61, 33]), 0x9f85_8776);
+        }
+
+        #[test]
+        fn lacing() {
+            let mut writer = OpusWriter::new(1);
+            let page = writer.page(&[0; 510], 0, 0);
+            assert_eq!(page[26..30], [3, 255, 255, 0]);
+            assert_eq!(page.len(), 27 + 3 + 510);
+            let page = writer.page(&[0; 300], 0, 0);
+            assert_eq!(page[26..29], [2, 255, 45]);
+            // the sequence number goes on with every page
+            assert_eq!(page[18..22], 1u32.to_le_bytes());
+        }
+
+        #[test]
+        fn samples_of_every_configuration() {
+            // frame durations in tenths of a millisecond, by configuration (RFC 6716 3.1)
+            let durations = [
+                100, 200, 400, 600, 100, 200, 400, 600, 100, 200, 400, 600, 100, 200, 100, 200, 25,
+                50, 100, 200, 25, 50, 100, 200, 25, 50, 100, 200, 25, 50, 100, 200,
+            ];
+            for (config, duration) in durations.into_iter().enumerate() {
+                let toc = (config as u8) << 3;
+                let frame = duration * 48 / 10;
+                assert_eq!(samples(&[toc]), frame, "configuration {config}");
+                assert_eq!(
+                    samples(&[toc | 0x04]),
+                    frame,
+                    "stereo configuration {config}"
+                );
+                assert_eq!(samples(&[toc | 0x01]), 2 * frame);
+                assert_eq!(samples(&[toc | 0x02]), 2 * frame);
+                assert_eq!(samples(&[toc | 0x03, 0x80 | 5]), 5 * frame);
+            }
+            assert_eq!(samples(&[0x03]), 0);
+            assert_eq!(samples(&[]), 0);
+        }
+
+        #[test]
+        fn granule_positions() {
+            let mut writer = OpusWriter::new(1);
+            // 20 ms CELT packets
+            let headers = writer.packet(0.0, &[0xf8]);
+            assert_eq!(headers[5], FLAG_BEGIN_OF_STREAM);
+            // the first packet is held back until the next one
+            let page = writer.packet(20_000.0, &[0xf8]);
+            assert_eq!(page[6..14], 960u64.to_le_bytes());
+            // a late packet does not go back in time
+            let page = writer.packet(10_000.0, &[0xf8]);
+            assert_eq!(page[6..14], 1920u64.to_le_bytes());
+            let page = writer.finish();
+            assert_eq!(page[5], FLAG_END_OF_STREAM);
+            assert_eq!(page[6..14], 2880u64.to_le_bytes());
+        }
+    }
+}
+
+/// WebM with a single VP9 track, written as a live stream: the segment has an unknown size and
+/// there are no cues
+mod webm {
+    const EBML: u32 = 0x1A45_DFA3;
+    const EBML_VERSION: u32 = 0x4286;
+    const EBML_READ_VERSION: u32 = 0x42F7;
+    const EBML_MAX_ID_LENGTH: u32 = 0x42F2;
+    const EBML_MAX_SIZE_LENGTH: u32 = 0x42F3;
+    const DOC_TYPE: u32 = 0x4282;
+    const DOC_TYPE_VERSION: u32 = 0x4287;
+    const DOC_TYPE_READ_VERSION: u32 = 0x4285;
+    const SEGMENT: u32 = 0x1853_8067;
+    const INFO: u32 = 0x1549_A966;
+    const TIMESTAMP_SCALE: u32 = 0x2A_D7B1;
+    const MUXING_APP: u32 = 0x4D80;
+    const WRITING_APP: u32 = 0x5741;
+    const TRACKS: u32 = 0x1654_AE6B;
+    const TRACK_ENTRY: u32 = 0xAE;
+    const TRACK_NUMBER: u32 = 0xD7;
+    const TRACK_UID: u32 = 0x73C5;
+    const TRACK_TYPE: u32 = 0x83;
+    const CODEC_ID: u32 = 0x86;
+    const VIDEO: u32 = 0xE0;
+    const PIXEL_WIDTH: u32 = 0xB0;
+    const PIXEL_HEIGHT: u32 = 0xBA;
+    const CLUSTER: u32 = 0x1F43_B675;
+    const CLUSTER_TIMESTAMP: u32 = 0xE7;
+    const SIMPLE_BLOCK: u32 = 0xA3;
+
+    const UNKNOWN_SIZE: [u8; 8] = [0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+
+    /// Used when the size cannot be read from the first key frame, the one of `CameraEncoder`
+    const DEFAULT_FRAME_SIZE: (u64, u64) = (1280, 720);
+
+    /// A cluster is closed at the first key frame after this time
+    const CLUSTER_DURATION_MS: u64 = 5_000;
+
+    /// Blocks are timed relative to their cluster with a signed 16 bit integer
+    const MAX_BLOCK_OFFSET_MS: u64 = i16::MAX as u64;
+
+    #[derive(Default)]
+    pub struct Vp9Writer {
+        header_written: bool,
+        cluster: Option<(u64, Vec<u8>)>,
+        last_time: u64,
+    }
+
+    impl Vp9Writer {
+        /// Adds a frame shown at `time_us` on the timeline of the recording
+        pub fn frame(&mut self, time_us: f64, frame: &[u8], key: bool) -> Vec<u8> {
+            let mut out = Vec::new();
+            if !self.header_written {
+                self.header_written = true;
+                out.extend(header(frame_size(frame).unwrap_or(DEFAULT_FRAME_SIZE)));
+            }
+
+            let time = ((time_us.max(0.0) / 1000.0) as u64).max(self.last_time);
+            self.last_time = time;
+            let start_cluster = match &self.cluster {
+                Some((cluster_time, _)) => {
+                    time - cluster_time > MAX_BLOCK_OFFSET_MS
+                        || (key && time - cluster_time >= CLUSTER_DURATION_MS)
+                }
+                None => true,
+            };
+            if start_cluster {
+                out.extend(self.finish());
+                self.cluster = Some((time, element(CLUSTER_TIMESTAMP, &uint(time))));
+            }
+
+            if let Some((cluster_time, cluster)) = &mut self.cluster {
+                let mut block = vec![0x81];
+                block.extend_from_slice(&((time - *cluster_time) as i16).to_be_bytes());
+                block.push(if key { 0x80 } else { 0x00 });
+                block.extend_from_slice(frame);
+                cluster.extend(element(SIMPLE_BLOCK, &block));
+            }
+            out
+        }
+
+        /// Writes the pending cluster
+        pub fn finish(&mut self) -> Vec<u8> {
+            match self.cluster.take() {
+                Some((_, cluster)) => element(CLUSTER, &cluster),
+                None => Vec::new(),
+            }
+        }
+    }
+
+    fn header((width, height): (u64, u64)) -> Vec<u8> {
+        let ebml = [
+            element(EBML_VERSION, &uint(1)),
+            element(EBML_READ_VERSION, &uint(1)),
+            element(EBML_MAX_ID_LENGTH, &uint(4)),
+            element(EBML_MAX_SIZE_LENGTH, &uint(8)),
+            element(DOC_TYPE, b"webm"),
+            element(DOC_TYPE_VERSION, &uint(4)),
+            element(DOC_TYPE_READ_VERSION, &uint(2)),
+        ]
+        .concat();
+        let info = [
+            element(TIMESTAMP_SCALE, &uint(1_000_000)),
+            element(MUXING_APP, b"rt-jam"),
+            element(WRITING_APP, b"rt-jam"),
+        ]
+        .concat();
+        let video = [
+            element(PIXEL_WIDTH, &uint(width)),
+            element(PIXEL_HEIGHT, &uint(height)),
+        ]
+        .concat();
+        let track = [
+            element(TRACK_NUMBER, &uint(1)),
+            element(TRACK_UID, &uint(1)),
+            element(TRACK_TYPE, &uint(1)),
+            element(CODEC_ID, b"V_VP9"),
+            element(VIDEO, &video),
+        ]
+        .concat();
+
+        let mut out = element(EBML, &ebml);
+        out.extend(id(SEGMENT));
+        out.extend(UNKNOWN_SIZE);
+        out.extend(element(INFO, &info));
+        out.extend(element(TRACKS, &element(TRACK_ENTRY, &track)));
+        out
+    }
+
+    fn id(id: u32) -> Vec<u8> {
+        let bytes = id.to_be_bytes();
+        let first = bytes.iter().position(|&b| b != 0).unwrap_or(3);
+        bytes[first..].to_vec()
+    }
+
+    /// An element with its size coded on 8 bytes
+    fn element(element_id: u32, data: &[u8]) -> Vec<u8> {
+        let mut out = id(element_id);
+        let mut size = (data.len() as u64).to_be_bytes();
+        size[0] = 0x01;
+        out.extend_from_slice(&size);
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn uint(value: u64) -> Vec<u8> {
+        let bytes = value.to_be_bytes();
+        let first = bytes.iter().position(|&b| b != 0).unwrap_or(7);
+        bytes[first..].to_vec()
+    }
+
+    /// Reads the size of a VP9 key frame from its uncompressed header
+    fn frame_size(frame: &[u8]) -> Option<(u64, u64)> {
+        let mut bits = BitReader {
+            data: frame,
+            pos: 0,
+        };
+        if bits.read(2)? != 2 {
+            return None;
+        }
+        let profile = bits.read(1)? | bits.read(1)? << 1;
+        if profile == 3 {
+            bits.read(1)?;
+        }
+        // show_existing_frame, then frame_type (0 is a key frame), show_frame, error_resilient
+        if bits.read(1)? == 1 || bits.read(1)? != 0 {
+            return None;
+        }
+        bits.read(2)?;
+        if bits.read(24)? != 0x49_8342 {
+            return None;
+        }
+        if profile >= 2 {
+            bits.read(1)?;
+        }
+        let color_space = bits.read(3)?;
+        if color_space != 7 {
+            bits.read(1)?;
+            if profile == 1 || profile == 3 {
+                bits.read(3)?;
+            }
+        } else if profile == 1 || profile == 3 {
+            bits.read(1)?;
+        }
+        let width = bits.read(16)? + 1;
+        let height = bits.read(16)? + 1;
+        Some((width, height))
+    }
+
+    struct BitReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl BitReader<'_> {
+        fn read(&mut self, count: usize) -> Option<u64> {
+            let mut value = 0;
+            for _ in 0..count {
+                let byte = self.data.get(self.pos / 8)?;
+                value = value << 1 | ((byte >> (7 - self.pos % 8)) & 1) as u64;
+                self.pos += 1;
+            }
+            Some(value)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Uncompressed header of a profile 0 key frame of 1280x720
+        const KEY_FRAME: [u8; 9] = [0x82, 0x49, 0x83, 0x42, 0x20, 0x4f, 0xf0, 0x2c, 0xf0];
+
+        /// Returns the clusters written in `out`, as the offsets of their blocks
+        fn clusters(out: &[u8]) -> Vec<Vec<i16>> {
+            let mut clusters = Vec::new();
+            let mut pos = 0;
+            while pos < out.len() {
+                let (element_id, data) = read_element(&out[pos..]);
+                pos += id(element_id).len() + 8;
+                // the segment has an unknown size, the clusters are in it
+                if element_id == SEGMENT {
+                    continue;
+                }
+                pos += data.len();
+                if element_id != CLUSTER {
+                    continue;
+                }
+                let mut blocks = Vec::new();
+                let mut inner = 0;
+                while inner < data.len() {
+                    let (element_id, block) = read_element(&data[inner..]);
+                    inner += id(element_id).len() + 8 + block.len();
+                    if element_id == SIMPLE_BLOCK {
+                        blocks.push(i16::from_be_bytes([block[1], block[2]]));
+                    }
+                }
+                clusters.push(blocks);
+            }
+            clusters
+        }
+
+        /// Reads an element written by [`element`], with its size on 8 bytes; the data of an
+        /// element of unknown size is empty
+        fn read_element(data: &[u8]) -> (u32, &[u8]) {
+            let id_length = match data[0] {
+                0x80.. => 1,
+                0x40.. => 2,
+                0x20.. => 3,
+                _ => 4,
+            };
+            let element_id = data[..id_length]
+                .iter()
+                .fold(0, |id, &byte| id << 8 | byte as u32);
+            let start = id_length + 8;
+            if data[id_length..start] == UNKNOWN_SIZE {
+                return (element_id, &[]);
+            }
+            let mut size = [0; 8];
+            size.copy_from_slice(&data[id_length..start]);
+            size[0] = 0;
+            let size = u64::from_be_bytes(size) as usize;
+            (element_id, &data[start..start + size])
+        }
+
+        #[test]
+        fn frame_size_of_key_frames() {
+            assert_eq!(frame_size(&KEY_FRAME), Some((1280, 720)));
+            // profile 1 codes the subsampling as well
+            let profile_1 = [0xa2, 0x49, 0x83, 0x42, 0x48, 0x04, 0xfe, 0x02, 0xce];
+            assert_eq!(frame_size(&profile_1), Some((640, 360)));
+        }
+
+        #[test]
+        fn frame_size_of_other_frames() {
+            // an inter frame
+            assert_eq!(frame_size(&[0x86, 0x00, 0x00]), None);
+            // a wrong sync code
+            let mut frame = KEY_FRAME;
+            frame[2] = 0x84;
+            assert_eq!(frame_size(&frame), None);
+            // cut before the size
+            assert_eq!(frame_size(&KEY_FRAME[..6]), None);
+            assert_eq!(frame_size(&[]), None);
+        }
+
+        #[test]
+        fn header_from_the_first_frame() {
+            let mut writer = Vp9Writer::default();
+            let out = writer.frame(0.0, &KEY_FRAME, true);
+            assert_eq!(out[..4], [0x1a, 0x45, 0xdf, 0xa3]);
+            let size = [
+                element(PIXEL_WIDTH, &uint(1280)),
+                element(PIXEL_HEIGHT, &uint(720)),
+            ]
+            .concat();
+            assert!(out.windows(size.len()).any(|window| window == size));
+        }
+
+        #[test]
+        fn cluster_rollover() {
+            let mut writer = Vp9Writer::default();
+            let mut out = writer.frame(0.0, &KEY_FRAME, true);
+            // the offset of a block in its cluster fits in 16 bits
+            let max_offset_us = MAX_BLOCK_OFFSET_MS as f64 * 1000.0;
+            assert!(writer.frame(max_offset_us, &[0], false).is_empty());
+            let closed = writer.frame(max_offset_us + 1000.0, &[0], false);
+            assert_eq!(clusters(&closed), [vec![0, i16::MAX]]);
+            out.extend(closed);
+            out.extend(writer.finish());
+            assert_eq!(clusters(&out), [vec![0, i16::MAX], vec![0]]);
+        }
+
+        #[test]
+        fn cluster_at_key_frames() {
+            let mut writer = Vp9Writer::default();
+            writer.frame(0.0, &KEY_FRAME, true);
+            let cluster_us = CLUSTER_DURATION_MS as f64 * 1000.0;
+            // only a key frame starts a cluster, and only after its duration
+            assert!(writer.frame(1_000_000.0, &KEY_FRAME, true).is_empty());
+            assert!(writer.frame(cluster_us, &[0], false).is_empty());
+            let closed = writer.frame(cluster_us + 1000.0, &KEY_FRAME, true);
+            assert_eq!(clusters(&closed), [vec![0, 1000, 5000]]);
+            // a late frame does not go back in time
+            writer.frame(0.0, &[0], false);
+            assert_eq!(clusters(&writer.finish()), [vec![0, 0]]);
+        }
+    }
+}
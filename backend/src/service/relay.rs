@@ -0,0 +1,386 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use anyhow::{anyhow, bail, Result};
+use axum::async_trait;
+use bytes::Bytes;
+use futures::{stream::BoxStream, Stream, StreamExt};
+
+/// Message delivered to the members of a room
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub subject: String,
+    pub payload: Bytes,
+}
+
+#[derive(Clone)]
+pub struct Config {
+    pub backend: String,
+    pub nats_url: Option<String>,
+}
+
+/// Bus used to fan out the packets of a peer to the other members of its room.
+///
+/// Every peer publishes on its own subject `room.{id}.{username}` and receives everything
+/// published on `room.{id}.*`, except what was published on its own subject.
+#[async_trait]
+pub trait Relay: Send + Sync {
+    async fn publish(&self, subject: String, payload: Bytes) -> Result<()>;
+
+    async fn subscribe_room(&self, room_id: &str, own_subject: &str) -> Result<Subscription>;
+
+    /// Subscribes to a subject outside of the rooms, used by the backend instances to talk to
+    /// each other
+    async fn subscribe(&self, subject: &str) -> Result<Subscription>;
+
+    async fn unsubscribe(&self, subscription: Subscription) -> Result<()>;
+}
+
+pub async fn connect(Config { backend, nats_url }: Config) -> Result<Arc<dyn Relay>> {
+    match backend.as_str() {
+        "nats" => {
+            let url = nats_url.ok_or(anyhow!("RTJAM_NATS_URL is required by the nats relay"))?;
+            Ok(Arc::new(nats::Relay::connect(url).await?))
+        }
+        "memory" => Ok(Arc::new(memory::Relay::default())),
+        backend => bail!("unknown relay backend {backend}"),
+    }
+}
+
+pub fn room_subject(room_id: &str) -> String {
+    format!("room.{}.*", room_id).replace(' ', "_")
+}
+
+pub fn peer_subject(room_id: &str, username: &str) -> String {
+    format!("room.{}.{}", room_id, username).replace(' ', "_")
+}
+
+/// Subject on which the backend publishes the changes of the metronome of a room, no peer can
+/// publish on it since a username is made of letters, digits, `_` and `-` only
+pub fn transport_subject(room_id: &str) -> String {
+    peer_subject(room_id, "$transport")
+}
+
+/// Subject on which the relays publish the moderation actions they applied, so that the sender
+/// gets them as well
+pub fn moderation_subject(room_id: &str) -> String {
+    peer_subject(room_id, "$moderation")
+}
+
+/// Subject on which the sessions of a user in a room claim it, see [`super::presence::claim`]
+pub fn session_subject(room_id: &str, username: &str) -> String {
+    format!("relay.session.{}.{}", room_id, username).replace(' ', "_")
+}
+
+/// Returns the room id of a `room.{id}.{username}` subject
+pub fn room_of(subject: &str) -> Option<&str> {
+    let mut parts = subject.split('.');
+    match (parts.next(), parts.next()) {
+        (Some("room"), Some(room_id)) => Some(room_id),
+        _ => None,
+    }
+}
+
+/// Returns the username of a `room.{id}.{username}` subject
+pub fn username_of(subject: &str) -> Option<&str> {
+    let mut parts = subject.splitn(3, '.');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some("room"), Some(_), Some(username)) => Some(username),
+        _ => None,
+    }
+}
+
+/// Stream of the messages published in a room by the other peers
+pub struct Subscription {
+    room_id: String,
+    own_subject: String,
+    messages: BoxStream<'static, Message>,
+}
+
+impl Subscription {
+    fn new(room_id: &str, own_subject: &str, messages: BoxStream<'static, Message>) -> Self {
+        Self {
+            room_id: room_id.to_owned(),
+            own_subject: own_subject.to_owned(),
+            messages,
+        }
+    }
+
+    pub fn room_id(&self) -> &str {
+        &self.room_id
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Message;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.messages.poll_next_unpin(cx) {
+                // a peer does not receive what it publishes
+                Poll::Ready(Some(msg)) if msg.subject == self.own_subject => continue,
+                poll => return poll,
+            }
+        }
+    }
+}
+
+pub mod nats {
+    use super::*;
+    use crate::metrics;
+
+    pub struct Relay {
+        client: async_nats::Client,
+    }
+
+    impl Relay {
+        pub async fn connect(url: String) -> Result<Self> {
+            let client = async_nats::connect(url).await?;
+            Ok(Self { client })
+        }
+    }
+
+    #[async_trait]
+    impl super::Relay for Relay {
+        async fn publish(&self, subject: String, payload: Bytes) -> Result<()> {
+            if let Err(e) = self.client.publish(subject, payload).await {
+                metrics::get().publish_failed();
+                return Err(e.into());
+            }
+            Ok(())
+        }
+
+        async fn subscribe_room(&self, room_id: &str, own_subject: &str) -> Result<Subscription> {
+            let subscriber = self.client.subscribe(room_subject(room_id)).await?;
+            let messages = subscriber
+                .map(|msg| Message {
+                    subject: msg.subject.to_string(),
+                    payload: msg.payload,
+                })
+                .boxed();
+            Ok(Subscription::new(room_id, own_subject, messages))
+        }
+
+        async fn subscribe(&self, subject: &str) -> Result<Subscription> {
+            let subscriber = self.client.subscribe(subject.to_owned()).await?;
+            let messages = subscriber
+                .map(|msg| Message {
+                    subject: msg.subject.to_string(),
+                    payload: msg.payload,
+                })
+                .boxed();
+            Ok(Subscription::new(subject, "", messages))
+        }
+
+        async fn unsubscribe(&self, subscription: Subscription) -> Result<()> {
+            // dropping the subscriber sends the UNSUB to the server
+            drop(subscription);
+            Ok(())
+        }
+    }
+}
+
+pub mod memory {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use tokio::sync::broadcast::{self, error::RecvError};
+    use tracing::warn;
+
+    use super::*;
+
+    const CHANNEL_CAPACITY: usize = 1024;
+
+    /// Single node relay: every room is a broadcast channel living in this process
+    #[derive(Default)]
+    pub struct Relay {
+        channels: Mutex<HashMap<String, broadcast::Sender<Message>>>,
+    }
+
+    impl Relay {
+        /// Room subjects share the channel of their room, any other subject has its own
+        fn channel_of(subject: &str) -> &str {
+            room_of(subject).unwrap_or(subject)
+        }
+
+        fn subscribe_channel(&self, channel: &str, own_subject: &str) -> Subscription {
+            let receiver = self
+                .channels
+                .lock()
+                .unwrap()
+                .entry(channel.to_owned())
+                .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+                .subscribe();
+
+            let messages = futures::stream::unfold(receiver, |mut receiver| async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(msg) => return Some((msg, receiver)),
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Subscriber lagged behind, skipped {} messages", skipped);
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            })
+            .boxed();
+            Subscription::new(channel, own_subject, messages)
+        }
+    }
+
+    #[async_trait]
+    impl super::Relay for Relay {
+        async fn publish(&self, subject: String, payload: Bytes) -> Result<()> {
+            let sender = self
+                .channels
+                .lock()
+                .unwrap()
+                .get(Self::channel_of(&subject))
+                .cloned();
+            if let Some(sender) = sender {
+                // an error only means that nobody is listening anymore
+                let _ = sender.send(Message { subject, payload });
+            }
+            Ok(())
+        }
+
+        async fn subscribe_room(&self, room_id: &str, own_subject: &str) -> Result<Subscription> {
+            Ok(self.subscribe_channel(&room_id.replace(' ', "_"), own_subject))
+        }
+
+        async fn subscribe(&self, subject: &str) -> Result<Subscription> {
+            Ok(self.subscribe_channel(subject, ""))
+        }
+
+        async fn unsubscribe(&self, subscription: Subscription) -> Result<()> {
+            let channel = subscription.room_id().to_owned();
+            drop(subscription);
+
+            let mut channels = self.channels.lock().unwrap();
+            if channels
+                .get(&channel)
+                .is_some_and(|sender| sender.receiver_count() == 0)
+            {
+                channels.remove(&channel);
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use futures::FutureExt;
+
+        use super::super::Relay as _;
+        use super::*;
+
+        /// Returns the messages already delivered to the subscription, without waiting for more
+        fn drain(sub: &mut Subscription) -> Vec<Message> {
+            let mut messages = Vec::new();
+            while let Some(Some(msg)) = sub.next().now_or_never() {
+                messages.push(msg);
+            }
+            messages
+        }
+
+        fn subjects(messages: Vec<Message>) -> Vec<String> {
+            messages.into_iter().map(|msg| msg.subject).collect()
+        }
+
+        #[tokio::test]
+        async fn fans_out_to_the_room_only() {
+            let relay = Relay::default();
+            let mut alice = relay
+                .subscribe_room("r1", &peer_subject("r1", "alice"))
+                .await
+                .unwrap();
+            let mut bob = relay
+                .subscribe_room("r1", &peer_subject("r1", "bob"))
+                .await
+                .unwrap();
+            let mut carol = relay
+                .subscribe_room("r2", &peer_subject("r2", "carol"))
+                .await
+                .unwrap();
+
+            relay
+                .publish(peer_subject("r1", "alice"), Bytes::from_static(b"hi"))
+                .await
+                .unwrap();
+            relay
+                .publish(transport_subject("r1"), Bytes::from_static(b"tick"))
+                .await
+                .unwrap();
+
+            let delivered = drain(&mut bob);
+            assert_eq!(
+                subjects(delivered.clone()),
+                ["room.r1.alice", "room.r1.$transport"]
+            );
+            assert_eq!(delivered[0].payload, Bytes::from_static(b"hi"));
+            assert_eq!(subjects(drain(&mut carol)), Vec::<String>::new());
+            // a peer does not get back what it publishes, but gets what the backend publishes
+            assert_eq!(subjects(drain(&mut alice)), ["room.r1.$transport"]);
+        }
+
+        #[tokio::test]
+        async fn keeps_other_subjects_out_of_the_rooms() {
+            let relay = Relay::default();
+            let mut peer = relay
+                .subscribe_room("r1", &peer_subject("r1", "alice"))
+                .await
+                .unwrap();
+            let mut instance = relay.subscribe("relay.presence").await.unwrap();
+            let mut session = relay
+                .subscribe(&session_subject("r1", "alice"))
+                .await
+                .unwrap();
+
+            relay
+                .publish("relay.presence".to_owned(), Bytes::from_static(b"{}"))
+                .await
+                .unwrap();
+            relay
+                .publish(peer_subject("r1", "bob"), Bytes::from_static(b"hi"))
+                .await
+                .unwrap();
+
+            assert_eq!(subjects(drain(&mut peer)), ["room.r1.bob"]);
+            assert_eq!(subjects(drain(&mut instance)), ["relay.presence"]);
+            assert_eq!(subjects(drain(&mut session)), Vec::<String>::new());
+        }
+
+        #[tokio::test]
+        async fn unsubscribe_drops_the_channel_with_its_last_subscriber() {
+            let relay = Relay::default();
+            let alice = relay
+                .subscribe_room("r1", &peer_subject("r1", "alice"))
+                .await
+                .unwrap();
+            let mut bob = relay
+                .subscribe_room("r1", &peer_subject("r1", "bob"))
+                .await
+                .unwrap();
+
+            relay.unsubscribe(alice).await.unwrap();
+            assert!(relay.channels.lock().unwrap().contains_key("r1"));
+            relay
+                .publish(peer_subject("r1", "carol"), Bytes::from_static(b"hi"))
+                .await
+                .unwrap();
+            assert_eq!(subjects(drain(&mut bob)), ["room.r1.carol"]);
+
+            relay.unsubscribe(bob).await.unwrap();
+            assert!(relay.channels.lock().unwrap().is_empty());
+            // nobody is listening, the message is dropped
+            relay
+                .publish(peer_subject("r1", "carol"), Bytes::from_static(b"hi"))
+                .await
+                .unwrap();
+            assert!(relay.channels.lock().unwrap().is_empty());
+        }
+    }
+}
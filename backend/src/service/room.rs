@@ -1,6 +1,9 @@
+use std::collections::HashMap;
+
 use axum::extract::FromRef;
-use sqlx::{prelude::FromRow, PgPool};
-use time::PrimitiveDateTime;
+use serde::Deserialize;
+use sqlx::{prelude::FromRow, PgConnection, PgPool};
+use time::{OffsetDateTime, PrimitiveDateTime};
 use uuid::Uuid;
 
 pub(crate) use super::error::Error;
@@ -20,10 +23,105 @@ pub struct Room {
     pub private: bool,
     pub open: bool,
     pub max_people_playing: i32,
+    /// Media is end-to-end encrypted, the server cannot read it
+    pub encrypted: bool,
+    /// Beats per minute of the metronome, a beat is a 1/`beat_unit` note
+    pub tempo: f64,
+    pub beats_per_bar: i32,
+    pub beat_unit: i32,
+    /// Bars counted in whenever the transport starts
+    pub count_in: i32,
+    /// First downbeat after the count-in, `None` while the transport is stopped
+    pub transport_started_at: Option<OffsetDateTime>,
     pub created_at: PrimitiveDateTime,
     pub updated_at: PrimitiveDateTime,
 }
 
+/// A room about to be created by `owner`
+pub struct NewRoom {
+    pub owner: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub private: bool,
+    pub open: bool,
+    pub max_people_playing: i32,
+    pub encrypted: bool,
+}
+
+/// A user let into a private room by its owner
+#[derive(Debug, FromRow)]
+pub struct Member {
+    pub room_id: Uuid,
+    pub username: String,
+    pub added_by: String,
+    pub added_at: OffsetDateTime,
+}
+
+/// A room `username` was let into or given a role in, as read back from its side
+#[derive(Debug)]
+pub struct Membership {
+    pub room_id: Uuid,
+    pub room_name: String,
+    pub added_by: Option<String>,
+    pub added_at: Option<OffsetDateTime>,
+    pub role: Role,
+}
+
+/// Role of a participant in a room, ordered from the one with the least rights
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Receives the room but cannot publish media
+    Listener,
+    /// The role of anyone without one stored
+    Musician,
+    Moderator,
+    /// The owner of the room, never stored as a role
+    Owner,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Listener => "listener",
+            Role::Musician => "musician",
+            Role::Moderator => "moderator",
+            Role::Owner => "owner",
+        }
+    }
+
+    fn parse(role: &str) -> Self {
+        match role {
+            "listener" => Role::Listener,
+            "moderator" => Role::Moderator,
+            _ => Role::Musician,
+        }
+    }
+}
+
+/// Which rooms a user is shown when listing them. Private rooms are only listed to their owner
+/// and members.
+#[derive(Debug, Default)]
+pub struct Filter {
+    /// Only the rooms owned by the user
+    pub mine: bool,
+    /// Only the rooms that are not private
+    pub public: bool,
+    /// Only the rooms that are open
+    pub open: bool,
+    /// Text the name of the room contains, case insensitive
+    pub search: Option<String>,
+}
+
+/// Order of the rooms listed
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    /// Most recently created first
+    #[default]
+    Newest,
+    /// Alphabetically, case insensitive
+    Name,
+}
 
 impl Service {
     pub fn new(db: PgPool) -> Self {
@@ -35,45 +133,468 @@ impl Service {
 
 // crud operations
 impl Service {
-    pub async fn create(
+    pub async fn create(&self, room: NewRoom) -> Result<Room, Error> {
+        let id = Uuid::new_v4();
+        let room = sqlx::query_as!(
+            Room,
+            r#"INSERT INTO rooms (id, owner, name, description, private, open, max_people_playing, encrypted) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+            id,
+            room.owner,
+            room.name,
+            room.description,
+            room.private,
+            room.open,
+            room.max_people_playing,
+            room.encrypted
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+
+        Ok(room)
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<Room>, Error> {
+        let room = sqlx::query_as!(Room, "SELECT * FROM rooms WHERE id = $1", id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(room)
+    }
+
+    /// Returns up to `limit` rooms `username` can see, in the order `order`, starting right after
+    /// the room `after` when given
+    pub async fn list(
+        &self,
+        username: &str,
+        filter: &Filter,
+        order: Order,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<Room>, Error> {
+        // the text is matched literally, wildcards included
+        let search = filter.search.as_ref().map(|search| {
+            search
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        });
+        let rooms = match order {
+            Order::Newest => {
+                sqlx::query_as!(
+                    Room,
+                    r#"SELECT * FROM rooms
+                    WHERE (NOT private OR owner = $1
+                        OR EXISTS (SELECT 1 FROM room_members m WHERE m.room_id = rooms.id AND m.username = $1))
+                    AND (NOT $2 OR owner = $1)
+                    AND (NOT $3 OR NOT private)
+                    AND (NOT $4 OR open)
+                    AND ($5::text IS NULL OR name ILIKE '%' || $5 || '%')
+                    AND ($6::uuid IS NULL OR (created_at, id) < (SELECT created_at, id FROM rooms WHERE id = $6))
+                    ORDER BY created_at DESC, id DESC
+                    LIMIT $7
+                    "#,
+                    username,
+                    filter.mine,
+                    filter.public,
+                    filter.open,
+                    search,
+                    after,
+                    limit
+                )
+                .fetch_all(&self.db)
+                .await?
+            }
+            Order::Name => {
+                sqlx::query_as!(
+                    Room,
+                    r#"SELECT * FROM rooms
+                    WHERE (NOT private OR owner = $1
+                        OR EXISTS (SELECT 1 FROM room_members m WHERE m.room_id = rooms.id AND m.username = $1))
+                    AND (NOT $2 OR owner = $1)
+                    AND (NOT $3 OR NOT private)
+                    AND (NOT $4 OR open)
+                    AND ($5::text IS NULL OR name ILIKE '%' || $5 || '%')
+                    AND ($6::uuid IS NULL OR (lower(name), id) > (SELECT lower(name), id FROM rooms WHERE id = $6))
+                    ORDER BY lower(name), id
+                    LIMIT $7
+                    "#,
+                    username,
+                    filter.mine,
+                    filter.public,
+                    filter.open,
+                    search,
+                    after,
+                    limit
+                )
+                .fetch_all(&self.db)
+                .await?
+            }
+        };
+
+        Ok(rooms)
+    }
+
+    /// Changes the settings of the room, returns the room updated or `None` if it does not exist
+    pub async fn update(
         &self,
-        owner: String,
+        id: Uuid,
         name: String,
         description: Option<String>,
         private: bool,
         open: bool,
         max_people_playing: i32,
-    ) -> Result<Room, Error> {
-        let id = Uuid::new_v4();
+    ) -> Result<Option<Room>, Error> {
         let room = sqlx::query_as!(
             Room,
-            r#"INSERT INTO rooms (id, owner, name, description, private, open, max_people_playing) 
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            r#"UPDATE rooms
+            SET name = $2, description = $3, private = $4, open = $5, max_people_playing = $6,
+                updated_at = now()
+            WHERE id = $1
             RETURNING *
             "#,
             id,
-            owner,
             name,
             description,
             private,
             open,
             max_people_playing
         )
-        .fetch_one(&self.db)
+        .fetch_optional(&self.db)
         .await?;
 
-
         Ok(room)
     }
 
-    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<Room>, Error> {
-        let room = sqlx::query_as!(Room, "SELECT * FROM rooms WHERE id = $1", id)
-            .fetch_optional(&self.db)
-            .await?;
+    /// Sets the metronome of the room, returns the room updated or `None` if it does not exist
+    pub async fn update_transport(
+        &self,
+        id: Uuid,
+        tempo: f64,
+        beats_per_bar: i32,
+        beat_unit: i32,
+        count_in: i32,
+        started_at: Option<OffsetDateTime>,
+    ) -> Result<Option<Room>, Error> {
+        let room = sqlx::query_as!(
+            Room,
+            r#"UPDATE rooms
+            SET tempo = $2, beats_per_bar = $3, beat_unit = $4, count_in = $5,
+                transport_started_at = $6, updated_at = now()
+            WHERE id = $1
+            RETURNING *
+            "#,
+            id,
+            tempo,
+            beats_per_bar,
+            beat_unit,
+            count_in,
+            started_at
+        )
+        .fetch_optional(&self.db)
+        .await?;
 
         Ok(room)
     }
 
+    /// Whether `username` can see and join the room: anyone can join a public room, only the
+    /// owner and the members a private one
+    pub async fn can_join(&self, room: &Room, username: &str) -> Result<bool, Error> {
+        if room.owner == username {
+            return Ok(true);
+        }
+        if self.banned_until(room.id, username).await?.is_some() {
+            return Ok(false);
+        }
+        if !room.private {
+            return Ok(true);
+        }
+        self.is_member(room.id, username).await
+    }
+
+    /// Keeps `username` out of the room until `expires_at`, a later ban replaces the previous one
+    pub async fn ban(
+        &self,
+        id: Uuid,
+        username: &str,
+        banned_by: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"INSERT INTO room_bans (room_id, username, banned_by, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (room_id, username)
+            DO UPDATE SET banned_by = EXCLUDED.banned_by, expires_at = EXCLUDED.expires_at
+            "#,
+            id,
+            username,
+            banned_by,
+            expires_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns when the ban of `username` from the room expires, `None` if it is not banned
+    pub async fn banned_until(
+        &self,
+        id: Uuid,
+        username: &str,
+    ) -> Result<Option<OffsetDateTime>, Error> {
+        let ban = sqlx::query!(
+            r#"SELECT expires_at FROM room_bans
+            WHERE room_id = $1 AND username = $2 AND expires_at > now()
+            "#,
+            id,
+            username
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(ban.map(|ban| ban.expires_at))
+    }
+
+    pub async fn is_member(&self, id: Uuid, username: &str) -> Result<bool, Error> {
+        let member = sqlx::query!(
+            "SELECT 1 AS one FROM room_members WHERE room_id = $1 AND username = $2",
+            id,
+            username
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(member.is_some())
+    }
+
+    pub async fn members(&self, id: Uuid) -> Result<Vec<Member>, Error> {
+        let members = sqlx::query_as!(
+            Member,
+            "SELECT * FROM room_members WHERE room_id = $1 ORDER BY added_at, username",
+            id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(members)
+    }
+
+    /// Lets `username` into the room, returns the membership, which is left as it is if the user
+    /// is already a member
+    pub async fn add_member(
+        &self,
+        id: Uuid,
+        username: &str,
+        added_by: &str,
+    ) -> Result<Member, Error> {
+        let member = sqlx::query_as!(
+            Member,
+            r#"INSERT INTO room_members (room_id, username, added_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (room_id, username) DO UPDATE SET room_id = EXCLUDED.room_id
+            RETURNING *
+            "#,
+            id,
+            username,
+            added_by
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(member)
+    }
+
+    /// Returns whether the user was a member of the room
+    pub async fn remove_member(&self, id: Uuid, username: &str) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "DELETE FROM room_members WHERE room_id = $1 AND username = $2",
+            id,
+            username
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns the roles stored for the room, the owner and the users without a role are missing
+    pub async fn roles(&self, id: Uuid) -> Result<HashMap<String, Role>, Error> {
+        let roles = sqlx::query!(
+            "SELECT username, role FROM room_roles WHERE room_id = $1",
+            id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(roles
+            .into_iter()
+            .map(|row| (row.username, Role::parse(&row.role)))
+            .collect())
+    }
+
+    pub async fn role_of(&self, room: &Room, username: &str) -> Result<Role, Error> {
+        if room.owner == username {
+            return Ok(Role::Owner);
+        }
+        let role = sqlx::query!(
+            "SELECT role FROM room_roles WHERE room_id = $1 AND username = $2",
+            room.id,
+            username
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(role.map_or(Role::Musician, |row| Role::parse(&row.role)))
+    }
+
+    /// Stores the role of `username` in the room, which cannot be [`Role::Owner`]
+    pub async fn set_role(&self, id: Uuid, username: &str, role: Role) -> Result<(), Error> {
+        sqlx::query!(
+            r#"INSERT INTO room_roles (room_id, username, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (room_id, username) DO UPDATE SET role = EXCLUDED.role, updated_at = now()
+            "#,
+            id,
+            username,
+            role.as_str()
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Makes `to` the owner of the room in place of `from`, who becomes a moderator and stays a
+    /// member of a private room. Returns whether `from` owned the room.
+    pub async fn transfer_ownership(&self, id: Uuid, from: &str, to: &str) -> Result<bool, Error> {
+        let mut tx = self.db.begin().await?;
+        let transferred = self.transfer_ownership_in(&mut tx, id, from, to).await?;
+        tx.commit().await?;
+
+        Ok(transferred)
+    }
+
+    /// Same as [`Service::transfer_ownership`], within the transaction of the caller
+    pub async fn transfer_ownership_in(
+        &self,
+        tx: &mut PgConnection,
+        id: Uuid,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "UPDATE rooms SET owner = $3, updated_at = now() WHERE id = $1 AND owner = $2",
+            id,
+            from,
+            to
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query!(
+            "DELETE FROM room_roles WHERE room_id = $1 AND username = $2",
+            id,
+            to
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO room_roles (room_id, username, role)
+            VALUES ($1, $2, 'moderator')
+            ON CONFLICT (room_id, username) DO UPDATE SET role = EXCLUDED.role, updated_at = now()
+            "#,
+            id,
+            from
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO room_members (room_id, username, added_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (room_id, username) DO NOTHING
+            "#,
+            id,
+            from,
+            to
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(true)
+    }
+
+    pub async fn owned_by(&self, username: &str) -> Result<Vec<Room>, Error> {
+        let rooms = sqlx::query_as!(
+            Room,
+            "SELECT * FROM rooms WHERE owner = $1 ORDER BY created_at, id",
+            username
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rooms)
+    }
+
+    /// Returns the rooms `username` is a member of or has a role in, the owned ones excluded
+    pub async fn memberships(&self, username: &str) -> Result<Vec<Membership>, Error> {
+        let rows = sqlx::query!(
+            r#"SELECT r.id, r.name, m.added_by AS "added_by?", m.added_at AS "added_at?", ro.role AS "role?"
+            FROM rooms r
+            LEFT JOIN room_members m ON m.room_id = r.id AND m.username = $1
+            LEFT JOIN room_roles ro ON ro.room_id = r.id AND ro.username = $1
+            WHERE r.owner <> $1 AND (m.username IS NOT NULL OR ro.username IS NOT NULL)
+            ORDER BY r.created_at, r.id
+            "#,
+            username
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Membership {
+                room_id: row.id,
+                room_name: row.name,
+                added_by: row.added_by,
+                added_at: row.added_at,
+                role: row.role.as_deref().map_or(Role::Musician, Role::parse),
+            })
+            .collect())
+    }
+
+    /// Returns who takes the room over when its owner leaves: the moderator with the role for
+    /// the longest time, otherwise the earliest member, the listeners last. `None` when nobody
+    /// else is left.
+    pub async fn successor(&self, room: &Room) -> Result<Option<String>, Error> {
+        let successor = sqlx::query!(
+            r#"SELECT c.username AS "username!" FROM (
+                SELECT username, 0 AS rank, updated_at AS since FROM room_roles
+                WHERE room_id = $1 AND role = 'moderator'
+                UNION ALL
+                SELECT m.username, CASE WHEN ro.role = 'listener' THEN 2 ELSE 1 END, m.added_at
+                FROM room_members m
+                LEFT JOIN room_roles ro ON ro.room_id = m.room_id AND ro.username = m.username
+                WHERE m.room_id = $1
+            ) c
+            WHERE c.username <> $2
+            ORDER BY c.rank, c.since, c.username
+            LIMIT 1
+            "#,
+            room.id,
+            room.owner
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(successor.map(|row| row.username))
+    }
+
     pub async fn delete(&self, id: Uuid) -> Result<(), Error> {
         sqlx::query!(r#"DELETE FROM rooms WHERE id = $1"#, id)
             .execute(&self.db)
@@ -81,4 +602,13 @@ impl Service {
 
         Ok(())
     }
+
+    /// Same as [`Service::delete`], within the transaction of the caller
+    pub async fn delete_in(&self, tx: &mut PgConnection, id: Uuid) -> Result<(), Error> {
+        sqlx::query!(r#"DELETE FROM rooms WHERE id = $1"#, id)
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,518 @@
+use std::collections::HashMap;
+
+use sqlx::{prelude::FromRow, PgPool};
+use time::{Duration, OffsetDateTime};
+use tracing::error;
+use uuid::Uuid;
+
+use super::{email, error::Result, user::session};
+
+/// How long before an occurrence its attendees are reminded of it
+const REMINDER_LEAD: Duration = Duration::hours(1);
+
+/// How often a planned session repeats, with the same length
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+}
+
+impl Recurrence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Recurrence::Daily => "daily",
+            Recurrence::Weekly => "weekly",
+        }
+    }
+
+    pub fn parse(recurrence: &str) -> Option<Self> {
+        match recurrence {
+            "daily" => Some(Recurrence::Daily),
+            "weekly" => Some(Recurrence::Weekly),
+            _ => None,
+        }
+    }
+
+    fn period(&self) -> Duration {
+        match self {
+            Recurrence::Daily => Duration::days(1),
+            Recurrence::Weekly => Duration::weeks(1),
+        }
+    }
+}
+
+/// Whether a user will attend a planned session
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Response {
+    Yes,
+    No,
+    Maybe,
+}
+
+impl Response {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Response::Yes => "yes",
+            Response::No => "no",
+            Response::Maybe => "maybe",
+        }
+    }
+
+    pub fn parse(response: &str) -> Option<Self> {
+        match response {
+            "yes" => Some(Response::Yes),
+            "no" => Some(Response::No),
+            "maybe" => Some(Response::Maybe),
+            _ => None,
+        }
+    }
+}
+
+/// A session planned in a room. A recurring one repeats until `repeat_until`, which bounds the
+/// start of its occurrences, or forever.
+#[derive(Debug, FromRow)]
+pub struct Schedule {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub title: String,
+    pub starts_at: OffsetDateTime,
+    pub ends_at: OffsetDateTime,
+    /// `daily` or `weekly`, `None` for a session that does not repeat
+    pub recurrence: Option<String>,
+    pub repeat_until: Option<OffsetDateTime>,
+    /// The room is open only while one of its sessions with the flag is in progress
+    pub opens_room: bool,
+    /// Start of the last occurrence the attendees were reminded of
+    pub reminded_for: Option<OffsetDateTime>,
+    pub created_by: String,
+    pub created_at: OffsetDateTime,
+}
+
+impl Schedule {
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        self.recurrence.as_deref().and_then(Recurrence::parse)
+    }
+
+    /// Returns the start and the end of the first occurrence that has not ended at `at`, `None`
+    /// once the schedule is over
+    pub fn next_occurrence(&self, at: OffsetDateTime) -> Option<(OffsetDateTime, OffsetDateTime)> {
+        let length = self.ends_at - self.starts_at;
+        let Some(period) = self.recurrence().map(|recurrence| recurrence.period()) else {
+            return (self.ends_at > at).then_some((self.starts_at, self.ends_at));
+        };
+        let mut start = self.starts_at;
+        if at >= self.ends_at {
+            // the occurrences ended by `at` are skipped
+            let skipped = (at - self.ends_at).whole_seconds() / period.whole_seconds() + 1;
+            start += period * skipped as i32;
+        }
+        if self.repeat_until.is_some_and(|until| start > until) {
+            return None;
+        }
+        Some((start, start + length))
+    }
+
+    pub fn in_progress(&self, at: OffsetDateTime) -> bool {
+        self.next_occurrence(at)
+            .is_some_and(|(start, _)| start <= at)
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct Rsvp {
+    pub schedule_id: Uuid,
+    pub username: String,
+    pub response: String,
+    pub updated_at: OffsetDateTime,
+}
+
+/// Someone who answered yes or maybe, told by email about the session
+#[derive(Debug, FromRow)]
+pub struct Attendee {
+    pub email: String,
+    pub first_name: String,
+}
+
+/// A schedule in the calendar feed of a user, along with the name of its room
+#[derive(Debug)]
+pub struct Entry {
+    pub schedule: Schedule,
+    pub room_name: String,
+}
+
+#[derive(Clone)]
+pub struct Service {
+    db: PgPool,
+    email_service: email::Service,
+}
+
+impl Service {
+    pub fn new(db: PgPool, email_service: email::Service) -> Self {
+        Self { db, email_service }
+    }
+}
+
+impl Service {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        &self,
+        room_id: Uuid,
+        created_by: &str,
+        title: String,
+        starts_at: OffsetDateTime,
+        ends_at: OffsetDateTime,
+        recurrence: Option<Recurrence>,
+        repeat_until: Option<OffsetDateTime>,
+        opens_room: bool,
+    ) -> Result<Schedule> {
+        let schedule = sqlx::query_as!(
+            Schedule,
+            r#"INSERT INTO room_schedules
+            (id, room_id, title, starts_at, ends_at, recurrence, repeat_until, opens_room, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
+            Uuid::new_v4(),
+            room_id,
+            title,
+            starts_at,
+            ends_at,
+            recurrence.map(|recurrence| recurrence.as_str()),
+            repeat_until,
+            opens_room,
+            created_by
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    pub async fn get(&self, room_id: Uuid, id: Uuid) -> Result<Option<Schedule>> {
+        let schedule = sqlx::query_as!(
+            Schedule,
+            "SELECT * FROM room_schedules WHERE room_id = $1 AND id = $2",
+            room_id,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    /// Returns the schedules of the room, the first to start first
+    pub async fn list(&self, room_id: Uuid) -> Result<Vec<Schedule>> {
+        let schedules = sqlx::query_as!(
+            Schedule,
+            "SELECT * FROM room_schedules WHERE room_id = $1 ORDER BY starts_at",
+            room_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(schedules)
+    }
+
+    /// Returns the answers given to the schedules, grouped by schedule
+    pub async fn rsvps(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Rsvp>>> {
+        let rsvps = sqlx::query_as!(
+            Rsvp,
+            r#"SELECT * FROM schedule_rsvps
+            WHERE schedule_id = ANY($1)
+            ORDER BY updated_at
+            "#,
+            ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut grouped = HashMap::<Uuid, Vec<Rsvp>>::new();
+        for rsvp in rsvps {
+            grouped.entry(rsvp.schedule_id).or_default().push(rsvp);
+        }
+        Ok(grouped)
+    }
+
+    /// Returns the answers given by `username`, to the schedules of any room
+    pub async fn rsvps_of(&self, username: &str) -> Result<Vec<Rsvp>> {
+        let rsvps = sqlx::query_as!(
+            Rsvp,
+            "SELECT * FROM schedule_rsvps WHERE username = $1 ORDER BY updated_at",
+            username
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rsvps)
+    }
+
+    /// Stores the answer of `username`, in place of the one given before
+    pub async fn respond(&self, id: Uuid, username: &str, response: Response) -> Result<Rsvp> {
+        let rsvp = sqlx::query_as!(
+            Rsvp,
+            r#"INSERT INTO schedule_rsvps (schedule_id, username, response)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (schedule_id, username)
+            DO UPDATE SET response = EXCLUDED.response, updated_at = now()
+            RETURNING *
+            "#,
+            id,
+            username,
+            response.as_str()
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(rsvp)
+    }
+
+    /// Returns who answered yes or maybe
+    pub async fn attendees(&self, id: Uuid) -> Result<Vec<Attendee>> {
+        let attendees = sqlx::query_as!(
+            Attendee,
+            r#"SELECT u.email, u.first_name FROM schedule_rsvps r
+            JOIN users u ON u.username = r.username
+            WHERE r.schedule_id = $1 AND r.response IN ('yes', 'maybe') AND u.enabled
+            "#,
+            id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(attendees)
+    }
+
+    /// Returns the schedules of the rooms `username` owns or is a member of, and the ones it
+    /// answered yes or maybe to
+    pub async fn feed(&self, username: &str) -> Result<Vec<Entry>> {
+        let rows = sqlx::query!(
+            r#"SELECT s.*, r.name AS room_name FROM room_schedules s
+            JOIN rooms r ON r.id = s.room_id
+            WHERE r.owner = $1
+                OR EXISTS (SELECT 1 FROM room_members m WHERE m.room_id = r.id AND m.username = $1)
+                OR EXISTS (SELECT 1 FROM schedule_rsvps a
+                    WHERE a.schedule_id = s.id AND a.username = $1 AND a.response IN ('yes', 'maybe'))
+            ORDER BY s.starts_at
+            "#,
+            username
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Entry {
+                schedule: Schedule {
+                    id: row.id,
+                    room_id: row.room_id,
+                    title: row.title,
+                    starts_at: row.starts_at,
+                    ends_at: row.ends_at,
+                    recurrence: row.recurrence,
+                    repeat_until: row.repeat_until,
+                    opens_room: row.opens_room,
+                    reminded_for: row.reminded_for,
+                    created_by: row.created_by,
+                    created_at: row.created_at,
+                },
+                room_name: row.room_name,
+            })
+            .collect())
+    }
+
+    /// Returns the token of the calendar feed of `username`, created the first time
+    pub async fn feed_token(&self, username: &str) -> Result<String> {
+        let feed = sqlx::query!(
+            r#"INSERT INTO calendar_feeds (username, token)
+            VALUES ($1, $2)
+            ON CONFLICT (username) DO UPDATE SET username = EXCLUDED.username
+            RETURNING token
+            "#,
+            username,
+            session::Service::generate_token()
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(feed.token)
+    }
+
+    /// Replaces the token of the calendar feed of `username`, the former link stops working
+    pub async fn rotate_feed_token(&self, username: &str) -> Result<String> {
+        let feed = sqlx::query!(
+            r#"INSERT INTO calendar_feeds (username, token)
+            VALUES ($1, $2)
+            ON CONFLICT (username) DO UPDATE SET token = EXCLUDED.token, created_at = now()
+            RETURNING token
+            "#,
+            username,
+            session::Service::generate_token()
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(feed.token)
+    }
+
+    /// Returns the user the feed token belongs to
+    pub async fn feed_owner(&self, token: &str) -> Result<Option<String>> {
+        let feed = sqlx::query!(
+            "SELECT username FROM calendar_feeds WHERE token = $1",
+            token
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(feed.map(|feed| feed.username))
+    }
+
+    /// Deletes the schedule and tells whoever answered yes or maybe, unless it is over. Returns
+    /// whether the room had the schedule.
+    pub async fn cancel(&self, schedule: Schedule, room_name: String) -> Result<bool> {
+        // the answers are gone along with the schedule
+        let attendees = match schedule.next_occurrence(OffsetDateTime::now_utc()) {
+            Some(_) => self.attendees(schedule.id).await?,
+            None => Vec::new(),
+        };
+        let result = sqlx::query!(
+            "DELETE FROM room_schedules WHERE room_id = $1 AND id = $2",
+            schedule.room_id,
+            schedule.id
+        )
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        let email_service = self.email_service.clone();
+        tokio::spawn(async move {
+            for attendee in attendees {
+                if let Err(e) = email_service
+                    .send_session_cancelled(
+                        &attendee.email,
+                        &attendee.first_name,
+                        &schedule.title,
+                        &room_name,
+                    )
+                    .await
+                {
+                    error!("Error sending the cancellation of {}: {}", schedule.id, e);
+                }
+            }
+        });
+        Ok(true)
+    }
+
+    /// Every `period` reminds the attendees of the occurrences about to start, and opens or closes
+    /// the rooms that follow their schedules
+    pub async fn continously_follow_schedules(self, period: tokio::time::Duration) -> Result<()> {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.follow_schedules().await {
+                error!("Error following the schedules: {}", e);
+            }
+        }
+    }
+
+    async fn follow_schedules(&self) -> Result<()> {
+        let now = OffsetDateTime::now_utc();
+        // the ones opening their room and the ones starting within the lead of a reminder, either
+        // for the first time or, for a recurring one, a period after its last start
+        let schedules = sqlx::query_as!(
+            Schedule,
+            r#"SELECT * FROM room_schedules
+            WHERE opens_room
+                OR (starts_at > $1 AND starts_at <= $1 + $2::bigint * interval '1 second')
+                OR (recurrence IS NOT NULL AND starts_at <= $1
+                    AND (repeat_until IS NULL OR repeat_until > $1)
+                    AND extract(epoch FROM $1 - starts_at)::bigint
+                        % CASE recurrence WHEN 'daily' THEN $3::bigint ELSE $4::bigint END
+                        >= CASE recurrence WHEN 'daily' THEN $3 ELSE $4 END - $2)
+            "#,
+            now,
+            REMINDER_LEAD.whole_seconds(),
+            Recurrence::Daily.period().whole_seconds(),
+            Recurrence::Weekly.period().whole_seconds()
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut open = HashMap::<Uuid, bool>::new();
+        for schedule in schedules.iter().filter(|schedule| schedule.opens_room) {
+            *open.entry(schedule.room_id).or_default() |= schedule.in_progress(now);
+        }
+        // a failing room or schedule does not hold back the others
+        for (room_id, open) in open {
+            if let Err(e) = sqlx::query!(
+                "UPDATE rooms SET open = $2, updated_at = now() WHERE id = $1 AND open <> $2",
+                room_id,
+                open
+            )
+            .execute(&self.db)
+            .await
+            {
+                error!("Error opening or closing room {}: {}", room_id, e);
+            }
+        }
+
+        for schedule in &schedules {
+            let Some((start, _)) = schedule.next_occurrence(now) else {
+                continue;
+            };
+            if start > now && start - now <= REMINDER_LEAD && schedule.reminded_for != Some(start) {
+                if let Err(e) = self.remind(schedule, start).await {
+                    error!("Error reminding the attendees of {}: {}", schedule.id, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn remind(&self, schedule: &Schedule, start: OffsetDateTime) -> Result<()> {
+        // claimed first, another instance of the backend may be following the same schedule
+        let claimed = sqlx::query!(
+            r#"UPDATE room_schedules SET reminded_for = $2
+            WHERE id = $1 AND reminded_for IS DISTINCT FROM $2
+            "#,
+            schedule.id,
+            start
+        )
+        .execute(&self.db)
+        .await?;
+        if claimed.rows_affected() == 0 {
+            return Ok(());
+        }
+
+        let room = sqlx::query!("SELECT name FROM rooms WHERE id = $1", schedule.room_id)
+            .fetch_one(&self.db)
+            .await?;
+        let attendees = self.attendees(schedule.id).await?;
+        let email_service = self.email_service.clone();
+        let (id, title, path) = (
+            schedule.id,
+            schedule.title.clone(),
+            format!("/session/{}", schedule.room_id),
+        );
+        tokio::spawn(async move {
+            for attendee in attendees {
+                if let Err(e) = email_service
+                    .send_session_reminder(
+                        &attendee.email,
+                        &attendee.first_name,
+                        &title,
+                        &room.name,
+                        start,
+                        &path,
+                    )
+                    .await
+                {
+                    error!("Error sending the reminder of {}: {}", id, e);
+                }
+            }
+        });
+        Ok(())
+    }
+}
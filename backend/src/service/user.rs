@@ -2,7 +2,7 @@ use argon2::{password_hash::SaltString, Argon2, PasswordVerifier};
 use base64::{engine::general_purpose, Engine};
 use rand_core::OsRng;
 use sqlx::prelude::FromRow;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use time::{OffsetDateTime, PrimitiveDateTime};
 use uuid::Uuid;
 
@@ -193,6 +193,84 @@ pub mod auth {
             Ok(())
         }
 
+        /// Looks up an enabled user by username or, when `username` is `None`, by email
+        pub async fn find(
+            &self,
+            username: Option<&str>,
+            email: Option<&str>,
+        ) -> Result<Option<User>> {
+            let user = sqlx::query_as!(
+                User,
+                r#"SELECT * FROM users
+                WHERE enabled = TRUE AND (username = $1 OR ($1::text IS NULL AND email = $2))
+                "#,
+                username,
+                email
+            )
+            .fetch_optional(&self.db)
+            .await?;
+
+            Ok(user)
+        }
+
+        pub async fn update_profile(
+            &self,
+            id: Uuid,
+            first_name: String,
+            last_name: String,
+        ) -> Result<User> {
+            let user = sqlx::query_as!(
+                User,
+                r#"UPDATE users SET
+                first_name = $2,
+                last_name = $3,
+                updated_at = now()
+                WHERE id = $1
+                RETURNING *
+                "#,
+                id,
+                first_name,
+                last_name
+            )
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or(Error::NoAuth)?;
+
+            Ok(user)
+        }
+
+        /// Replaces the password of the user, who must know the current one
+        pub async fn update_password(
+            &self,
+            username: String,
+            current_password: String,
+            password: String,
+        ) -> Result<()> {
+            let user = self.login(username, current_password).await?;
+            let password = Self::hash_password(password)?;
+
+            sqlx::query!(
+                "UPDATE users SET password = $2, updated_at = now() WHERE id = $1",
+                user.id,
+                password
+            )
+            .execute(&self.db)
+            .await?;
+
+            Ok(())
+        }
+
+        /// Deletes the user along with its memberships, roles, answers and calendar feed, within
+        /// the transaction of the caller. The rooms it owns must have been handed over or deleted
+        /// before.
+        pub async fn delete_in(&self, tx: &mut PgConnection, id: Uuid) -> Result<()> {
+            sqlx::query!("DELETE FROM users WHERE id = $1", id)
+                .execute(tx)
+                .await?;
+
+            Ok(())
+        }
+
         fn hash_password(plain_text: String) -> Result<String> {
             let argon2 = Argon2::default();
             let salt = SaltString::generate(&mut OsRng);
@@ -208,6 +286,7 @@ pub mod session {
     use rand_chacha::ChaCha8Rng;
     use rand_core::{RngCore, SeedableRng};
     use serde::{Deserialize, Serialize};
+    use time::Duration;
 
     use super::*;
 
@@ -222,10 +301,47 @@ pub mod session {
         }
     }
 
+    /// How long a remembered session lasts without being used
+    pub const REMEMBERED_LIFETIME: Duration = Duration::days(30);
+    /// How long any other session lasts without being used, its cookie is also dropped when the
+    /// browser is closed
+    pub const LIFETIME: Duration = Duration::days(1);
+    /// Least time between two updates of when a session was last seen
+    const LAST_SEEN_PERIOD: Duration = Duration::minutes(1);
+
     pub struct Session {
+        /// The token in the cookie, never shown
         pub id: String,
         pub data: Vec<u8>,
         pub expiry_date: OffsetDateTime,
+        /// Stands for the session when it is listed or signed out
+        pub public_id: Uuid,
+        pub remember: bool,
+        pub user_agent: Option<String>,
+        pub ip: Option<String>,
+        pub created_at: OffsetDateTime,
+        pub last_seen_at: OffsetDateTime,
+    }
+
+    impl Session {
+        pub fn data(&self) -> Result<SessionData> {
+            serde_json::from_slice(&self.data).map_err(Error::SerializationError)
+        }
+
+        /// Time the session lasts from its last use
+        pub fn lifetime(&self) -> Duration {
+            if self.remember {
+                REMEMBERED_LIFETIME
+            } else {
+                LIFETIME
+            }
+        }
+    }
+
+    pub struct JoinTicket {
+        pub id: String,
+        pub session_id: String,
+        pub expiry_date: OffsetDateTime,
     }
 
     #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -238,47 +354,86 @@ pub mod session {
     }
 
     impl Service {
+        /// Signs a user in from the client described by `user_agent` and `ip`
         pub async fn create(
             &self,
             token: &str,
             data: &SessionData,
-            expiry_date: OffsetDateTime,
+            remember: bool,
+            user_agent: Option<&str>,
+            ip: Option<&str>,
         ) -> Result<Session> {
+            let lifetime = if remember {
+                REMEMBERED_LIFETIME
+            } else {
+                LIFETIME
+            };
             let session = sqlx::query_as!(
                 Session,
-                r#"INSERT INTO sessions (id, data, expiry_date) 
-            VALUES ($1, $2, $3)
+                r#"INSERT INTO sessions (id, data, expiry_date, user_id, public_id, remember, user_agent, ip)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             ON CONFLICT (id) DO UPDATE SET 
                 data = excluded.data,
                 expiry_date = excluded.expiry_date
-            RETURNING *
+            RETURNING id, data, expiry_date, public_id, remember, user_agent, ip, created_at, last_seen_at
             "#,
                 token,
                 serde_json::to_vec(data).map_err(|e| Error::SerializationError(e))?,
-                expiry_date
+                OffsetDateTime::now_utc() + lifetime,
+                data.id,
+                Uuid::new_v4(),
+                remember,
+                user_agent,
+                ip
             )
             .fetch_one(&self.db)
             .await?;
             Ok(session)
         }
-        pub async fn get(&self, token: String) -> Result<Option<SessionData>> {
+
+        pub async fn find(&self, token: &str) -> Result<Option<Session>> {
             let session = sqlx::query_as!(
                 Session,
-                r#"SELECT * FROM sessions WHERE id = $1 AND expiry_date > $2"#,
+                r#"SELECT id, data, expiry_date, public_id, remember, user_agent, ip, created_at, last_seen_at
+                FROM sessions WHERE id = $1 AND expiry_date > $2
+                "#,
                 token,
                 OffsetDateTime::now_utc()
             )
             .fetch_optional(&self.db)
             .await?;
 
-            if let Some(session) = session {
-                return Ok(Some(
-                    serde_json::from_slice(&session.data)
-                        .map_err(|e| Error::SerializationError(e))?,
-                ));
+            Ok(session)
+        }
+
+        pub async fn get(&self, token: String) -> Result<Option<SessionData>> {
+            match self.find(&token).await? {
+                Some(session) => Ok(Some(session.data()?)),
+                None => Ok(None),
+            }
+        }
+
+        /// Marks the session as seen now and pushes its expiry back by its lifetime. The session
+        /// is written at most once per [`LAST_SEEN_PERIOD`], returns the new expiry when it was.
+        pub async fn touch(&self, session: &Session) -> Result<Option<OffsetDateTime>> {
+            let now = OffsetDateTime::now_utc();
+            if now - session.last_seen_at < LAST_SEEN_PERIOD {
+                return Ok(None);
             }
+            let touched = sqlx::query!(
+                r#"UPDATE sessions SET last_seen_at = $2, expiry_date = $3
+                WHERE id = $1 AND last_seen_at < $4
+                RETURNING expiry_date
+                "#,
+                session.id,
+                now,
+                now + session.lifetime(),
+                now - LAST_SEEN_PERIOD
+            )
+            .fetch_optional(&self.db)
+            .await?;
 
-            Ok(None)
+            Ok(touched.map(|row| row.expiry_date))
         }
 
         pub async fn delete(&self, token: &str) -> Result<()> {
@@ -289,6 +444,124 @@ pub mod session {
             Ok(())
         }
 
+        /// Signs the user out of the session listed as `public_id`, returns the token of the
+        /// session or `None` if it was not one of its sessions
+        pub async fn delete_of(&self, user_id: Uuid, public_id: Uuid) -> Result<Option<String>> {
+            let session = sqlx::query!(
+                "DELETE FROM sessions WHERE user_id = $1 AND public_id = $2 RETURNING id",
+                user_id,
+                public_id
+            )
+            .fetch_optional(&self.db)
+            .await?;
+
+            Ok(session.map(|session| session.id))
+        }
+
+        /// Returns the sessions of the user that have not expired, the most recently used first
+        pub async fn list_of(&self, user_id: Uuid) -> Result<Vec<Session>> {
+            let sessions = sqlx::query_as!(
+                Session,
+                r#"SELECT id, data, expiry_date, public_id, remember, user_agent, ip, created_at, last_seen_at
+                FROM sessions
+                WHERE user_id = $1 AND expiry_date > $2
+                ORDER BY last_seen_at DESC
+                "#,
+                user_id,
+                OffsetDateTime::now_utc()
+            )
+            .fetch_all(&self.db)
+            .await?;
+
+            Ok(sessions)
+        }
+
+        /// Stores `data` in every session of its user, which sees the change on its next request
+        pub async fn refresh(&self, data: &SessionData) -> Result<()> {
+            sqlx::query!(
+                "UPDATE sessions SET data = $2 WHERE user_id = $1",
+                data.id,
+                serde_json::to_vec(data).map_err(Error::SerializationError)?
+            )
+            .execute(&self.db)
+            .await?;
+
+            Ok(())
+        }
+
+        /// Signs the user out of every session but the one of `token`, when given
+        pub async fn delete_all_of(&self, user_id: Uuid, token: Option<&str>) -> Result<()> {
+            self.delete_all_of_in(&mut *self.db.acquire().await?, user_id, token)
+                .await
+        }
+
+        /// Same as [`Service::delete_all_of`], within the transaction of the caller
+        pub async fn delete_all_of_in(
+            &self,
+            tx: &mut PgConnection,
+            user_id: Uuid,
+            token: Option<&str>,
+        ) -> Result<()> {
+            sqlx::query!(
+                r#"DELETE FROM sessions
+                WHERE user_id = $1 AND ($2::text IS NULL OR id <> $2)
+                "#,
+                user_id,
+                token
+            )
+            .execute(tx)
+            .await?;
+
+            Ok(())
+        }
+
+        pub async fn create_join_ticket(
+            &self,
+            token: &str,
+            room_id: Uuid,
+            expiry_date: OffsetDateTime,
+        ) -> Result<JoinTicket> {
+            let ticket = sqlx::query_as!(
+                JoinTicket,
+                r#"INSERT INTO join_tickets (id, session_id, room_id, expiry_date)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, session_id, expiry_date
+            "#,
+                Self::generate_token(),
+                token,
+                room_id,
+                expiry_date
+            )
+            .fetch_one(&self.db)
+            .await?;
+            Ok(ticket)
+        }
+
+        // Tickets are single use: redeeming one deletes it, so a ticket leaked through a url
+        // cannot be replayed.
+        pub async fn redeem_join_ticket(
+            &self,
+            ticket: &str,
+            room_id: Uuid,
+        ) -> Result<Option<SessionData>> {
+            let ticket = sqlx::query_as!(
+                JoinTicket,
+                r#"DELETE FROM join_tickets WHERE id = $1 AND room_id = $2 AND expiry_date > $3
+            RETURNING id, session_id, expiry_date
+            "#,
+                ticket,
+                room_id,
+                OffsetDateTime::now_utc()
+            )
+            .fetch_optional(&self.db)
+            .await?;
+
+            match ticket {
+                Some(ticket) => self.get(ticket.session_id).await,
+                None => Ok(None),
+            }
+        }
+
         pub async fn continously_delete_expired_sessions(
             self,
             period: tokio::time::Duration,
@@ -311,6 +584,9 @@ pub mod session {
             sqlx::query!(r#"DELETE FROM sessions WHERE expiry_date < (now() AT TIME ZONE 'utc')"#)
                 .execute(&self.db)
                 .await?;
+            sqlx::query!(r#"DELETE FROM join_tickets WHERE expiry_date < (now() AT TIME ZONE 'utc')"#)
+                .execute(&self.db)
+                .await?;
             Ok(())
         }
     }
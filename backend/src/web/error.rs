@@ -77,7 +77,11 @@ impl Error {
         use Error::*;
         match self {
             Service(e) => match e {
+                BannedFromRoom => (StatusCode::FORBIDDEN, ClientError::BANNED_FROM_ROOM),
                 InvalidCredentials => (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL),
+                InviteForAnotherEmail => (StatusCode::FORBIDDEN, ClientError::NOT_ALLOWED),
+                RecordingInProgress => (StatusCode::CONFLICT, ClientError::RECORDING_IN_PROGRESS),
+                RoomEncrypted => (StatusCode::CONFLICT, ClientError::ROOM_ENCRYPTED),
                 _ => (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     ClientError::SERVICE_ERROR,
@@ -90,6 +94,7 @@ impl Error {
 
             // -- Model
             NotFound => (StatusCode::NOT_FOUND, ClientError::NOT_FOUND),
+            ValidationError(_) => (StatusCode::BAD_REQUEST, ClientError::INVALID_PARAMS),
 
             // -- Fallback.
             _ => (
@@ -109,5 +114,9 @@ pub enum ClientError {
     SERVICE_ERROR,
     NOT_ALLOWED,
     NOT_FOUND,
+    BANNED_FROM_ROOM,
+    RECORDING_IN_PROGRESS,
+    ROOM_ENCRYPTED,
+    INVALID_PARAMS,
 }
 // endregion: --- Client Error
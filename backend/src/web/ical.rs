@@ -0,0 +1,136 @@
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use time::{OffsetDateTime, UtcOffset};
+
+use crate::service::schedule::{Entry, Recurrence};
+
+/// Most octets of a line of an iCalendar file, longer ones are folded
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Planned sessions written as an iCalendar (RFC 5545) file, the times are in UTC
+pub struct Calendar {
+    name: String,
+    lines: Vec<String>,
+    file_name: Option<String>,
+}
+
+impl Calendar {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            lines: Vec::new(),
+            file_name: None,
+        }
+    }
+
+    /// Makes the calendar a download named `file_name`, instead of a feed
+    pub fn attachment(mut self, file_name: &str) -> Self {
+        self.file_name = Some(file_name.to_owned());
+        self
+    }
+
+    /// Adds the occurrences of a schedule, `url` being the page of its room
+    pub fn event(
+        mut self,
+        Entry {
+            schedule,
+            room_name,
+        }: &Entry,
+        url: &str,
+    ) -> Self {
+        self.lines.extend([
+            String::from("BEGIN:VEVENT"),
+            format!("UID:{}@rtjam", schedule.id),
+            format!("DTSTAMP:{}", format_time(schedule.created_at)),
+            format!("DTSTART:{}", format_time(schedule.starts_at)),
+            format!("DTEND:{}", format_time(schedule.ends_at)),
+            format!("SUMMARY:{}", escape(&schedule.title)),
+            format!("LOCATION:{}", escape(room_name)),
+            format!("URL:{}", url),
+        ]);
+        if let Some(recurrence) = schedule.recurrence() {
+            let frequency = match recurrence {
+                Recurrence::Daily => "DAILY",
+                Recurrence::Weekly => "WEEKLY",
+            };
+            self.lines.push(match schedule.repeat_until {
+                Some(until) => format!("RRULE:FREQ={};UNTIL={}", frequency, format_time(until)),
+                None => format!("RRULE:FREQ={}", frequency),
+            });
+        }
+        self.lines.push(String::from("END:VEVENT"));
+        self
+    }
+}
+
+impl IntoResponse for Calendar {
+    fn into_response(self) -> Response {
+        let mut body = String::new();
+        let header = [
+            String::from("BEGIN:VCALENDAR"),
+            String::from("VERSION:2.0"),
+            String::from("PRODID:-//RT-Jam//Scheduled sessions//EN"),
+            String::from("CALSCALE:GREGORIAN"),
+            format!("X-WR-CALNAME:{}", escape(&self.name)),
+        ];
+        for line in header
+            .into_iter()
+            .chain(self.lines)
+            .chain([String::from("END:VCALENDAR")])
+        {
+            fold(&line, &mut body);
+        }
+
+        let disposition = match self.file_name {
+            Some(file_name) => format!("attachment; filename=\"{}\"", file_name),
+            None => String::from("inline"),
+        };
+        (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    String::from("text/calendar; charset=utf-8"),
+                ),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            body,
+        )
+            .into_response()
+    }
+}
+
+fn format_time(at: OffsetDateTime) -> String {
+    let at = at.to_offset(UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        at.year(),
+        u8::from(at.month()),
+        at.day(),
+        at.hour(),
+        at.minute(),
+        at.second()
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Writes `line` ended by CRLF, a longer line goes on in the next ones, each starting with a space
+fn fold(line: &str, out: &mut String) {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            out.push_str("\r\n ");
+            length = 1;
+        }
+        out.push(c);
+        length += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
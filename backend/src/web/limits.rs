@@ -0,0 +1,171 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::metrics::{self, Delivery};
+
+/// Violations are forgiven at the rate of `max_violations` per window
+const VIOLATION_WINDOW: Duration = Duration::from_secs(10);
+
+/// What a single session may send to the relay
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Datagrams per second
+    pub datagrams: u32,
+    /// Unidirectional streams per second
+    pub streams: u32,
+    /// Bytes per second, datagrams and streams together
+    pub bytes: u64,
+    /// Size of the biggest packet accepted on a unidirectional stream
+    pub max_stream_size: usize,
+    /// Unidirectional streams a peer can keep open at the same time, enforced by QUIC
+    pub max_concurrent_streams: u32,
+    /// Violations tolerated in a window before the peer is disconnected
+    pub max_violations: u32,
+}
+
+/// What to do with a packet received from a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Publish,
+    Drop,
+    /// The peer keeps exceeding its limits and must be disconnected
+    Disconnect,
+}
+
+/// Token bucket refilled at `rate` per second, starting full
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, capacity: f64) -> Self {
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Allows bursts of one second worth of tokens
+    fn per_second(rate: f64) -> Self {
+        Self::new(rate, rate)
+    }
+
+    fn take(&mut self, now: Instant, tokens: f64) -> bool {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.refilled_at = now;
+        if self.tokens < tokens {
+            return false;
+        }
+        self.tokens -= tokens;
+        true
+    }
+}
+
+/// Enforces the [`Limits`] of a session, shared by the tasks reading from the peer.
+///
+/// Packets over the limits are dropped and counted as violations. Violations are tolerated
+/// in short bursts, a peer that keeps exceeding its limits is disconnected.
+#[derive(Clone)]
+pub struct Limiter {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    username: String,
+    max_stream_size: usize,
+    buckets: Mutex<Buckets>,
+}
+
+struct Buckets {
+    datagrams: TokenBucket,
+    streams: TokenBucket,
+    bytes: TokenBucket,
+    violations: TokenBucket,
+    disconnected: bool,
+}
+
+impl Limiter {
+    pub fn new(username: &str, limits: &Limits) -> Self {
+        let max_violations = f64::from(limits.max_violations);
+        Self {
+            inner: Arc::new(Inner {
+                username: username.to_owned(),
+                max_stream_size: limits.max_stream_size,
+                buckets: Mutex::new(Buckets {
+                    datagrams: TokenBucket::per_second(f64::from(limits.datagrams)),
+                    streams: TokenBucket::per_second(f64::from(limits.streams)),
+                    bytes: TokenBucket::per_second(limits.bytes as f64),
+                    violations: TokenBucket::new(
+                        max_violations / VIOLATION_WINDOW.as_secs_f64(),
+                        max_violations,
+                    ),
+                    disconnected: false,
+                }),
+            }),
+        }
+    }
+
+    /// Size of the biggest packet accepted on a unidirectional stream
+    pub fn max_stream_size(&self) -> usize {
+        self.inner.max_stream_size
+    }
+
+    /// Accounts a packet of `bytes` received from the peer
+    pub fn check(&self, delivery: Delivery, bytes: usize) -> Verdict {
+        let now = Instant::now();
+        let mut buckets = self.inner.buckets.lock().unwrap();
+        if buckets.disconnected {
+            return Verdict::Disconnect;
+        }
+        let packets = match delivery {
+            Delivery::Datagram => &mut buckets.datagrams,
+            Delivery::Stream => &mut buckets.streams,
+        };
+        let limit = if !packets.take(now, 1.0) {
+            "rate"
+        } else if !buckets.bytes.take(now, bytes as f64) {
+            "bytes"
+        } else {
+            return Verdict::Publish;
+        };
+        self.violated(&mut buckets, now, delivery, limit)
+    }
+
+    /// Accounts a stream the peer tried to send over [`max_stream_size`](Self::max_stream_size)
+    pub fn oversized(&self) -> Verdict {
+        let now = Instant::now();
+        let mut buckets = self.inner.buckets.lock().unwrap();
+        if buckets.disconnected {
+            return Verdict::Disconnect;
+        }
+        self.violated(&mut buckets, now, Delivery::Stream, "size")
+    }
+
+    fn violated(
+        &self,
+        buckets: &mut Buckets,
+        now: Instant,
+        delivery: Delivery,
+        limit: &'static str,
+    ) -> Verdict {
+        metrics::get().packet_limited(delivery, limit);
+        if buckets.violations.take(now, 1.0) {
+            return Verdict::Drop;
+        }
+        buckets.disconnected = true;
+        warn!(
+            "Disconnecting {}: too many packets over the {} limit",
+            self.inner.username, limit
+        );
+        metrics::get().limit_disconnected();
+        Verdict::Disconnect
+    }
+}
@@ -1,16 +1,45 @@
+use time::OffsetDateTime;
 use tokio::sync::OnceCell;
-use tower_cookies::Key;
+use tower_cookies::{cookie::SameSite, Cookie, Key};
 
 pub mod context;
 pub mod error;
+pub mod ical;
 pub mod json;
+pub mod limits;
+pub mod moderation;
 pub mod mw_auth;
 pub mod mw_req_stamp;
 pub mod mw_res_map;
+pub mod outbound;
+pub mod routes_account;
+pub mod routes_chat;
+pub mod routes_metrics;
 pub mod routes_login;
+pub mod routes_member;
+pub mod routes_recording;
 pub mod routes_room;
+pub mod routes_schedule;
 pub mod signed_cookies;
+pub mod stamp;
 pub mod webtransport;
 
 pub const SESSION_COOKIE_NAME: &str = "session-id";
 pub static SESSION_COOKIE_KEY: OnceCell<Key> = OnceCell::const_new();
+
+/// The cookie of a session, kept by the browser until `expires` or, when `None`, until it is
+/// closed
+pub fn session_cookie(token: String, expires: Option<OffsetDateTime>) -> Cookie<'static> {
+    Cookie::build(Cookie::new(SESSION_COOKIE_NAME, token))
+        .http_only(true)
+        .expires(expires)
+        .path("/")
+        .same_site(SameSite::Strict)
+        .build()
+}
+
+/// Removes the session cookie, the path must be the one of [`session_cookie`] for the browser to
+/// drop it
+pub fn session_cookie_removal() -> Cookie<'static> {
+    Cookie::build(SESSION_COOKIE_NAME).path("/").build()
+}
@@ -0,0 +1,235 @@
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use common::protos::moderation_packet::{self, moderation_packet::Action, ModerationPacket};
+use common::protos::packet_wrapper::{packet_wrapper::PacketType, PacketWrapper};
+use protobuf::Message;
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::metrics;
+use crate::service::relay::{self, Relay};
+use crate::service::room::{self, Role};
+
+/// How long a kicked peer is kept out of the room
+const KICK_BAN: Duration = Duration::minutes(15);
+
+/// Role of a peer during its session, and the moderation actions it takes.
+///
+/// The relay of the sender checks an action against the roles stored for the room, where it
+/// applies it, then publishes it with the resulting role on the moderation subject of the room.
+/// Every relay of the room follows it, the one of the sender included: the media of a listener is
+/// no longer published and a kicked peer is disconnected.
+///
+/// A participant only acts on the ones with a lower role, and cannot raise anyone to its own
+/// role; only the owner transfers the ownership.
+#[derive(Clone)]
+pub struct Moderation {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// Username of the account, as stored: the roles, the bans and the published actions refer to
+    /// it
+    username: String,
+    room_id: Uuid,
+    role: Mutex<Role>,
+    room_service: room::Service,
+    relay: Arc<dyn Relay>,
+}
+
+impl Moderation {
+    pub fn new(
+        username: &str,
+        room_id: Uuid,
+        role: Role,
+        room_service: room::Service,
+        relay: Arc<dyn Relay>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                username: username.to_owned(),
+                room_id,
+                role: Mutex::new(role),
+                room_service,
+                relay,
+            }),
+        }
+    }
+
+    pub fn role(&self) -> Role {
+        *self.inner.role.lock().unwrap()
+    }
+
+    /// Whether the media of the peer is published to the room
+    pub fn publishes_media(&self) -> bool {
+        self.role() != Role::Listener
+    }
+
+    /// Applies an action of the peer in the background, it is published once stored
+    pub fn request(&self, data: &[u8]) {
+        let Ok(packet) = ModerationPacket::parse_from_bytes(data) else {
+            debug!(
+                "Dropping malformed moderation packet from {}",
+                self.inner.username
+            );
+            metrics::get().packet_malformed();
+            return;
+        };
+        let Ok(action) = packet.action.enum_value() else {
+            metrics::get().packet_malformed();
+            return;
+        };
+        let moderation = self.clone();
+        tokio::spawn(async move {
+            let target = packet.username;
+            match moderation.apply(action, &target).await {
+                Ok(Some(role)) => moderation.publish(action, &target, role).await,
+                Ok(None) => info!(
+                    "Refused {:?} of {} by {} in room {}",
+                    action, target, moderation.inner.username, moderation.inner.room_id
+                ),
+                Err(e) => error!(
+                    "Error applying {:?} of {} in room {}: {}",
+                    action, target, moderation.inner.room_id, e
+                ),
+            }
+        });
+    }
+
+    /// Returns the role of `target` after the action, `None` when the peer may not take it
+    async fn apply(&self, action: Action, target: &str) -> Result<Option<Role>, room::Error> {
+        let Inner {
+            username,
+            room_id,
+            room_service,
+            ..
+        } = &*self.inner;
+        if target == username {
+            return Ok(None);
+        }
+        let Some(room) = room_service.get_by_id(*room_id).await? else {
+            return Ok(None);
+        };
+        // the stored roles are the reference, the one of the session may be late
+        let role = room_service.role_of(&room, username).await?;
+        let target_role = room_service.role_of(&room, target).await?;
+        if role < Role::Moderator || target_role >= role {
+            return Ok(None);
+        }
+
+        let role = match action {
+            Action::MUTE => target_role,
+            Action::PROMOTE | Action::DEMOTE => {
+                let changed = match (action, target_role) {
+                    (Action::PROMOTE, Role::Listener) => Role::Musician,
+                    (Action::PROMOTE, Role::Musician) => Role::Moderator,
+                    (Action::DEMOTE, Role::Moderator) => Role::Musician,
+                    (Action::DEMOTE, Role::Musician) => Role::Listener,
+                    _ => return Ok(None),
+                };
+                if changed >= role {
+                    return Ok(None);
+                }
+                room_service.set_role(room.id, target, changed).await?;
+                changed
+            }
+            Action::KICK => {
+                // an invite to a private room does not let the peer back in before the ban expires
+                if room.private {
+                    room_service.remove_member(room.id, target).await?;
+                }
+                let expires_at = OffsetDateTime::now_utc() + KICK_BAN;
+                room_service
+                    .ban(room.id, target, username, expires_at)
+                    .await?;
+                target_role
+            }
+            Action::TRANSFER_OWNERSHIP => {
+                if role != Role::Owner
+                    || !room_service
+                        .transfer_ownership(room.id, username, target)
+                        .await?
+                {
+                    return Ok(None);
+                }
+                Role::Owner
+            }
+        };
+        Ok(Some(role))
+    }
+
+    async fn publish(&self, action: Action, target: &str, role: Role) {
+        let packet = ModerationPacket {
+            action: action.into(),
+            username: target.to_owned(),
+            role: role_packet(role).into(),
+            ..Default::default()
+        };
+        let Some(payload) = moderation_wrapper(&self.inner.username, &packet) else {
+            return;
+        };
+        let subject = relay::moderation_subject(&self.inner.room_id.to_string());
+        if let Err(e) = self.inner.relay.publish(subject.clone(), payload).await {
+            error!("Error publishing to subject {}: {}", subject, e);
+        }
+    }
+
+    /// Follows an action published in the room by `sender`, returns `true` when the peer is
+    /// kicked
+    pub fn follow(&self, sender: &str, packet: &ModerationPacket) -> bool {
+        let action = packet.action.enum_value();
+        let mut role = self.inner.role.lock().unwrap();
+        if packet.username == self.inner.username {
+            *role = role_of_packet(packet.role.enum_value_or_default());
+        } else if sender == self.inner.username && action == Ok(Action::TRANSFER_OWNERSHIP) {
+            *role = Role::Moderator;
+        }
+        packet.username == self.inner.username && action == Ok(Action::KICK)
+    }
+}
+
+pub fn role_packet(role: Role) -> moderation_packet::Role {
+    match role {
+        Role::Listener => moderation_packet::Role::LISTENER,
+        Role::Musician => moderation_packet::Role::MUSICIAN,
+        Role::Moderator => moderation_packet::Role::MODERATOR,
+        Role::Owner => moderation_packet::Role::OWNER,
+    }
+}
+
+fn role_of_packet(role: moderation_packet::Role) -> Role {
+    match role {
+        moderation_packet::Role::LISTENER => Role::Listener,
+        moderation_packet::Role::MUSICIAN => Role::Musician,
+        moderation_packet::Role::MODERATOR => Role::Moderator,
+        moderation_packet::Role::OWNER => Role::Owner,
+    }
+}
+
+pub fn parse_moderation(payload: &[u8]) -> Option<(String, ModerationPacket)> {
+    let packet_wrapper = PacketWrapper::parse_from_bytes(payload).ok()?;
+    if packet_wrapper.packet_type != PacketType::MODERATION.into() {
+        return None;
+    }
+    let packet = ModerationPacket::parse_from_bytes(&packet_wrapper.data).ok()?;
+    Some((packet_wrapper.email, packet))
+}
+
+fn moderation_wrapper(sender: &str, packet: &ModerationPacket) -> Option<Bytes> {
+    let data = packet
+        .write_to_bytes()
+        .map_err(|e| error!("Error serializing moderation packet: {}", e))
+        .ok()?;
+    PacketWrapper {
+        packet_type: PacketType::MODERATION.into(),
+        email: sender.to_owned(),
+        data,
+        ..Default::default()
+    }
+    .write_to_bytes()
+    .map_err(|e| error!("Error serializing moderation packet: {}", e))
+    .ok()
+    .map(Bytes::from)
+}
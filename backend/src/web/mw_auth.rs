@@ -1,5 +1,5 @@
 use crate::service::user::session;
-use crate::web::SESSION_COOKIE_NAME;
+use crate::web::{session_cookie, session_cookie_removal, SESSION_COOKIE_NAME};
 use axum::async_trait;
 use axum::body::Body;
 use axum::extract::{FromRequestParts, State};
@@ -8,8 +8,7 @@ use axum::http::Request;
 use axum::middleware::Next;
 use axum::response::Response;
 use serde::Serialize;
-use tower_cookies::Cookie;
-use tracing::debug;
+use tracing::{debug, error};
 
 use super::context::Context;
 use super::error::{Error, Result};
@@ -39,7 +38,7 @@ pub async fn mw_ctx_resolver(
     let ctx_ext_result = ctx_resolve(service, &cookies).await;
 
     if ctx_ext_result.is_err() && !matches!(ctx_ext_result, Err(CtxExtError::TokenNotInCookie)) {
-        cookies.remove(Cookie::from(SESSION_COOKIE_NAME))
+        cookies.remove(session_cookie_removal())
     }
 
     // Store the ctx_ext_result in the request extension
@@ -50,18 +49,29 @@ pub async fn mw_ctx_resolver(
 }
 
 async fn ctx_resolve(session_service: session::Service, cookies: &Cookies<'_>) -> CtxExtResult {
-    match cookies
+    let token = cookies
         .get(SESSION_COOKIE_NAME)
         .map(|c| c.value().to_string())
-        .ok_or(CtxExtError::TokenNotInCookie)
-    {
-        Ok(s) => match session_service.get(s.clone()).await {
-            Ok(Some(session)) => Ok(CtxW(Context::new(session, s))),
-            Ok(None) => Err(CtxExtError::UserNotFound),
-            Err(e) => Err(CtxExtError::SessionError(e.to_string())),
-        },
-        Err(e) => Err(e),
+        .ok_or(CtxExtError::TokenNotInCookie)?;
+    let session = match session_service.find(&token).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Err(CtxExtError::UserNotFound),
+        Err(e) => return Err(CtxExtError::SessionError(e.to_string())),
+    };
+    let data = session
+        .data()
+        .map_err(|e| CtxExtError::SessionError(e.to_string()))?;
+
+    // sliding expiration: the session lasts from its last use, written at most once a minute
+    match session_service.touch(&session).await {
+        Ok(Some(expiry_date)) if session.remember => {
+            cookies.add(session_cookie(token.clone(), Some(expiry_date)))
+        }
+        Ok(_) => {}
+        Err(e) => error!("Error updating the session: {}", e),
     }
+
+    Ok(CtxW(Context::new(data, token)))
 }
 // region:    --- Ctx Extractor
 #[derive(Debug, Clone)]
@@ -1,18 +1,23 @@
 use crate::log::log_request;
+use crate::metrics;
+use crate::service;
 use crate::web::error::Error;
 use crate::web::mw_auth::CtxW;
 use crate::web::mw_req_stamp::ReqStamp;
+use axum::extract::MatchedPath;
 use axum::http::{Method, Uri};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde_json::{json, to_value};
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tracing::debug;
 use uuid::Uuid;
 
 pub async fn mw_reponse_map(
 	ctx: Option<CtxW>,
 	uri: Uri,
+	matched_path: Option<MatchedPath>,
 	req_method: Method,
 	req_stamp: ReqStamp,
 	res: Response,
@@ -54,6 +59,24 @@ pub async fn mw_reponse_map(
 	// -- Build and log the server log line.
 	let client_error = client_status_error.unzip().1;
 
+	// -- Record the request, labelled by route to keep the series bounded.
+	let status = error_response.as_ref().unwrap_or(&res).status();
+	let route = matched_path.as_ref().map_or("unmatched", |p| p.as_str());
+	let duration = OffsetDateTime::now_utc() - req_stamp.time_in;
+	metrics::get().observe_request(
+		req_method.as_str(),
+		route,
+		status.as_u16(),
+		client_error.as_ref().map_or("", |e| e.as_ref()),
+		duration.as_seconds_f64(),
+	);
+	if let Some(Error::Service(service::error::Error::DatabaseError(
+		sqlx::Error::PoolTimedOut,
+	))) = web_error
+	{
+		metrics::get().db_pool_timed_out();
+	}
+
 	// TODO: Need to hander if log_request fail (but should not fail request)
 	let _ = log_request(
 		req_method,
@@ -0,0 +1,581 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use common::protos::media_packet::{media_packet::MediaType, MediaPacket};
+use common::protos::packet_wrapper::{
+    packet_wrapper::{MediaKind, PacketType},
+    PacketWrapper,
+};
+use protobuf::Message;
+use tokio::sync::Notify;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::metrics::{self, Delivery, LaneSeries};
+
+/// Video packets waiting for a subscriber, the oldest are dropped beyond this
+const VIDEO_QUEUE_CAPACITY: usize = 64;
+
+/// Audio packets waiting for a subscriber, the oldest are dropped beyond this
+const AUDIO_QUEUE_CAPACITY: usize = 256;
+
+/// Heartbeats and latency probes waiting for a subscriber, the oldest are dropped beyond this
+const CONTROL_QUEUE_CAPACITY: usize = 64;
+
+/// Packets that cannot be lost waiting for a subscriber, which is closed beyond this
+const RELIABLE_QUEUE_CAPACITY: usize = 512;
+
+/// Packets handed to each task writing the streams of a subscriber
+pub const STREAM_QUEUE_CAPACITY: usize = 64;
+
+/// How often a lagging subscriber is reported
+const REPORT_PERIOD: Duration = Duration::from_secs(10);
+
+/// Bytes added by WebTransport in front of every datagram (the session id, a varint of at most
+/// 8 bytes)
+pub const WEBTRANSPORT_DATAGRAM_OVERHEAD: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lane {
+    /// Heartbeats and latency probes: sent first, the stale ones are dropped
+    Control,
+    /// Everything that changes the state of the peer, from the roster and the keys to the chat:
+    /// never dropped, always sent on a stream
+    Reliable,
+    /// Never waits behind video
+    Audio,
+    /// Dropped first when the subscriber falls behind
+    Video,
+}
+
+impl Lane {
+    /// Every lane, in the order their packets are sent
+    const ALL: [Lane; 4] = [Lane::Control, Lane::Reliable, Lane::Audio, Lane::Video];
+
+    fn name(self) -> &'static str {
+        match self {
+            Lane::Control => "control",
+            Lane::Reliable => "reliable",
+            Lane::Audio => "audio",
+            Lane::Video => "video",
+        }
+    }
+
+    fn capacity(self) -> usize {
+        match self {
+            Lane::Control => CONTROL_QUEUE_CAPACITY,
+            Lane::Reliable => RELIABLE_QUEUE_CAPACITY,
+            Lane::Audio => AUDIO_QUEUE_CAPACITY,
+            Lane::Video => VIDEO_QUEUE_CAPACITY,
+        }
+    }
+
+    /// Media encrypted end to end cannot be read, its kind is the one the sender tells in clear;
+    /// media of an unknown kind is kept in the audio lane so that it is never dropped in favour
+    /// of video.
+    fn of(payload: &[u8]) -> Self {
+        let Ok(packet_wrapper) = PacketWrapper::parse_from_bytes(payload) else {
+            return Lane::Video;
+        };
+        match packet_wrapper.packet_type.enum_value() {
+            Ok(PacketType::MEDIA) => {}
+            Ok(PacketType::PING | PacketType::PONG) => return Lane::Control,
+            _ => return Lane::Reliable,
+        }
+        let media_type = MediaPacket::parse_from_bytes(&packet_wrapper.data)
+            .ok()
+            .and_then(|packet| packet.media_type.enum_value().ok());
+        match (media_type, packet_wrapper.media_kind.enum_value()) {
+            (Some(MediaType::VIDEO), _) => Lane::Video,
+            (Some(MediaType::AUDIO), _) => Lane::Audio,
+            (Some(MediaType::HEARTBEAT), _) => Lane::Control,
+            (None, Ok(MediaKind::VIDEO)) => Lane::Video,
+            (None, Ok(MediaKind::HEARTBEAT)) => Lane::Control,
+            (None, _) => Lane::Audio,
+        }
+    }
+}
+
+/// Counters of an outbound queue, observable for the whole life of the session
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stats {
+    pub audio_depth: usize,
+    pub video_depth: usize,
+    pub max_depth: usize,
+    pub audio_dropped: u64,
+    pub video_dropped: u64,
+    pub datagrams_sent: u64,
+    pub datagrams_failed: u64,
+    pub streams_sent: u64,
+}
+
+/// A packet popped from the queue
+pub struct Packet {
+    pub payload: Bytes,
+    /// Must not be sent as a datagram, which could be lost
+    pub reliable: bool,
+    pub lane: Lane,
+}
+
+struct Queue {
+    packets: VecDeque<Bytes>,
+    series: LaneSeries,
+}
+
+impl Queue {
+    fn push(&mut self, payload: Bytes) {
+        self.packets.push_back(payload);
+        self.series.set_queued(self.packets.len());
+    }
+
+    fn pop(&mut self) -> Option<Bytes> {
+        let payload = self.packets.pop_front()?;
+        self.series.set_queued(self.packets.len());
+        Some(payload)
+    }
+
+    fn clear(&mut self) {
+        self.packets.clear();
+        self.series.set_queued(0);
+    }
+}
+
+struct Lanes {
+    /// By lane, in the order of [`Lane::ALL`]
+    queues: [Queue; 4],
+    closed: bool,
+    /// The subscriber fell so far behind that packets which cannot be dropped piled up
+    overflowed: bool,
+    stats: Stats,
+}
+
+impl Lanes {
+    fn new(session: &str) -> Self {
+        let queue = |lane: Lane| Queue {
+            packets: VecDeque::new(),
+            series: metrics::get().queue_lane(session, lane.name()),
+        };
+        Self {
+            queues: Lane::ALL.map(queue),
+            closed: false,
+            overflowed: false,
+            stats: Stats::default(),
+        }
+    }
+
+    fn queue(&mut self, lane: Lane) -> &mut Queue {
+        &mut self.queues[lane as usize]
+    }
+
+    fn depth(&self) -> usize {
+        self.queues.iter().map(|queue| queue.packets.len()).sum()
+    }
+
+    /// Drops the packet left behind by a lagging subscriber, or gives up on the subscriber when
+    /// the packet cannot be dropped
+    fn drop_packet(&mut self, subject: &str, lane: Lane) {
+        match lane {
+            Lane::Control | Lane::Audio | Lane::Video => {
+                match lane {
+                    Lane::Audio => self.stats.audio_dropped += 1,
+                    Lane::Video => self.stats.video_dropped += 1,
+                    _ => {}
+                }
+                self.queue(lane).series.dropped();
+            }
+            Lane::Reliable => {
+                if !self.overflowed {
+                    warn!(
+                        "Subscriber {} is not reading its {:?} packets, closing it",
+                        subject, lane
+                    );
+                }
+                self.overflowed = true;
+                self.closed = true;
+                for queue in &mut self.queues {
+                    queue.clear();
+                }
+            }
+        }
+    }
+}
+
+struct Shared {
+    subject: String,
+    lanes: Mutex<Lanes>,
+    notify: Notify,
+}
+
+/// Creates the outbound queue of the subscriber identified by `subject`.
+///
+/// The relay pushes into the [`Sender`] without ever waiting for the peer, the task writing to
+/// the peer pops from the [`Receiver`]: heartbeats first, then the packets that cannot be lost,
+/// audio and video. When the peer falls behind the stale video is dropped before any audio; a
+/// peer so far behind that the packets which cannot be dropped pile up is closed.
+///
+/// The depth of every lane and the packets dropped from it are exported for the session, under
+/// an id logged here.
+pub fn channel(subject: &str) -> (Sender, Receiver) {
+    let session = Uuid::new_v4().to_string();
+    info!(
+        "Outbound queue of {} opened as session {}",
+        subject, session
+    );
+    let shared = Arc::new(Shared {
+        subject: subject.to_owned(),
+        lanes: Mutex::new(Lanes::new(&session)),
+        notify: Notify::new(),
+    });
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver {
+            shared,
+            reported: Stats::default(),
+            last_report: Instant::now(),
+        },
+    )
+}
+
+pub struct Sender {
+    shared: Arc<Shared>,
+}
+
+impl Sender {
+    pub fn push(&self, payload: Bytes) {
+        let lane = Lane::of(&payload);
+        {
+            let mut lanes = self.shared.lanes.lock().unwrap();
+            if lanes.closed {
+                return;
+            }
+            let queue = lanes.queue(lane);
+            queue.push(payload);
+            if queue.packets.len() > lane.capacity() {
+                queue.pop();
+                lanes.drop_packet(&self.shared.subject, lane);
+            }
+            let depth = lanes.depth();
+            lanes.stats.max_depth = lanes.stats.max_depth.max(depth);
+        }
+        self.shared.notify.notify_one();
+    }
+
+    /// Whether the subscriber fell too far behind and was closed, nothing is sent to it anymore
+    pub fn overflowed(&self) -> bool {
+        self.shared.lanes.lock().unwrap().overflowed
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        self.shared.lanes.lock().unwrap().closed = true;
+        self.shared.notify.notify_one();
+    }
+}
+
+pub struct Receiver {
+    shared: Arc<Shared>,
+    reported: Stats,
+    last_report: Instant,
+}
+
+impl Receiver {
+    /// Waits for the next packet to send, `None` once the sender is gone
+    pub async fn recv(&mut self) -> Option<Packet> {
+        loop {
+            {
+                let mut lanes = self.shared.lanes.lock().unwrap();
+                let packet = Lane::ALL.into_iter().find_map(|lane| {
+                    lanes.queue(lane).pop().map(|payload| Packet {
+                        payload,
+                        reliable: lane == Lane::Reliable,
+                        lane,
+                    })
+                });
+                if packet.is_some() || lanes.closed {
+                    drop(lanes);
+                    self.report();
+                    return packet;
+                }
+            }
+            self.shared.notify.notified().await;
+        }
+    }
+
+    pub fn datagram_sent(&self, bytes: usize) {
+        self.shared.lanes.lock().unwrap().stats.datagrams_sent += 1;
+        metrics::get().packet_sent(Delivery::Datagram, bytes);
+    }
+
+    pub fn datagram_failed(&self) {
+        self.shared.lanes.lock().unwrap().stats.datagrams_failed += 1;
+        metrics::get().send_failed(Delivery::Datagram);
+    }
+
+    /// The task writing the streams of the `lane` is still busy with the previous packets: the
+    /// packet is dropped like the ones left in the lane
+    pub fn stream_backlogged(&self, lane: Lane) {
+        self.shared
+            .lanes
+            .lock()
+            .unwrap()
+            .drop_packet(&self.shared.subject, lane);
+    }
+
+    /// Counters for the tasks writing the streams
+    pub fn streams(&self) -> Streams {
+        Streams {
+            shared: self.shared.clone(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        let lanes = self.shared.lanes.lock().unwrap();
+        Stats {
+            audio_depth: lanes.queues[Lane::Audio as usize].packets.len(),
+            video_depth: lanes.queues[Lane::Video as usize].packets.len(),
+            ..lanes.stats
+        }
+    }
+
+    /// Warns about the packets dropped since the last report
+    fn report(&mut self) {
+        if self.last_report.elapsed() < REPORT_PERIOD {
+            return;
+        }
+        self.last_report = Instant::now();
+        let stats = self.stats();
+        let audio_dropped = stats.audio_dropped - self.reported.audio_dropped;
+        let video_dropped = stats.video_dropped - self.reported.video_dropped;
+        let datagrams_failed = stats.datagrams_failed - self.reported.datagrams_failed;
+        if audio_dropped > 0 || video_dropped > 0 || datagrams_failed > 0 {
+            warn!(
+                "Subscriber {} is falling behind: dropped {} audio and {} video packets, {} datagrams failed, {} audio and {} video packets queued",
+                self.shared.subject,
+                audio_dropped,
+                video_dropped,
+                datagrams_failed,
+                stats.audio_depth,
+                stats.video_depth
+            );
+        }
+        self.reported = stats;
+    }
+}
+
+/// Counts the packets written by a task to the streams of the subscriber
+#[derive(Clone)]
+pub struct Streams {
+    shared: Arc<Shared>,
+}
+
+impl Streams {
+    pub fn sent(&self, bytes: usize) {
+        self.shared.lanes.lock().unwrap().stats.streams_sent += 1;
+        metrics::get().packet_sent(Delivery::Stream, bytes);
+    }
+
+    /// A stream could not be opened or written, the packet is lost
+    pub fn failed(&self) {
+        metrics::get().send_failed(Delivery::Stream);
+    }
+}
+
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        let stats = self.stats();
+        info!(
+            "Outbound queue of {} closed: sent {} datagrams and {} streams, {} datagrams failed, dropped {} audio and {} video packets, at most {} packets queued",
+            self.shared.subject,
+            stats.datagrams_sent,
+            stats.streams_sent,
+            stats.datagrams_failed,
+            stats.audio_dropped,
+            stats.video_dropped,
+            stats.max_depth
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+
+    use super::*;
+
+    fn wrapper(packet_type: PacketType, data: Vec<u8>) -> Bytes {
+        PacketWrapper {
+            packet_type: packet_type.into(),
+            data,
+            ..Default::default()
+        }
+        .write_to_bytes()
+        .unwrap()
+        .into()
+    }
+
+    fn media(media_type: MediaType, frame: u8) -> Bytes {
+        let data = MediaPacket {
+            media_type: media_type.into(),
+            data: vec![frame],
+            ..Default::default()
+        }
+        .write_to_bytes()
+        .unwrap();
+        wrapper(PacketType::MEDIA, data)
+    }
+
+    /// Media encrypted end to end, of the kind told by the sender
+    fn encrypted(media_kind: MediaKind) -> Bytes {
+        PacketWrapper {
+            packet_type: PacketType::MEDIA.into(),
+            data: vec![0xff],
+            media_kind: media_kind.into(),
+            ..Default::default()
+        }
+        .write_to_bytes()
+        .unwrap()
+        .into()
+    }
+
+    fn frame(payload: &[u8]) -> u8 {
+        let packet_wrapper = PacketWrapper::parse_from_bytes(payload).unwrap();
+        MediaPacket::parse_from_bytes(&packet_wrapper.data)
+            .unwrap()
+            .data[0]
+    }
+
+    /// Pops the packets already queued, without waiting for more
+    fn drain(rx: &mut Receiver) -> Vec<Packet> {
+        let mut packets = Vec::new();
+        while let Some(Some(packet)) = rx.recv().now_or_never() {
+            packets.push(packet);
+        }
+        packets
+    }
+
+    #[test]
+    fn lane_of() {
+        assert_eq!(Lane::of(&media(MediaType::VIDEO, 0)), Lane::Video);
+        assert_eq!(Lane::of(&media(MediaType::AUDIO, 0)), Lane::Audio);
+        assert_eq!(Lane::of(&media(MediaType::HEARTBEAT, 0)), Lane::Control);
+        assert_eq!(Lane::of(&wrapper(PacketType::CHAT, vec![])), Lane::Reliable);
+        assert_eq!(
+            Lane::of(&wrapper(PacketType::TRANSPORT, vec![])),
+            Lane::Reliable
+        );
+        assert_eq!(
+            Lane::of(&wrapper(PacketType::MODERATION, vec![])),
+            Lane::Reliable
+        );
+        for packet_type in [
+            PacketType::ROSTER,
+            PacketType::REJECTION,
+            PacketType::GOING_AWAY,
+            PacketType::AES_KEY,
+        ] {
+            assert_eq!(Lane::of(&wrapper(packet_type, vec![])), Lane::Reliable);
+        }
+        assert_eq!(Lane::of(&wrapper(PacketType::PING, vec![])), Lane::Control);
+        assert_eq!(Lane::of(&wrapper(PacketType::PONG, vec![])), Lane::Control);
+        // encrypted media cannot be parsed, its kind is told in clear
+        assert_eq!(Lane::of(&encrypted(MediaKind::VIDEO)), Lane::Video);
+        assert_eq!(Lane::of(&encrypted(MediaKind::AUDIO)), Lane::Audio);
+        assert_eq!(Lane::of(&encrypted(MediaKind::HEARTBEAT)), Lane::Control);
+        assert_eq!(Lane::of(&encrypted(MediaKind::UNKNOWN)), Lane::Audio);
+        assert_eq!(Lane::of(&[0xff, 0xff]), Lane::Video);
+    }
+
+    #[test]
+    fn video_drops_oldest() {
+        let (tx, mut rx) = channel("test");
+        for i in 0..VIDEO_QUEUE_CAPACITY + 2 {
+            tx.push(media(MediaType::VIDEO, i as u8));
+        }
+        let packets = drain(&mut rx);
+        assert_eq!(packets.len(), VIDEO_QUEUE_CAPACITY);
+        assert_eq!(frame(&packets[0].payload), 2);
+        assert_eq!(rx.stats().video_dropped, 2);
+        assert!(!tx.overflowed());
+    }
+
+    #[test]
+    fn audio_drops_oldest() {
+        let (tx, mut rx) = channel("test");
+        for i in 0..AUDIO_QUEUE_CAPACITY + 3 {
+            tx.push(media(MediaType::AUDIO, i as u8));
+        }
+        let packets = drain(&mut rx);
+        assert_eq!(packets.len(), AUDIO_QUEUE_CAPACITY);
+        assert_eq!(frame(&packets[0].payload), 3);
+        assert_eq!(rx.stats().audio_dropped, 3);
+        assert!(!tx.overflowed());
+    }
+
+    #[test]
+    fn control_drops_oldest() {
+        let (tx, mut rx) = channel("test");
+        for i in 0..CONTROL_QUEUE_CAPACITY + 1 {
+            tx.push(media(MediaType::HEARTBEAT, i as u8));
+        }
+        let packets = drain(&mut rx);
+        assert_eq!(packets.len(), CONTROL_QUEUE_CAPACITY);
+        assert_eq!(frame(&packets[0].payload), 1);
+        assert!(!tx.overflowed());
+    }
+
+    #[test]
+    fn reliable_overflow_closes() {
+        let (tx, mut rx) = channel("test");
+        tx.push(media(MediaType::AUDIO, 0));
+        for _ in 0..RELIABLE_QUEUE_CAPACITY + 1 {
+            tx.push(wrapper(PacketType::ROSTER, vec![]));
+        }
+        assert!(tx.overflowed());
+        assert!(rx.recv().now_or_never().unwrap().is_none());
+        tx.push(wrapper(PacketType::ROSTER, vec![]));
+        assert!(rx.recv().now_or_never().unwrap().is_none());
+    }
+
+    #[test]
+    fn backlogged_stream() {
+        let (tx, rx) = channel("test");
+        rx.stream_backlogged(Lane::Video);
+        rx.stream_backlogged(Lane::Audio);
+        assert_eq!(rx.stats().video_dropped, 1);
+        assert_eq!(rx.stats().audio_dropped, 1);
+        assert!(!tx.overflowed());
+        rx.stream_backlogged(Lane::Reliable);
+        assert!(tx.overflowed());
+    }
+
+    #[test]
+    fn recv_priority() {
+        let (tx, mut rx) = channel("test");
+        tx.push(media(MediaType::VIDEO, 0));
+        tx.push(media(MediaType::AUDIO, 0));
+        tx.push(wrapper(PacketType::ROSTER, vec![]));
+        tx.push(wrapper(PacketType::PING, vec![]));
+        let packets = drain(&mut rx);
+        let lanes: Vec<_> = packets.iter().map(|packet| packet.lane).collect();
+        assert_eq!(
+            lanes,
+            [Lane::Control, Lane::Reliable, Lane::Audio, Lane::Video]
+        );
+        let reliable: Vec<_> = packets.iter().map(|packet| packet.reliable).collect();
+        assert_eq!(reliable, [false, true, false, false]);
+    }
+
+    #[tokio::test]
+    async fn recv_ends_with_sender() {
+        let (tx, mut rx) = channel("test");
+        tx.push(media(MediaType::AUDIO, 0));
+        drop(tx);
+        assert!(rx.recv().await.is_some());
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -0,0 +1,283 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, put},
+    Json as AJson, Router,
+};
+use common::types::{
+    AccountExportResponse, DeleteAccountRequest, MembershipExport, MessageExport, ProfileExport,
+    RoomResponse, RsvpExport, UpdatePasswordRequest, UpdateProfileRequest, UserResponse,
+};
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::service::{
+    self, chat, recording, room, schedule,
+    user::{auth, session},
+};
+
+use super::{
+    error::{Error, Result},
+    json::Json,
+    mw_auth::CtxW,
+    routes_login::session_response,
+    session_cookie_removal,
+    signed_cookies::Cookies,
+};
+
+#[derive(Clone)]
+struct AppState {
+    db: PgPool,
+    auth_service: auth::Service,
+    session_service: session::Service,
+    room_service: room::Service,
+    recording_service: recording::Service,
+    chat_service: chat::Service,
+    schedule_service: schedule::Service,
+}
+
+/// Routes under `/api/account`, where the user signed in manages its own account
+pub fn router(
+    db: PgPool,
+    auth_service: auth::Service,
+    session_service: session::Service,
+    room_service: room::Service,
+    recording_service: recording::Service,
+    chat_service: chat::Service,
+    schedule_service: schedule::Service,
+) -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(profile).patch(update_profile).delete(delete_account),
+        )
+        .route("/password", put(update_password))
+        .route("/export", get(export))
+        .with_state(AppState {
+            db,
+            auth_service,
+            session_service,
+            room_service,
+            recording_service,
+            chat_service,
+            schedule_service,
+        })
+}
+
+async fn profile(
+    State(AppState { auth_service, .. }): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let user = auth_service
+        .find(Some(&context.0.get_session().username), None)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(AJson(UserResponse::from(user)))
+}
+
+/// Changes the name of the user, in its sessions too
+async fn update_profile(
+    State(AppState {
+        auth_service,
+        session_service,
+        ..
+    }): State<AppState>,
+    context: CtxW,
+    Json(UpdateProfileRequest {
+        first_name,
+        last_name,
+    }): Json<UpdateProfileRequest>,
+) -> Result<impl IntoResponse> {
+    let user = auth_service
+        .update_profile(context.0.get_session().id, first_name, last_name)
+        .await?;
+    session_service
+        .refresh(&session::SessionData::from(user.clone()))
+        .await?;
+
+    Ok(AJson(UserResponse::from(user)))
+}
+
+/// Changes the password of the user, the other sessions are signed out
+async fn update_password(
+    State(AppState {
+        auth_service,
+        session_service,
+        ..
+    }): State<AppState>,
+    context: CtxW,
+    Json(UpdatePasswordRequest {
+        current_password,
+        password,
+        ..
+    }): Json<UpdatePasswordRequest>,
+) -> Result<impl IntoResponse> {
+    let session = context.0.get_session();
+    auth_service
+        .update_password(session.username, current_password, password)
+        .await?;
+    session_service
+        .delete_all_of(session.id, Some(&context.0.get_token()))
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Deletes the account of the user along with its messages. Each room it owns goes to the
+/// successor picked by the room service, the rooms nobody else is left in are deleted with their
+/// recordings. Everything is deleted at once, the files of the recordings once the account is
+/// gone.
+async fn delete_account(
+    State(AppState {
+        db,
+        auth_service,
+        session_service,
+        room_service,
+        recording_service,
+        chat_service,
+        ..
+    }): State<AppState>,
+    context: CtxW,
+    cookies: Cookies<'_>,
+    Json(DeleteAccountRequest { password }): Json<DeleteAccountRequest>,
+) -> Result<impl IntoResponse> {
+    let session = context.0.get_session();
+    // the password confirms the deletion
+    auth_service
+        .login(session.username.clone(), password)
+        .await?;
+
+    let mut rooms = Vec::new();
+    let mut recordings = Vec::new();
+    for room in room_service.owned_by(&session.username).await? {
+        let successor = room_service.successor(&room).await?;
+        if successor.is_none() {
+            for recording in recording_service.list(room.id).await? {
+                recordings.push(recording_service.stop(recording).await?.id);
+            }
+        }
+        rooms.push((room, successor));
+    }
+
+    let deleted: service::error::Result<()> = async {
+        let mut tx = db.begin().await?;
+        for (room, successor) in &rooms {
+            match successor {
+                Some(successor) => {
+                    room_service
+                        .transfer_ownership_in(&mut tx, room.id, &room.owner, successor)
+                        .await?;
+                }
+                // the recordings go away with the room
+                None => room_service.delete_in(&mut tx, room.id).await?,
+            }
+        }
+        chat_service
+            .delete_sent_by_in(&mut tx, &session.username)
+            .await?;
+        session_service
+            .delete_all_of_in(&mut tx, session.id, None)
+            .await?;
+        auth_service.delete_in(&mut tx, session.id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    .await;
+    deleted?;
+    cookies.remove(session_cookie_removal());
+
+    for id in recordings {
+        if let Err(e) = recording_service.delete_files(id).await {
+            error!("Error deleting the files of recording {}: {}", id, e);
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Downloads everything stored about the user as a JSON file
+async fn export(
+    State(AppState {
+        auth_service,
+        session_service,
+        room_service,
+        chat_service,
+        schedule_service,
+        ..
+    }): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let session = context.0.get_session();
+    let user = auth_service
+        .find(Some(&session.username), None)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let token = context.0.get_token();
+
+    let export = AccountExportResponse {
+        profile: ProfileExport {
+            id: user.id,
+            email: user.email,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            username: user.username,
+            // sqlx sessions run in UTC, where now() fills the TIMESTAMP columns
+            created_at: user.created_at.assume_utc(),
+            updated_at: user.updated_at.assume_utc(),
+        },
+        rooms: room_service
+            .owned_by(&session.username)
+            .await?
+            .into_iter()
+            .map(RoomResponse::from)
+            .collect(),
+        memberships: room_service
+            .memberships(&session.username)
+            .await?
+            .into_iter()
+            .map(|membership| MembershipExport {
+                room_id: membership.room_id,
+                room_name: membership.room_name,
+                added_by: membership.added_by,
+                added_at: membership.added_at,
+                role: membership.role.as_str().to_owned(),
+            })
+            .collect(),
+        sessions: session_service
+            .list_of(session.id)
+            .await?
+            .into_iter()
+            .map(|listed| session_response(listed, &token))
+            .collect(),
+        messages: chat_service
+            .sent_by(&session.username)
+            .await?
+            .into_iter()
+            .map(|message| MessageExport {
+                room_id: message.room_id,
+                text: message.text,
+                sent_at: message.sent_at,
+            })
+            .collect(),
+        rsvps: schedule_service
+            .rsvps_of(&session.username)
+            .await?
+            .into_iter()
+            .map(|rsvp| RsvpExport {
+                schedule_id: rsvp.schedule_id,
+                response: rsvp.response,
+                updated_at: rsvp.updated_at,
+            })
+            .collect(),
+    };
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"rtjam-{}.json\"", session.username),
+        )],
+        AJson(export),
+    ))
+}
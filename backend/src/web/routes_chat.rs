@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json as AJson, Router,
+};
+use common::types::ChatMessageResponse;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::service::{
+    chat::{self, Message},
+    room,
+};
+
+use super::{
+    error::{Error, Result},
+    mw_auth::CtxW,
+};
+
+/// Messages returned when the client does not ask for a number
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Most messages returned by a single request
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Clone)]
+struct AppState {
+    room_service: room::Service,
+    chat_service: chat::Service,
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    /// Id of the oldest message the client has, the page starts right before it
+    before: Option<Uuid>,
+    limit: Option<i64>,
+}
+
+/// Routes under `/api/rooms/:id/messages`, the chat history is readable by anyone who can join
+/// the room
+pub fn router(room_service: room::Service, chat_service: chat::Service) -> Router {
+    Router::new()
+        .route("/:id/messages", get(list))
+        .with_state(AppState {
+            room_service,
+            chat_service,
+        })
+}
+
+/// Pages through the history newest first: the next page is asked for with the id of the last
+/// message received as `before`
+async fn list(
+    Path(id): Path<Uuid>,
+    Query(PageQuery { before, limit }): Query<PageQuery>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let room = state
+        .room_service
+        .get_by_id(id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if !state
+        .room_service
+        .can_join(&room, &context.0.get_session().username)
+        .await?
+    {
+        return Err(Error::NotFound);
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let messages = state.chat_service.page(id, before, limit).await?;
+
+    Ok(AJson(
+        messages
+            .into_iter()
+            .map(ChatMessageResponse::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+impl From<Message> for ChatMessageResponse {
+    fn from(
+        Message {
+            id,
+            username,
+            text,
+            sent_at,
+            ..
+        }: Message,
+    ) -> Self {
+        Self {
+            id,
+            username,
+            text,
+            sent_at,
+        }
+    }
+}
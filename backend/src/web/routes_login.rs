@@ -1,34 +1,47 @@
+use std::net::{IpAddr, SocketAddr};
+
 use axum::{
-    extract::State,
+    extract::{ConnectInfo, Path, State},
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post},
     Json as AJson, Router,
 };
 use serde_json::json;
-use time::{Duration, OffsetDateTime};
-use tower_cookies::{cookie::SameSite, Cookie};
+use uuid::Uuid;
 
 use crate::service::user::{
     auth,
-    session::{self},
+    session::{self, Session},
     User,
 };
 
 use super::{
-    error::Result, json::Json, mw_auth::CtxW, signed_cookies::Cookies, SESSION_COOKIE_NAME,
+    error::{Error, Result},
+    json::Json,
+    mw_auth::CtxW,
+    session_cookie, session_cookie_removal,
+    signed_cookies::Cookies,
 };
 
 use common::types::{
-    ChangePasswordRequest, LoginRequest, RegisterRequest, StartResetRequest, UserResponse,
+    ChangePasswordRequest, LoginRequest, RegisterRequest, SessionResponse, StartResetRequest,
+    UserResponse,
 };
 
 #[derive(Clone)]
 struct AppState {
     auth_service: auth::Service,
     session_service: session::Service,
+    /// Proxies whose `X-Forwarded-For` is believed
+    trusted_proxies: Vec<IpAddr>,
 }
 
-pub fn router(auth_service: auth::Service, session_service: session::Service) -> Router {
+pub fn router(
+    auth_service: auth::Service,
+    session_service: session::Service,
+    trusted_proxies: Vec<IpAddr>,
+) -> Router {
     Router::new()
         .route("/sign-in", post(login))
         .route("/sign-up", post(register))
@@ -36,9 +49,13 @@ pub fn router(auth_service: auth::Service, session_service: session::Service) ->
         .route("/me", get(me))
         .route("/change-password", post(change_password))
         .route("/start-reset", post(start_reset))
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/:id", delete(delete_session))
+        .route("/sign-out-everywhere", post(logout_everywhere))
         .with_state(AppState {
             auth_service,
             session_service,
+            trusted_proxies,
         })
 }
 
@@ -46,40 +63,63 @@ async fn login(
     State(AppState {
         auth_service,
         session_service,
+        trusted_proxies,
     }): State<AppState>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     cookies: Cookies<'_>,
-    Json(LoginRequest { username, password }): Json<LoginRequest>,
+    Json(LoginRequest {
+        username,
+        password,
+        remember_me,
+    }): Json<LoginRequest>,
 ) -> Result<impl IntoResponse> {
     let user = auth_service.login(username, password).await?;
-    let expiration = {
-        let this = OffsetDateTime::now_utc();
-        let duration = Duration::days(7);
-        this.checked_add(duration)
-            .expect("resulting value is out of range")
-    };
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok());
     let token = session::Service::generate_token();
-    session_service
+    let session = session_service
         .create(
             &token,
             &session::SessionData::from(user.clone()),
-            expiration,
+            remember_me,
+            user_agent,
+            Some(&client_ip(&headers, address, &trusted_proxies)),
         )
         .await?;
 
-    let cookie = Cookie::build(Cookie::new(SESSION_COOKIE_NAME, token))
-        .http_only(true)
-        .expires(expiration)
-        .path("/")
-        .same_site(SameSite::Strict)
-        .build();
-
-    cookies.add(cookie);
+    cookies.add(session_cookie(
+        token,
+        session.remember.then_some(session.expiry_date),
+    ));
 
     Ok(AJson(json!({
         "result": { "success": true }
     })))
 }
 
+/// Address of the client. Behind a trusted proxy it is taken from `X-Forwarded-For`, where each
+/// proxy appends the address it got the request from: the client is the last one not added by
+/// a trusted proxy.
+fn client_ip(headers: &HeaderMap, address: SocketAddr, trusted_proxies: &[IpAddr]) -> String {
+    if !trusted_proxies.contains(&address.ip()) {
+        return address.ip().to_string();
+    }
+    headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            value
+                .rsplit(',')
+                .map(|ip| ip.trim().parse::<IpAddr>())
+                .find(|ip| !matches!(ip, Ok(ip) if trusted_proxies.contains(ip)))
+        })
+        .and_then(|ip| ip.ok())
+        .unwrap_or(address.ip())
+        .to_string()
+}
+
 async fn me(context: CtxW) -> Result<impl IntoResponse> {
     let session = context.0.get_session();
     let session = session.clone();
@@ -105,6 +145,60 @@ async fn logout(
     })))
 }
 
+/// Signs the user out of all of its sessions, this one included
+async fn logout_everywhere(
+    State(AppState {
+        session_service, ..
+    }): State<AppState>,
+    context: CtxW,
+    cookies: Cookies<'_>,
+) -> Result<impl IntoResponse> {
+    session_service
+        .delete_all_of(context.0.get_session().id, None)
+        .await?;
+    cookies.remove(session_cookie_removal());
+    Ok(AJson(json!({
+        "result": { "success": true }
+    })))
+}
+
+/// The sessions of the user that have not expired, the most recently used first
+async fn list_sessions(
+    State(AppState {
+        session_service, ..
+    }): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let token = context.0.get_token();
+    let sessions = session_service.list_of(context.0.get_session().id).await?;
+
+    Ok(AJson(
+        sessions
+            .into_iter()
+            .map(|session| session_response(session, &token))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Signs the user out of one of its sessions, which can be the current one
+async fn delete_session(
+    Path(id): Path<Uuid>,
+    State(AppState {
+        session_service, ..
+    }): State<AppState>,
+    context: CtxW,
+    cookies: Cookies<'_>,
+) -> Result<impl IntoResponse> {
+    let token = session_service
+        .delete_of(context.0.get_session().id, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if token == context.0.get_token() {
+        cookies.remove(session_cookie_removal());
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
 async fn register(
     State(AppState { auth_service, .. }): State<AppState>,
     Json(RegisterRequest {
@@ -165,3 +259,17 @@ impl From<User> for UserResponse {
         }
     }
 }
+
+/// `token` is the one of the session the request was made with
+pub fn session_response(session: Session, token: &str) -> SessionResponse {
+    SessionResponse {
+        current: session.id == token,
+        id: session.public_id,
+        user_agent: session.user_agent,
+        ip: session.ip,
+        remember: session.remember,
+        created_at: session.created_at,
+        last_seen_at: session.last_seen_at,
+        expires_at: session.expiry_date,
+    }
+}
@@ -0,0 +1,316 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, post},
+    Json as AJson, Router,
+};
+use common::types::{
+    CreateInviteRequest, InvitationResponse, InviteMemberRequest, InviteResponse, RoomMemberResponse,
+    RoomResponse,
+};
+use time::{Duration, OffsetDateTime};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::service::{
+    email,
+    invite::{self, Invite},
+    room::{self, Member, Room},
+    user::auth,
+};
+
+use super::{
+    error::{Error, Result},
+    json::Json,
+    mw_auth::CtxW,
+};
+
+/// How long an invite link lasts when the owner does not say
+const DEFAULT_INVITE_TTL: Duration = Duration::days(7);
+
+#[derive(Clone)]
+struct AppState {
+    room_service: room::Service,
+    invite_service: invite::Service,
+    auth_service: auth::Service,
+    email_service: email::Service,
+}
+
+/// Routes under `/api/rooms/:id/members` and `/api/rooms/:id/invites`, who is let into a private
+/// room is managed by its owner
+pub fn router(
+    room_service: room::Service,
+    invite_service: invite::Service,
+    auth_service: auth::Service,
+    email_service: email::Service,
+) -> Router {
+    Router::new()
+        .route("/:id/members", get(list_members).post(invite_member))
+        .route("/:id/members/:username", delete(remove_member))
+        .route("/:id/invites", get(list_invites).post(create_invite))
+        .route("/:id/invites/:invite_id", delete(delete_invite))
+        .with_state(AppState {
+            room_service,
+            invite_service,
+            auth_service,
+            email_service,
+        })
+}
+
+/// Route under `/api/invites`, opening an invite link makes the user a member of the room
+pub fn accept_router(room_service: room::Service, invite_service: invite::Service) -> Router {
+    Router::new()
+        .route("/:token", post(accept_invite))
+        .with_state((room_service, invite_service))
+}
+
+async fn owned_room(state: &AppState, id: Uuid, context: &CtxW) -> Result<Room> {
+    let room = state
+        .room_service
+        .get_by_id(id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if room.owner != context.0.get_session().username {
+        return Err(Error::NotAllowed);
+    }
+    Ok(room)
+}
+
+/// The members of a room are listed to whoever can join it
+async fn list_members(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let room = state
+        .room_service
+        .get_by_id(id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if !state
+        .room_service
+        .can_join(&room, &context.0.get_session().username)
+        .await?
+    {
+        return Err(Error::NotFound);
+    }
+
+    let members = state.room_service.members(id).await?;
+
+    Ok(AJson(
+        members
+            .into_iter()
+            .map(RoomMemberResponse::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Adds a user as a member and tells them by email, or sends an invite link to an email that
+/// belongs to no user
+async fn invite_member(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    context: CtxW,
+    Json(InviteMemberRequest { username, email }): Json<InviteMemberRequest>,
+) -> Result<impl IntoResponse> {
+    let room = owned_room(&state, id, &context).await?;
+    let inviter = context.0.get_session().username;
+
+    let user = state
+        .auth_service
+        .find(username.as_deref(), email.as_deref())
+        .await?;
+    let (to, name, path, response) = match (user, email) {
+        (Some(user), _) => {
+            let member = state
+                .room_service
+                .add_member(id, &user.username, &inviter)
+                .await?;
+            (
+                user.email,
+                user.first_name,
+                format!("/session/{}", id),
+                InvitationResponse {
+                    member: Some(RoomMemberResponse::from(member)),
+                    invite: None,
+                },
+            )
+        }
+        (None, Some(email)) => {
+            let invite = state
+                .invite_service
+                .create(
+                    id,
+                    &inviter,
+                    Some(email.clone()),
+                    Some(1),
+                    OffsetDateTime::now_utc() + DEFAULT_INVITE_TTL,
+                )
+                .await?;
+            (
+                email.clone(),
+                email,
+                format!("/invite/{}", invite.token),
+                InvitationResponse {
+                    member: None,
+                    invite: Some(InviteResponse::from(invite)),
+                },
+            )
+        }
+        (None, None) => return Err(Error::NotFound),
+    };
+
+    let email_service = state.email_service.clone();
+    tokio::spawn(async move {
+        if let Err(e) = email_service
+            .send_room_invitation(&to, &name, &inviter, &room.name, &path)
+            .await
+        {
+            error!("Error sending the invitation to room {}: {}", room.id, e);
+        }
+    });
+
+    Ok((StatusCode::CREATED, AJson(response)))
+}
+
+/// Removes a member, reserved to the owner and to the member itself
+async fn remove_member(
+    Path((id, username)): Path<(Uuid, String)>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let room = state
+        .room_service
+        .get_by_id(id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let caller = context.0.get_session().username;
+    if room.owner != caller && username != caller {
+        return Err(Error::NotAllowed);
+    }
+
+    if !state.room_service.remove_member(id, &username).await? {
+        return Err(Error::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_invites(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    owned_room(&state, id, &context).await?;
+
+    let invites = state.invite_service.list(id).await?;
+
+    Ok(AJson(
+        invites
+            .into_iter()
+            .map(InviteResponse::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+async fn create_invite(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    context: CtxW,
+    Json(CreateInviteRequest {
+        max_uses,
+        expires_in_hours,
+    }): Json<CreateInviteRequest>,
+) -> Result<impl IntoResponse> {
+    owned_room(&state, id, &context).await?;
+
+    let ttl = expires_in_hours.map_or(DEFAULT_INVITE_TTL, |hours| {
+        Duration::hours(i64::from(hours))
+    });
+    let invite = state
+        .invite_service
+        .create(
+            id,
+            &context.0.get_session().username,
+            None,
+            max_uses.map(|uses| uses as i32),
+            OffsetDateTime::now_utc() + ttl,
+        )
+        .await?;
+
+    Ok((StatusCode::CREATED, AJson(InviteResponse::from(invite))))
+}
+
+async fn delete_invite(
+    Path((id, invite_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    owned_room(&state, id, &context).await?;
+
+    if !state.invite_service.delete(id, invite_id).await? {
+        return Err(Error::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn accept_invite(
+    Path(token): Path<String>,
+    State((room_service, invite_service)): State<(room::Service, invite::Service)>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let room_id = invite_service
+        .accept(&token, &context.0.get_session().username)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let room = room_service
+        .get_by_id(room_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(AJson(RoomResponse::from(room)))
+}
+
+impl From<Member> for RoomMemberResponse {
+    fn from(
+        Member {
+            username,
+            added_by,
+            added_at,
+            ..
+        }: Member,
+    ) -> Self {
+        Self {
+            username,
+            added_by,
+            added_at,
+        }
+    }
+}
+
+impl From<Invite> for InviteResponse {
+    fn from(
+        Invite {
+            id,
+            token,
+            created_by,
+            email,
+            max_uses,
+            uses,
+            expires_at,
+            created_at,
+            ..
+        }: Invite,
+    ) -> Self {
+        Self {
+            id,
+            token,
+            created_by,
+            email,
+            max_uses: max_uses.map(|uses| uses as u32),
+            uses: uses as u32,
+            expires_at,
+            created_at,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router};
+use sqlx::PgPool;
+
+use crate::metrics;
+
+/// `GET /metrics` in the Prometheus text format
+pub fn router(db: PgPool) -> Router {
+    Router::new().route("/metrics", get(render)).with_state(db)
+}
+
+async fn render(State(db): State<PgPool>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics::get().render(&db),
+    )
+}
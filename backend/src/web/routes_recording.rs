@@ -0,0 +1,226 @@
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Json as AJson, Router,
+};
+use bytes::Bytes;
+use common::types::{RecordingResponse, RecordingTrackResponse};
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+use crate::service::{
+    recording::{self, Recording, Track},
+    room::{self, Room},
+};
+
+use super::{
+    error::{Error, Result},
+    mw_auth::CtxW,
+};
+
+/// Size of the chunks a track is downloaded in
+const DOWNLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone)]
+struct AppState {
+    room_service: room::Service,
+    recording_service: recording::Service,
+}
+
+/// Routes under `/api/rooms/:id/recordings`, all of them are reserved to the owner of the room
+pub fn router(room_service: room::Service, recording_service: recording::Service) -> Router {
+    Router::new()
+        .route("/:id/recordings", post(start).get(list))
+        .route(
+            "/:id/recordings/:recording_id",
+            get(get_by_id).delete(delete_recording),
+        )
+        .route("/:id/recordings/:recording_id/stop", post(stop))
+        .route(
+            "/:id/recordings/:recording_id/tracks/:track_id",
+            get(download),
+        )
+        .with_state(AppState {
+            room_service,
+            recording_service,
+        })
+}
+
+async fn start(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let room = owned_room(&state, id, &context).await?;
+
+    let recording = state
+        .recording_service
+        .start(&room, context.0.get_session().username)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        AJson(recording_response(recording, Vec::new())),
+    ))
+}
+
+async fn list(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    owned_room(&state, id, &context).await?;
+
+    let mut recordings = Vec::new();
+    for recording in state.recording_service.list(id).await? {
+        let tracks = state.recording_service.tracks(recording.id).await?;
+        recordings.push(recording_response(recording, tracks));
+    }
+
+    Ok(AJson(recordings))
+}
+
+async fn get_by_id(
+    Path((id, recording_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let recording = owned_recording(&state, id, recording_id, &context).await?;
+    let tracks = state.recording_service.tracks(recording.id).await?;
+
+    Ok(AJson(recording_response(recording, tracks)))
+}
+
+async fn stop(
+    Path((id, recording_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let recording = owned_recording(&state, id, recording_id, &context).await?;
+    let recording = state.recording_service.stop(recording).await?;
+    let tracks = state.recording_service.tracks(recording.id).await?;
+
+    Ok(AJson(recording_response(recording, tracks)))
+}
+
+async fn delete_recording(
+    Path((id, recording_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let recording = owned_recording(&state, id, recording_id, &context).await?;
+    state.recording_service.delete(recording).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn download(
+    Path((id, recording_id, track_id)): Path<(Uuid, Uuid, Uuid)>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let recording = owned_recording(&state, id, recording_id, &context).await?;
+    let track = state
+        .recording_service
+        .get_track(recording.id, track_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let file = tokio::fs::File::open(state.recording_service.path_of(&track))
+        .await
+        .map_err(|_| Error::NotFound)?;
+
+    // the files of a long session do not fit in memory
+    let body = Body::from_stream(futures::stream::try_unfold(file, |mut file| async move {
+        let mut chunk = vec![0; DOWNLOAD_CHUNK_SIZE];
+        let read = file.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        chunk.truncate(read);
+        Ok::<_, std::io::Error>(Some((Bytes::from(chunk), file)))
+    }));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, track.content_type().to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", track.file_name()),
+            ),
+        ],
+        body,
+    ))
+}
+
+async fn owned_room(state: &AppState, id: Uuid, context: &CtxW) -> Result<Room> {
+    let room = state
+        .room_service
+        .get_by_id(id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if room.owner != context.0.get_session().username {
+        return Err(Error::NotAllowed);
+    }
+    Ok(room)
+}
+
+async fn owned_recording(
+    state: &AppState,
+    id: Uuid,
+    recording_id: Uuid,
+    context: &CtxW,
+) -> Result<Recording> {
+    owned_room(state, id, context).await?;
+    state
+        .recording_service
+        .get(id, recording_id)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+fn recording_response(
+    Recording {
+        id,
+        room_id,
+        started_by,
+        started_at,
+        stopped_at,
+    }: Recording,
+    tracks: Vec<Track>,
+) -> RecordingResponse {
+    RecordingResponse {
+        id,
+        room_id,
+        started_by,
+        started_at,
+        stopped_at,
+        tracks: tracks
+            .into_iter()
+            .map(RecordingTrackResponse::from)
+            .collect(),
+    }
+}
+
+impl From<Track> for RecordingTrackResponse {
+    fn from(
+        Track {
+            id,
+            username,
+            kind,
+            offset_ms,
+            size,
+            ..
+        }: Track,
+    ) -> Self {
+        Self {
+            id,
+            username,
+            kind,
+            offset_ms,
+            size,
+        }
+    }
+}
@@ -1,48 +1,178 @@
-use crate::service::room::{self, Room};
+use crate::service::{
+    presence, recording,
+    relay::{self, Relay},
+    room::{self, NewRoom, Room},
+    user::session,
+};
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
-    routing::{delete, post},
+    routing::{delete, post, put},
     Json as AJson, Router,
 };
-use common::types::{CreateRoomRequest, RoomResponse};
+use common::types::{
+    CreateRoomRequest, JoinTicketResponse, RoomListEntry, RoomListResponse, RoomResponse,
+    TransportResponse, UpdateRoomRequest, UpdateTransportRequest,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use tracing::error;
 
 use super::{
     error::{Error, Result},
     json::Json,
     mw_auth::CtxW,
+    webtransport::transport_wrapper,
 };
 
+/// How long a join ticket can be presented to the relay before it expires
+const JOIN_TICKET_TTL: Duration = Duration::seconds(30);
+
+/// How far ahead the count-in starts, for the change to reach every peer before it
+const TRANSPORT_LEAD: Duration = Duration::milliseconds(500);
+
+/// Rooms listed when the client does not ask for a number
+const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Most rooms listed by a single request
+const MAX_PAGE_SIZE: i64 = 100;
+
 #[derive(Clone)]
 struct AppState {
     room_service: room::Service,
+    session_service: session::Service,
+    recording_service: recording::Service,
+    presence_service: presence::Service,
+    relay: Arc<dyn Relay>,
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    #[serde(default)]
+    mine: bool,
+    #[serde(default)]
+    public: bool,
+    #[serde(default)]
+    open: bool,
+    q: Option<String>,
+    #[serde(default)]
+    sort: room::Order,
+    /// Id of the last room the client has, the page starts right after it
+    after: Option<uuid::Uuid>,
+    limit: Option<i64>,
 }
 
-pub fn router(room_service: room::Service) -> Router {
+pub fn router(
+    room_service: room::Service,
+    session_service: session::Service,
+    recording_service: recording::Service,
+    presence_service: presence::Service,
+    relay: Arc<dyn Relay>,
+) -> Router {
     Router::new()
-        .route("/", post(create))
-        .route("/:id", delete(delete_room).get(get_by_id))
-        .with_state(AppState { room_service })
+        .route("/", post(create).get(list))
+        .route(
+            "/:id",
+            delete(delete_room).get(get_by_id).patch(update_room),
+        )
+        .route("/:id/ticket", post(create_ticket))
+        .route("/:id/transport", put(update_transport))
+        .with_state(AppState {
+            room_service,
+            session_service,
+            recording_service,
+            presence_service,
+            relay,
+        })
 }
 
 async fn create(
     context: CtxW,
-    State(AppState { room_service }): State<AppState>,
-    Json(CreateRoomRequest { name }): Json<CreateRoomRequest>,
+    State(AppState { room_service, .. }): State<AppState>,
+    Json(CreateRoomRequest {
+        name,
+        description,
+        private,
+        open,
+        max_people_playing,
+        encrypted,
+    }): Json<CreateRoomRequest>,
 ) -> Result<impl IntoResponse> {
     let username = context.0.get_session().username;
 
     let room = room_service
-        .create(username, name, None, false, true, 5)
+        .create(NewRoom {
+            owner: username,
+            name,
+            description: description.filter(|description| !description.is_empty()),
+            private,
+            open,
+            max_people_playing: max_people_playing as i32,
+            encrypted,
+        })
         .await?;
 
     Ok((StatusCode::CREATED, AJson(RoomResponse::from(room))))
 }
 
+/// Lists the rooms the user can see, along with how many peers are in each. The next page is
+/// asked for with the `next` of the response as `after`.
+async fn list(
+    context: CtxW,
+    Query(query): Query<ListQuery>,
+    State(AppState {
+        room_service,
+        presence_service,
+        ..
+    }): State<AppState>,
+) -> Result<impl IntoResponse> {
+    let username = context.0.get_session().username;
+    let filter = room::Filter {
+        mine: query.mine,
+        public: query.public,
+        open: query.open,
+        search: query
+            .q
+            .map(|q| q.trim().to_owned())
+            .filter(|q| !q.is_empty()),
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    // one more room tells whether there is a next page
+    let mut rooms = room_service
+        .list(&username, &filter, query.sort, query.after, limit + 1)
+        .await?;
+    let next = if rooms.len() as i64 > limit {
+        rooms.truncate(limit as usize);
+        rooms.last().map(|room| room.id)
+    } else {
+        None
+    };
+
+    Ok(AJson(RoomListResponse {
+        rooms: rooms
+            .into_iter()
+            .map(|room| RoomListEntry {
+                participants: presence_service.members(&room.id.to_string()).len() as u32,
+                room: RoomResponse::from(room),
+            })
+            .collect(),
+        next,
+    }))
+}
+
 async fn delete_room(
     Path(id): Path<uuid::Uuid>,
-    State(AppState { room_service, .. }): State<AppState>,
+    State(AppState {
+        room_service,
+        recording_service,
+        ..
+    }): State<AppState>,
     context: CtxW,
 ) -> Result<impl IntoResponse> {
     let room = room_service.get_by_id(id).await?;
@@ -51,31 +181,204 @@ async fn delete_room(
             return Err(Error::NotAllowed);
         }
 
+        recording_service.delete_all(id).await?;
         room_service.delete(id).await?;
     }
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Private rooms are not found by the users who cannot join them
 async fn get_by_id(
     Path(id): Path<uuid::Uuid>,
-    State(AppState { room_service }): State<AppState>,
+    State(AppState { room_service, .. }): State<AppState>,
+    context: CtxW,
 ) -> Result<impl IntoResponse> {
     let room = room_service.get_by_id(id).await?;
 
-    if let Some(room) = room {
-        Ok(AJson(RoomResponse::from(room)))
-    } else {
-        Err(Error::NotFound)
+    match room {
+        Some(room)
+            if room_service
+                .can_join(&room, &context.0.get_session().username)
+                .await? =>
+        {
+            Ok(AJson(RoomResponse::from(room)))
+        }
+        _ => Err(Error::NotFound),
+    }
+}
+
+/// Changes the settings of the room, reserved to the owner. The peers already in the room are
+/// not affected, the room is checked again when a peer joins.
+async fn update_room(
+    Path(id): Path<uuid::Uuid>,
+    State(AppState { room_service, .. }): State<AppState>,
+    context: CtxW,
+    Json(request): Json<UpdateRoomRequest>,
+) -> Result<impl IntoResponse> {
+    let room = room_service.get_by_id(id).await?.ok_or(Error::NotFound)?;
+    if room.owner != context.0.get_session().username {
+        return Err(Error::NotAllowed);
+    }
+
+    let description = match request.description {
+        Some(description) if description.is_empty() => None,
+        Some(description) => Some(description),
+        None => room.description,
+    };
+    let room = room_service
+        .update(
+            id,
+            request.name.unwrap_or(room.name),
+            description,
+            request.private.unwrap_or(room.private),
+            request.open.unwrap_or(room.open),
+            request
+                .max_people_playing
+                .map_or(room.max_people_playing, |people| people as i32),
+        )
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(AJson(RoomResponse::from(room)))
+}
+
+/// Changes the metronome of the room, reserved to the owner. The peers in the room receive the
+/// new state from the relay.
+async fn update_transport(
+    Path(id): Path<uuid::Uuid>,
+    State(AppState {
+        room_service,
+        relay,
+        ..
+    }): State<AppState>,
+    context: CtxW,
+    Json(request): Json<UpdateTransportRequest>,
+) -> Result<impl IntoResponse> {
+    let room = room_service.get_by_id(id).await?.ok_or(Error::NotFound)?;
+    if room.owner != context.0.get_session().username {
+        return Err(Error::NotAllowed);
+    }
+
+    let tempo = request.tempo.unwrap_or(room.tempo);
+    let beats_per_bar = request
+        .beats_per_bar
+        .map_or(room.beats_per_bar, |beats| beats as i32);
+    let beat_unit = request.beat_unit.map_or(room.beat_unit, |unit| unit as i32);
+    let count_in = request.count_in.map_or(room.count_in, |bars| bars as i32);
+    let regrid =
+        tempo != room.tempo || beats_per_bar != room.beats_per_bar || beat_unit != room.beat_unit;
+    let started_at = match (
+        request
+            .running
+            .unwrap_or(room.transport_started_at.is_some()),
+        room.transport_started_at,
+    ) {
+        (false, _) => None,
+        (true, Some(started_at)) if !regrid => Some(started_at),
+        (true, _) => {
+            let bar = 60.0 / tempo * f64::from(beats_per_bar);
+            Some(
+                OffsetDateTime::now_utc()
+                    + TRANSPORT_LEAD
+                    + Duration::seconds_f64(bar * f64::from(count_in)),
+            )
+        }
+    };
+
+    let room = room_service
+        .update_transport(id, tempo, beats_per_bar, beat_unit, count_in, started_at)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if let Some(payload) = transport_wrapper(&room) {
+        if let Err(e) = relay
+            .publish(relay::transport_subject(&id.to_string()), payload)
+            .await
+        {
+            error!("Error publishing the transport of room {}: {}", id, e);
+        }
     }
 
+    Ok(AJson(RoomResponse::from(room)))
+}
+
+// Browsers do not send cookies along with a WebTransport CONNECT, so the client exchanges its
+// session for a short-lived ticket and passes it to the relay in the query string.
+async fn create_ticket(
+    Path(id): Path<uuid::Uuid>,
+    State(AppState {
+        room_service,
+        session_service,
+        ..
+    }): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let room = room_service.get_by_id(id).await?.ok_or(Error::NotFound)?;
+    let username = context.0.get_session().username;
+    // a kicked peer is told so rather than not finding the room
+    if room_service.banned_until(id, &username).await?.is_some() {
+        return Err(Error::NotAllowed);
+    }
+    if !room_service.can_join(&room, &username).await? {
+        return Err(Error::NotFound);
+    }
+
+    let expiration = OffsetDateTime::now_utc()
+        .checked_add(JOIN_TICKET_TTL)
+        .expect("resulting value is out of range");
+
+    let ticket = session_service
+        .create_join_ticket(&context.0.get_token(), id, expiration)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        AJson(JoinTicketResponse {
+            ticket: ticket.id,
+            expires_at: ticket.expiry_date,
+        }),
+    ))
 }
 
 impl From<Room> for RoomResponse {
     fn from(
         Room {
-            id, name, owner, ..
+            id,
+            name,
+            description,
+            owner,
+            private,
+            open,
+            max_people_playing,
+            encrypted,
+            tempo,
+            beats_per_bar,
+            beat_unit,
+            count_in,
+            transport_started_at,
+            created_at,
+            updated_at,
         }: Room,
     ) -> Self {
-        Self { id, name, owner }
+        Self {
+            id,
+            name,
+            description,
+            owner,
+            private,
+            open,
+            max_people_playing: max_people_playing as u32,
+            encrypted,
+            transport: TransportResponse {
+                tempo,
+                beats_per_bar: beats_per_bar as u32,
+                beat_unit: beat_unit as u32,
+                count_in: count_in as u32,
+                running: transport_started_at.is_some(),
+                started_at: transport_started_at,
+            },
+            // sqlx sessions run in UTC, where now() fills the TIMESTAMP columns
+            created_at: created_at.assume_utc(),
+            updated_at: updated_at.assume_utc(),
+        }
     }
 }
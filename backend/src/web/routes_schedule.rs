@@ -0,0 +1,324 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, put},
+    Json as AJson, Router,
+};
+use common::types::{
+    CalendarFeedResponse, CreateScheduleRequest, RsvpRequest, RsvpResponse, ScheduleResponse,
+};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::service::{
+    room::{self, Role, Room},
+    schedule::{self, Entry, Recurrence, Response, Rsvp, Schedule},
+};
+
+use super::{
+    error::{Error, Result},
+    ical::Calendar,
+    json::Json,
+    mw_auth::CtxW,
+};
+
+#[derive(Clone)]
+struct AppState {
+    room_service: room::Service,
+    schedule_service: schedule::Service,
+    app_url: String,
+}
+
+/// Routes under `/api/rooms/:id/schedules`, the sessions planned in a room are seen and answered
+/// by anyone who can join it and planned by its owner and moderators
+pub fn router(
+    room_service: room::Service,
+    schedule_service: schedule::Service,
+    app_url: String,
+) -> Router {
+    Router::new()
+        .route("/:id/schedules", get(list).post(create))
+        .route("/:id/schedules/:schedule_id", delete(delete_schedule))
+        .route("/:id/schedules/:schedule_id/rsvp", put(respond))
+        .route("/:id/schedules/:schedule_id/ics", get(export))
+        .with_state(AppState {
+            room_service,
+            schedule_service,
+            app_url,
+        })
+}
+
+/// Routes under `/api/calendar`, the link of the calendar feed of the user
+pub fn calendar_router(schedule_service: schedule::Service, app_url: String) -> Router {
+    Router::new()
+        .route("/", get(feed_link).post(rotate_feed_link))
+        .with_state((schedule_service, app_url))
+}
+
+/// Route under `/api/feeds`, read by calendar apps: the token in the link stands for the session
+pub fn feed_router(schedule_service: schedule::Service, app_url: String) -> Router {
+    Router::new()
+        .route("/:token", get(feed))
+        .with_state((schedule_service, app_url))
+}
+
+async fn joinable_room(state: &AppState, id: Uuid, context: &CtxW) -> Result<Room> {
+    let room = state
+        .room_service
+        .get_by_id(id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if !state
+        .room_service
+        .can_join(&room, &context.0.get_session().username)
+        .await?
+    {
+        return Err(Error::NotFound);
+    }
+    Ok(room)
+}
+
+/// Returns the room if the user can plan its sessions
+async fn moderated_room(state: &AppState, id: Uuid, context: &CtxW) -> Result<Room> {
+    let room = joinable_room(state, id, context).await?;
+    let role = state
+        .room_service
+        .role_of(&room, &context.0.get_session().username)
+        .await?;
+    if role < Role::Moderator {
+        return Err(Error::NotAllowed);
+    }
+    Ok(room)
+}
+
+async fn list(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    joinable_room(&state, id, &context).await?;
+
+    let schedules = state.schedule_service.list(id).await?;
+    let ids = schedules
+        .iter()
+        .map(|schedule| schedule.id)
+        .collect::<Vec<_>>();
+    let mut rsvps = state.schedule_service.rsvps(&ids).await?;
+
+    Ok(AJson(
+        schedules
+            .into_iter()
+            .map(|schedule| {
+                let rsvps = rsvps.remove(&schedule.id).unwrap_or_default();
+                schedule_response(schedule, rsvps)
+            })
+            .collect::<Vec<_>>(),
+    ))
+}
+
+async fn create(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    context: CtxW,
+    Json(CreateScheduleRequest {
+        title,
+        starts_at,
+        ends_at,
+        recurrence,
+        repeat_until,
+        opens_room,
+    }): Json<CreateScheduleRequest>,
+) -> Result<impl IntoResponse> {
+    moderated_room(&state, id, &context).await?;
+
+    let schedule = state
+        .schedule_service
+        .create(
+            id,
+            &context.0.get_session().username,
+            title,
+            starts_at,
+            ends_at,
+            recurrence.as_deref().and_then(Recurrence::parse),
+            repeat_until,
+            opens_room,
+        )
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        AJson(schedule_response(schedule, Vec::new())),
+    ))
+}
+
+/// Deletes a schedule, whoever answered yes or maybe is told it was cancelled
+async fn delete_schedule(
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let room = moderated_room(&state, id, &context).await?;
+    let schedule = state
+        .schedule_service
+        .get(id, schedule_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if !state.schedule_service.cancel(schedule, room.name).await? {
+        return Err(Error::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn respond(
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    context: CtxW,
+    Json(RsvpRequest { response }): Json<RsvpRequest>,
+) -> Result<impl IntoResponse> {
+    joinable_room(&state, id, &context).await?;
+    state
+        .schedule_service
+        .get(id, schedule_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let response = Response::parse(&response).ok_or(Error::NotFound)?;
+    let rsvp = state
+        .schedule_service
+        .respond(schedule_id, &context.0.get_session().username, response)
+        .await?;
+
+    Ok(AJson(RsvpResponse::from(rsvp)))
+}
+
+/// Downloads a schedule as an iCalendar file
+async fn export(
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let room = joinable_room(&state, id, &context).await?;
+    let schedule = state
+        .schedule_service
+        .get(id, schedule_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let url = room_url(&state.app_url, id);
+    let file_name = format!("{}.ics", schedule.id);
+    let entry = Entry {
+        schedule,
+        room_name: room.name,
+    };
+    Ok(Calendar::new(&entry.room_name)
+        .attachment(&file_name)
+        .event(&entry, &url))
+}
+
+/// Returns the link of the feed of the user, created the first time
+async fn feed_link(
+    State((schedule_service, app_url)): State<(schedule::Service, String)>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let token = schedule_service
+        .feed_token(&context.0.get_session().username)
+        .await?;
+
+    Ok(AJson(feed_response(&app_url, &token)))
+}
+
+/// Replaces the link of the feed of the user, the calendars subscribed to the former one stop
+/// receiving the sessions
+async fn rotate_feed_link(
+    State((schedule_service, app_url)): State<(schedule::Service, String)>,
+    context: CtxW,
+) -> Result<impl IntoResponse> {
+    let token = schedule_service
+        .rotate_feed_token(&context.0.get_session().username)
+        .await?;
+
+    Ok(AJson(feed_response(&app_url, &token)))
+}
+
+/// The sessions planned in the rooms the owner of the token owns or is a member of, and the ones
+/// it will attend
+async fn feed(
+    Path(token): Path<String>,
+    State((schedule_service, app_url)): State<(schedule::Service, String)>,
+) -> Result<impl IntoResponse> {
+    // calendar apps expect the link to name a file
+    let token = token.strip_suffix(".ics").unwrap_or(&token);
+    let username = schedule_service
+        .feed_owner(token)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let calendar = schedule_service
+        .feed(&username)
+        .await?
+        .iter()
+        .fold(Calendar::new("RT-Jam"), |calendar, entry| {
+            calendar.event(entry, &room_url(&app_url, entry.schedule.room_id))
+        });
+    Ok(calendar)
+}
+
+fn room_url(app_url: &str, id: Uuid) -> String {
+    format!("{}/session/{}", app_url, id)
+}
+
+fn feed_response(app_url: &str, token: &str) -> CalendarFeedResponse {
+    CalendarFeedResponse {
+        url: format!("{}/api/feeds/{}.ics", app_url, token),
+    }
+}
+
+fn schedule_response(schedule: Schedule, rsvps: Vec<Rsvp>) -> ScheduleResponse {
+    let next_starts_at = schedule
+        .next_occurrence(OffsetDateTime::now_utc())
+        .map(|(start, _)| start);
+    let Schedule {
+        id,
+        room_id,
+        title,
+        starts_at,
+        ends_at,
+        recurrence,
+        repeat_until,
+        opens_room,
+        created_by,
+        ..
+    } = schedule;
+    ScheduleResponse {
+        id,
+        room_id,
+        title,
+        starts_at,
+        ends_at,
+        recurrence,
+        repeat_until,
+        opens_room,
+        next_starts_at,
+        created_by,
+        rsvps: rsvps.into_iter().map(RsvpResponse::from).collect(),
+    }
+}
+
+impl From<Rsvp> for RsvpResponse {
+    fn from(
+        Rsvp {
+            username,
+            response,
+            updated_at,
+            ..
+        }: Rsvp,
+    ) -> Self {
+        Self {
+            username,
+            response,
+            updated_at,
+        }
+    }
+}
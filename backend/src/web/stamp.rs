@@ -0,0 +1,241 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use common::protos::chat_packet::ChatPacket;
+use common::protos::packet_wrapper::{packet_wrapper::PacketType, PacketWrapper};
+use common::protos::ping_packet::PingPacket;
+use protobuf::Message;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use crate::metrics;
+use crate::service::chat;
+
+use super::moderation::Moderation;
+
+/// Stamps what a peer publishes with the identity it authenticated with.
+///
+/// Receivers tell the senders apart by `PacketWrapper.email`, so the relay overwrites it with
+/// the username of the session before publishing. A different non empty name is a spoofing
+/// attempt, and so is a packet only the relay may send (ROSTER, REJECTION, GOING_AWAY,
+/// NEGOTIATION and TRANSPORT): both are counted, the latter are dropped. `MediaPacket.email`
+/// travels encrypted in the rooms with end to end encryption and cannot be attested, receivers
+/// must not rely on it.
+///
+/// Chat messages are stamped too: the relay assigns their id, author and time, and stores them
+/// in the history of the room before publishing. A PING addressed to the relay is not published,
+/// the relay answers it with a PONG carrying the time of its clock, which peers synchronize to.
+/// A MODERATION packet is a request of the peer, handed to its [`Moderation`], and the media of
+/// a listener is dropped.
+#[derive(Clone)]
+pub struct Stamp {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    username: String,
+    room_id: Uuid,
+    chat_service: chat::Service,
+    moderation: Moderation,
+    spoofed: AtomicU64,
+}
+
+/// What the relay does with a packet of the peer
+pub enum Stamped {
+    /// Publish to the room
+    Publish(Bytes),
+    /// Send back to the peer only
+    Answer(Bytes),
+    Drop,
+}
+
+impl Stamp {
+    pub fn new(
+        username: &str,
+        room_id: Uuid,
+        chat_service: chat::Service,
+        moderation: Moderation,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                username: username.to_owned(),
+                room_id,
+                chat_service,
+                moderation,
+                spoofed: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn apply(&self, payload: Bytes) -> Stamped {
+        let Ok(packet) = PacketWrapper::parse_from_bytes(&payload) else {
+            debug!("Dropping malformed packet from {}", self.inner.username);
+            metrics::get().packet_malformed();
+            return Stamped::Drop;
+        };
+        if packet.packet_type == PacketType::PING.into() {
+            match PingPacket::parse_from_bytes(&packet.data) {
+                Ok(ping) if !ping.peers => {
+                    return pong_wrapper(ping).map_or(Stamped::Drop, Stamped::Answer);
+                }
+                Ok(_) => {}
+                Err(_) => {
+                    metrics::get().packet_malformed();
+                    return Stamped::Drop;
+                }
+            }
+        }
+        if packet.packet_type == PacketType::MODERATION.into() {
+            self.inner.moderation.request(&packet.data);
+            return Stamped::Drop;
+        }
+        if packet.packet_type == PacketType::MEDIA.into()
+            && !self.inner.moderation.publishes_media()
+        {
+            return Stamped::Drop;
+        }
+        self.stamp(payload, packet)
+            .map_or(Stamped::Drop, Stamped::Publish)
+    }
+
+    /// Returns the payload to publish, `None` when the packet must be dropped
+    fn stamp(&self, payload: Bytes, mut packet: PacketWrapper) -> Option<Bytes> {
+        if let Ok(
+            packet_type @ (PacketType::ROSTER
+            | PacketType::REJECTION
+            | PacketType::GOING_AWAY
+            | PacketType::NEGOTIATION
+            | PacketType::TRANSPORT),
+        ) = packet.packet_type.enum_value()
+        {
+            self.spoofed(&format!("a {:?} packet", packet_type));
+            return None;
+        }
+        if packet.packet_type == PacketType::CHAT.into() {
+            packet.data = self.chat(&packet.data)?;
+        } else if packet.email == self.inner.username {
+            return Some(payload);
+        }
+
+        if !packet.email.is_empty() && packet.email != self.inner.username {
+            self.spoofed(&format!("a packet as {}", packet.email));
+        }
+        packet.email = self.inner.username.clone();
+        match packet.write_to_bytes() {
+            Ok(payload) => Some(payload.into()),
+            Err(e) => {
+                warn!("Error stamping packet of {}: {}", self.inner.username, e);
+                None
+            }
+        }
+    }
+
+    /// Stamps and stores a chat message, returns the packet to publish in its place
+    fn chat(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let Ok(packet) = ChatPacket::parse_from_bytes(data) else {
+            debug!(
+                "Dropping malformed chat packet from {}",
+                self.inner.username
+            );
+            metrics::get().packet_malformed();
+            return None;
+        };
+        let text = packet.text.trim();
+        if text.is_empty() || text.chars().count() > chat::MAX_MESSAGE_LENGTH {
+            debug!(
+                "Dropping chat message of {}: invalid length",
+                self.inner.username
+            );
+            metrics::get().packet_malformed();
+            return None;
+        }
+        let message = chat::Message::new(
+            self.inner.room_id,
+            self.inner.username.clone(),
+            text.to_owned(),
+        );
+        let stamped = chat_packet(&message).write_to_bytes();
+        let chat_service = self.inner.chat_service.clone();
+        tokio::spawn(async move {
+            if let Err(e) = chat_service.create(&message).await {
+                error!("Error storing chat message {}: {}", message.id, e);
+            }
+        });
+        stamped
+            .map_err(|e| {
+                warn!(
+                    "Error stamping chat message of {}: {}",
+                    self.inner.username, e
+                )
+            })
+            .ok()
+    }
+
+    /// Warns about the first attempt only, the total is reported when the session ends
+    fn spoofed(&self, attempt: &str) {
+        metrics::get().packet_spoofed();
+        if self.inner.spoofed.fetch_add(1, Ordering::Relaxed) == 0 {
+            warn!("Peer {} tried to publish {}", self.inner.username, attempt);
+        }
+    }
+}
+
+/// The packet carrying a stored message, as published live and replayed to late joiners
+pub fn chat_packet(message: &chat::Message) -> ChatPacket {
+    ChatPacket {
+        id: message.id.to_string(),
+        username: message.username.clone(),
+        text: message.text.clone(),
+        sent_at: (message.sent_at.unix_timestamp_nanos() / 1_000_000) as i64,
+        ..Default::default()
+    }
+}
+
+/// The PONG of the relay to a PING addressed to it
+fn pong_wrapper(ping: PingPacket) -> Option<Bytes> {
+    let received_at = now_ms();
+    let mut pong = PingPacket {
+        sequence: ping.sequence,
+        sent_at: ping.sent_at,
+        received_at,
+        ..Default::default()
+    };
+    // taken as late as possible, the peers estimate the offset of their clocks with it
+    pong.answered_at = now_ms();
+    let data = pong
+        .write_to_bytes()
+        .map_err(|e| error!("Error serializing pong packet: {}", e))
+        .ok()?;
+    PacketWrapper {
+        packet_type: PacketType::PONG.into(),
+        data,
+        ..Default::default()
+    }
+    .write_to_bytes()
+    .map_err(|e| error!("Error serializing pong packet: {}", e))
+    .ok()
+    .map(Bytes::from)
+}
+
+/// Milliseconds since the epoch, the unit of the times in PING and PONG
+fn now_ms() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+        * 1000.0
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        let spoofed = *self.spoofed.get_mut();
+        if spoofed > 0 {
+            warn!(
+                "Peer {} tried to spoof {} packets during its session",
+                self.username, spoofed
+            );
+        }
+    }
+}
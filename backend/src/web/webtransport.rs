@@ -1,15 +1,23 @@
 use anyhow::{anyhow, Context, Result};
 use bytes::Bytes;
+use common::close_codes;
+use common::protocol::{E2EE_NONE, E2EE_RSA_AES128_CBC, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
 use common::protos::connection_packet::ConnectionPacket;
+use common::protos::going_away_packet::GoingAwayPacket;
+use common::protos::moderation_packet::{self, moderation_packet::Action};
+use common::protos::negotiation_packet::NegotiationPacket;
 use common::protos::packet_wrapper::packet_wrapper::PacketType;
 use common::protos::packet_wrapper::PacketWrapper;
+use common::protos::rejection_packet::{rejection_packet::Reason, RejectionPacket};
+use common::protos::roster_packet::roster_packet::EventType;
+use common::protos::roster_packet::RosterPacket;
+use common::protos::transport_packet::TransportPacket;
 use futures::StreamExt;
-use http::Method;
+use http::{header, Method, Request};
 use protobuf::Message;
 use quinn::crypto::rustls::HandshakeData;
 use quinn::VarInt;
 use rustls::{Certificate, PrivateKey};
-use sec_http3::error::Code;
 use sec_http3::sec_http3_quinn as h3_quinn;
 use sec_http3::webtransport::{server::WebTransportSession, stream};
 use sec_http3::{
@@ -18,23 +26,56 @@ use sec_http3::{
     quic::{self, RecvDatagramExt, SendDatagramExt, SendStreamUnframed},
     server::Connection,
 };
-use std::env;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeMap, HashMap};
 use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
-use tokio::sync::{watch, RwLock};
+use tokio::sync::{mpsc, watch, Notify, RwLock};
+use tower_cookies::cookie::CookieJar;
+use tower_cookies::Cookie;
 use tracing::{error, info, trace_span};
+use uuid::Uuid;
+
+use crate::metrics::{self, Delivery};
+use crate::service::chat;
+use crate::service::presence::{self, Membership};
+use crate::service::relay::{self, Relay};
+use crate::service::room::{self, Role, Room};
+use crate::service::user::session::{self, SessionData};
+use crate::web::limits::{Limiter, Limits, Verdict};
+use crate::web::moderation::{self, Moderation};
+use crate::web::outbound::{self, Lane};
+use crate::web::stamp::{self, Stamp, Stamped};
+use crate::web::{SESSION_COOKIE_KEY, SESSION_COOKIE_NAME};
 
 pub const WEB_TRANSPORT_ALPN: &[&[u8]] = &[b"h3", b"h3-32", b"h3-31", b"h3-30", b"h3-29"];
 
 pub const QUIC_ALPN: &[u8] = b"hq-29";
 
-const MAX_UNIDIRECTIONAL_STREAM_SIZE: usize = 1000_000;
+/// How long a joining peer waits for the other members to answer before getting its snapshot
+const ROSTER_SNAPSHOT_DELAY: Duration = Duration::from_millis(250);
+
+/// How long a refused peer has to read the rejection packet and close the connection itself
+const REJECTION_GRACE: Duration = Duration::from_secs(1);
+
+/// How long a WebTransport peer has to send its connection packet once the session is accepted
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Chat messages replayed to a peer joining the room
+const CHAT_HISTORY_SIZE: i64 = 50;
+
+/// Audio codecs accepted by the relay, in order of preference. Every peer of a room uses the
+/// same codecs, and the recordings only mux Opus and VP9.
+const AUDIO_CODECS: &[&str] = &["opus"];
+
+/// Video codecs accepted by the relay, in order of preference
+const VIDEO_CODECS: &[&str] = &["vp09.00.10.08"];
 
 #[derive(Debug)]
 pub struct WebTransportOpt {
     pub listen: SocketAddr,
     pub certs: Certs,
+    /// How long the peers have to move to another instance once the shutdown begins
+    pub drain_period: Duration,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +84,327 @@ pub struct Certs {
     pub key: PathBuf,
 }
 
+/// Services the relay needs to authenticate and connect peers
+#[derive(Clone)]
+pub struct RelayState {
+    pub session_service: session::Service,
+    pub room_service: room::Service,
+    pub presence_service: presence::Service,
+    pub chat_service: chat::Service,
+    pub relay: Arc<dyn Relay>,
+    /// What each session may send
+    pub limits: Limits,
+    /// Set to `true` when the server starts shutting down
+    pub shutdown: watch::Receiver<bool>,
+}
+
+/// Credential presented by a peer when it opens a session
+#[derive(Debug)]
+enum Credential {
+    /// The `session-id` cookie set by the REST API
+    Session(String),
+    /// A single use ticket issued by `POST /api/rooms/:id/ticket`
+    Ticket(String),
+}
+
+/// Reasons for refusing a peer, each one mapped to a distinct close code
+#[derive(Debug)]
+enum Rejection {
+    MalformedRequest(&'static str),
+    InvalidCredentials,
+    RoomNotFound,
+    ForbiddenName,
+    RoomClosed,
+    RoomFull,
+    /// The same user opened a newer session in the room
+    Replaced,
+    UnsupportedVersion,
+    UnsupportedCapabilities(&'static str),
+    /// The peer kept exceeding the limits of its session
+    RateLimited,
+    /// A moderator of the room kicked the peer
+    Kicked,
+    /// The peer did not read the packets relayed to it
+    TooSlow,
+    Internal,
+}
+
+impl Rejection {
+    fn code(&self) -> VarInt {
+        VarInt::from_u32(match self {
+            Rejection::MalformedRequest(_) => close_codes::MALFORMED_REQUEST,
+            Rejection::InvalidCredentials => close_codes::INVALID_CREDENTIALS,
+            Rejection::RoomNotFound => close_codes::ROOM_NOT_FOUND,
+            Rejection::ForbiddenName => close_codes::FORBIDDEN_NAME,
+            Rejection::RoomClosed => close_codes::ROOM_CLOSED,
+            Rejection::RoomFull => close_codes::ROOM_FULL,
+            Rejection::Replaced => close_codes::REPLACED,
+            Rejection::UnsupportedVersion => close_codes::UNSUPPORTED_VERSION,
+            Rejection::UnsupportedCapabilities(_) => close_codes::UNSUPPORTED_CAPABILITIES,
+            Rejection::RateLimited => close_codes::RATE_LIMITED,
+            Rejection::Kicked => close_codes::KICKED,
+            Rejection::TooSlow => close_codes::TOO_SLOW,
+            Rejection::Internal => close_codes::INTERNAL_ERROR,
+        })
+    }
+
+    fn reason(&self) -> &'static str {
+        match self {
+            Rejection::MalformedRequest(reason) => reason,
+            Rejection::InvalidCredentials => "Invalid credentials",
+            Rejection::RoomNotFound => "Room not found",
+            Rejection::ForbiddenName => "Invalid path input chars",
+            Rejection::RoomClosed => "The room is closed",
+            Rejection::RoomFull => "The room is full",
+            Rejection::Replaced => "Replaced by another session",
+            Rejection::UnsupportedVersion => "Unsupported protocol version",
+            Rejection::UnsupportedCapabilities(reason) => reason,
+            Rejection::RateLimited => "Too many packets",
+            Rejection::Kicked => "Kicked by a moderator",
+            Rejection::TooSlow => "Too slow to read the room",
+            Rejection::Internal => "Internal error",
+        }
+    }
+
+    /// Rejections happening after the session is established are also explained to the peer
+    /// with a REJECTION packet
+    fn packet(&self) -> Option<Bytes> {
+        let reason = match self {
+            Rejection::RoomClosed => Reason::ROOM_CLOSED,
+            Rejection::RoomFull => Reason::ROOM_FULL,
+            Rejection::Replaced => Reason::REPLACED,
+            Rejection::UnsupportedVersion => Reason::UNSUPPORTED_VERSION,
+            Rejection::UnsupportedCapabilities(_) => Reason::UNSUPPORTED_CAPABILITIES,
+            Rejection::Kicked => Reason::KICKED,
+            _ => return None,
+        };
+        let data = RejectionPacket {
+            reason: reason.into(),
+            message: self.reason().to_owned(),
+            ..Default::default()
+        }
+        .write_to_bytes()
+        .ok()?;
+        PacketWrapper {
+            packet_type: PacketType::REJECTION.into(),
+            data,
+            ..Default::default()
+        }
+        .write_to_bytes()
+        .ok()
+        .map(Bytes::from)
+    }
+
+    /// Closes the connection with the code of this rejection
+    fn close(self, conn: &quinn::Connection) -> anyhow::Error {
+        conn.close(self.code(), self.reason().as_bytes());
+        anyhow!(self.reason())
+    }
+}
+
+/// Identity of a peer as derived by the server, never taken from the client
+#[derive(Debug, Clone)]
+struct PeerIdentity {
+    username: String,
+    room_id: Uuid,
+    lobby_id: String,
+    /// Role in the room when the peer joined
+    role: Role,
+}
+
+impl PeerIdentity {
+    fn specific_subject(&self) -> String {
+        relay::peer_subject(&self.lobby_id, &self.username)
+    }
+}
+
+impl Credential {
+    fn from_request<T>(req: &Request<T>) -> Option<Self> {
+        if let Some(ticket) = req.uri().query().and_then(|query| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == "ticket")
+                .and_then(|(_, value)| urlencoding::decode(value).ok())
+        }) {
+            return Some(Credential::Ticket(ticket.into_owned()));
+        }
+
+        let mut jar = CookieJar::new();
+        for value in req.headers().get_all(header::COOKIE) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            for cookie in Cookie::split_parse_encoded(value.to_owned()).flatten() {
+                jar.add_original(cookie);
+            }
+        }
+        let key = SESSION_COOKIE_KEY
+            .get()
+            .expect("SESSION KEY IS NOT INITIALIZED");
+        jar.signed(key)
+            .get(SESSION_COOKIE_NAME)
+            .map(|cookie| Credential::Session(cookie.value().to_string()))
+    }
+}
+
+/// Resolves the credential to a session, checks the room and derives the identity of the peer,
+/// returned with the roles stored for the room
+async fn authenticate(
+    state: &RelayState,
+    credential: Credential,
+    room_id: Uuid,
+) -> std::result::Result<(PeerIdentity, Room, HashMap<String, Role>), Rejection> {
+    let session = match credential {
+        Credential::Session(token) => state.session_service.get(token).await,
+        Credential::Ticket(ticket) => {
+            state
+                .session_service
+                .redeem_join_ticket(&ticket, room_id)
+                .await
+        }
+    };
+    let SessionData { username, .. } = match session {
+        Ok(Some(session)) => session,
+        Ok(None) => return Err(Rejection::InvalidCredentials),
+        Err(e) => {
+            error!("Error resolving session: {}", e);
+            return Err(Rejection::Internal);
+        }
+    };
+
+    let room = match state.room_service.get_by_id(room_id).await {
+        Ok(Some(room)) => room,
+        Ok(None) => return Err(Rejection::RoomNotFound),
+        Err(e) => {
+            error!("Error loading room {}: {}", room_id, e);
+            return Err(Rejection::Internal);
+        }
+    };
+    // a private room is not found by the users who cannot join it, as in the REST API
+    match state.room_service.can_join(&room, &username).await {
+        Ok(true) => {}
+        Ok(false) => return Err(Rejection::RoomNotFound),
+        Err(e) => {
+            error!("Error checking the members of room {}: {}", room_id, e);
+            return Err(Rejection::Internal);
+        }
+    }
+    let roles = match state.room_service.roles(room_id).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            error!("Error loading the roles of room {}: {}", room_id, e);
+            return Err(Rejection::Internal);
+        }
+    };
+    let role = if room.owner == username {
+        Role::Owner
+    } else {
+        roles.get(&username).copied().unwrap_or(Role::Musician)
+    };
+
+    // the username is the name of the peer on the relay, as stored: rewriting it would let two
+    // accounts share it
+    let lobby_id = room_id.to_string();
+    let re = regex::Regex::new("^[a-zA-Z0-9_-]*$").unwrap();
+    if !re.is_match(&username) || !re.is_match(&lobby_id) {
+        return Err(Rejection::ForbiddenName);
+    }
+
+    Ok((
+        PeerIdentity {
+            username,
+            room_id,
+            lobby_id,
+            role,
+        },
+        room,
+        roles,
+    ))
+}
+
+/// Settles what the session uses from what the client supports: the codecs are the preferred ones
+/// of the relay, the end to end encryption is imposed by the room
+fn negotiate(
+    packet: &ConnectionPacket,
+    room: &Room,
+) -> std::result::Result<NegotiationPacket, Rejection> {
+    if !(MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&packet.protocol_version) {
+        return Err(Rejection::UnsupportedVersion);
+    }
+    info!(
+        "Client {} speaks version {} of the protocol",
+        packet.client_version, packet.protocol_version
+    );
+
+    let capabilities = &packet.capabilities;
+    let pick = |supported: &[&str], offered: &[String]| {
+        supported
+            .iter()
+            .find(|codec| offered.iter().any(|offered| offered == *codec))
+            .map(|codec| codec.to_string())
+    };
+    let audio_codec = pick(AUDIO_CODECS, &capabilities.audio_codecs).ok_or(
+        Rejection::UnsupportedCapabilities("No audio codec in common"),
+    )?;
+    let video_codec = pick(VIDEO_CODECS, &capabilities.video_codecs).ok_or(
+        Rejection::UnsupportedCapabilities("No video codec in common"),
+    )?;
+    let (e2ee_scheme, refusal) = if room.encrypted {
+        (
+            E2EE_RSA_AES128_CBC,
+            "The room requires end to end encryption",
+        )
+    } else {
+        (E2EE_NONE, "The room does not use end to end encryption")
+    };
+    if !capabilities
+        .e2ee_schemes
+        .iter()
+        .any(|scheme| scheme == e2ee_scheme)
+    {
+        return Err(Rejection::UnsupportedCapabilities(refusal));
+    }
+
+    Ok(NegotiationPacket {
+        protocol_version: packet.protocol_version,
+        server_version: format!("rtjam-backend/{}", env!("CARGO_PKG_VERSION")),
+        audio_codec,
+        video_codec,
+        e2ee_scheme: e2ee_scheme.to_owned(),
+        datagrams: capabilities.datagrams,
+        ..Default::default()
+    })
+}
+
+/// Counts the peer among the publishers of the room, if the room lets it in. Listeners take no
+/// seat, any number of them joins a room
+fn admit(
+    state: &RelayState,
+    identity: &PeerIdentity,
+    room: &Room,
+    roles: &HashMap<String, Role>,
+    packet: &ConnectionPacket,
+) -> std::result::Result<(Membership, NegotiationPacket), Rejection> {
+    if !room.open {
+        return Err(Rejection::RoomClosed);
+    }
+    let mut negotiated = negotiate(packet, room)?;
+    negotiated.role = moderation::role_packet(identity.role).into();
+    let capacity = if identity.role == Role::Listener {
+        usize::MAX
+    } else {
+        usize::try_from(room.max_people_playing).unwrap_or_default()
+    };
+    let membership = state
+        .presence_service
+        .join(&identity.lobby_id, &identity.username, capacity, |member| {
+            roles.get(member) != Some(&Role::Listener)
+        })
+        .ok_or(Rejection::RoomFull)?;
+    Ok((membership, negotiated))
+}
+
 fn get_key_and_cert_chain(certs: Certs) -> anyhow::Result<(PrivateKey, Vec<Certificate>)> {
     let key_path = certs.key;
     let cert_path = certs.cert;
@@ -90,7 +452,10 @@ pub fn is_http3(conn: &quinn::Connection) -> bool {
     false
 }
 
-pub async fn start(opt: WebTransportOpt) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn start(
+    opt: WebTransportOpt,
+    state: RelayState,
+) -> Result<(), Box<dyn std::error::Error>> {
     info!("WebTransportOpt: {opt:#?}");
 
     let (key, certs) = get_key_and_cert_chain(opt.certs)?;
@@ -117,20 +482,26 @@ pub async fn start(opt: WebTransportOpt) -> Result<(), Box<dyn std::error::Error
     let mut transport_config = quinn::TransportConfig::default();
     transport_config.keep_alive_interval(Some(Duration::from_secs(2)));
     transport_config.max_idle_timeout(Some(VarInt::from_u32(10_000).into()));
+    transport_config.max_concurrent_uni_streams(VarInt::from_u32(
+        state.limits.max_concurrent_streams,
+    ));
     server_config.transport = Arc::new(transport_config);
     let endpoint = quinn::Endpoint::server(server_config, opt.listen)?;
 
     info!("listening on {}", opt.listen);
 
-    let addr = env::var("RTJAM_NATS_URL")?;
-    let nc =
-        async_nats::connect(addr)
-            .await?;
-
     // 2. Accept new quic connections and spawn a new task to handle them
-    while let Some(new_conn) = endpoint.accept().await {
+    let mut shutdown = state.shutdown.clone();
+    loop {
+        let new_conn = tokio::select! {
+            new_conn = endpoint.accept() => new_conn,
+            _ = shutdown.wait_for(|shutdown| *shutdown) => None,
+        };
+        let Some(new_conn) = new_conn else {
+            break;
+        };
         trace_span!("New connection being attempted");
-        let nc = nc.clone();
+        let state = state.clone();
 
         tokio::spawn(async move {
             match new_conn.await {
@@ -143,17 +514,15 @@ pub async fn start(opt: WebTransportOpt) -> Result<(), Box<dyn std::error::Error
                             .enable_datagram(true)
                             .max_webtransport_sessions(1)
                             .send_grease(true)
-                            .build(h3_quinn::Connection::new(conn))
+                            .build(h3_quinn::Connection::new(conn.clone()))
                             .await
                             .unwrap();
-                        let nc = nc.clone();
-                        if let Err(err) = handle_h3_connection(h3_conn, nc).await {
+                        if let Err(err) = handle_h3_connection(h3_conn, conn, state).await {
                             error!("Failed to handle connection: {err:?}");
                         }
                     } else {
                         info!("new quic established");
-                        let nc = nc.clone();
-                        if let Err(err) = handle_quic_connection(conn, nc).await {
+                        if let Err(err) = handle_quic_connection(conn, state).await {
                             error!("Failed to handle connection: {err:?}");
                         }
                     }
@@ -166,6 +535,19 @@ pub async fn start(opt: WebTransportOpt) -> Result<(), Box<dyn std::error::Error
     }
 
     // shut down gracefully
+    // refuse new connections, the sessions have been told to go away and get the drain period to
+    // reconnect elsewhere before being closed
+    endpoint.set_server_config(None);
+    info!("Draining connections for {:?}", opt.drain_period);
+    if tokio::time::timeout(opt.drain_period, endpoint.wait_idle())
+        .await
+        .is_err()
+    {
+        endpoint.close(
+            VarInt::from_u32(close_codes::GOING_AWAY),
+            b"Server going away",
+        );
+    }
     // wait for connections to be closed before exiting
     endpoint.wait_idle().await;
 
@@ -174,7 +556,8 @@ pub async fn start(opt: WebTransportOpt) -> Result<(), Box<dyn std::error::Error
 
 async fn handle_h3_connection(
     mut conn: Connection<h3_quinn::Connection, Bytes>,
-    nc: async_nats::client::Client,
+    quic_conn: quinn::Connection,
+    state: RelayState,
 ) -> Result<()> {
     // 3. TODO: Conditionally, if the client indicated that this is a webtransport session, we should accept it here, else use regular h3.
     // if this is a webtransport session, then h3 needs to stop handing the datagrams, bidirectional streams, and unidirectional streams and give them
@@ -196,21 +579,28 @@ async fn handle_h3_connection(
                         // filter out the empty strings
                         let parts = parts.iter().filter(|s| !s.is_empty()).collect::<Vec<_>>();
                         info!("Parts {:?}", parts);
-                        if parts.len() != 3 {
-                            conn.close(Code::H3_REQUEST_REJECTED, "Invalid path wrong length");
-                            return Err(anyhow!("Invalid path wrong length"));
+                        if parts.len() != 2 {
+                            return Err(Rejection::MalformedRequest("Invalid path wrong length")
+                                .close(&quic_conn));
                         } else if parts[0] != &"room" {
-                            conn.close(Code::H3_REQUEST_REJECTED, "Invalid path wrong prefix");
-                            return Err(anyhow!("Invalid path wrong prefix"));
+                            return Err(Rejection::MalformedRequest("Invalid path wrong prefix")
+                                .close(&quic_conn));
                         }
 
-                        let username = parts[1].replace(' ', "_");
-                        let lobby_id = parts[2].replace(' ', "_");
-                        let re = regex::Regex::new("^[a-zA-Z0-9_]*$").unwrap();
-                        if !re.is_match(&username) && !re.is_match(&lobby_id) {
-                            conn.close(Code::H3_REQUEST_REJECTED, "Invalid path input chars");
-                            return Err(anyhow!("Invalid path input chars"));
-                        }
+                        let Ok(room_id) = Uuid::parse_str(parts[1]) else {
+                            return Err(Rejection::MalformedRequest("Invalid room id")
+                                .close(&quic_conn));
+                        };
+
+                        let Some(credential) = Credential::from_request(&req) else {
+                            return Err(Rejection::InvalidCredentials.close(&quic_conn));
+                        };
+
+                        let (identity, room, roles) =
+                            match authenticate(&state, credential, room_id).await {
+                                Ok(authenticated) => authenticated,
+                                Err(rejection) => return Err(rejection.close(&quic_conn)),
+                            };
 
                         info!("Peer wants to initiate a webtransport session");
 
@@ -218,9 +608,57 @@ async fn handle_h3_connection(
 
                         let session = WebTransportSession::accept(req, stream, conn).await?;
                         info!("Established webtransport session");
+
+                        // clients speaking no version of the protocol never send a connection
+                        // packet
+                        let admission = match tokio::time::timeout(
+                            HANDSHAKE_TIMEOUT,
+                            read_connection(&session, state.limits.max_stream_size),
+                        )
+                        .await
+                        {
+                            Ok(Some(packet)) => admit(&state, &identity, &room, &roles, &packet),
+                            Ok(None) | Err(_) => Err(Rejection::UnsupportedVersion),
+                        };
+                        let (_membership, negotiated) = match admission {
+                            Ok(admitted) => admitted,
+                            Err(rejection) => {
+                                if let Some(packet) = rejection.packet() {
+                                    if let Err(e) = send_to_session(&session, &packet).await {
+                                        error!("Error sending rejection: {}", e);
+                                    }
+                                }
+                                let _ = tokio::time::timeout(REJECTION_GRACE, quic_conn.closed())
+                                    .await;
+                                return Err(rejection.close(&quic_conn));
+                            }
+                        };
+                        if let Some(packet) = negotiation_wrapper(&negotiated) {
+                            if let Err(e) = send_to_session(&session, &packet).await {
+                                error!("Error sending negotiation: {}", e);
+                            }
+                        }
+                        let PeerIdentity {
+                            username,
+                            room_id,
+                            lobby_id,
+                            role,
+                        } = identity;
+                        let _session_metrics =
+                            metrics::get().session_opened("webtransport", &lobby_id);
                         // 4. Get datagrams, bidirectional streams, and unidirectional streams and wait for client requests here.
                         // h3_conn needs to handover the datagrams, bidirectional streams, and unidirectional streams to the webtransport session.
-                        handle_session(session, &username, &lobby_id, nc.clone()).await?;
+                        handle_session(
+                            session,
+                            quic_conn,
+                            &username,
+                            room_id,
+                            &lobby_id,
+                            role,
+                            negotiated.datagrams,
+                            &state,
+                        )
+                        .await?;
                         return Ok(());
                     }
                     _ => {
@@ -246,12 +684,57 @@ async fn handle_h3_connection(
     Ok(())
 }
 
-#[tracing::instrument(level = "trace", skip(session))]
+/// Waits for the connection packet of a WebTransport peer, the streams sent before it are
+/// discarded
+async fn read_connection(
+    session: &WebTransportSession<h3_quinn::Connection, Bytes>,
+    max_stream_size: usize,
+) -> Option<ConnectionPacket> {
+    while let Ok(Some((_id, uni_stream))) = session.accept_uni().await {
+        let mut buf = Vec::new();
+        if let Err(e) = uni_stream
+            .take(max_stream_size as u64)
+            .read_to_end(&mut buf)
+            .await
+        {
+            error!("Error reading from unidirectional stream: {}", e);
+            continue;
+        }
+        let Ok(packet_wrapper) = PacketWrapper::parse_from_bytes(&buf) else {
+            continue;
+        };
+        if packet_wrapper.packet_type == PacketType::CONNECTION.into() {
+            info!("Got connection packet");
+            return ConnectionPacket::parse_from_bytes(&packet_wrapper.data).ok();
+        }
+    }
+    None
+}
+
+async fn send_to_session(
+    session: &WebTransportSession<h3_quinn::Connection, Bytes>,
+    packet: &[u8],
+) -> Result<()> {
+    let mut uni_stream = session
+        .open_uni(session.session_id())
+        .await
+        .context("opening unidirectional stream")?;
+    uni_stream.write_all(packet).await?;
+    uni_stream.shutdown().await?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(level = "trace", skip(session, quic_conn, state))]
 async fn handle_session<C>(
     session: WebTransportSession<C, Bytes>,
+    quic_conn: quinn::Connection,
     username: &str,
+    room_id: Uuid,
     lobby_id: &str,
-    nc: async_nats::client::Client,
+    role: Role,
+    datagrams: bool,
+    state: &RelayState,
 ) -> anyhow::Result<()>
 where
     // Use trait bounds to ensure we only happen to use implementation that are only for the quinn
@@ -282,55 +765,122 @@ where
 {
     let session_id = session.session_id();
     let session = Arc::new(RwLock::new(session));
-    let should_run = Arc::new(AtomicBool::new(true));
+    let stop = Arc::new(Notify::new());
+    let relay = state.relay.clone();
 
-    let subject = format!("room.{}.*", lobby_id).replace(' ', "_");
-    let specific_subject = format!("room.{}.{}", lobby_id, username).replace(' ', "_");
-    let mut sub = match nc
-        .queue_subscribe(subject.clone(), specific_subject.clone())
-        .await
-    {
+    let specific_subject = relay::peer_subject(lobby_id, username);
+    let moderation = Moderation::new(
+        username,
+        room_id,
+        role,
+        state.room_service.clone(),
+        relay.clone(),
+    );
+    let stamp = Stamp::new(
+        username,
+        room_id,
+        state.chat_service.clone(),
+        moderation.clone(),
+    );
+    let limiter = Limiter::new(username, &state.limits);
+    let sub = match relay.subscribe_room(lobby_id, &specific_subject).await {
         Ok(sub) => {
-            info!("Subscribed to subject {}", subject);
+            info!("Subscribed to subject {}", relay::room_subject(lobby_id));
             sub
         }
         Err(e) => {
-            let err = format!("error subscribing to subject {}: {}", subject, e);
+            let err = format!("error subscribing to room {}: {}", lobby_id, e);
             error!("{}", err);
             return Err(anyhow!(err));
         }
     };
+    let claim = presence::claim(&relay, lobby_id, username).await?;
 
-    let specific_subject_clone = specific_subject.clone();
+    let (outbound_tx, mut outbound_rx) = outbound::channel(&specific_subject);
+    send_chat_history(&state.chat_service, room_id, &outbound_tx).await;
+    send_transport(&state.room_service, room_id, &outbound_tx).await;
 
-    let nats_task = {
-        let session = session.clone();
-        let should_run = should_run.clone();
+    let relay_task = {
+        let quic_conn = quic_conn.clone();
+        let relay_room = relay_room(
+            relay.clone(),
+            sub,
+            claim,
+            specific_subject.clone(),
+            username.to_owned(),
+            moderation,
+            stop.clone(),
+            state.shutdown.clone(),
+            outbound_tx,
+        );
         tokio::spawn(async move {
-            while let Some(msg) = sub.next().await {
-                if !should_run.load(Ordering::SeqCst) {
-                    break;
-                }
-                if msg.subject == specific_subject_clone {
-                    continue;
-                }
+            if let Err(rejection) = relay_room.await {
+                let _ = tokio::time::timeout(REJECTION_GRACE, quic_conn.closed()).await;
+                let err = rejection.close(&quic_conn);
+                info!("Closed session: {}", err);
+            }
+        })
+    };
+
+    let writer_task = {
+        let session = session.clone();
+        let quic_conn = quic_conn.clone();
+        // every stream is opened by the task of its kind, so that the datagrams and the other
+        // packets never wait behind a slow video stream
+        let stream_writer = |streams: outbound::Streams| {
+            let session = session.clone();
+            let (tx, mut rx) = mpsc::channel::<Bytes>(outbound::STREAM_QUEUE_CAPACITY);
+            tokio::spawn(async move {
                 let session = session.read().await;
-                if msg.payload.len() > 400 {
-                    let stream = session.open_uni(session_id).await;
-                    tokio::spawn(async move {
-                        match stream {
-                            Ok(mut uni_stream) => {
-                                if let Err(e) = uni_stream.write_all(&msg.payload).await {
+                while let Some(payload) = rx.recv().await {
+                    match session.open_uni(session_id).await {
+                        Ok(mut uni_stream) => {
+                            streams.sent(payload.len());
+                            let streams = streams.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = uni_stream.write_all(&payload).await {
+                                    streams.failed();
                                     error!("Error writing to unidirectional stream: {}", e);
                                 }
-                            }
-                            Err(e) => {
-                                error!("Error opening unidirectional stream: {}", e);
-                            }
+                            });
                         }
-                    });
-                } else if let Err(e) = session.send_datagram(msg.payload) {
-                    error!("Error sending datagram: {}", e);
+                        Err(e) => {
+                            streams.failed();
+                            error!("Error opening unidirectional stream: {}", e);
+                        }
+                    }
+                }
+            });
+            tx
+        };
+        let streams = stream_writer(outbound_rx.streams());
+        let video_streams = stream_writer(outbound_rx.streams());
+        tokio::spawn(async move {
+            let session = session.read().await;
+            while let Some(packet) = outbound_rx.recv().await {
+                let payload = packet.payload;
+                let max_datagram_size = quic_conn
+                    .max_datagram_size()
+                    .filter(|_| datagrams && !packet.reliable)
+                    .map(|size| size.saturating_sub(outbound::WEBTRANSPORT_DATAGRAM_OVERHEAD));
+                if max_datagram_size.is_some_and(|size| payload.len() <= size) {
+                    match session.send_datagram(payload.clone()) {
+                        Ok(()) => {
+                            outbound_rx.datagram_sent(payload.len());
+                            continue;
+                        }
+                        Err(e) => {
+                            outbound_rx.datagram_failed();
+                            error!("Error sending datagram, falling back to a stream: {}", e);
+                        }
+                    }
+                }
+                let streams = match packet.lane {
+                    Lane::Video => &video_streams,
+                    _ => &streams,
+                };
+                if streams.try_send(payload).is_err() {
+                    outbound_rx.stream_backlogged(packet.lane);
                 }
             }
         })
@@ -338,20 +888,59 @@ where
 
     let quic_task = {
         let session = session.clone();
-        let nc = nc.clone();
+        let answers = session.clone();
+        let relay = relay.clone();
         let specific_subject = specific_subject.clone();
+        let stamp = stamp.clone();
+        let limiter = limiter.clone();
+        let quic_conn = quic_conn.clone();
         tokio::spawn(async move {
             let session = session.read().await;
             while let Ok(uni_stream) = session.accept_uni().await {
                 if let Some((_id, mut uni_stream)) = uni_stream {
-                    let nc = nc.clone();
+                    let answers = answers.clone();
+                    let relay = relay.clone();
                     let specific_subject = specific_subject.clone();
+                    let stamp = stamp.clone();
+                    let limiter = limiter.clone();
+                    let quic_conn = quic_conn.clone();
                     tokio::spawn(async move {
                         let mut buf = Vec::new();
-                        if let Err(e) = uni_stream.read_to_end(&mut buf).await {
+                        let max_stream_size = limiter.max_stream_size();
+                        // one byte more tells an oversized stream apart
+                        if let Err(e) = (&mut uni_stream)
+                            .take(max_stream_size as u64 + 1)
+                            .read_to_end(&mut buf)
+                            .await
+                        {
                             error!("Error reading from unidirectional stream: {}", e);
                         }
-                        if let Err(e) = nc.publish(specific_subject.clone(), buf.into()).await {
+                        metrics::get().packet_received(buf.len());
+                        let verdict = if buf.len() > max_stream_size {
+                            limiter.oversized()
+                        } else {
+                            limiter.check(Delivery::Stream, buf.len())
+                        };
+                        if !within_limits(verdict, &quic_conn) {
+                            return;
+                        }
+                        let buf = match stamp.apply(buf.into()) {
+                            Stamped::Publish(buf) => buf,
+                            Stamped::Answer(answer) => {
+                                let session = answers.read().await;
+                                match session.open_uni(session_id).await {
+                                    Ok(mut uni_stream) => {
+                                        if let Err(e) = uni_stream.write_all(&answer).await {
+                                            error!("Error writing to unidirectional stream: {}", e);
+                                        }
+                                    }
+                                    Err(e) => error!("Error opening unidirectional stream: {}", e),
+                                }
+                                return;
+                            }
+                            Stamped::Drop => return,
+                        };
+                        if let Err(e) = relay.publish(specific_subject.clone(), buf).await {
                             error!("Error publishing to subject {}: {}", &specific_subject, e);
                         }
                     });
@@ -365,8 +954,21 @@ where
             let session = session.read().await;
             while let Ok(datagram) = session.accept_datagram().await {
                 if let Some((_id, buf)) = datagram {
-                    let nc = nc.clone();
-                    if let Err(e) = nc.publish(specific_subject.clone(), buf).await {
+                    metrics::get().packet_received(buf.len());
+                    if !within_limits(limiter.check(Delivery::Datagram, buf.len()), &quic_conn) {
+                        continue;
+                    }
+                    let buf = match stamp.apply(buf) {
+                        Stamped::Publish(buf) => buf,
+                        Stamped::Answer(answer) => {
+                            if let Err(e) = session.send_datagram(answer) {
+                                error!("Error sending datagram: {}", e);
+                            }
+                            continue;
+                        }
+                        Stamped::Drop => continue,
+                    };
+                    if let Err(e) = relay.publish(specific_subject.clone(), buf).await {
                         error!("Error publishing to subject {}: {}", specific_subject, e);
                     }
                 }
@@ -374,109 +976,286 @@ where
         })
     };
     quic_task.await?;
-    should_run.store(false, Ordering::SeqCst);
-    nats_task.abort();
+    stop.notify_one();
+    relay_task.await?;
+    writer_task.await?;
     info!("Finished handling session");
     Ok(())
 }
 
-async fn handle_quic_connection(
-    conn: quinn::Connection,
-    nc: async_nats::client::Client,
-) -> Result<()> {
+async fn handle_quic_connection(conn: quinn::Connection, state: RelayState) -> Result<()> {
     let _session_id = conn.stable_id();
-    let session = Arc::new(RwLock::new(conn));
-    let should_run = Arc::new(AtomicBool::new(true));
-    let (specific_subject_tx, mut specific_subject_rx) = watch::channel::<Option<String>>(None);
+    let session = Arc::new(RwLock::new(conn.clone()));
+    let stop = Arc::new(Notify::new());
+    let relay = state.relay.clone();
+    let (identity_tx, mut identity_rx) =
+        watch::channel::<Option<(PeerIdentity, NegotiationPacket, Moderation)>>(None);
+    // the uni streams and the datagrams of the session share the count of spoofing attempts and
+    // the limits, set once the peer is admitted
+    let checks = Arc::new(std::sync::OnceLock::<(Stamp, Limiter)>::new());
+    let membership = Arc::new(std::sync::Mutex::new(None::<Membership>));
 
-    let nats_task = {
+    let relay_task = {
+        let conn = conn.clone();
         let session = session.clone();
-        let should_run = should_run.clone();
-        let nc_clone = nc.clone();
-        let specific_subject_rx_clone = specific_subject_rx.clone();
+        let relay = relay.clone();
+        let stop = stop.clone();
+        let shutdown = state.shutdown.clone();
+        let chat_service = state.chat_service.clone();
+        let room_service = state.room_service.clone();
+        let identity_rx_clone = identity_rx.clone();
         tokio::spawn(async move {
-            let mut specific_subject_rx = specific_subject_rx_clone;
-            let nc = nc_clone;
-            specific_subject_rx.changed().await.unwrap();
-            let specific_subject = specific_subject_rx.borrow().clone().unwrap();
-            let subject = session_subject_to_lobby_subject(&specific_subject);
-            let mut sub = match nc
-                .queue_subscribe(subject.clone(), specific_subject.clone())
+            let mut identity_rx = identity_rx_clone;
+            tokio::select! {
+                changed = identity_rx.changed() => {
+                    if let Err(e) = changed {
+                        error!("Error waiting for the identity of the peer: {}", e);
+                        return;
+                    }
+                }
+                _ = stop.notified() => return,
+            }
+            let (identity, negotiated, moderation) = identity_rx.borrow().clone().unwrap();
+            let _session_metrics = metrics::get().session_opened("quic", &identity.lobby_id);
+            let specific_subject = identity.specific_subject();
+            let sub = match relay
+                .subscribe_room(&identity.lobby_id, &specific_subject)
                 .await
             {
                 Ok(sub) => {
-                    info!("Subscribed to subject {}", subject);
+                    info!("Subscribed to subject {}", relay::room_subject(&identity.lobby_id));
                     sub
                 }
                 Err(e) => {
-                    let err = format!("error subscribing to subject {}: {}", subject, e);
+                    let err = format!("error subscribing to room {}: {}", identity.lobby_id, e);
                     error!("{}", err);
                     return;
                 }
             };
-            while let Some(msg) = sub.next().await {
-                if !should_run.load(Ordering::SeqCst) {
-                    break;
-                }
-                if Some(msg.subject) == specific_subject_rx.borrow().clone() {
-                    continue;
+            let claim = match presence::claim(&relay, &identity.lobby_id, &identity.username).await
+            {
+                Ok(claim) => claim,
+                Err(e) => {
+                    error!("Error claiming room {}: {}", identity.lobby_id, e);
+                    return;
                 }
-                let session = session.read().await;
-                if msg.payload.len() > 400 {
-                    let stream = session.open_uni().await;
-                    tokio::spawn(async move {
-                        match stream {
+            };
+            let (outbound_tx, mut outbound_rx) = outbound::channel(&specific_subject);
+            send_chat_history(&chat_service, identity.room_id, &outbound_tx).await;
+            send_transport(&room_service, identity.room_id, &outbound_tx).await;
+            // every stream is opened by the task of its kind, so that the datagrams and the other
+            // packets never wait behind a slow video stream
+            let stream_writer = |streams: outbound::Streams| {
+                let session = session.clone();
+                let (tx, mut rx) = mpsc::channel::<Bytes>(outbound::STREAM_QUEUE_CAPACITY);
+                tokio::spawn(async move {
+                    let session = session.read().await;
+                    while let Some(payload) = rx.recv().await {
+                        match session.open_uni().await {
                             Ok(mut uni_stream) => {
-                                if let Err(e) = uni_stream.write_all(&msg.payload).await {
-                                    error!("Error writing to unidirectional stream: {}", e);
-                                }
+                                streams.sent(payload.len());
+                                let streams = streams.clone();
+                                tokio::spawn(async move {
+                                    if let Err(e) = uni_stream.write_all(&payload).await {
+                                        streams.failed();
+                                        error!("Error writing to unidirectional stream: {}", e);
+                                    }
+                                });
                             }
                             Err(e) => {
+                                streams.failed();
                                 error!("Error opening unidirectional stream: {}", e);
                             }
                         }
-                    });
-                } else if let Err(e) = session.send_datagram(msg.payload) {
-                    error!("Error sending datagram: {}", e);
+                    }
+                });
+                tx
+            };
+            let streams = stream_writer(outbound_rx.streams());
+            let video_streams = stream_writer(outbound_rx.streams());
+            let writer_task = tokio::spawn(async move {
+                let session = session.read().await;
+                while let Some(packet) = outbound_rx.recv().await {
+                    let payload = packet.payload;
+                    if negotiated.datagrams
+                        && !packet.reliable
+                        && session
+                            .max_datagram_size()
+                            .is_some_and(|size| payload.len() <= size)
+                    {
+                        match session.send_datagram(payload.clone()) {
+                            Ok(()) => {
+                                outbound_rx.datagram_sent(payload.len());
+                                continue;
+                            }
+                            Err(e) => {
+                                outbound_rx.datagram_failed();
+                                error!("Error sending datagram, falling back to a stream: {}", e);
+                            }
+                        }
+                    }
+                    let streams = match packet.lane {
+                        Lane::Video => &video_streams,
+                        _ => &streams,
+                    };
+                    if streams.try_send(payload).is_err() {
+                        outbound_rx.stream_backlogged(packet.lane);
+                    }
                 }
+            });
+            let ended = relay_room(
+                relay,
+                sub,
+                claim,
+                specific_subject,
+                identity.username,
+                moderation,
+                stop,
+                shutdown,
+                outbound_tx,
+            )
+            .await;
+            if let Err(e) = writer_task.await {
+                error!("Error joining writer task: {}", e);
+            }
+            if let Err(rejection) = ended {
+                let _ = tokio::time::timeout(REJECTION_GRACE, conn.closed()).await;
+                let err = rejection.close(&conn);
+                info!("Closed session: {}", err);
             }
         })
     };
 
     let quic_task = {
-        let specific_subject_rx_clone = specific_subject_rx.clone();
+        let conn = conn.clone();
+        let identity_rx_clone = identity_rx.clone();
         let session = session.clone();
-        let nc = nc.clone();
+        let relay = relay.clone();
+        let membership = membership.clone();
+        let checks = checks.clone();
         tokio::spawn(async move {
             let session = session.read().await;
-            let specific_subject_tx = Arc::new(specific_subject_tx);
+            let identity_tx = Arc::new(identity_tx);
             while let Ok(mut uni_stream) = session.accept_uni().await {
-                let nc = nc.clone();
-                let specific_subject_tx_clone = specific_subject_tx.clone();
-                let specific_subject_rx = specific_subject_rx_clone.clone();
+                let relay = relay.clone();
+                let conn = conn.clone();
+                let state = state.clone();
+                let membership = membership.clone();
+                let checks = checks.clone();
+                let identity_tx_clone = identity_tx.clone();
+                let identity_rx = identity_rx_clone.clone();
                 tokio::spawn(async move {
-                    if let Ok(d) = uni_stream.read_to_end(MAX_UNIDIRECTIONAL_STREAM_SIZE).await {
-                        if specific_subject_rx.borrow().is_none() {
+                    let read = uni_stream.read_to_end(state.limits.max_stream_size).await;
+                    if let Err(quinn::ReadToEndError::TooLong) = read {
+                        match checks.get() {
+                            Some((_, limiter)) => {
+                                within_limits(limiter.oversized(), &conn);
+                            }
+                            None => error!("Connection packet too long"),
+                        }
+                        return;
+                    }
+                    if let Ok(d) = read {
+                        if identity_rx.borrow().is_none() {
                             if let Ok(packet_wrapper) = PacketWrapper::parse_from_bytes(&d) {
                                 if packet_wrapper.packet_type == PacketType::CONNECTION.into() {
                                     info!("Got connection packet");
-                                    let connection_packet =
+                                    let Ok(connection_packet) =
                                         ConnectionPacket::parse_from_bytes(&packet_wrapper.data)
-                                            .unwrap();
-                                    let specific_subject = format!(
-                                        "room.{}.{}",
-                                        connection_packet.meeting_id, packet_wrapper.email
-                                    )
-                                    .replace(' ', "_");
-                                    info!("Specific subject: {}", specific_subject);
-                                    specific_subject_tx_clone
-                                        .send(Some(specific_subject.clone()))
+                                    else {
+                                        let err = Rejection::MalformedRequest(
+                                            "Invalid connection packet",
+                                        )
+                                        .close(&conn);
+                                        error!("Rejected connection: {}", err);
+                                        return;
+                                    };
+                                    let Ok(room_id) = Uuid::parse_str(&connection_packet.meeting_id)
+                                    else {
+                                        let err = Rejection::MalformedRequest("Invalid room id")
+                                            .close(&conn);
+                                        error!("Rejected connection: {}", err);
+                                        return;
+                                    };
+                                    let credential =
+                                        Credential::Ticket(connection_packet.ticket.clone());
+                                    let admission =
+                                        match authenticate(&state, credential, room_id).await {
+                                            Ok((identity, room, roles)) => admit(
+                                                &state,
+                                                &identity,
+                                                &room,
+                                                &roles,
+                                                &connection_packet,
+                                            )
+                                            .map(|(membership, negotiated)| {
+                                                (identity, membership, negotiated)
+                                            }),
+                                            Err(rejection) => Err(rejection),
+                                        };
+                                    let (identity, negotiated) = match admission {
+                                        Ok((identity, admitted, negotiated)) => {
+                                            membership.lock().unwrap().replace(admitted);
+                                            (identity, negotiated)
+                                        }
+                                        Err(rejection) => {
+                                            if let Some(packet) = rejection.packet() {
+                                                send_rejection(&conn, &packet).await;
+                                            }
+                                            let err = rejection.close(&conn);
+                                            error!("Rejected connection: {}", err);
+                                            return;
+                                        }
+                                    };
+                                    if let Some(packet) = negotiation_wrapper(&negotiated) {
+                                        if let Err(e) = send_uni(&conn, &packet).await {
+                                            error!("Error sending negotiation: {}", e);
+                                        }
+                                    }
+                                    let moderation = Moderation::new(
+                                        &identity.username,
+                                        identity.room_id,
+                                        identity.role,
+                                        state.room_service.clone(),
+                                        relay.clone(),
+                                    );
+                                    let _ = checks.set((
+                                        Stamp::new(
+                                            &identity.username,
+                                            identity.room_id,
+                                            state.chat_service.clone(),
+                                            moderation.clone(),
+                                        ),
+                                        Limiter::new(&identity.username, &state.limits),
+                                    ));
+                                    info!("Specific subject: {}", identity.specific_subject());
+                                    identity_tx_clone
+                                        .send(Some((identity, negotiated, moderation)))
                                         .unwrap();
                                 }
                             }
                         } else {
-                            let specific_subject = specific_subject_rx.borrow().clone().unwrap();
-                            if let Err(e) = nc.publish(specific_subject.clone(), d.into()).await {
+                            let specific_subject = {
+                                let identity = identity_rx.borrow();
+                                let (identity, ..) = identity.as_ref().unwrap();
+                                identity.specific_subject()
+                            };
+                            let (stamp, limiter) = checks.get().unwrap();
+                            metrics::get().packet_received(d.len());
+                            if !within_limits(limiter.check(Delivery::Stream, d.len()), &conn) {
+                                return;
+                            }
+                            let d = match stamp.apply(d.into()) {
+                                Stamped::Publish(d) => d,
+                                Stamped::Answer(answer) => {
+                                    if let Err(e) = send_uni(&conn, &answer).await {
+                                        error!("Error sending answer: {}", e);
+                                    }
+                                    return;
+                                }
+                                Stamped::Drop => return,
+                            };
+                            if let Err(e) = relay.publish(specific_subject.clone(), d).await {
                                 error!("Error publishing to subject {}: {}", &specific_subject, e);
                             }
                         }
@@ -491,30 +1270,417 @@ async fn handle_quic_connection(
     let _datagrams_task = {
         tokio::spawn(async move {
             let session = session.read().await;
-            if specific_subject_rx.borrow().is_none() {
-                specific_subject_rx.changed().await.unwrap();
+            if identity_rx.borrow().is_none() {
+                if let Err(e) = identity_rx.changed().await {
+                    error!("Error waiting for the identity of the peer: {}", e);
+                    return;
+                }
             }
-            let specific_subject = specific_subject_rx.borrow().clone().unwrap();
+            let specific_subject = {
+                let identity = identity_rx.borrow();
+                let (identity, ..) = identity.as_ref().unwrap();
+                identity.specific_subject()
+            };
+            let (stamp, limiter) = checks.get().unwrap();
             while let Ok(datagram) = session.read_datagram().await {
-                let nc = nc.clone();
-                if let Err(e) = nc.publish(specific_subject.clone(), datagram).await {
+                metrics::get().packet_received(datagram.len());
+                if !within_limits(limiter.check(Delivery::Datagram, datagram.len()), &conn) {
+                    continue;
+                }
+                let datagram = match stamp.apply(datagram) {
+                    Stamped::Publish(datagram) => datagram,
+                    Stamped::Answer(answer) => {
+                        if let Err(e) = session.send_datagram(answer) {
+                            error!("Error sending datagram: {}", e);
+                        }
+                        continue;
+                    }
+                    Stamped::Drop => continue,
+                };
+                if let Err(e) = relay.publish(specific_subject.clone(), datagram).await {
                     error!("Error publishing to subject {}: {}", specific_subject, e);
                 }
             }
         })
     };
     quic_task.await?;
-    should_run.store(false, Ordering::SeqCst);
-    nats_task.abort();
+    stop.notify_one();
+    relay_task.await?;
+    membership.lock().unwrap().take();
     info!("Finished handling session");
     Ok(())
 }
 
-fn session_subject_to_lobby_subject(subject: &str) -> String {
-    let parts = subject.split('.').collect::<Vec<&str>>();
-    let mut lobby_subject = String::from("room.");
-    lobby_subject.push_str(parts[1]);
-    lobby_subject.push_str(".*");
-    lobby_subject
+/// Whether a packet of the peer may be published, the connection of a peer that keeps exceeding
+/// its limits is closed
+fn within_limits(verdict: Verdict, conn: &quinn::Connection) -> bool {
+    match verdict {
+        Verdict::Publish => true,
+        Verdict::Drop => false,
+        Verdict::Disconnect => {
+            let err = Rejection::RateLimited.close(conn);
+            info!("Closed session: {}", err);
+            false
+        }
+    }
+}
+
+async fn send_uni(conn: &quinn::Connection, packet: &[u8]) -> Result<()> {
+    let mut uni_stream = conn
+        .open_uni()
+        .await
+        .context("opening unidirectional stream")?;
+    uni_stream.write_all(packet).await?;
+    uni_stream.finish().await?;
+    Ok(())
+}
+
+async fn send_rejection(conn: &quinn::Connection, packet: &[u8]) {
+    if let Err(e) = send_uni(conn, packet).await {
+        error!("Error sending rejection: {}", e);
+    }
+    let _ = tokio::time::timeout(REJECTION_GRACE, conn.closed()).await;
 }
 
+/// Queues what is published in the room for a single peer until `stop` is notified, keeping the
+/// peer up to date with the members of the room.
+///
+/// The relay announces the peer with JOINED when it starts and with LEFT when it stops. The
+/// relays of the other members answer a JOINED with PRESENT, which is collected into the
+/// SNAPSHOT handed to the newcomer and never forwarded as is.
+///
+/// When the server starts shutting down the peer gets a GOING_AWAY packet, the room is still
+/// relayed until the peer leaves or the drain period ends.
+///
+/// When the same user opens a newer session in the room the peer gets a REJECTION packet and
+/// [`Rejection::Replaced`] is returned for the caller to close the connection. The newer
+/// session has already announced the user, so no LEFT is published.
+///
+/// The roster carries the role of every member, kept up to date with the moderation actions
+/// published in the room. The peer follows the actions targeting it, a kicked peer gets the
+/// action and a REJECTION packet before [`Rejection::Kicked`] is returned.
+///
+/// A peer whose outbound queue overflowed is left with [`Rejection::TooSlow`].
+#[allow(clippy::too_many_arguments)]
+async fn relay_room(
+    relay: Arc<dyn Relay>,
+    mut sub: relay::Subscription,
+    mut claim: presence::Claim,
+    specific_subject: String,
+    username: String,
+    moderation: Moderation,
+    stop: Arc<Notify>,
+    mut shutdown: watch::Receiver<bool>,
+    outbound: outbound::Sender,
+) -> std::result::Result<(), Rejection> {
+    let moderation_subject = relay::moderation_subject(sub.room_id());
+    let mut members = BTreeMap::new();
+    publish_roster(
+        &relay,
+        &specific_subject,
+        EventType::JOINED,
+        &username,
+        moderation.role(),
+    )
+    .await;
+
+    let snapshot = tokio::time::sleep(ROSTER_SNAPSHOT_DELAY);
+    tokio::pin!(snapshot);
+    let mut snapshot_sent = false;
+    let mut going_away = false;
+    let mut replaced = false;
+    let mut kicked = false;
+    let mut too_slow = false;
+
+    loop {
+        if outbound.overflowed() {
+            too_slow = true;
+            break;
+        }
+        let msg = tokio::select! {
+            msg = sub.next() => msg,
+            _ = &mut snapshot, if !snapshot_sent => {
+                snapshot_sent = true;
+                let packet = RosterPacket {
+                    event_type: EventType::SNAPSHOT.into(),
+                    members: members.keys().cloned().collect(),
+                    roles: members
+                        .iter()
+                        .map(|(member, role)| (member.clone(), *role))
+                        .collect(),
+                    ..Default::default()
+                };
+                if let Some(payload) = roster_wrapper(&username, &packet) {
+                    outbound.push(payload);
+                }
+                continue;
+            }
+            res = shutdown.changed(), if !going_away => {
+                going_away = true;
+                if res.is_ok() && *shutdown.borrow() {
+                    if let Some(payload) = going_away_wrapper() {
+                        outbound.push(payload);
+                    }
+                }
+                continue;
+            }
+            _ = claim.replaced() => {
+                info!("Session of {} replaced by a newer one", username);
+                replaced = true;
+                None
+            }
+            _ = stop.notified() => None,
+        };
+        let Some(msg) = msg else {
+            break;
+        };
+
+        let mut payload = msg.payload;
+        if msg.subject == moderation_subject {
+            let Some((sender, packet)) = moderation::parse_moderation(&payload) else {
+                continue;
+            };
+            if let Some(role) = members.get_mut(&packet.username) {
+                *role = packet.role;
+            }
+            if packet.action == Action::TRANSFER_OWNERSHIP.into() {
+                if let Some(role) = members.get_mut(&sender) {
+                    *role = moderation_packet::Role::MODERATOR.into();
+                }
+            }
+            if moderation.follow(&sender, &packet) {
+                info!("{} kicked by {}", username, sender);
+                outbound.push(payload);
+                kicked = true;
+                break;
+            }
+        } else if let Some(roster) = parse_roster(&payload) {
+            match roster.event_type.enum_value() {
+                Ok(EventType::JOINED) => {
+                    members.insert(roster.username.clone(), roster.role);
+                    publish_roster(
+                        &relay,
+                        &specific_subject,
+                        EventType::PRESENT,
+                        &username,
+                        moderation.role(),
+                    )
+                    .await;
+                }
+                Ok(EventType::PRESENT) => {
+                    // every member sees the answers meant for a newcomer: only unknown members
+                    // matter, and they are forwarded as JOINED once the snapshot is gone
+                    if members
+                        .insert(roster.username.clone(), roster.role)
+                        .is_some()
+                        || !snapshot_sent
+                    {
+                        continue;
+                    }
+                    let packet = RosterPacket {
+                        event_type: EventType::JOINED.into(),
+                        username: roster.username.clone(),
+                        role: roster.role,
+                        ..Default::default()
+                    };
+                    let Some(joined) = roster_wrapper(&roster.username, &packet) else {
+                        continue;
+                    };
+                    payload = joined;
+                }
+                Ok(EventType::LEFT) => {
+                    members.remove(&roster.username);
+                }
+                _ => continue,
+            }
+        }
+        outbound.push(payload);
+    }
+
+    if !replaced {
+        publish_roster(
+            &relay,
+            &specific_subject,
+            EventType::LEFT,
+            &username,
+            moderation.role(),
+        )
+        .await;
+    }
+    if let Err(e) = relay.unsubscribe(sub).await {
+        error!("Error unsubscribing: {}", e);
+    }
+    claim.release().await;
+
+    let rejection = match (replaced, kicked, too_slow) {
+        (true, ..) => Rejection::Replaced,
+        (false, true, _) => Rejection::Kicked,
+        (false, false, true) => Rejection::TooSlow,
+        (false, false, false) => return Ok(()),
+    };
+    if let Some(payload) = rejection.packet() {
+        outbound.push(payload);
+    }
+    Err(rejection)
+}
+
+fn roster_wrapper(username: &str, packet: &RosterPacket) -> Option<Bytes> {
+    let data = packet
+        .write_to_bytes()
+        .map_err(|e| error!("Error serializing roster packet: {}", e))
+        .ok()?;
+    PacketWrapper {
+        packet_type: PacketType::ROSTER.into(),
+        email: username.to_owned(),
+        data,
+        ..Default::default()
+    }
+    .write_to_bytes()
+    .map_err(|e| error!("Error serializing roster packet: {}", e))
+    .ok()
+    .map(Bytes::from)
+}
+
+fn going_away_wrapper() -> Option<Bytes> {
+    let data = GoingAwayPacket {
+        message: "The server is shutting down".to_owned(),
+        ..Default::default()
+    }
+    .write_to_bytes()
+    .map_err(|e| error!("Error serializing going away packet: {}", e))
+    .ok()?;
+    PacketWrapper {
+        packet_type: PacketType::GOING_AWAY.into(),
+        data,
+        ..Default::default()
+    }
+    .write_to_bytes()
+    .map_err(|e| error!("Error serializing going away packet: {}", e))
+    .ok()
+    .map(Bytes::from)
+}
+
+fn negotiation_wrapper(packet: &NegotiationPacket) -> Option<Bytes> {
+    let data = packet
+        .write_to_bytes()
+        .map_err(|e| error!("Error serializing negotiation packet: {}", e))
+        .ok()?;
+    PacketWrapper {
+        packet_type: PacketType::NEGOTIATION.into(),
+        data,
+        ..Default::default()
+    }
+    .write_to_bytes()
+    .map_err(|e| error!("Error serializing negotiation packet: {}", e))
+    .ok()
+    .map(Bytes::from)
+}
+
+/// Replays the latest messages of the room to a joining peer, oldest first
+async fn send_chat_history(
+    chat_service: &chat::Service,
+    room_id: Uuid,
+    outbound: &outbound::Sender,
+) {
+    let messages = match chat_service.page(room_id, None, CHAT_HISTORY_SIZE).await {
+        Ok(messages) => messages,
+        Err(e) => {
+            error!("Error loading chat history of room {}: {}", room_id, e);
+            return;
+        }
+    };
+    for message in messages.iter().rev() {
+        if let Some(payload) = chat_wrapper(message) {
+            outbound.push(payload);
+        }
+    }
+}
+
+fn chat_wrapper(message: &chat::Message) -> Option<Bytes> {
+    let data = stamp::chat_packet(message)
+        .write_to_bytes()
+        .map_err(|e| error!("Error serializing chat packet: {}", e))
+        .ok()?;
+    PacketWrapper {
+        packet_type: PacketType::CHAT.into(),
+        email: message.username.clone(),
+        data,
+        ..Default::default()
+    }
+    .write_to_bytes()
+    .map_err(|e| error!("Error serializing chat packet: {}", e))
+    .ok()
+    .map(Bytes::from)
+}
+
+/// Tells a joining peer the metronome of the room, loaded once subscribed to the room so that no
+/// change is missed
+async fn send_transport(
+    room_service: &room::Service,
+    room_id: Uuid,
+    outbound: &outbound::Sender,
+) {
+    match room_service.get_by_id(room_id).await {
+        Ok(Some(room)) => {
+            if let Some(payload) = transport_wrapper(&room) {
+                outbound.push(payload);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => error!("Error loading the transport of room {}: {}", room_id, e),
+    }
+}
+
+pub fn transport_wrapper(room: &Room) -> Option<Bytes> {
+    let data = TransportPacket {
+        tempo: room.tempo,
+        beats_per_bar: room.beats_per_bar as u32,
+        beat_unit: room.beat_unit as u32,
+        running: room.transport_started_at.is_some(),
+        count_in: room.count_in as u32,
+        started_at: room.transport_started_at.map_or(0.0, |started_at| {
+            (started_at.unix_timestamp_nanos() / 1_000) as f64 / 1000.0
+        }),
+        ..Default::default()
+    }
+    .write_to_bytes()
+    .map_err(|e| error!("Error serializing transport packet: {}", e))
+    .ok()?;
+    PacketWrapper {
+        packet_type: PacketType::TRANSPORT.into(),
+        data,
+        ..Default::default()
+    }
+    .write_to_bytes()
+    .map_err(|e| error!("Error serializing transport packet: {}", e))
+    .ok()
+    .map(Bytes::from)
+}
+
+fn parse_roster(payload: &[u8]) -> Option<RosterPacket> {
+    let packet_wrapper = PacketWrapper::parse_from_bytes(payload).ok()?;
+    if packet_wrapper.packet_type != PacketType::ROSTER.into() {
+        return None;
+    }
+    RosterPacket::parse_from_bytes(&packet_wrapper.data).ok()
+}
+
+async fn publish_roster(
+    relay: &Arc<dyn Relay>,
+    specific_subject: &str,
+    event_type: EventType,
+    username: &str,
+    role: Role,
+) {
+    let packet = RosterPacket {
+        event_type: event_type.into(),
+        username: username.to_owned(),
+        role: moderation::role_packet(role).into(),
+        ..Default::default()
+    };
+    if let Some(payload) = roster_wrapper(username, &packet) {
+        if let Err(e) = relay.publish(specific_subject.to_owned(), payload).await {
+            error!("Error publishing to subject {}: {}", specific_subject, e);
+        }
+    }
+}
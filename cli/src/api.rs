@@ -0,0 +1,114 @@
+use anyhow::{anyhow, bail, Result};
+use bytes::Bytes;
+use common::types::{JoinTicketResponse, LoginRequest};
+use http_body_util::{BodyExt, Full};
+use hyper::{header, Method, Request, StatusCode};
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::TokioExecutor;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Name of the cookie set by `POST /api/auth/sign-in`
+const SESSION_COOKIE_NAME: &str = "session-id";
+
+/// Minimal client of the REST API, enough to sign in and get join tickets
+#[derive(Clone)]
+pub struct Api {
+    client: Client<HttpConnector, Full<Bytes>>,
+    base_url: String,
+}
+
+/// A signed in user
+#[derive(Clone)]
+pub struct Session {
+    pub username: String,
+    cookie: String,
+}
+
+impl Api {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: Client::builder(TokioExecutor::new()).build_http(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    pub async fn sign_in(&self, username: &str, password: &str) -> Result<Session> {
+        let req = LoginRequest {
+            username: username.to_owned(),
+            password: password.to_owned(),
+            remember_me: false,
+        };
+        let res = self
+            .send(Method::POST, "/api/auth/sign-in", None, Some(&req))
+            .await?;
+        let cookie = res
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| value.split(';').next())
+            .find(|pair| pair.starts_with(&format!("{SESSION_COOKIE_NAME}=")))
+            .ok_or(anyhow!("no session cookie for {username}"))?
+            .to_owned();
+        Ok(Session {
+            username: username.to_owned(),
+            cookie,
+        })
+    }
+
+    /// Asks for a join ticket, it must be presented to the relay within a few seconds
+    pub async fn join_ticket(&self, session: &Session, room_id: &str) -> Result<String> {
+        let res = self
+            .send::<()>(
+                Method::POST,
+                &format!("/api/rooms/{room_id}/ticket"),
+                Some(session),
+                None,
+            )
+            .await?;
+        let JoinTicketResponse { ticket, .. } = json(res).await?;
+        Ok(ticket)
+    }
+
+    async fn send<T: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        session: Option<&Session>,
+        body: Option<&T>,
+    ) -> Result<hyper::Response<hyper::body::Incoming>> {
+        let mut req = Request::builder()
+            .method(method)
+            .uri(format!("{}{}", self.base_url, path));
+        if let Some(session) = session {
+            req = req.header(header::COOKIE, &session.cookie);
+        }
+        let body = match body {
+            Some(body) => {
+                req = req.header(header::CONTENT_TYPE, "application/json");
+                Bytes::from(serde_json::to_vec(body)?)
+            }
+            None => Bytes::new(),
+        };
+        let res = self.client.request(req.body(Full::new(body))?).await?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let body = res.into_body().collect().await?.to_bytes();
+            bail!(
+                "{} failed with {}: {}",
+                path,
+                status,
+                String::from_utf8_lossy(&body)
+            );
+        }
+        Ok(res)
+    }
+}
+
+async fn json<T: DeserializeOwned>(res: hyper::Response<hyper::body::Incoming>) -> Result<T> {
+    if res.status() == StatusCode::NO_CONTENT {
+        bail!("empty response");
+    }
+    let body = res.into_body().collect().await?.to_bytes();
+    Ok(serde_json::from_slice(&body)?)
+}
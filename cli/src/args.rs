@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use common::protos::moderation_packet::moderation_packet::Action;
+use rtjam_cli::connection;
+
+pub const USAGE: &str = "\
+Usage: rtjam-cli --server <HOST:PORT> --room <ROOM_ID> --ticket <TICKET> [OPTIONS]
+
+Joins a room over raw QUIC (hq-29), prints the roster and the packet stats.
+
+Options:
+  --server <HOST:PORT>     address of the WebTransport endpoint (RTJAM_WEBTRANSPORT_ADDRESS)
+  --room <ROOM_ID>         id of the room to join
+  --ticket <TICKET>        join ticket issued by POST /api/rooms/:id/ticket
+  --server-name <NAME>     name checked against the certificate [default: host of --server]
+  --cert <PATH>            trust this certificate (PEM or DER) besides the system roots
+  --insecure               do not verify the certificate of the server
+  --username <NAME>        sender claimed on the published packets, the relay stamps them with the
+                           account of the ticket and counts any other name as spoofing
+                           [default: none]
+  --publish <SOURCE>       publish `synthetic` media or replay a file written by --record
+  --chat <TEXT>            send a chat message to the room once joined
+  --moderate <ACTION:USER> take a moderation action once joined, one of mute, promote, demote,
+                           kick and transfer (of the ownership)
+  --dump                   print every received packet
+  --record <PATH>          append every received packet to a file
+  --stats <SECONDS>        interval between two stats reports [default: 5]
+  --duration <SECONDS>     leave the room after this time [default: until Ctrl+C]
+  --help                   print this message";
+
+/// Media published by the client
+#[derive(Debug, Clone)]
+pub enum Publish {
+    /// Audio and video packets filled with a fixed pattern, at the rate of a real client
+    Synthetic,
+    /// Packets previously written by `--record`, replayed in a loop
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct Args {
+    pub connection: connection::Options,
+    pub room: String,
+    pub ticket: String,
+    pub username: String,
+    pub publish: Option<Publish>,
+    pub chat: Option<String>,
+    /// Moderation action and its target
+    pub moderate: Option<(Action, String)>,
+    pub dump: bool,
+    pub record: Option<PathBuf>,
+    pub stats_interval: Duration,
+    pub duration: Option<Duration>,
+}
+
+impl Args {
+    /// Parses the command line, `Ok(None)` means that the usage was requested
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>> {
+        let mut server = None;
+        let mut server_name = None;
+        let mut room = None;
+        let mut ticket = None;
+        let mut cert = None;
+        let mut insecure = false;
+        let mut username = String::new();
+        let mut publish = None;
+        let mut chat = None;
+        let mut moderate = None;
+        let mut dump = false;
+        let mut record = None;
+        let mut stats_interval = Duration::from_secs(5);
+        let mut duration = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(anyhow!("missing value for {arg}"));
+            match arg.as_str() {
+                "--server" => server = Some(value()?),
+                "--server-name" => server_name = Some(value()?),
+                "--room" => room = Some(value()?),
+                "--ticket" => ticket = Some(value()?),
+                "--cert" => cert = Some(PathBuf::from(value()?)),
+                "--insecure" => insecure = true,
+                "--username" => username = value()?,
+                "--publish" => {
+                    publish = Some(match value()?.as_str() {
+                        "synthetic" => Publish::Synthetic,
+                        path => Publish::File(PathBuf::from(path)),
+                    })
+                }
+                "--chat" => chat = Some(value()?),
+                "--moderate" => moderate = Some(moderation(&value()?)?),
+                "--dump" => dump = true,
+                "--record" => record = Some(PathBuf::from(value()?)),
+                "--stats" => stats_interval = seconds(&value()?)?,
+                "--duration" => duration = Some(seconds(&value()?)?),
+                "--help" | "-h" => return Ok(None),
+                arg => bail!("unknown argument {arg}"),
+            }
+        }
+
+        Ok(Some(Self {
+            connection: connection::Options {
+                server: server.ok_or(anyhow!("--server is required"))?,
+                server_name,
+                cert,
+                insecure,
+            },
+            room: room.ok_or(anyhow!("--room is required"))?,
+            ticket: ticket.ok_or(anyhow!("--ticket is required"))?,
+            username,
+            publish,
+            chat,
+            moderate,
+            dump,
+            record,
+            stats_interval,
+            duration,
+        }))
+    }
+}
+
+fn moderation(value: &str) -> Result<(Action, String)> {
+    let (action, username) = value
+        .split_once(':')
+        .ok_or(anyhow!("invalid moderation {value}, expected ACTION:USER"))?;
+    let action = match action {
+        "mute" => Action::MUTE,
+        "promote" => Action::PROMOTE,
+        "demote" => Action::DEMOTE,
+        "kick" => Action::KICK,
+        "transfer" => Action::TRANSFER_OWNERSHIP,
+        action => bail!("unknown moderation action {action}"),
+    };
+    Ok((action, username.to_owned()))
+}
+
+fn seconds(value: &str) -> Result<Duration> {
+    let seconds = value
+        .parse::<u64>()
+        .with_context(|| format!("invalid number of seconds {value}"))?;
+    Ok(Duration::from_secs(seconds))
+}
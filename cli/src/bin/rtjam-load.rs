@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use rtjam_cli::{connection, load};
+use tracing_subscriber::EnvFilter;
+
+const USAGE: &str = "\
+Usage: rtjam-load --api <URL> --server <HOST:PORT> --users <PATH> --room <ROOM_ID>... [OPTIONS]
+
+Opens many sessions over raw QUIC (hq-29) spread across rooms, every session publishes synthetic
+audio, video and heartbeats like a browser and measures what it receives from the others. The
+report is written as JSON.
+
+WebTransport sessions are not simulated: they are served by the same relay loop, only the
+transport in front of it differs.
+
+Options:
+  --api <URL>              base URL of the REST API, e.g. http://127.0.0.1:3000
+  --server <HOST:PORT>     address of the WebTransport endpoint (RTJAM_WEBTRANSPORT_ADDRESS)
+  --users <PATH>           file of `username:password` lines, enabled accounts used by the sessions
+  --room <ROOM_ID>         a room to fill, repeat for more rooms
+  --sessions <N>           number of sessions, spread evenly across the rooms [default: 10]
+  --ramp-up <SECONDS>      time over which the sessions are started [default: 5]
+  --duration <SECONDS>     time the sessions stay once all have started [default: 30]
+  --report <PATH>          where the JSON report is written [default: stdout]
+  --server-name <NAME>     name checked against the certificate [default: host of --server]
+  --cert <PATH>            trust this certificate (PEM or DER) besides the system roots
+  --insecure               do not verify the certificate of the server
+  --help                   print this message";
+
+struct Args {
+    config: load::Config,
+    report: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    let args = match parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let report = load::run(args.config).await?;
+    let report = serde_json::to_string_pretty(&report)?;
+    match args.report {
+        Some(path) => tokio::fs::write(&path, report)
+            .await
+            .with_context(|| format!("cannot write {}", path.display()))?,
+        None => println!("{report}"),
+    }
+    Ok(())
+}
+
+/// Parses the command line, `Ok(None)` means that the usage was requested
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Args>> {
+    let mut api = None;
+    let mut server = None;
+    let mut server_name = None;
+    let mut cert = None;
+    let mut insecure = false;
+    let mut users = None;
+    let mut rooms = Vec::new();
+    let mut sessions = 10;
+    let mut ramp_up = Duration::from_secs(5);
+    let mut duration = Duration::from_secs(30);
+    let mut report = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(anyhow!("missing value for {arg}"));
+        match arg.as_str() {
+            "--api" => api = Some(value()?),
+            "--server" => server = Some(value()?),
+            "--server-name" => server_name = Some(value()?),
+            "--cert" => cert = Some(PathBuf::from(value()?)),
+            "--insecure" => insecure = true,
+            "--users" => users = Some(read_users(Path::new(&value()?))?),
+            "--room" => rooms.push(value()?),
+            "--sessions" => {
+                let value = value()?;
+                sessions = value
+                    .parse()
+                    .with_context(|| format!("invalid number of sessions {value}"))?;
+            }
+            "--ramp-up" => ramp_up = seconds(&value()?)?,
+            "--duration" => duration = seconds(&value()?)?,
+            "--report" => report = Some(PathBuf::from(value()?)),
+            "--help" | "-h" => return Ok(None),
+            arg => bail!("unknown argument {arg}"),
+        }
+    }
+    if rooms.is_empty() {
+        bail!("--room is required");
+    }
+
+    Ok(Some(Args {
+        config: load::Config {
+            connection: connection::Options {
+                server: server.ok_or(anyhow!("--server is required"))?,
+                server_name,
+                cert,
+                insecure,
+            },
+            api: api.ok_or(anyhow!("--api is required"))?,
+            users: users.ok_or(anyhow!("--users is required"))?,
+            rooms,
+            sessions,
+            ramp_up,
+            duration,
+        },
+        report,
+    }))
+}
+
+/// Reads `username:password` lines, blank lines and lines starting with `#` are skipped
+fn read_users(path: &Path) -> Result<Vec<(String, String)>> {
+    let users =
+        std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    users
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.split_once(':')
+                .map(|(username, password)| (username.to_owned(), password.to_owned()))
+                .ok_or(anyhow!("expected username:password in {}", path.display()))
+        })
+        .collect()
+}
+
+fn seconds(value: &str) -> Result<Duration> {
+    let seconds = value
+        .parse::<u64>()
+        .with_context(|| format!("invalid number of seconds {value}"))?;
+    Ok(Duration::from_secs(seconds))
+}
@@ -0,0 +1,189 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use common::protocol::{E2EE_NONE, E2EE_RSA_AES128_CBC, PROTOCOL_VERSION};
+use common::protos::connection_packet::{Capabilities, ConnectionPacket};
+use common::protos::packet_wrapper::{packet_wrapper::PacketType, PacketWrapper};
+use protobuf::Message;
+use rustls::{
+    client::{ServerCertVerified, ServerCertVerifier},
+    Certificate, RootCertStore, ServerName,
+};
+use tracing::debug;
+
+/// ALPN of the raw QUIC path of the relay
+pub const QUIC_ALPN: &[u8] = b"hq-29";
+
+/// Packets bigger than this are read from unidirectional streams
+const MAX_UNIDIRECTIONAL_STREAM_SIZE: usize = 1_000_000;
+
+/// Codecs of the synthetic media, the ones of the browser client
+const AUDIO_CODEC: &str = "opus";
+const VIDEO_CODEC: &str = "vp09.00.10.08";
+
+/// Where the relay is and how to trust it
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub server: String,
+    pub server_name: Option<String>,
+    pub cert: Option<PathBuf>,
+    pub insecure: bool,
+}
+
+impl Options {
+    /// Name checked against the certificate of the server
+    pub fn server_name(&self) -> &str {
+        self.server_name.as_deref().unwrap_or_else(|| {
+            self.server
+                .rsplit_once(':')
+                .map_or(self.server.as_str(), |(host, _)| host)
+        })
+    }
+}
+
+/// Opens a connection to the relay and joins the room with the ticket.
+///
+/// The relay answers with a NEGOTIATION packet, or with a REJECTION packet before closing the
+/// connection.
+pub async fn connect(
+    options: &Options,
+    room: &str,
+    ticket: &str,
+    username: &str,
+) -> Result<(quinn::Endpoint, quinn::Connection)> {
+    let addr = tokio::net::lookup_host(&options.server)
+        .await?
+        .next()
+        .ok_or(anyhow!("cannot resolve {}", options.server))?;
+
+    let mut endpoint = quinn::Endpoint::client(if addr.is_ipv6() {
+        "[::]:0".parse()?
+    } else {
+        "0.0.0.0:0".parse()?
+    })?;
+    endpoint.set_default_client_config(client_config(options)?);
+
+    debug!("connecting to {} ({})", options.server, addr);
+    let conn = endpoint.connect(addr, options.server_name())?.await?;
+    debug!("connected to {}", conn.remote_address());
+
+    let data = ConnectionPacket {
+        meeting_id: room.to_owned(),
+        ticket: ticket.to_owned(),
+        protocol_version: PROTOCOL_VERSION,
+        client_version: format!("rtjam-cli/{}", env!("CARGO_PKG_VERSION")),
+        capabilities: Some(Capabilities {
+            audio_codecs: vec![AUDIO_CODEC.to_owned()],
+            video_codecs: vec![VIDEO_CODEC.to_owned()],
+            // encrypted media is only counted, never decrypted
+            e2ee_schemes: vec![E2EE_NONE.to_owned(), E2EE_RSA_AES128_CBC.to_owned()],
+            datagrams: true,
+            ..Default::default()
+        })
+        .into(),
+        ..Default::default()
+    }
+    .write_to_bytes()?;
+    let packet = PacketWrapper {
+        packet_type: PacketType::CONNECTION.into(),
+        email: username.to_owned(),
+        data,
+        ..Default::default()
+    };
+    let mut stream = conn.open_uni().await?;
+    stream.write_all(&packet.write_to_bytes()?).await?;
+    stream.finish().await?;
+
+    Ok((endpoint, conn))
+}
+
+fn client_config(options: &Options) -> Result<quinn::ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    for cert in rustls_native_certs::load_native_certs()? {
+        // a broken system certificate should not prevent connecting
+        let _ = roots.add(&Certificate(cert.0));
+    }
+    if let Some(path) = &options.cert {
+        for cert in read_certs(path)? {
+            roots.add(&cert)?;
+        }
+    }
+
+    let mut tls_config = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    if options.insecure {
+        tls_config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(SkipServerVerification));
+    }
+    tls_config.alpn_protocols = vec![QUIC_ALPN.to_vec()];
+
+    Ok(quinn::ClientConfig::new(Arc::new(tls_config)))
+}
+
+fn read_certs(path: &Path) -> Result<Vec<Certificate>> {
+    let certs = std::fs::read(path).context("failed to read certificate")?;
+    if path.extension().is_some_and(|x| x == "der") {
+        return Ok(vec![Certificate(certs)]);
+    }
+    Ok(rustls_pemfile::certs(&mut &*certs)
+        .context("invalid PEM-encoded certificate")?
+        .into_iter()
+        .map(Certificate)
+        .collect())
+}
+
+/// Accepts any certificate, the development certificates of the repository are self signed
+struct SkipServerVerification;
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+/// Sends a packet the same way the relay does: as a datagram when it fits, on its own
+/// unidirectional stream otherwise
+pub async fn send(conn: &quinn::Connection, payload: Bytes) -> Result<()> {
+    if conn
+        .max_datagram_size()
+        .is_some_and(|size| payload.len() <= size)
+    {
+        match conn.send_datagram(payload.clone()) {
+            Ok(()) => return Ok(()),
+            Err(e) => debug!("cannot send datagram, falling back to a stream: {}", e),
+        }
+    }
+    // finishing waits for the acknowledgment, the next packets must not wait for it
+    let mut stream = conn.open_uni().await?;
+    tokio::spawn(async move {
+        if let Err(e) = stream.write_all(&payload).await {
+            debug!("cannot write to unidirectional stream: {}", e);
+        }
+        let _ = stream.finish().await;
+    });
+    Ok(())
+}
+
+/// Reads a packet sent on a unidirectional stream
+pub async fn read_stream(mut stream: quinn::RecvStream) -> Result<Bytes> {
+    Ok(stream
+        .read_to_end(MAX_UNIDIRECTIONAL_STREAM_SIZE)
+        .await?
+        .into())
+}
@@ -0,0 +1,6 @@
+pub mod api;
+pub mod connection;
+pub mod load;
+pub mod publish;
+pub mod recording;
+pub mod stats;
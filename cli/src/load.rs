@@ -0,0 +1,454 @@
+//! Load generator: many peers publishing and receiving the media of the browser client.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use bytes::Bytes;
+use common::protos::media_packet::{media_packet::MediaType, MediaPacket};
+use common::protos::packet_wrapper::{packet_wrapper::PacketType, PacketWrapper};
+use protobuf::Message;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio::time::{sleep_until, timeout, Instant};
+use tracing::{error, info, warn};
+
+use crate::api::{Api, Session};
+use crate::{connection, publish};
+
+/// Relative width of the buckets of the latency histogram
+const LATENCY_BUCKET_GROWTH: f64 = 1.02;
+
+/// How long a leaving session waits for the relay to acknowledge the close
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct Config {
+    pub connection: connection::Options,
+    pub api: String,
+    /// Usernames and passwords of enabled accounts
+    pub users: Vec<(String, String)>,
+    pub rooms: Vec<String>,
+    pub sessions: usize,
+    /// Sessions are started evenly over this time
+    pub ramp_up: Duration,
+    /// How long the sessions stay in the rooms once they have all been started
+    pub duration: Duration,
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    pub server: String,
+    pub sessions: usize,
+    pub rooms: usize,
+    pub duration_secs: f64,
+    pub connected: usize,
+    pub failed: usize,
+    pub total: Totals,
+    pub receivers: Vec<ReceiverReport>,
+}
+
+#[derive(Serialize, Default)]
+pub struct Totals {
+    pub sent_packets: u64,
+    pub sent_bytes: u64,
+    pub received_packets: u64,
+    pub received_bytes: u64,
+    pub received_kbps: f64,
+    pub media_packets: u64,
+    pub lost_media_packets: u64,
+    pub loss_ratio: f64,
+    pub latency_ms: Latency,
+}
+
+#[derive(Serialize)]
+pub struct ReceiverReport {
+    pub session: usize,
+    pub username: String,
+    pub room: String,
+    pub error: Option<String>,
+    pub connected_secs: f64,
+    pub sent_packets: u64,
+    pub sent_bytes: u64,
+    pub received_packets: u64,
+    pub received_bytes: u64,
+    pub received_kbps: f64,
+    /// Audio and video packets received from the other sessions
+    pub media_packets: u64,
+    /// Audio and video packets missing from the sequences of the other sessions
+    pub lost_media_packets: u64,
+    pub loss_ratio: f64,
+    /// From the publisher to this receiver, through the relay
+    pub latency_ms: Latency,
+    #[serde(skip)]
+    histogram: Histogram,
+}
+
+#[derive(Serialize, Default)]
+pub struct Latency {
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+/// Log-linear histogram of latencies, in microseconds
+#[derive(Default, Clone)]
+struct Histogram {
+    counts: Vec<u64>,
+    total: u64,
+    sum: f64,
+    max: f64,
+}
+
+impl Histogram {
+    fn record(&mut self, millis: f64) {
+        let millis = millis.max(0.0);
+        let micros = (millis * 1000.0).max(1.0);
+        let bucket = (micros.ln() / LATENCY_BUCKET_GROWTH.ln()) as usize;
+        if self.counts.len() <= bucket {
+            self.counts.resize(bucket + 1, 0);
+        }
+        self.counts[bucket] += 1;
+        self.total += 1;
+        self.sum += millis;
+        self.max = self.max.max(millis);
+    }
+
+    fn merge(&mut self, other: &Histogram) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.total += other.total;
+        self.sum += other.sum;
+        self.max = self.max.max(other.max);
+    }
+
+    /// Upper bound of the bucket holding the `quantile`, in milliseconds
+    fn quantile(&self, quantile: f64) -> f64 {
+        let target = (quantile * self.total as f64).ceil() as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= target.max(1) {
+                let millis = LATENCY_BUCKET_GROWTH.powi(bucket as i32 + 1) / 1000.0;
+                return millis.min(self.max);
+            }
+        }
+        self.max
+    }
+
+    fn latency(&self) -> Latency {
+        if self.total == 0 {
+            return Latency::default();
+        }
+        Latency {
+            mean: self.sum / self.total as f64,
+            p50: self.quantile(0.5),
+            p90: self.quantile(0.9),
+            p99: self.quantile(0.99),
+            max: self.max,
+        }
+    }
+}
+
+/// First and last sequence number received from a publisher, and how many in between
+struct Sequence {
+    first: u64,
+    last: u64,
+    received: u64,
+}
+
+impl Sequence {
+    fn expected(&self) -> u64 {
+        self.last - self.first + 1
+    }
+}
+
+#[derive(Default)]
+struct Counter {
+    packets: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl Counter {
+    fn add(&self, bytes: usize) {
+        self.packets.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+impl ReceiverReport {
+    fn new(session: usize, username: &str, room: &str) -> Self {
+        Self {
+            session,
+            username: username.to_owned(),
+            room: room.to_owned(),
+            error: None,
+            connected_secs: 0.0,
+            sent_packets: 0,
+            sent_bytes: 0,
+            received_packets: 0,
+            received_bytes: 0,
+            received_kbps: 0.0,
+            media_packets: 0,
+            lost_media_packets: 0,
+            loss_ratio: 0.0,
+            latency_ms: Latency::default(),
+            histogram: Histogram::default(),
+        }
+    }
+}
+
+pub async fn run(config: Config) -> Result<Report> {
+    let Config {
+        connection,
+        api,
+        users,
+        rooms,
+        sessions,
+        ramp_up,
+        duration,
+    } = config;
+    if rooms.is_empty() {
+        bail!("at least one room is required");
+    }
+    // every member of a room needs its own user
+    let members_per_room = sessions.div_ceil(rooms.len());
+    if users.len() < members_per_room {
+        bail!(
+            "{} sessions in {} rooms need at least {} users, got {}",
+            sessions,
+            rooms.len(),
+            members_per_room,
+            users.len()
+        );
+    }
+
+    let api = Api::new(&api);
+    let mut signed_in = Vec::with_capacity(members_per_room);
+    for (username, password) in users.iter().take(members_per_room) {
+        signed_in.push(api.sign_in(username, password).await?);
+    }
+    info!(
+        "{} users signed in, starting {} sessions in {} rooms",
+        signed_in.len(),
+        sessions,
+        rooms.len()
+    );
+
+    let started_at = Instant::now();
+    let end_at = started_at + ramp_up + duration;
+    let mut tasks = JoinSet::new();
+    for index in 0..sessions {
+        let start_at = started_at + ramp_up.mul_f64(index as f64 / sessions as f64);
+        tasks.spawn(run_session(
+            index,
+            api.clone(),
+            connection.clone(),
+            signed_in[index / rooms.len()].clone(),
+            rooms[index % rooms.len()].clone(),
+            start_at,
+            end_at,
+        ));
+    }
+
+    let mut receivers = Vec::with_capacity(sessions);
+    while let Some(report) = tasks.join_next().await {
+        match report {
+            Ok(report) => receivers.push(report),
+            Err(e) => error!("session task failed: {}", e),
+        }
+    }
+    receivers.sort_by_key(|report| report.session);
+
+    let mut total = Totals::default();
+    let mut histogram = Histogram::default();
+    for report in receivers.iter().filter(|report| report.error.is_none()) {
+        total.sent_packets += report.sent_packets;
+        total.sent_bytes += report.sent_bytes;
+        total.received_packets += report.received_packets;
+        total.received_bytes += report.received_bytes;
+        total.received_kbps += report.received_kbps;
+        total.media_packets += report.media_packets;
+        total.lost_media_packets += report.lost_media_packets;
+        histogram.merge(&report.histogram);
+    }
+    total.loss_ratio = ratio(
+        total.lost_media_packets,
+        total.media_packets + total.lost_media_packets,
+    );
+    total.latency_ms = histogram.latency();
+
+    let failed = receivers
+        .iter()
+        .filter(|report| report.error.is_some())
+        .count();
+    Ok(Report {
+        server: connection.server,
+        sessions,
+        rooms: rooms.len(),
+        duration_secs: started_at.elapsed().as_secs_f64(),
+        connected: receivers.len() - failed,
+        failed,
+        total,
+        receivers,
+    })
+}
+
+async fn run_session(
+    index: usize,
+    api: Api,
+    options: connection::Options,
+    session: Session,
+    room: String,
+    start_at: Instant,
+    end_at: Instant,
+) -> ReceiverReport {
+    sleep_until(start_at).await;
+    let mut report = ReceiverReport::new(index, &session.username, &room);
+
+    let connected = async {
+        let ticket = api.join_ticket(&session, &room).await?;
+        connection::connect(&options, &room, &ticket, &session.username).await
+    };
+    let (endpoint, conn) = match connected.await {
+        Ok(connected) => connected,
+        Err(e) => {
+            warn!("session {} cannot join {}: {}", index, room, e);
+            report.error = Some(e.to_string());
+            return report;
+        }
+    };
+    let connected_at = Instant::now();
+
+    let sent = Arc::new(Counter::default());
+    let mut publishers = JoinSet::new();
+    {
+        let sent = sent.clone();
+        publishers.spawn(publish::heartbeat(
+            conn.clone(),
+            session.username.clone(),
+            move |bytes| sent.add(bytes),
+        ));
+    }
+    {
+        let sent = sent.clone();
+        publishers.spawn(publish::synthetic(
+            conn.clone(),
+            session.username.clone(),
+            move |bytes| sent.add(bytes),
+        ));
+    }
+
+    let mut sequences = HashMap::new();
+    let (stream_tx, mut stream_rx) = mpsc::unbounded_channel::<Bytes>();
+    let deadline = sleep_until(end_at);
+    tokio::pin!(deadline);
+    loop {
+        let payload = tokio::select! {
+            datagram = conn.read_datagram() => match datagram {
+                Ok(payload) => payload,
+                Err(e) => {
+                    report.error = Some(e.to_string());
+                    break;
+                }
+            },
+            stream = conn.accept_uni() => match stream {
+                Ok(stream) => {
+                    let stream_tx = stream_tx.clone();
+                    tokio::spawn(async move {
+                        if let Ok(payload) = connection::read_stream(stream).await {
+                            let _ = stream_tx.send(payload);
+                        }
+                    });
+                    continue;
+                }
+                Err(e) => {
+                    report.error = Some(e.to_string());
+                    break;
+                }
+            },
+            Some(payload) = stream_rx.recv() => payload,
+            _ = &mut deadline => break,
+        };
+        on_payload(&mut report, &mut sequences, &payload);
+    }
+
+    publishers.abort_all();
+    conn.close(0u32.into(), b"bye");
+    let _ = timeout(CLOSE_TIMEOUT, endpoint.wait_idle()).await;
+
+    let connected_secs = connected_at.elapsed().as_secs_f64();
+    report.connected_secs = connected_secs;
+    report.sent_packets = sent.packets.load(Ordering::Relaxed);
+    report.sent_bytes = sent.bytes.load(Ordering::Relaxed);
+    report.received_kbps = report.received_bytes as f64 * 8.0 / 1000.0 / connected_secs;
+    report.lost_media_packets = sequences
+        .values()
+        .map(|sequence: &Sequence| sequence.expected().saturating_sub(sequence.received))
+        .sum();
+    report.loss_ratio = ratio(
+        report.lost_media_packets,
+        report.media_packets + report.lost_media_packets,
+    );
+    report.latency_ms = report.histogram.latency();
+    report
+}
+
+fn on_payload(
+    report: &mut ReceiverReport,
+    sequences: &mut HashMap<(String, MediaType), Sequence>,
+    payload: &[u8],
+) {
+    report.received_packets += 1;
+    report.received_bytes += payload.len() as u64;
+
+    let Ok(packet) = PacketWrapper::parse_from_bytes(payload) else {
+        return;
+    };
+    if packet.packet_type != PacketType::MEDIA.into() {
+        return;
+    }
+    let Ok(media) = MediaPacket::parse_from_bytes(&packet.data) else {
+        return;
+    };
+    let media_type = match media.media_type.enum_value() {
+        Ok(media_type @ (MediaType::AUDIO | MediaType::VIDEO)) => media_type,
+        _ => return,
+    };
+    let Some(number) = publish::frame_sequence(&media.data) else {
+        return;
+    };
+
+    report.media_packets += 1;
+    report
+        .histogram
+        .record(publish::now_millis() - media.timestamp);
+    sequences
+        .entry((packet.email, media_type))
+        .and_modify(|sequence| {
+            sequence.first = sequence.first.min(number);
+            sequence.last = sequence.last.max(number);
+            sequence.received += 1;
+        })
+        .or_insert(Sequence {
+            first: number,
+            last: number,
+            received: 1,
+        });
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
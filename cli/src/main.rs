@@ -0,0 +1,348 @@
+mod args;
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use bytes::Bytes;
+use common::close_codes;
+use common::protos::chat_packet::ChatPacket;
+use common::protos::going_away_packet::GoingAwayPacket;
+use common::protos::media_packet::MediaPacket;
+use common::protos::moderation_packet::ModerationPacket;
+use common::protos::negotiation_packet::NegotiationPacket;
+use common::protos::packet_wrapper::{packet_wrapper::PacketType, PacketWrapper};
+use common::protos::ping_packet::PingPacket;
+use common::protos::rejection_packet::RejectionPacket;
+use common::protos::transport_packet::TransportPacket;
+use protobuf::Message;
+use quinn::{ConnectionError, VarInt};
+use tokio::{signal, sync::mpsc};
+use tracing::{error, info, warn};
+use tracing_subscriber::EnvFilter;
+
+use args::{Args, Publish, USAGE};
+use rtjam_cli::recording::Recorder;
+use rtjam_cli::stats::{RosterChange, Stats};
+use rtjam_cli::{connection, publish};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let (endpoint, conn) =
+        connection::connect(&args.connection, &args.room, &args.ticket, &args.username).await?;
+    let stats = Arc::new(Mutex::new(Stats::default()));
+
+    {
+        let stats = stats.clone();
+        tokio::spawn(publish::heartbeat(
+            conn.clone(),
+            args.username.clone(),
+            move |bytes| stats.lock().unwrap().on_sent(bytes),
+        ));
+    }
+    {
+        let stats = stats.clone();
+        tokio::spawn(publish::ping(
+            conn.clone(),
+            args.username.clone(),
+            move |bytes| stats.lock().unwrap().on_sent(bytes),
+        ));
+    }
+    match args.publish.clone() {
+        Some(Publish::Synthetic) => {
+            let stats = stats.clone();
+            tokio::spawn(publish::synthetic(
+                conn.clone(),
+                args.username.clone(),
+                move |bytes| stats.lock().unwrap().on_sent(bytes),
+            ));
+        }
+        Some(Publish::File(path)) => {
+            let conn = conn.clone();
+            let username = args.username.clone();
+            let stats = stats.clone();
+            tokio::spawn(async move {
+                let on_sent = move |bytes| stats.lock().unwrap().on_sent(bytes);
+                if let Err(e) = publish::replay(conn, username, &path, on_sent).await {
+                    error!("cannot replay {}: {}", path.display(), e);
+                }
+            });
+        }
+        None => {}
+    }
+
+    let mut recorder = match &args.record {
+        Some(path) => Some(Recorder::create(path).await?),
+        None => None,
+    };
+
+    // packets read from unidirectional streams, each one is read by its own task
+    let (stream_tx, mut stream_rx) = mpsc::unbounded_channel::<Bytes>();
+    let mut stats_ticker = tokio::time::interval(args.stats_interval);
+    stats_ticker.tick().await;
+    let deadline = async {
+        match args.duration {
+            Some(duration) => tokio::time::sleep(duration).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(deadline);
+
+    let close_reason = loop {
+        tokio::select! {
+            datagram = conn.read_datagram() => match datagram {
+                Ok(payload) => {
+                    stats.lock().unwrap().on_datagram(payload.len());
+                    on_payload(&args, &conn, &stats, &mut recorder, payload).await;
+                }
+                Err(e) => break Some(e),
+            },
+            stream = conn.accept_uni() => match stream {
+                Ok(stream) => {
+                    let stream_tx = stream_tx.clone();
+                    tokio::spawn(async move {
+                        match connection::read_stream(stream).await {
+                            Ok(payload) => {
+                                let _ = stream_tx.send(payload);
+                            }
+                            Err(e) => error!("cannot read unidirectional stream: {}", e),
+                        }
+                    });
+                }
+                Err(e) => break Some(e),
+            },
+            Some(payload) = stream_rx.recv() => {
+                stats.lock().unwrap().on_stream(payload.len());
+                on_payload(&args, &conn, &stats, &mut recorder, payload).await;
+            }
+            _ = stats_ticker.tick() => {
+                print!("{}", stats.lock().unwrap());
+            }
+            _ = &mut deadline => break None,
+            _ = signal::ctrl_c() => break None,
+        }
+    };
+
+    if let Some(recorder) = recorder.as_mut() {
+        recorder.flush().await?;
+    }
+    print!("{}", stats.lock().unwrap());
+
+    match close_reason {
+        None => {
+            conn.close(VarInt::from_u32(0), b"bye");
+            endpoint.wait_idle().await;
+            Ok(())
+        }
+        Some(ConnectionError::ApplicationClosed(close)) => {
+            let code = u32::try_from(close.error_code.into_inner()).unwrap_or_default();
+            warn!(
+                "closed by the server: {} ({:#x}) {}",
+                close_code_name(code),
+                code,
+                String::from_utf8_lossy(&close.reason)
+            );
+            std::process::exit(1);
+        }
+        Some(e) => {
+            warn!("connection lost: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn on_payload(
+    args: &Args,
+    conn: &quinn::Connection,
+    stats: &Mutex<Stats>,
+    recorder: &mut Option<Recorder>,
+    payload: Bytes,
+) {
+    if let Some(recorder) = recorder {
+        if let Err(e) = recorder.write(&payload).await {
+            error!("cannot record packet: {}", e);
+        }
+    }
+
+    let packet = match PacketWrapper::parse_from_bytes(&payload) {
+        Ok(packet) => packet,
+        Err(e) => {
+            stats.lock().unwrap().on_malformed();
+            warn!("malformed packet: {}", e);
+            return;
+        }
+    };
+    if args.dump {
+        dump(&packet);
+    }
+
+    let mut stats = stats.lock().unwrap();
+    match stats.on_packet(&packet, payload.len()) {
+        Some(RosterChange::Joined(username)) => println!("+ {username}"),
+        Some(RosterChange::Left(username)) => println!("- {username}"),
+        Some(RosterChange::Snapshot) => {
+            let members = stats.members().iter().cloned().collect::<Vec<_>>();
+            println!("= {}", members.join(" "));
+        }
+        None => {}
+    }
+
+    match packet.packet_type.enum_value() {
+        Ok(PacketType::REJECTION) => {
+            if let Ok(rejection) = RejectionPacket::parse_from_bytes(&packet.data) {
+                warn!(
+                    "rejected: {:?} {}",
+                    rejection.reason.enum_value_or_default(),
+                    rejection.message
+                );
+            }
+        }
+        Ok(PacketType::NEGOTIATION) => {
+            if let Ok(negotiated) = NegotiationPacket::parse_from_bytes(&packet.data) {
+                info!(
+                    "joined {} with protocol version {}: {} {} e2ee {} as {:?}",
+                    negotiated.server_version,
+                    negotiated.protocol_version,
+                    negotiated.audio_codec,
+                    negotiated.video_codec,
+                    negotiated.e2ee_scheme,
+                    negotiated.role.enum_value_or_default()
+                );
+            }
+            // the relay ignores what is sent before the session is admitted
+            if let Some(text) = args.chat.clone() {
+                let conn = conn.clone();
+                let username = args.username.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = publish::chat(&conn, &username, text).await {
+                        error!("cannot send chat message: {}", e);
+                    }
+                });
+            }
+            if let Some((action, target)) = args.moderate.clone() {
+                let conn = conn.clone();
+                let username = args.username.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = publish::moderate(&conn, &username, action, target).await {
+                        error!("cannot send moderation: {}", e);
+                    }
+                });
+            }
+        }
+        Ok(PacketType::GOING_AWAY) => {
+            if let Ok(going_away) = GoingAwayPacket::parse_from_bytes(&packet.data) {
+                warn!("server going away: {}", going_away.message);
+            }
+        }
+        Ok(PacketType::PING) => {
+            if let Ok(ping) = PingPacket::parse_from_bytes(&packet.data) {
+                if ping.peers {
+                    let conn = conn.clone();
+                    let username = args.username.clone();
+                    let from = packet.email.clone();
+                    tokio::spawn(async move {
+                        publish::pong(&conn, &username, &from, ping).await;
+                    });
+                }
+            }
+        }
+        Ok(PacketType::CHAT) => {
+            if let Ok(chat) = ChatPacket::parse_from_bytes(&packet.data) {
+                println!("> {}: {}", chat.username, chat.text);
+            }
+        }
+        Ok(PacketType::MODERATION) => {
+            if let Ok(moderation) = ModerationPacket::parse_from_bytes(&packet.data) {
+                println!(
+                    "! {} {:?} {}: {:?}",
+                    packet.email,
+                    moderation.action.enum_value_or_default(),
+                    moderation.username,
+                    moderation.role.enum_value_or_default()
+                );
+            }
+        }
+        Ok(PacketType::TRANSPORT) => {
+            if let Ok(transport) = TransportPacket::parse_from_bytes(&packet.data) {
+                println!(
+                    "~ {} bpm {}/{} {}",
+                    transport.tempo,
+                    transport.beats_per_bar,
+                    transport.beat_unit,
+                    if transport.running {
+                        "running"
+                    } else {
+                        "stopped"
+                    }
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Prints a packet on a single line, media is summarized instead of printed in full
+fn dump(packet: &PacketWrapper) {
+    let packet_type = packet
+        .packet_type
+        .enum_value()
+        .map_or_else(|value| format!("UNKNOWN({value})"), |t| t.to_string());
+    match packet.packet_type.enum_value() {
+        Ok(PacketType::MEDIA) => match MediaPacket::parse_from_bytes(&packet.data) {
+            Ok(media) => println!(
+                "<< {} from {}: {:?} {} {} bytes at {}",
+                packet_type,
+                packet.email,
+                media.media_type.enum_value_or_default(),
+                media.frame_type,
+                media.data.len(),
+                media.timestamp
+            ),
+            Err(_) => println!(
+                "<< {} from {}: {} encrypted bytes",
+                packet_type,
+                packet.email,
+                packet.data.len()
+            ),
+        },
+        _ => println!("<< {} from {}: {}", packet_type, packet.email, packet),
+    }
+}
+
+fn close_code_name(code: u32) -> &'static str {
+    match code {
+        close_codes::INTERNAL_ERROR => "INTERNAL_ERROR",
+        close_codes::MALFORMED_REQUEST => "MALFORMED_REQUEST",
+        close_codes::INVALID_CREDENTIALS => "INVALID_CREDENTIALS",
+        close_codes::ROOM_NOT_FOUND => "ROOM_NOT_FOUND",
+        close_codes::FORBIDDEN_NAME => "FORBIDDEN_NAME",
+        close_codes::ROOM_CLOSED => "ROOM_CLOSED",
+        close_codes::ROOM_FULL => "ROOM_FULL",
+        close_codes::GOING_AWAY => "GOING_AWAY",
+        close_codes::REPLACED => "REPLACED",
+        close_codes::UNSUPPORTED_VERSION => "UNSUPPORTED_VERSION",
+        close_codes::UNSUPPORTED_CAPABILITIES => "UNSUPPORTED_CAPABILITIES",
+        close_codes::RATE_LIMITED => "RATE_LIMITED",
+        close_codes::KICKED => "KICKED",
+        close_codes::TOO_SLOW => "TOO_SLOW",
+        _ => "UNKNOWN",
+    }
+}
@@ -0,0 +1,320 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use bytes::Bytes;
+use common::protos::chat_packet::ChatPacket;
+use common::protos::media_packet::{media_packet::MediaType, MediaPacket};
+use common::protos::moderation_packet::{moderation_packet::Action, ModerationPacket};
+use common::protos::packet_wrapper::{
+    packet_wrapper::{MediaKind, PacketType},
+    PacketWrapper,
+};
+use common::protos::ping_packet::PingPacket;
+use protobuf::Message;
+use tokio::time::{interval, sleep_until, Instant, MissedTickBehavior};
+use tracing::{error, info};
+
+use crate::{connection, recording};
+
+/// An Opus frame every 20ms, at the 320 kbit/s of `MicrophoneEncoder`
+const AUDIO_PERIOD: Duration = Duration::from_millis(20);
+const AUDIO_FRAME_SIZE: usize = 800;
+
+/// A VP9 frame every 33ms at the 100 kbit/s of `CameraEncoder`, with a key frame every 50 frames
+const VIDEO_PERIOD: Duration = Duration::from_millis(33);
+const VIDEO_KEY_FRAME_SIZE: usize = 6_000;
+const VIDEO_DELTA_FRAME_SIZE: usize = 300;
+const VIDEO_KEY_FRAME_INTERVAL: u64 = 50;
+
+/// The heartbeat of `Connection::start_heartbeat`
+const HEARTBEAT_PERIOD: Duration = Duration::from_secs(1);
+
+/// Sends a heartbeat every second, like the browser client, so that the other peers show this
+/// client even when it does not publish media
+pub async fn heartbeat(conn: quinn::Connection, username: String, on_sent: impl Fn(usize)) {
+    let mut ticker = interval(HEARTBEAT_PERIOD);
+    loop {
+        ticker.tick().await;
+        let packet = media_packet(&username, MediaType::HEARTBEAT, "", Vec::new());
+        if !send(&conn, packet, &on_sent).await {
+            return;
+        }
+    }
+}
+
+/// Pings the relay every second, the relay answers with a PONG
+pub async fn ping(conn: quinn::Connection, username: String, on_sent: impl Fn(usize)) {
+    let mut ticker = interval(HEARTBEAT_PERIOD);
+    let mut sequence = 0;
+    loop {
+        ticker.tick().await;
+        sequence += 1;
+        let packet = PingPacket {
+            sequence,
+            sent_at: now_millis(),
+            ..Default::default()
+        };
+        if !send_ping(&conn, &username, PacketType::PING, packet, &on_sent).await {
+            return;
+        }
+    }
+}
+
+/// Answers the PING of a peer, the PONG is addressed to it
+pub async fn pong(conn: &quinn::Connection, username: &str, from: &str, ping: PingPacket) {
+    let packet = PingPacket {
+        sequence: ping.sequence,
+        sent_at: ping.sent_at,
+        to: from.to_owned(),
+        received_at: now_millis(),
+        ..Default::default()
+    };
+    send_ping(conn, username, PacketType::PONG, packet, &|_| {}).await;
+}
+
+async fn send_ping(
+    conn: &quinn::Connection,
+    username: &str,
+    packet_type: PacketType,
+    packet: PingPacket,
+    on_sent: &impl Fn(usize),
+) -> bool {
+    let payload = packet.write_to_bytes().and_then(|data| {
+        PacketWrapper {
+            packet_type: packet_type.into(),
+            email: username.to_owned(),
+            data,
+            ..Default::default()
+        }
+        .write_to_bytes()
+    });
+    match payload {
+        Ok(payload) => send_payload(conn, payload.into(), on_sent).await,
+        Err(e) => {
+            error!("cannot serialize packet: {}", e);
+            true
+        }
+    }
+}
+
+/// Sends a chat message, always on a stream: a lost message would not be sent again
+pub async fn chat(conn: &quinn::Connection, username: &str, text: String) -> Result<()> {
+    let data = ChatPacket {
+        text,
+        ..Default::default()
+    }
+    .write_to_bytes()?;
+    let packet = PacketWrapper {
+        packet_type: PacketType::CHAT.into(),
+        email: username.to_owned(),
+        data,
+        ..Default::default()
+    };
+    let mut stream = conn.open_uni().await?;
+    stream.write_all(&packet.write_to_bytes()?).await?;
+    stream.finish().await?;
+    Ok(())
+}
+
+/// Asks the relay to take a moderation action, on a stream like a chat message
+pub async fn moderate(
+    conn: &quinn::Connection,
+    username: &str,
+    action: Action,
+    target: String,
+) -> Result<()> {
+    let data = ModerationPacket {
+        action: action.into(),
+        username: target,
+        ..Default::default()
+    }
+    .write_to_bytes()?;
+    let packet = PacketWrapper {
+        packet_type: PacketType::MODERATION.into(),
+        email: username.to_owned(),
+        data,
+        ..Default::default()
+    };
+    let mut stream = conn.open_uni().await?;
+    stream.write_all(&packet.write_to_bytes()?).await?;
+    stream.finish().await?;
+    Ok(())
+}
+
+/// Publishes audio and video frames with the sizes and the cadence of the browser client.
+///
+/// Every frame starts with its sequence number (u64, big endian) and carries the time it was
+/// sent in `timestamp`, the rest is a fixed pattern. The frames cannot be decoded, browsers in
+/// the room drop them: they only exercise the relay.
+pub async fn synthetic(conn: quinn::Connection, username: String, on_sent: impl Fn(usize)) {
+    let mut audio = interval(AUDIO_PERIOD);
+    let mut video = interval(VIDEO_PERIOD);
+    audio.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    video.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut audio_sequence = 0u64;
+    let mut video_sequence = 0u64;
+
+    loop {
+        let packet = tokio::select! {
+            _ = audio.tick() => {
+                audio_sequence += 1;
+                media_packet(
+                    &username,
+                    MediaType::AUDIO,
+                    "key",
+                    frame_data(audio_sequence, AUDIO_FRAME_SIZE),
+                )
+            }
+            _ = video.tick() => {
+                video_sequence += 1;
+                let (frame_type, size) = if video_sequence % VIDEO_KEY_FRAME_INTERVAL == 1 {
+                    ("key", VIDEO_KEY_FRAME_SIZE)
+                } else {
+                    ("delta", VIDEO_DELTA_FRAME_SIZE)
+                };
+                let mut packet = media_packet(
+                    &username,
+                    MediaType::VIDEO,
+                    frame_type,
+                    frame_data(video_sequence, size),
+                );
+                packet.video_metadata.mut_or_insert_default().sequence = video_sequence;
+                packet
+            }
+        };
+        if !send(&conn, packet, &on_sent).await {
+            return;
+        }
+    }
+}
+
+/// Reads the sequence number written by [`synthetic`] at the start of a frame
+pub fn frame_sequence(data: &[u8]) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(..8)?.try_into().ok()?))
+}
+
+/// Milliseconds since the epoch, the clock of `timestamp`
+pub fn now_millis() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+        * 1000.0
+}
+
+/// Replays a recording in a loop with its original timing, as if the packets were sent by this
+/// client. Packets generated by the server are skipped.
+pub async fn replay(
+    conn: quinn::Connection,
+    username: String,
+    path: &Path,
+    on_sent: impl Fn(usize),
+) -> Result<()> {
+    let packets = recording::read(path)
+        .await?
+        .into_iter()
+        .filter_map(|recorded| {
+            let mut packet = PacketWrapper::parse_from_bytes(&recorded.payload).ok()?;
+            match packet.packet_type.enum_value() {
+                Ok(PacketType::MEDIA) | Ok(PacketType::RSA_PUB_KEY) | Ok(PacketType::AES_KEY) => {
+                    packet.email = username.clone();
+                    Some((recorded.offset, packet))
+                }
+                _ => None,
+            }
+        })
+        .collect::<Vec<_>>();
+    if packets.is_empty() {
+        bail!("nothing to publish in {}", path.display());
+    }
+    info!(
+        "replaying {} packets from {}",
+        packets.len(),
+        path.display()
+    );
+
+    loop {
+        let started_at = Instant::now();
+        for (offset, packet) in &packets {
+            sleep_until(started_at + *offset).await;
+            let payload = match packet.write_to_bytes() {
+                Ok(payload) => Bytes::from(payload),
+                Err(e) => {
+                    error!("cannot serialize packet: {}", e);
+                    continue;
+                }
+            };
+            if !send_payload(&conn, payload, &on_sent).await {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn frame_data(sequence: u64, size: usize) -> Vec<u8> {
+    let mut data = vec![0xA5; size.max(8)];
+    data[..8].copy_from_slice(&sequence.to_be_bytes());
+    data
+}
+
+fn media_packet(
+    username: &str,
+    media_type: MediaType,
+    frame_type: &str,
+    data: Vec<u8>,
+) -> MediaPacket {
+    MediaPacket {
+        media_type: media_type.into(),
+        email: username.to_owned(),
+        data,
+        frame_type: frame_type.to_owned(),
+        ..Default::default()
+    }
+}
+
+/// Stamps the media packet with the current time and sends it, returns `false` once the
+/// connection is gone
+async fn send(conn: &quinn::Connection, mut packet: MediaPacket, on_sent: &impl Fn(usize)) -> bool {
+    packet.timestamp = now_millis();
+    let payload = packet.write_to_bytes().and_then(|data| {
+        PacketWrapper {
+            packet_type: PacketType::MEDIA.into(),
+            email: packet.email.clone(),
+            data,
+            media_kind: media_kind(packet.media_type.enum_value_or_default()).into(),
+            ..Default::default()
+        }
+        .write_to_bytes()
+    });
+    match payload {
+        Ok(payload) => send_payload(conn, payload.into(), on_sent).await,
+        Err(e) => {
+            error!("cannot serialize packet: {}", e);
+            true
+        }
+    }
+}
+
+/// Kind of the media told in clear to the relay, which prioritizes it without reading the packet
+fn media_kind(media_type: MediaType) -> MediaKind {
+    match media_type {
+        MediaType::AUDIO => MediaKind::AUDIO,
+        MediaType::VIDEO => MediaKind::VIDEO,
+        MediaType::HEARTBEAT => MediaKind::HEARTBEAT,
+    }
+}
+
+async fn send_payload(conn: &quinn::Connection, payload: Bytes, on_sent: &impl Fn(usize)) -> bool {
+    let len = payload.len();
+    match connection::send(conn, payload).await {
+        Ok(()) => {
+            on_sent(len);
+            true
+        }
+        Err(e) => {
+            error!("cannot send packet: {}", e);
+            conn.close_reason().is_none()
+        }
+    }
+}
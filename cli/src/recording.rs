@@ -0,0 +1,64 @@
+//! Packets saved by `--record` and replayed by `--publish <PATH>`.
+//!
+//! Every packet is stored as the milliseconds elapsed since the recording started (u64, big
+//! endian), its length (u32, big endian) and the serialized `PacketWrapper`.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use bytes::{Buf, Bytes};
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+pub struct Recorder {
+    file: BufWriter<File>,
+    started_at: Instant,
+}
+
+impl Recorder {
+    pub async fn create(path: &Path) -> Result<Self> {
+        Ok(Self {
+            file: BufWriter::new(File::create(path).await?),
+            started_at: Instant::now(),
+        })
+    }
+
+    pub async fn write(&mut self, payload: &[u8]) -> Result<()> {
+        let offset = self.started_at.elapsed().as_millis() as u64;
+        self.file.write_u64(offset).await?;
+        self.file.write_u32(u32::try_from(payload.len())?).await?;
+        self.file.write_all(payload).await?;
+        Ok(())
+    }
+
+    pub async fn flush(&mut self) -> Result<()> {
+        Ok(self.file.flush().await?)
+    }
+}
+
+/// A recorded packet and when it was received
+pub struct Recorded {
+    pub offset: Duration,
+    pub payload: Bytes,
+}
+
+pub async fn read(path: &Path) -> Result<Vec<Recorded>> {
+    let mut data = Bytes::from(tokio::fs::read(path).await?);
+    let mut packets = Vec::new();
+    while data.has_remaining() {
+        if data.remaining() < 12 {
+            bail!("truncated recording {}", path.display());
+        }
+        let offset = Duration::from_millis(data.get_u64());
+        let len = data.get_u32() as usize;
+        if data.remaining() < len {
+            bail!("truncated recording {}", path.display());
+        }
+        packets.push(Recorded {
+            offset,
+            payload: data.split_to(len),
+        });
+    }
+    Ok(packets)
+}
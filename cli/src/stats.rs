@@ -0,0 +1,205 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::time::Instant;
+
+use common::protos::media_packet::{media_packet::MediaType, MediaPacket};
+use common::protos::packet_wrapper::{packet_wrapper::PacketType, PacketWrapper};
+use common::protos::ping_packet::PingPacket;
+use common::protos::roster_packet::{roster_packet::EventType, RosterPacket};
+use protobuf::Message;
+
+use crate::publish::now_millis;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Counter {
+    packets: u64,
+    bytes: u64,
+}
+
+impl Counter {
+    fn add(&mut self, bytes: usize) {
+        self.packets += 1;
+        self.bytes += bytes as u64;
+    }
+}
+
+/// What happened to the roster because of a packet
+pub enum RosterChange {
+    Joined(String),
+    Left(String),
+    Snapshot,
+}
+
+/// Packets received and sent since the client joined
+pub struct Stats {
+    started_at: Instant,
+    received_datagrams: Counter,
+    received_streams: Counter,
+    sent: Counter,
+    malformed: u64,
+    by_type: BTreeMap<String, Counter>,
+    by_peer: BTreeMap<String, Counter>,
+    members: BTreeSet<String>,
+    /// Smoothed round trip time to the relay, in milliseconds
+    rtt: Option<f64>,
+    /// Offset of the clock of the relay measured by the fastest exchange, and its round trip
+    clock: Option<(f64, f64)>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            received_datagrams: Counter::default(),
+            received_streams: Counter::default(),
+            sent: Counter::default(),
+            malformed: 0,
+            by_type: BTreeMap::new(),
+            by_peer: BTreeMap::new(),
+            members: BTreeSet::new(),
+            rtt: None,
+            clock: None,
+        }
+    }
+}
+
+impl Stats {
+    pub fn on_datagram(&mut self, bytes: usize) {
+        self.received_datagrams.add(bytes);
+    }
+
+    pub fn on_stream(&mut self, bytes: usize) {
+        self.received_streams.add(bytes);
+    }
+
+    pub fn on_sent(&mut self, bytes: usize) {
+        self.sent.add(bytes);
+    }
+
+    pub fn on_malformed(&mut self) {
+        self.malformed += 1;
+    }
+
+    /// Accounts a received packet, returning the roster change it carries if any
+    pub fn on_packet(&mut self, packet: &PacketWrapper, bytes: usize) -> Option<RosterChange> {
+        self.by_type
+            .entry(type_name(packet))
+            .or_default()
+            .add(bytes);
+
+        match packet.packet_type.enum_value() {
+            Ok(PacketType::ROSTER) => {
+                let roster = RosterPacket::parse_from_bytes(&packet.data).ok()?;
+                match roster.event_type.enum_value() {
+                    Ok(EventType::JOINED) => {
+                        self.members.insert(roster.username.clone());
+                        Some(RosterChange::Joined(roster.username))
+                    }
+                    Ok(EventType::LEFT) => {
+                        self.members.remove(&roster.username);
+                        Some(RosterChange::Left(roster.username))
+                    }
+                    Ok(EventType::SNAPSHOT) => {
+                        self.members = roster.members.into_iter().collect();
+                        Some(RosterChange::Snapshot)
+                    }
+                    Ok(EventType::PRESENT) | Err(_) => None,
+                }
+            }
+            // the relay answers its pings without a sender
+            Ok(PacketType::PONG) if packet.email.is_empty() => {
+                let pong = PingPacket::parse_from_bytes(&packet.data).ok()?;
+                let now = now_millis();
+                let rtt = now - pong.sent_at;
+                self.rtt = Some(match self.rtt {
+                    Some(srtt) => srtt + (rtt - srtt) / 8.0,
+                    None => rtt,
+                });
+                let answered_at = if pong.answered_at == 0.0 {
+                    pong.received_at
+                } else {
+                    pong.answered_at
+                };
+                let delay = rtt - (answered_at - pong.received_at);
+                if self.clock.is_none_or(|(_, fastest)| delay < fastest) {
+                    let offset = ((pong.received_at - pong.sent_at) + (answered_at - now)) / 2.0;
+                    self.clock = Some((offset, delay));
+                }
+                None
+            }
+            _ => {
+                self.by_peer
+                    .entry(packet.email.clone())
+                    .or_default()
+                    .add(bytes);
+                None
+            }
+        }
+    }
+
+    pub fn members(&self) -> &BTreeSet<String> {
+        &self.members
+    }
+}
+
+/// Name of the packet type, with the media type for unencrypted media
+fn type_name(packet: &PacketWrapper) -> String {
+    match packet.packet_type.enum_value() {
+        Ok(PacketType::MEDIA) => match MediaPacket::parse_from_bytes(&packet.data)
+            .map(|media| media.media_type.enum_value())
+        {
+            Ok(Ok(MediaType::VIDEO)) => "MEDIA/VIDEO".to_owned(),
+            Ok(Ok(MediaType::AUDIO)) => "MEDIA/AUDIO".to_owned(),
+            Ok(Ok(MediaType::HEARTBEAT)) => "MEDIA/HEARTBEAT".to_owned(),
+            _ => "MEDIA/ENCRYPTED".to_owned(),
+        },
+        Ok(packet_type) => packet_type.to_string(),
+        Err(value) => format!("UNKNOWN({value})"),
+    }
+}
+
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} packets, {} bytes", self.packets, self.bytes)
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        let received = self.received_datagrams.bytes + self.received_streams.bytes;
+        writeln!(f, "after {:.1}s", elapsed)?;
+        writeln!(
+            f,
+            "  members: {}",
+            self.members.iter().cloned().collect::<Vec<_>>().join(", ")
+        )?;
+        writeln!(f, "  datagrams received: {}", self.received_datagrams)?;
+        writeln!(f, "  streams received: {}", self.received_streams)?;
+        writeln!(
+            f,
+            "  received: {:.1} kbit/s, {} malformed",
+            received as f64 * 8.0 / 1000.0 / elapsed.max(f64::EPSILON),
+            self.malformed
+        )?;
+        writeln!(f, "  sent: {}", self.sent)?;
+        if let Some(rtt) = self.rtt {
+            writeln!(f, "  round trip to the relay: {:.1} ms", rtt)?;
+        }
+        if let Some((offset, delay)) = self.clock {
+            writeln!(
+                f,
+                "  clock of the relay: {:+.1} ms, within {:.1} ms",
+                offset,
+                delay / 2.0
+            )?;
+        }
+        for (packet_type, counter) in &self.by_type {
+            writeln!(f, "  {}: {}", packet_type, counter)?;
+        }
+        for (peer, counter) in &self.by_peer {
+            writeln!(f, "  from {}: {}", peer, counter)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+//! Application error codes sent by the relay when it closes a connection.
+
+/// The relay failed while handling the request
+pub const INTERNAL_ERROR: u32 = 0x5254_0000;
+
+/// The CONNECT request (or the connection packet, for raw QUIC clients) could not be parsed
+pub const MALFORMED_REQUEST: u32 = 0x5254_0001;
+
+/// The session cookie or join ticket is missing, expired or unknown
+pub const INVALID_CREDENTIALS: u32 = 0x5254_0002;
+
+/// The requested room does not exist
+pub const ROOM_NOT_FOUND: u32 = 0x5254_0003;
+
+/// The username cannot be used to build a relay subject
+pub const FORBIDDEN_NAME: u32 = 0x5254_0004;
+
+/// The room is not accepting new peers
+pub const ROOM_CLOSED: u32 = 0x5254_0005;
+
+/// The room has reached its maximum number of peers
+pub const ROOM_FULL: u32 = 0x5254_0006;
+
+/// The server is shutting down, the peer should reconnect to another instance
+pub const GOING_AWAY: u32 = 0x5254_0007;
+
+/// The same user opened a newer session in the room, the peer should not reconnect
+pub const REPLACED: u32 = 0x5254_0008;
+
+/// The client speaks a version of the protocol the relay does not support
+pub const UNSUPPORTED_VERSION: u32 = 0x5254_0009;
+
+/// The client and the relay have no codec, encryption scheme or transport feature in common
+pub const UNSUPPORTED_CAPABILITIES: u32 = 0x5254_000a;
+
+/// The peer kept sending more than the limits of its session
+pub const RATE_LIMITED: u32 = 0x5254_000b;
+
+/// A moderator of the room disconnected the peer, the peer should not reconnect on its own
+pub const KICKED: u32 = 0x5254_000c;
+
+/// The peer did not read the packets relayed to it and fell too far behind the room
+pub const TOO_SLOW: u32 = 0x5254_000d;
@@ -1,3 +1,5 @@
+pub mod close_codes;
+pub mod protocol;
 pub mod protos;
 pub mod types;
 pub mod utils;
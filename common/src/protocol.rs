@@ -0,0 +1,13 @@
+//! Version and capabilities of the protocol spoken between the clients and the relay.
+
+/// Version of the protocol, bumped on every change an older peer cannot ignore
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest version of the protocol the relay still accepts
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// The media is sent in clear, the relay can record it
+pub const E2EE_NONE: &str = "none";
+
+/// AES-128-CBC keys exchanged under RSA by the peers, the relay cannot read the media
+pub const E2EE_RSA_AES128_CBC: &str = "rsa-aes128-cbc";
@@ -0,0 +1,238 @@
+// This file is generated by rust-protobuf 3.4.0. Do not edit
+// .proto file is parsed by protoc --rust-out=...
+// @generated
+
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unused_results)]
+#![allow(unused_mut)]
+
+//! Generated file from `types/chat_packet.proto`
+
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_3_4_0;
+
+// @@protoc_insertion_point(message:ChatPacket)
+#[derive(PartialEq,Clone,Default,Debug)]
+pub struct ChatPacket {
+    // message fields
+    // @@protoc_insertion_point(field:ChatPacket.id)
+    pub id: ::std::string::String,
+    // @@protoc_insertion_point(field:ChatPacket.username)
+    pub username: ::std::string::String,
+    // @@protoc_insertion_point(field:ChatPacket.text)
+    pub text: ::std::string::String,
+    // @@protoc_insertion_point(field:ChatPacket.sent_at)
+    pub sent_at: i64,
+    // special fields
+    // @@protoc_insertion_point(special_field:ChatPacket.special_fields)
+    pub special_fields: ::protobuf::SpecialFields,
+}
+
+impl<'a> ::std::default::Default for &'a ChatPacket {
+    fn default() -> &'a ChatPacket {
+        <ChatPacket as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ChatPacket {
+    pub fn new() -> ChatPacket {
+        ::std::default::Default::default()
+    }
+
+    fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
+        let mut fields = ::std::vec::Vec::with_capacity(4);
+        let mut oneofs = ::std::vec::Vec::with_capacity(0);
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "id",
+            |m: &ChatPacket| { &m.id },
+            |m: &mut ChatPacket| { &mut m.id },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "username",
+            |m: &ChatPacket| { &m.username },
+            |m: &mut ChatPacket| { &mut m.username },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "text",
+            |m: &ChatPacket| { &m.text },
+            |m: &mut ChatPacket| { &mut m.text },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "sent_at",
+            |m: &ChatPacket| { &m.sent_at },
+            |m: &mut ChatPacket| { &mut m.sent_at },
+        ));
+        ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<ChatPacket>(
+            "ChatPacket",
+            fields,
+            oneofs,
+        )
+    }
+}
+
+impl ::protobuf::Message for ChatPacket {
+    const NAME: &'static str = "ChatPacket";
+
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+        while let Some(tag) = is.read_raw_tag_or_eof()? {
+            match tag {
+                10 => {
+                    self.id = is.read_string()?;
+                },
+                18 => {
+                    self.username = is.read_string()?;
+                },
+                26 => {
+                    self.text = is.read_string()?;
+                },
+                32 => {
+                    self.sent_at = is.read_int64()?;
+                },
+                tag => {
+                    ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u64 {
+        let mut my_size = 0;
+        if !self.id.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.id);
+        }
+        if !self.username.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.username);
+        }
+        if !self.text.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.text);
+        }
+        if self.sent_at != 0 {
+            my_size += ::protobuf::rt::int64_size(4, self.sent_at);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+        self.special_fields.cached_size().set(my_size as u32);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+        if !self.id.is_empty() {
+            os.write_string(1, &self.id)?;
+        }
+        if !self.username.is_empty() {
+            os.write_string(2, &self.username)?;
+        }
+        if !self.text.is_empty() {
+            os.write_string(3, &self.text)?;
+        }
+        if self.sent_at != 0 {
+            os.write_int64(4, self.sent_at)?;
+        }
+        os.write_unknown_fields(self.special_fields.unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn special_fields(&self) -> &::protobuf::SpecialFields {
+        &self.special_fields
+    }
+
+    fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+        &mut self.special_fields
+    }
+
+    fn new() -> ChatPacket {
+        ChatPacket::new()
+    }
+
+    fn clear(&mut self) {
+        self.id.clear();
+        self.username.clear();
+        self.text.clear();
+        self.sent_at = 0;
+        self.special_fields.clear();
+    }
+
+    fn default_instance() -> &'static ChatPacket {
+        static instance: ChatPacket = ChatPacket {
+            id: ::std::string::String::new(),
+            username: ::std::string::String::new(),
+            text: ::std::string::String::new(),
+            sent_at: 0,
+            special_fields: ::protobuf::SpecialFields::new(),
+        };
+        &instance
+    }
+}
+
+impl ::protobuf::MessageFull for ChatPacket {
+    fn descriptor() -> ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::Lazy::new();
+        descriptor.get(|| file_descriptor().message_by_package_relative_name("ChatPacket").unwrap()).clone()
+    }
+}
+
+impl ::std::fmt::Display for ChatPacket {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ChatPacket {
+    type RuntimeType = ::protobuf::reflect::rt::RuntimeTypeMessage<Self>;
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n\x17types/chat_packet.proto\"e\n\nChatPacket\x12\x0e\n\x02id\x18\x01\
+    \x20\x01(\tR\x02id\x12\x1a\n\x08username\x18\x02\x20\x01(\tR\x08username\
+    \x12\x12\n\x04text\x18\x03\x20\x01(\tR\x04text\x12\x17\n\x07sent_at\x18\
+    \x04\x20\x01(\x03R\x06sentAtb\x06proto3\
+";
+
+/// `FileDescriptorProto` object which was a source for this generated file
+fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
+    static file_descriptor_proto_lazy: ::protobuf::rt::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::Lazy::new();
+    file_descriptor_proto_lazy.get(|| {
+        ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
+    })
+}
+
+/// `FileDescriptor` object which allows dynamic access to files
+pub fn file_descriptor() -> &'static ::protobuf::reflect::FileDescriptor {
+    static generated_file_descriptor_lazy: ::protobuf::rt::Lazy<::protobuf::reflect::GeneratedFileDescriptor> = ::protobuf::rt::Lazy::new();
+    static file_descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::FileDescriptor> = ::protobuf::rt::Lazy::new();
+    file_descriptor.get(|| {
+        let generated_file_descriptor = generated_file_descriptor_lazy.get(|| {
+            let mut deps = ::std::vec::Vec::with_capacity(0);
+            let mut messages = ::std::vec::Vec::with_capacity(1);
+            messages.push(ChatPacket::generated_message_descriptor_data());
+            let mut enums = ::std::vec::Vec::with_capacity(0);
+            ::protobuf::reflect::GeneratedFileDescriptor::new_generated(
+                file_descriptor_proto(),
+                deps,
+                messages,
+                enums,
+            )
+        });
+        ::protobuf::reflect::FileDescriptor::new_generated_2(generated_file_descriptor)
+    })
+}
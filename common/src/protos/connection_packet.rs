@@ -31,6 +31,14 @@ pub struct ConnectionPacket {
     // message fields
     // @@protoc_insertion_point(field:ConnectionPacket.meeting_id)
     pub meeting_id: ::std::string::String,
+    // @@protoc_insertion_point(field:ConnectionPacket.ticket)
+    pub ticket: ::std::string::String,
+    // @@protoc_insertion_point(field:ConnectionPacket.protocol_version)
+    pub protocol_version: u32,
+    // @@protoc_insertion_point(field:ConnectionPacket.client_version)
+    pub client_version: ::std::string::String,
+    // @@protoc_insertion_point(field:ConnectionPacket.capabilities)
+    pub capabilities: ::protobuf::MessageField<Capabilities>,
     // special fields
     // @@protoc_insertion_point(special_field:ConnectionPacket.special_fields)
     pub special_fields: ::protobuf::SpecialFields,
@@ -48,13 +56,33 @@ impl ConnectionPacket {
     }
 
     fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
-        let mut fields = ::std::vec::Vec::with_capacity(1);
+        let mut fields = ::std::vec::Vec::with_capacity(5);
         let mut oneofs = ::std::vec::Vec::with_capacity(0);
         fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
             "meeting_id",
             |m: &ConnectionPacket| { &m.meeting_id },
             |m: &mut ConnectionPacket| { &mut m.meeting_id },
         ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "ticket",
+            |m: &ConnectionPacket| { &m.ticket },
+            |m: &mut ConnectionPacket| { &mut m.ticket },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "protocol_version",
+            |m: &ConnectionPacket| { &m.protocol_version },
+            |m: &mut ConnectionPacket| { &mut m.protocol_version },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "client_version",
+            |m: &ConnectionPacket| { &m.client_version },
+            |m: &mut ConnectionPacket| { &mut m.client_version },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_message_field_accessor::<_, Capabilities>(
+            "capabilities",
+            |m: &ConnectionPacket| { &m.capabilities },
+            |m: &mut ConnectionPacket| { &mut m.capabilities },
+        ));
         ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<ConnectionPacket>(
             "ConnectionPacket",
             fields,
@@ -76,6 +104,18 @@ impl ::protobuf::Message for ConnectionPacket {
                 10 => {
                     self.meeting_id = is.read_string()?;
                 },
+                18 => {
+                    self.ticket = is.read_string()?;
+                },
+                24 => {
+                    self.protocol_version = is.read_uint32()?;
+                },
+                34 => {
+                    self.client_version = is.read_string()?;
+                },
+                42 => {
+                    ::protobuf::rt::read_singular_message_into_field(is, &mut self.capabilities)?;
+                },
                 tag => {
                     ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
                 },
@@ -91,6 +131,19 @@ impl ::protobuf::Message for ConnectionPacket {
         if !self.meeting_id.is_empty() {
             my_size += ::protobuf::rt::string_size(1, &self.meeting_id);
         }
+        if !self.ticket.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.ticket);
+        }
+        if self.protocol_version != 0 {
+            my_size += ::protobuf::rt::uint32_size(3, self.protocol_version);
+        }
+        if !self.client_version.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.client_version);
+        }
+        if let Some(v) = self.capabilities.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
         self.special_fields.cached_size().set(my_size as u32);
         my_size
@@ -100,6 +153,18 @@ impl ::protobuf::Message for ConnectionPacket {
         if !self.meeting_id.is_empty() {
             os.write_string(1, &self.meeting_id)?;
         }
+        if !self.ticket.is_empty() {
+            os.write_string(2, &self.ticket)?;
+        }
+        if self.protocol_version != 0 {
+            os.write_uint32(3, self.protocol_version)?;
+        }
+        if !self.client_version.is_empty() {
+            os.write_string(4, &self.client_version)?;
+        }
+        if let Some(v) = self.capabilities.as_ref() {
+            ::protobuf::rt::write_message_field_with_cached_size(5, v, os)?;
+        }
         os.write_unknown_fields(self.special_fields.unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -118,12 +183,20 @@ impl ::protobuf::Message for ConnectionPacket {
 
     fn clear(&mut self) {
         self.meeting_id.clear();
+        self.ticket.clear();
+        self.protocol_version = 0;
+        self.client_version.clear();
+        self.capabilities.clear();
         self.special_fields.clear();
     }
 
     fn default_instance() -> &'static ConnectionPacket {
         static instance: ConnectionPacket = ConnectionPacket {
             meeting_id: ::std::string::String::new(),
+            ticket: ::std::string::String::new(),
+            protocol_version: 0,
+            client_version: ::std::string::String::new(),
+            capabilities: ::protobuf::MessageField::none(),
             special_fields: ::protobuf::SpecialFields::new(),
         };
         &instance
@@ -147,14 +220,193 @@ impl ::protobuf::reflect::ProtobufValue for ConnectionPacket {
     type RuntimeType = ::protobuf::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
+// @@protoc_insertion_point(message:Capabilities)
+#[derive(PartialEq,Clone,Default,Debug)]
+pub struct Capabilities {
+    // message fields
+    // @@protoc_insertion_point(field:Capabilities.audio_codecs)
+    pub audio_codecs: ::std::vec::Vec<::std::string::String>,
+    // @@protoc_insertion_point(field:Capabilities.video_codecs)
+    pub video_codecs: ::std::vec::Vec<::std::string::String>,
+    // @@protoc_insertion_point(field:Capabilities.e2ee_schemes)
+    pub e2ee_schemes: ::std::vec::Vec<::std::string::String>,
+    // @@protoc_insertion_point(field:Capabilities.datagrams)
+    pub datagrams: bool,
+    // special fields
+    // @@protoc_insertion_point(special_field:Capabilities.special_fields)
+    pub special_fields: ::protobuf::SpecialFields,
+}
+
+impl<'a> ::std::default::Default for &'a Capabilities {
+    fn default() -> &'a Capabilities {
+        <Capabilities as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Capabilities {
+    pub fn new() -> Capabilities {
+        ::std::default::Default::default()
+    }
+
+    fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
+        let mut fields = ::std::vec::Vec::with_capacity(4);
+        let mut oneofs = ::std::vec::Vec::with_capacity(0);
+        fields.push(::protobuf::reflect::rt::v2::make_vec_simpler_accessor::<_, _>(
+            "audio_codecs",
+            |m: &Capabilities| { &m.audio_codecs },
+            |m: &mut Capabilities| { &mut m.audio_codecs },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_vec_simpler_accessor::<_, _>(
+            "video_codecs",
+            |m: &Capabilities| { &m.video_codecs },
+            |m: &mut Capabilities| { &mut m.video_codecs },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_vec_simpler_accessor::<_, _>(
+            "e2ee_schemes",
+            |m: &Capabilities| { &m.e2ee_schemes },
+            |m: &mut Capabilities| { &mut m.e2ee_schemes },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "datagrams",
+            |m: &Capabilities| { &m.datagrams },
+            |m: &mut Capabilities| { &mut m.datagrams },
+        ));
+        ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<Capabilities>(
+            "Capabilities",
+            fields,
+            oneofs,
+        )
+    }
+}
+
+impl ::protobuf::Message for Capabilities {
+    const NAME: &'static str = "Capabilities";
+
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+        while let Some(tag) = is.read_raw_tag_or_eof()? {
+            match tag {
+                10 => {
+                    self.audio_codecs.push(is.read_string()?);
+                },
+                18 => {
+                    self.video_codecs.push(is.read_string()?);
+                },
+                26 => {
+                    self.e2ee_schemes.push(is.read_string()?);
+                },
+                32 => {
+                    self.datagrams = is.read_bool()?;
+                },
+                tag => {
+                    ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u64 {
+        let mut my_size = 0;
+        for value in &self.audio_codecs {
+            my_size += ::protobuf::rt::string_size(1, &value);
+        };
+        for value in &self.video_codecs {
+            my_size += ::protobuf::rt::string_size(2, &value);
+        };
+        for value in &self.e2ee_schemes {
+            my_size += ::protobuf::rt::string_size(3, &value);
+        };
+        if self.datagrams != false {
+            my_size += 1 + 1;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+        self.special_fields.cached_size().set(my_size as u32);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+        for v in &self.audio_codecs {
+            os.write_string(1, &v)?;
+        };
+        for v in &self.video_codecs {
+            os.write_string(2, &v)?;
+        };
+        for v in &self.e2ee_schemes {
+            os.write_string(3, &v)?;
+        };
+        if self.datagrams != false {
+            os.write_bool(4, self.datagrams)?;
+        }
+        os.write_unknown_fields(self.special_fields.unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn special_fields(&self) -> &::protobuf::SpecialFields {
+        &self.special_fields
+    }
+
+    fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+        &mut self.special_fields
+    }
+
+    fn new() -> Capabilities {
+        Capabilities::new()
+    }
+
+    fn clear(&mut self) {
+        self.audio_codecs.clear();
+        self.video_codecs.clear();
+        self.e2ee_schemes.clear();
+        self.datagrams = false;
+        self.special_fields.clear();
+    }
+
+    fn default_instance() -> &'static Capabilities {
+        static instance: Capabilities = Capabilities {
+            audio_codecs: ::std::vec::Vec::new(),
+            video_codecs: ::std::vec::Vec::new(),
+            e2ee_schemes: ::std::vec::Vec::new(),
+            datagrams: false,
+            special_fields: ::protobuf::SpecialFields::new(),
+        };
+        &instance
+    }
+}
+
+impl ::protobuf::MessageFull for Capabilities {
+    fn descriptor() -> ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::Lazy::new();
+        descriptor.get(|| file_descriptor().message_by_package_relative_name("Capabilities").unwrap()).clone()
+    }
+}
+
+impl ::std::fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Capabilities {
+    type RuntimeType = ::protobuf::reflect::rt::RuntimeTypeMessage<Self>;
+}
+
 static file_descriptor_proto_data: &'static [u8] = b"\
-    \n\x1dtypes/connection_packet.proto\"1\n\x10ConnectionPacket\x12\x1d\n\n\
-    meeting_id\x18\x01\x20\x01(\tR\tmeetingIdJa\n\x06\x12\x04\0\0\x04\x01\n\
-    \x08\n\x01\x0c\x12\x03\0\0\x12\n\n\n\x02\x04\0\x12\x04\x02\0\x04\x01\n\n\
-    \n\x03\x04\0\x01\x12\x03\x02\x08\x18\n\x0b\n\x04\x04\0\x02\0\x12\x03\x03\
-    \x02\x18\n\x0c\n\x05\x04\0\x02\0\x05\x12\x03\x03\x02\x08\n\x0c\n\x05\x04\
-    \0\x02\0\x01\x12\x03\x03\t\x13\n\x0c\n\x05\x04\0\x02\0\x03\x12\x03\x03\
-    \x16\x17b\x06proto3\
+    \n\x1dtypes/connection_packet.proto\"\xce\x01\n\x10ConnectionPacket\x12\
+    \x1d\n\nmeeting_id\x18\x01\x20\x01(\tR\tmeetingId\x12\x16\n\x06ticket\
+    \x18\x02\x20\x01(\tR\x06ticket\x12)\n\x10protocol_version\x18\x03\x20\
+    \x01(\rR\x0fprotocolVersion\x12%\n\x0eclient_version\x18\x04\x20\x01(\tR\
+    \rclientVersion\x121\n\x0ccapabilities\x18\x05\x20\x01(\x0b2\r.Capabilit\
+    iesR\x0ccapabilities\"\x95\x01\n\x0cCapabilities\x12!\n\x0caudio_codecs\
+    \x18\x01\x20\x03(\tR\x0baudioCodecs\x12!\n\x0cvideo_codecs\x18\x02\x20\
+    \x03(\tR\x0bvideoCodecs\x12!\n\x0ce2ee_schemes\x18\x03\x20\x03(\tR\x0be2\
+    eeSchemes\x12\x1c\n\tdatagrams\x18\x04\x20\x01(\x08R\tdatagramsb\x06prot\
+    o3\
 ";
 
 /// `FileDescriptorProto` object which was a source for this generated file
@@ -172,8 +424,9 @@ pub fn file_descriptor() -> &'static ::protobuf::reflect::FileDescriptor {
     file_descriptor.get(|| {
         let generated_file_descriptor = generated_file_descriptor_lazy.get(|| {
             let mut deps = ::std::vec::Vec::with_capacity(0);
-            let mut messages = ::std::vec::Vec::with_capacity(1);
+            let mut messages = ::std::vec::Vec::with_capacity(2);
             messages.push(ConnectionPacket::generated_message_descriptor_data());
+            messages.push(Capabilities::generated_message_descriptor_data());
             let mut enums = ::std::vec::Vec::with_capacity(0);
             ::protobuf::reflect::GeneratedFileDescriptor::new_generated(
                 file_descriptor_proto(),
@@ -45,6 +45,8 @@ pub struct MediaPacket {
     pub audio_metadata: ::protobuf::MessageField<AudioMetadata>,
     // @@protoc_insertion_point(field:MediaPacket.video_metadata)
     pub video_metadata: ::protobuf::MessageField<VideoMetadata>,
+    // @@protoc_insertion_point(field:MediaPacket.capture_time)
+    pub capture_time: f64,
     // special fields
     // @@protoc_insertion_point(special_field:MediaPacket.special_fields)
     pub special_fields: ::protobuf::SpecialFields,
@@ -62,7 +64,7 @@ impl MediaPacket {
     }
 
     fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
-        let mut fields = ::std::vec::Vec::with_capacity(8);
+        let mut fields = ::std::vec::Vec::with_capacity(9);
         let mut oneofs = ::std::vec::Vec::with_capacity(0);
         fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
             "media_type",
@@ -104,6 +106,11 @@ impl MediaPacket {
             |m: &MediaPacket| { &m.video_metadata },
             |m: &mut MediaPacket| { &mut m.video_metadata },
         ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "capture_time",
+            |m: &MediaPacket| { &m.capture_time },
+            |m: &mut MediaPacket| { &mut m.capture_time },
+        ));
         ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<MediaPacket>(
             "MediaPacket",
             fields,
@@ -146,6 +153,9 @@ impl ::protobuf::Message for MediaPacket {
                 66 => {
                     ::protobuf::rt::read_singular_message_into_field(is, &mut self.video_metadata)?;
                 },
+                73 => {
+                    self.capture_time = is.read_double()?;
+                },
                 tag => {
                     ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
                 },
@@ -184,6 +194,9 @@ impl ::protobuf::Message for MediaPacket {
             let len = v.compute_size();
             my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
         }
+        if self.capture_time != 0. {
+            my_size += 1 + 8;
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
         self.special_fields.cached_size().set(my_size as u32);
         my_size
@@ -214,6 +227,9 @@ impl ::protobuf::Message for MediaPacket {
         if let Some(v) = self.video_metadata.as_ref() {
             ::protobuf::rt::write_message_field_with_cached_size(8, v, os)?;
         }
+        if self.capture_time != 0. {
+            os.write_double(9, self.capture_time)?;
+        }
         os.write_unknown_fields(self.special_fields.unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -239,6 +255,7 @@ impl ::protobuf::Message for MediaPacket {
         self.duration = 0.;
         self.audio_metadata.clear();
         self.video_metadata.clear();
+        self.capture_time = 0.;
         self.special_fields.clear();
     }
 
@@ -252,6 +269,7 @@ impl ::protobuf::Message for MediaPacket {
             duration: 0.,
             audio_metadata: ::protobuf::MessageField::none(),
             video_metadata: ::protobuf::MessageField::none(),
+            capture_time: 0.,
             special_fields: ::protobuf::SpecialFields::new(),
         };
         &instance
@@ -648,68 +666,22 @@ impl ::protobuf::reflect::ProtobufValue for VideoMetadata {
 }
 
 static file_descriptor_proto_data: &'static [u8] = b"\
-    \n\x18types/media_packet.proto\"\xe7\x02\n\x0bMediaPacket\x125\n\nmedia_\
+    \n\x18types/media_packet.proto\"\x8a\x03\n\x0bMediaPacket\x125\n\nmedia_\
     type\x18\x01\x20\x01(\x0e2\x16.MediaPacket.MediaTypeR\tmediaType\x12\x14\
     \n\x05email\x18\x02\x20\x01(\tR\x05email\x12\x12\n\x04data\x18\x03\x20\
     \x01(\x0cR\x04data\x12\x1d\n\nframe_type\x18\x04\x20\x01(\tR\tframeType\
     \x12\x1c\n\ttimestamp\x18\x05\x20\x01(\x01R\ttimestamp\x12\x1a\n\x08dura\
     tion\x18\x06\x20\x01(\x01R\x08duration\x125\n\x0eaudio_metadata\x18\x07\
     \x20\x01(\x0b2\x0e.AudioMetadataR\raudioMetadata\x125\n\x0evideo_metadat\
-    a\x18\x08\x20\x01(\x0b2\x0e.VideoMetadataR\rvideoMetadata\"0\n\tMediaTyp\
-    e\x12\t\n\x05VIDEO\x10\0\x12\t\n\x05AUDIO\x10\x01\x12\r\n\tHEARTBEAT\x10\
-    \x03\"\xcc\x01\n\rAudioMetadata\x12!\n\x0caudio_format\x18\x01\x20\x01(\
-    \tR\x0baudioFormat\x127\n\x18audio_number_of_channels\x18\x02\x20\x01(\r\
-    R\x15audioNumberOfChannels\x123\n\x16audio_number_of_frames\x18\x03\x20\
-    \x01(\rR\x13audioNumberOfFrames\x12*\n\x11audio_sample_rate\x18\x04\x20\
-    \x01(\x02R\x0faudioSampleRate\"+\n\rVideoMetadata\x12\x1a\n\x08sequence\
-    \x18\x01\x20\x01(\x04R\x08sequenceJ\xce\x07\n\x06\x12\x04\0\0\x1b\x01\n\
-    \x08\n\x01\x0c\x12\x03\0\0\x12\n\n\n\x02\x04\0\x12\x04\x02\0\x10\x01\n\n\
-    \n\x03\x04\0\x01\x12\x03\x02\x08\x13\n\x0c\n\x04\x04\0\x04\0\x12\x04\x03\
-    \x02\x07\x03\n\x0c\n\x05\x04\0\x04\0\x01\x12\x03\x03\x07\x10\n\r\n\x06\
-    \x04\0\x04\0\x02\0\x12\x03\x04\x04\x0e\n\x0e\n\x07\x04\0\x04\0\x02\0\x01\
-    \x12\x03\x04\x04\t\n\x0e\n\x07\x04\0\x04\0\x02\0\x02\x12\x03\x04\x0c\r\n\
-    \r\n\x06\x04\0\x04\0\x02\x01\x12\x03\x05\x04\x0e\n\x0e\n\x07\x04\0\x04\0\
-    \x02\x01\x01\x12\x03\x05\x04\t\n\x0e\n\x07\x04\0\x04\0\x02\x01\x02\x12\
-    \x03\x05\x0c\r\n\r\n\x06\x04\0\x04\0\x02\x02\x12\x03\x06\x04\x12\n\x0e\n\
-    \x07\x04\0\x04\0\x02\x02\x01\x12\x03\x06\x04\r\n\x0e\n\x07\x04\0\x04\0\
-    \x02\x02\x02\x12\x03\x06\x10\x11\n\x0b\n\x04\x04\0\x02\0\x12\x03\x08\x02\
-    \x1b\n\x0c\n\x05\x04\0\x02\0\x06\x12\x03\x08\x02\x0b\n\x0c\n\x05\x04\0\
-    \x02\0\x01\x12\x03\x08\x0c\x16\n\x0c\n\x05\x04\0\x02\0\x03\x12\x03\x08\
-    \x19\x1a\n\x0b\n\x04\x04\0\x02\x01\x12\x03\t\x02\x13\n\x0c\n\x05\x04\0\
-    \x02\x01\x05\x12\x03\t\x02\x08\n\x0c\n\x05\x04\0\x02\x01\x01\x12\x03\t\t\
-    \x0e\n\x0c\n\x05\x04\0\x02\x01\x03\x12\x03\t\x11\x12\n\x0b\n\x04\x04\0\
-    \x02\x02\x12\x03\n\x02\x11\n\x0c\n\x05\x04\0\x02\x02\x05\x12\x03\n\x02\
-    \x07\n\x0c\n\x05\x04\0\x02\x02\x01\x12\x03\n\x08\x0c\n\x0c\n\x05\x04\0\
-    \x02\x02\x03\x12\x03\n\x0f\x10\n\x0b\n\x04\x04\0\x02\x03\x12\x03\x0b\x02\
-    \x18\n\x0c\n\x05\x04\0\x02\x03\x05\x12\x03\x0b\x02\x08\n\x0c\n\x05\x04\0\
-    \x02\x03\x01\x12\x03\x0b\t\x13\n\x0c\n\x05\x04\0\x02\x03\x03\x12\x03\x0b\
-    \x16\x17\n\x0b\n\x04\x04\0\x02\x04\x12\x03\x0c\x02\x17\n\x0c\n\x05\x04\0\
-    \x02\x04\x05\x12\x03\x0c\x02\x08\n\x0c\n\x05\x04\0\x02\x04\x01\x12\x03\
-    \x0c\t\x12\n\x0c\n\x05\x04\0\x02\x04\x03\x12\x03\x0c\x15\x16\n\x0b\n\x04\
-    \x04\0\x02\x05\x12\x03\r\x02\x16\n\x0c\n\x05\x04\0\x02\x05\x05\x12\x03\r\
-    \x02\x08\n\x0c\n\x05\x04\0\x02\x05\x01\x12\x03\r\t\x11\n\x0c\n\x05\x04\0\
-    \x02\x05\x03\x12\x03\r\x14\x15\n\x0b\n\x04\x04\0\x02\x06\x12\x03\x0e\x02\
-    #\n\x0c\n\x05\x04\0\x02\x06\x06\x12\x03\x0e\x02\x0f\n\x0c\n\x05\x04\0\
-    \x02\x06\x01\x12\x03\x0e\x10\x1e\n\x0c\n\x05\x04\0\x02\x06\x03\x12\x03\
-    \x0e!\"\n\x0b\n\x04\x04\0\x02\x07\x12\x03\x0f\x02#\n\x0c\n\x05\x04\0\x02\
-    \x07\x06\x12\x03\x0f\x02\x0f\n\x0c\n\x05\x04\0\x02\x07\x01\x12\x03\x0f\
-    \x10\x1e\n\x0c\n\x05\x04\0\x02\x07\x03\x12\x03\x0f!\"\n\n\n\x02\x04\x01\
-    \x12\x04\x12\0\x17\x01\n\n\n\x03\x04\x01\x01\x12\x03\x12\x08\x15\n\x0b\n\
-    \x04\x04\x01\x02\0\x12\x03\x13\x02\x1a\n\x0c\n\x05\x04\x01\x02\0\x05\x12\
-    \x03\x13\x02\x08\n\x0c\n\x05\x04\x01\x02\0\x01\x12\x03\x13\t\x15\n\x0c\n\
-    \x05\x04\x01\x02\0\x03\x12\x03\x13\x18\x19\n\x0b\n\x04\x04\x01\x02\x01\
-    \x12\x03\x14\x02&\n\x0c\n\x05\x04\x01\x02\x01\x05\x12\x03\x14\x02\x08\n\
-    \x0c\n\x05\x04\x01\x02\x01\x01\x12\x03\x14\t!\n\x0c\n\x05\x04\x01\x02\
-    \x01\x03\x12\x03\x14$%\n\x0b\n\x04\x04\x01\x02\x02\x12\x03\x15\x02$\n\
-    \x0c\n\x05\x04\x01\x02\x02\x05\x12\x03\x15\x02\x08\n\x0c\n\x05\x04\x01\
-    \x02\x02\x01\x12\x03\x15\t\x1f\n\x0c\n\x05\x04\x01\x02\x02\x03\x12\x03\
-    \x15\"#\n\x0b\n\x04\x04\x01\x02\x03\x12\x03\x16\x02\x1e\n\x0c\n\x05\x04\
-    \x01\x02\x03\x05\x12\x03\x16\x02\x07\n\x0c\n\x05\x04\x01\x02\x03\x01\x12\
-    \x03\x16\x08\x19\n\x0c\n\x05\x04\x01\x02\x03\x03\x12\x03\x16\x1c\x1d\n\n\
-    \n\x02\x04\x02\x12\x04\x19\0\x1b\x01\n\n\n\x03\x04\x02\x01\x12\x03\x19\
-    \x08\x15\n\x0b\n\x04\x04\x02\x02\0\x12\x03\x1a\x02\x16\n\x0c\n\x05\x04\
-    \x02\x02\0\x05\x12\x03\x1a\x02\x08\n\x0c\n\x05\x04\x02\x02\0\x01\x12\x03\
-    \x1a\t\x11\n\x0c\n\x05\x04\x02\x02\0\x03\x12\x03\x1a\x14\x15b\x06proto3\
+    a\x18\x08\x20\x01(\x0b2\x0e.VideoMetadataR\rvideoMetadata\x12!\n\x0ccapt\
+    ure_time\x18\t\x20\x01(\x01R\x0bcaptureTime\"0\n\tMediaType\x12\t\n\x05V\
+    IDEO\x10\0\x12\t\n\x05AUDIO\x10\x01\x12\r\n\tHEARTBEAT\x10\x03\"\xcc\x01\
+    \n\rAudioMetadata\x12!\n\x0caudio_format\x18\x01\x20\x01(\tR\x0baudioFor\
+    mat\x127\n\x18audio_number_of_channels\x18\x02\x20\x01(\rR\x15audioNumbe\
+    rOfChannels\x123\n\x16audio_number_of_frames\x18\x03\x20\x01(\rR\x13audi\
+    oNumberOfFrames\x12*\n\x11audio_sample_rate\x18\x04\x20\x01(\x02R\x0faud\
+    ioSampleRate\"+\n\rVideoMetadata\x12\x1a\n\x08sequence\x18\x01\x20\x01(\
+    \x04R\x08sequenceb\x06proto3\
 ";
 
 /// `FileDescriptorProto` object which was a source for this generated file
@@ -1,7 +1,15 @@
 // @generated
 
 pub mod aes_packet;
+pub mod chat_packet;
 pub mod connection_packet;
+pub mod going_away_packet;
 pub mod media_packet;
+pub mod moderation_packet;
+pub mod negotiation_packet;
 pub mod packet_wrapper;
+pub mod ping_packet;
+pub mod rejection_packet;
+pub mod roster_packet;
 pub mod rsa_packet;
+pub mod transport_packet;
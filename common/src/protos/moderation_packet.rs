@@ -0,0 +1,378 @@
+// This file is generated by rust-protobuf 3.4.0. Do not edit
+// .proto file is parsed by protoc --rust-out=...
+// @generated
+
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unused_results)]
+#![allow(unused_mut)]
+
+//! Generated file from `types/moderation_packet.proto`
+
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_3_4_0;
+
+// @@protoc_insertion_point(message:ModerationPacket)
+#[derive(PartialEq,Clone,Default,Debug)]
+pub struct ModerationPacket {
+    // message fields
+    // @@protoc_insertion_point(field:ModerationPacket.action)
+    pub action: ::protobuf::EnumOrUnknown<moderation_packet::Action>,
+    // @@protoc_insertion_point(field:ModerationPacket.username)
+    pub username: ::std::string::String,
+    // @@protoc_insertion_point(field:ModerationPacket.role)
+    pub role: ::protobuf::EnumOrUnknown<Role>,
+    // special fields
+    // @@protoc_insertion_point(special_field:ModerationPacket.special_fields)
+    pub special_fields: ::protobuf::SpecialFields,
+}
+
+impl<'a> ::std::default::Default for &'a ModerationPacket {
+    fn default() -> &'a ModerationPacket {
+        <ModerationPacket as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ModerationPacket {
+    pub fn new() -> ModerationPacket {
+        ::std::default::Default::default()
+    }
+
+    fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
+        let mut fields = ::std::vec::Vec::with_capacity(3);
+        let mut oneofs = ::std::vec::Vec::with_capacity(0);
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "action",
+            |m: &ModerationPacket| { &m.action },
+            |m: &mut ModerationPacket| { &mut m.action },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "username",
+            |m: &ModerationPacket| { &m.username },
+            |m: &mut ModerationPacket| { &mut m.username },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "role",
+            |m: &ModerationPacket| { &m.role },
+            |m: &mut ModerationPacket| { &mut m.role },
+        ));
+        ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<ModerationPacket>(
+            "ModerationPacket",
+            fields,
+            oneofs,
+        )
+    }
+}
+
+impl ::protobuf::Message for ModerationPacket {
+    const NAME: &'static str = "ModerationPacket";
+
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+        while let Some(tag) = is.read_raw_tag_or_eof()? {
+            match tag {
+                8 => {
+                    self.action = is.read_enum_or_unknown()?;
+                },
+                18 => {
+                    self.username = is.read_string()?;
+                },
+                24 => {
+                    self.role = is.read_enum_or_unknown()?;
+                },
+                tag => {
+                    ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u64 {
+        let mut my_size = 0;
+        if self.action != ::protobuf::EnumOrUnknown::new(moderation_packet::Action::MUTE) {
+            my_size += ::protobuf::rt::int32_size(1, self.action.value());
+        }
+        if !self.username.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.username);
+        }
+        if self.role != ::protobuf::EnumOrUnknown::new(Role::MUSICIAN) {
+            my_size += ::protobuf::rt::int32_size(3, self.role.value());
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+        self.special_fields.cached_size().set(my_size as u32);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+        if self.action != ::protobuf::EnumOrUnknown::new(moderation_packet::Action::MUTE) {
+            os.write_enum(1, ::protobuf::EnumOrUnknown::value(&self.action))?;
+        }
+        if !self.username.is_empty() {
+            os.write_string(2, &self.username)?;
+        }
+        if self.role != ::protobuf::EnumOrUnknown::new(Role::MUSICIAN) {
+            os.write_enum(3, ::protobuf::EnumOrUnknown::value(&self.role))?;
+        }
+        os.write_unknown_fields(self.special_fields.unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn special_fields(&self) -> &::protobuf::SpecialFields {
+        &self.special_fields
+    }
+
+    fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+        &mut self.special_fields
+    }
+
+    fn new() -> ModerationPacket {
+        ModerationPacket::new()
+    }
+
+    fn clear(&mut self) {
+        self.action = ::protobuf::EnumOrUnknown::new(moderation_packet::Action::MUTE);
+        self.username.clear();
+        self.role = ::protobuf::EnumOrUnknown::new(Role::MUSICIAN);
+        self.special_fields.clear();
+    }
+
+    fn default_instance() -> &'static ModerationPacket {
+        static instance: ModerationPacket = ModerationPacket {
+            action: ::protobuf::EnumOrUnknown::from_i32(0),
+            username: ::std::string::String::new(),
+            role: ::protobuf::EnumOrUnknown::from_i32(0),
+            special_fields: ::protobuf::SpecialFields::new(),
+        };
+        &instance
+    }
+}
+
+impl ::protobuf::MessageFull for ModerationPacket {
+    fn descriptor() -> ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::Lazy::new();
+        descriptor.get(|| file_descriptor().message_by_package_relative_name("ModerationPacket").unwrap()).clone()
+    }
+}
+
+impl ::std::fmt::Display for ModerationPacket {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ModerationPacket {
+    type RuntimeType = ::protobuf::reflect::rt::RuntimeTypeMessage<Self>;
+}
+
+/// Nested message and enums of message `ModerationPacket`
+pub mod moderation_packet {
+    #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
+    // @@protoc_insertion_point(enum:ModerationPacket.Action)
+    pub enum Action {
+        // @@protoc_insertion_point(enum_value:ModerationPacket.Action.MUTE)
+        MUTE = 0,
+        // @@protoc_insertion_point(enum_value:ModerationPacket.Action.PROMOTE)
+        PROMOTE = 1,
+        // @@protoc_insertion_point(enum_value:ModerationPacket.Action.DEMOTE)
+        DEMOTE = 2,
+        // @@protoc_insertion_point(enum_value:ModerationPacket.Action.KICK)
+        KICK = 3,
+        // @@protoc_insertion_point(enum_value:ModerationPacket.Action.TRANSFER_OWNERSHIP)
+        TRANSFER_OWNERSHIP = 4,
+    }
+
+    impl ::protobuf::Enum for Action {
+        const NAME: &'static str = "Action";
+
+        fn value(&self) -> i32 {
+            *self as i32
+        }
+
+        fn from_i32(value: i32) -> ::std::option::Option<Action> {
+            match value {
+                0 => ::std::option::Option::Some(Action::MUTE),
+                1 => ::std::option::Option::Some(Action::PROMOTE),
+                2 => ::std::option::Option::Some(Action::DEMOTE),
+                3 => ::std::option::Option::Some(Action::KICK),
+                4 => ::std::option::Option::Some(Action::TRANSFER_OWNERSHIP),
+                _ => ::std::option::Option::None
+            }
+        }
+
+        fn from_str(str: &str) -> ::std::option::Option<Action> {
+            match str {
+                "MUTE" => ::std::option::Option::Some(Action::MUTE),
+                "PROMOTE" => ::std::option::Option::Some(Action::PROMOTE),
+                "DEMOTE" => ::std::option::Option::Some(Action::DEMOTE),
+                "KICK" => ::std::option::Option::Some(Action::KICK),
+                "TRANSFER_OWNERSHIP" => ::std::option::Option::Some(Action::TRANSFER_OWNERSHIP),
+                _ => ::std::option::Option::None
+            }
+        }
+
+        const VALUES: &'static [Action] = &[
+            Action::MUTE,
+            Action::PROMOTE,
+            Action::DEMOTE,
+            Action::KICK,
+            Action::TRANSFER_OWNERSHIP,
+        ];
+    }
+
+    impl ::protobuf::EnumFull for Action {
+        fn enum_descriptor() -> ::protobuf::reflect::EnumDescriptor {
+            static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::Lazy::new();
+            descriptor.get(|| super::file_descriptor().enum_by_package_relative_name("ModerationPacket.Action").unwrap()).clone()
+        }
+
+        fn descriptor(&self) -> ::protobuf::reflect::EnumValueDescriptor {
+            let index = *self as usize;
+            Self::enum_descriptor().value_by_index(index)
+        }
+    }
+
+    impl ::std::default::Default for Action {
+        fn default() -> Self {
+            Action::MUTE
+        }
+    }
+
+    impl Action {
+        pub(in super) fn generated_enum_descriptor_data() -> ::protobuf::reflect::GeneratedEnumDescriptorData {
+            ::protobuf::reflect::GeneratedEnumDescriptorData::new::<Action>("ModerationPacket.Action")
+        }
+    }
+}
+
+#[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
+// @@protoc_insertion_point(enum:Role)
+pub enum Role {
+    // @@protoc_insertion_point(enum_value:Role.MUSICIAN)
+    MUSICIAN = 0,
+    // @@protoc_insertion_point(enum_value:Role.LISTENER)
+    LISTENER = 1,
+    // @@protoc_insertion_point(enum_value:Role.MODERATOR)
+    MODERATOR = 2,
+    // @@protoc_insertion_point(enum_value:Role.OWNER)
+    OWNER = 3,
+}
+
+impl ::protobuf::Enum for Role {
+    const NAME: &'static str = "Role";
+
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<Role> {
+        match value {
+            0 => ::std::option::Option::Some(Role::MUSICIAN),
+            1 => ::std::option::Option::Some(Role::LISTENER),
+            2 => ::std::option::Option::Some(Role::MODERATOR),
+            3 => ::std::option::Option::Some(Role::OWNER),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn from_str(str: &str) -> ::std::option::Option<Role> {
+        match str {
+            "MUSICIAN" => ::std::option::Option::Some(Role::MUSICIAN),
+            "LISTENER" => ::std::option::Option::Some(Role::LISTENER),
+            "MODERATOR" => ::std::option::Option::Some(Role::MODERATOR),
+            "OWNER" => ::std::option::Option::Some(Role::OWNER),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    const VALUES: &'static [Role] = &[
+        Role::MUSICIAN,
+        Role::LISTENER,
+        Role::MODERATOR,
+        Role::OWNER,
+    ];
+}
+
+impl ::protobuf::EnumFull for Role {
+    fn enum_descriptor() -> ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::Lazy::new();
+        descriptor.get(|| file_descriptor().enum_by_package_relative_name("Role").unwrap()).clone()
+    }
+
+    fn descriptor(&self) -> ::protobuf::reflect::EnumValueDescriptor {
+        let index = *self as usize;
+        Self::enum_descriptor().value_by_index(index)
+    }
+}
+
+impl ::std::default::Default for Role {
+    fn default() -> Self {
+        Role::MUSICIAN
+    }
+}
+
+impl Role {
+    fn generated_enum_descriptor_data() -> ::protobuf::reflect::GeneratedEnumDescriptorData {
+        ::protobuf::reflect::GeneratedEnumDescriptorData::new::<Role>("Role")
+    }
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n\x1dtypes/moderation_packet.proto\"\xca\x01\n\x10ModerationPacket\x120\
+    \n\x06action\x18\x01\x20\x01(\x0e2\x18.ModerationPacket.ActionR\x06actio\
+    n\x12\x1a\n\x08username\x18\x02\x20\x01(\tR\x08username\x12\x19\n\x04rol\
+    e\x18\x03\x20\x01(\x0e2\x05.RoleR\x04role\"M\n\x06Action\x12\x08\n\x04MU\
+    TE\x10\0\x12\x0b\n\x07PROMOTE\x10\x01\x12\n\n\x06DEMOTE\x10\x02\x12\x08\
+    \n\x04KICK\x10\x03\x12\x16\n\x12TRANSFER_OWNERSHIP\x10\x04*<\n\x04Role\
+    \x12\x0c\n\x08MUSICIAN\x10\0\x12\x0c\n\x08LISTENER\x10\x01\x12\r\n\tMODE\
+    RATOR\x10\x02\x12\t\n\x05OWNER\x10\x03b\x06proto3\
+";
+
+/// `FileDescriptorProto` object which was a source for this generated file
+fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
+    static file_descriptor_proto_lazy: ::protobuf::rt::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::Lazy::new();
+    file_descriptor_proto_lazy.get(|| {
+        ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
+    })
+}
+
+/// `FileDescriptor` object which allows dynamic access to files
+pub fn file_descriptor() -> &'static ::protobuf::reflect::FileDescriptor {
+    static generated_file_descriptor_lazy: ::protobuf::rt::Lazy<::protobuf::reflect::GeneratedFileDescriptor> = ::protobuf::rt::Lazy::new();
+    static file_descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::FileDescriptor> = ::protobuf::rt::Lazy::new();
+    file_descriptor.get(|| {
+        let generated_file_descriptor = generated_file_descriptor_lazy.get(|| {
+            let mut deps = ::std::vec::Vec::with_capacity(0);
+            let mut messages = ::std::vec::Vec::with_capacity(1);
+            messages.push(ModerationPacket::generated_message_descriptor_data());
+            let mut enums = ::std::vec::Vec::with_capacity(2);
+            enums.push(Role::generated_enum_descriptor_data());
+            enums.push(moderation_packet::Action::generated_enum_descriptor_data());
+            ::protobuf::reflect::GeneratedFileDescriptor::new_generated(
+                file_descriptor_proto(),
+                deps,
+                messages,
+                enums,
+            )
+        });
+        ::protobuf::reflect::FileDescriptor::new_generated_2(generated_file_descriptor)
+    })
+}
@@ -0,0 +1,297 @@
+// This file is generated by rust-protobuf 3.4.0. Do not edit
+// .proto file is parsed by protoc --rust-out=...
+// @generated
+
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unused_results)]
+#![allow(unused_mut)]
+
+//! Generated file from `types/negotiation_packet.proto`
+
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_3_4_0;
+
+// @@protoc_insertion_point(message:NegotiationPacket)
+#[derive(PartialEq,Clone,Default,Debug)]
+pub struct NegotiationPacket {
+    // message fields
+    // @@protoc_insertion_point(field:NegotiationPacket.protocol_version)
+    pub protocol_version: u32,
+    // @@protoc_insertion_point(field:NegotiationPacket.server_version)
+    pub server_version: ::std::string::String,
+    // @@protoc_insertion_point(field:NegotiationPacket.audio_codec)
+    pub audio_codec: ::std::string::String,
+    // @@protoc_insertion_point(field:NegotiationPacket.video_codec)
+    pub video_codec: ::std::string::String,
+    // @@protoc_insertion_point(field:NegotiationPacket.e2ee_scheme)
+    pub e2ee_scheme: ::std::string::String,
+    // @@protoc_insertion_point(field:NegotiationPacket.datagrams)
+    pub datagrams: bool,
+    // @@protoc_insertion_point(field:NegotiationPacket.role)
+    pub role: ::protobuf::EnumOrUnknown<super::moderation_packet::Role>,
+    // special fields
+    // @@protoc_insertion_point(special_field:NegotiationPacket.special_fields)
+    pub special_fields: ::protobuf::SpecialFields,
+}
+
+impl<'a> ::std::default::Default for &'a NegotiationPacket {
+    fn default() -> &'a NegotiationPacket {
+        <NegotiationPacket as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl NegotiationPacket {
+    pub fn new() -> NegotiationPacket {
+        ::std::default::Default::default()
+    }
+
+    fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
+        let mut fields = ::std::vec::Vec::with_capacity(7);
+        let mut oneofs = ::std::vec::Vec::with_capacity(0);
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "protocol_version",
+            |m: &NegotiationPacket| { &m.protocol_version },
+            |m: &mut NegotiationPacket| { &mut m.protocol_version },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "server_version",
+            |m: &NegotiationPacket| { &m.server_version },
+            |m: &mut NegotiationPacket| { &mut m.server_version },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "audio_codec",
+            |m: &NegotiationPacket| { &m.audio_codec },
+            |m: &mut NegotiationPacket| { &mut m.audio_codec },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "video_codec",
+            |m: &NegotiationPacket| { &m.video_codec },
+            |m: &mut NegotiationPacket| { &mut m.video_codec },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "e2ee_scheme",
+            |m: &NegotiationPacket| { &m.e2ee_scheme },
+            |m: &mut NegotiationPacket| { &mut m.e2ee_scheme },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "datagrams",
+            |m: &NegotiationPacket| { &m.datagrams },
+            |m: &mut NegotiationPacket| { &mut m.datagrams },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "role",
+            |m: &NegotiationPacket| { &m.role },
+            |m: &mut NegotiationPacket| { &mut m.role },
+        ));
+        ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<NegotiationPacket>(
+            "NegotiationPacket",
+            fields,
+            oneofs,
+        )
+    }
+}
+
+impl ::protobuf::Message for NegotiationPacket {
+    const NAME: &'static str = "NegotiationPacket";
+
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+        while let Some(tag) = is.read_raw_tag_or_eof()? {
+            match tag {
+                8 => {
+                    self.protocol_version = is.read_uint32()?;
+                },
+                18 => {
+                    self.server_version = is.read_string()?;
+                },
+                26 => {
+                    self.audio_codec = is.read_string()?;
+                },
+                34 => {
+                    self.video_codec = is.read_string()?;
+                },
+                42 => {
+                    self.e2ee_scheme = is.read_string()?;
+                },
+                48 => {
+                    self.datagrams = is.read_bool()?;
+                },
+                56 => {
+                    self.role = is.read_enum_or_unknown()?;
+                },
+                tag => {
+                    ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u64 {
+        let mut my_size = 0;
+        if self.protocol_version != 0 {
+            my_size += ::protobuf::rt::uint32_size(1, self.protocol_version);
+        }
+        if !self.server_version.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.server_version);
+        }
+        if !self.audio_codec.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.audio_codec);
+        }
+        if !self.video_codec.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.video_codec);
+        }
+        if !self.e2ee_scheme.is_empty() {
+            my_size += ::protobuf::rt::string_size(5, &self.e2ee_scheme);
+        }
+        if self.datagrams != false {
+            my_size += 1 + 1;
+        }
+        if self.role != ::protobuf::EnumOrUnknown::new(super::moderation_packet::Role::MUSICIAN) {
+            my_size += ::protobuf::rt::int32_size(7, self.role.value());
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+        self.special_fields.cached_size().set(my_size as u32);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+        if self.protocol_version != 0 {
+            os.write_uint32(1, self.protocol_version)?;
+        }
+        if !self.server_version.is_empty() {
+            os.write_string(2, &self.server_version)?;
+        }
+        if !self.audio_codec.is_empty() {
+            os.write_string(3, &self.audio_codec)?;
+        }
+        if !self.video_codec.is_empty() {
+            os.write_string(4, &self.video_codec)?;
+        }
+        if !self.e2ee_scheme.is_empty() {
+            os.write_string(5, &self.e2ee_scheme)?;
+        }
+        if self.datagrams != false {
+            os.write_bool(6, self.datagrams)?;
+        }
+        if self.role != ::protobuf::EnumOrUnknown::new(super::moderation_packet::Role::MUSICIAN) {
+            os.write_enum(7, ::protobuf::EnumOrUnknown::value(&self.role))?;
+        }
+        os.write_unknown_fields(self.special_fields.unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn special_fields(&self) -> &::protobuf::SpecialFields {
+        &self.special_fields
+    }
+
+    fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+        &mut self.special_fields
+    }
+
+    fn new() -> NegotiationPacket {
+        NegotiationPacket::new()
+    }
+
+    fn clear(&mut self) {
+        self.protocol_version = 0;
+        self.server_version.clear();
+        self.audio_codec.clear();
+        self.video_codec.clear();
+        self.e2ee_scheme.clear();
+        self.datagrams = false;
+        self.role = ::protobuf::EnumOrUnknown::new(super::moderation_packet::Role::MUSICIAN);
+        self.special_fields.clear();
+    }
+
+    fn default_instance() -> &'static NegotiationPacket {
+        static instance: NegotiationPacket = NegotiationPacket {
+            protocol_version: 0,
+            server_version: ::std::string::String::new(),
+            audio_codec: ::std::string::String::new(),
+            video_codec: ::std::string::String::new(),
+            e2ee_scheme: ::std::string::String::new(),
+            datagrams: false,
+            role: ::protobuf::EnumOrUnknown::from_i32(0),
+            special_fields: ::protobuf::SpecialFields::new(),
+        };
+        &instance
+    }
+}
+
+impl ::protobuf::MessageFull for NegotiationPacket {
+    fn descriptor() -> ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::Lazy::new();
+        descriptor.get(|| file_descriptor().message_by_package_relative_name("NegotiationPacket").unwrap()).clone()
+    }
+}
+
+impl ::std::fmt::Display for NegotiationPacket {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for NegotiationPacket {
+    type RuntimeType = ::protobuf::reflect::rt::RuntimeTypeMessage<Self>;
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n\x1etypes/negotiation_packet.proto\x1a\x1dtypes/moderation_packet.prot\
+    o\"\x81\x02\n\x11NegotiationPacket\x12)\n\x10protocol_version\x18\x01\
+    \x20\x01(\rR\x0fprotocolVersion\x12%\n\x0eserver_version\x18\x02\x20\x01\
+    (\tR\rserverVersion\x12\x1f\n\x0baudio_codec\x18\x03\x20\x01(\tR\naudioC\
+    odec\x12\x1f\n\x0bvideo_codec\x18\x04\x20\x01(\tR\nvideoCodec\x12\x1f\n\
+    \x0be2ee_scheme\x18\x05\x20\x01(\tR\ne2eeScheme\x12\x1c\n\tdatagrams\x18\
+    \x06\x20\x01(\x08R\tdatagrams\x12\x19\n\x04role\x18\x07\x20\x01(\x0e2\
+    \x05.RoleR\x04roleb\x06proto3\
+";
+
+/// `FileDescriptorProto` object which was a source for this generated file
+fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
+    static file_descriptor_proto_lazy: ::protobuf::rt::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::Lazy::new();
+    file_descriptor_proto_lazy.get(|| {
+        ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
+    })
+}
+
+/// `FileDescriptor` object which allows dynamic access to files
+pub fn file_descriptor() -> &'static ::protobuf::reflect::FileDescriptor {
+    static generated_file_descriptor_lazy: ::protobuf::rt::Lazy<::protobuf::reflect::GeneratedFileDescriptor> = ::protobuf::rt::Lazy::new();
+    static file_descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::FileDescriptor> = ::protobuf::rt::Lazy::new();
+    file_descriptor.get(|| {
+        let generated_file_descriptor = generated_file_descriptor_lazy.get(|| {
+            let mut deps = ::std::vec::Vec::with_capacity(1);
+            deps.push(super::moderation_packet::file_descriptor().clone());
+            let mut messages = ::std::vec::Vec::with_capacity(1);
+            messages.push(NegotiationPacket::generated_message_descriptor_data());
+            let mut enums = ::std::vec::Vec::with_capacity(0);
+            ::protobuf::reflect::GeneratedFileDescriptor::new_generated(
+                file_descriptor_proto(),
+                deps,
+                messages,
+                enums,
+            )
+        });
+        ::protobuf::reflect::FileDescriptor::new_generated_2(generated_file_descriptor)
+    })
+}
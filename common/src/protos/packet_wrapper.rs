@@ -35,6 +35,8 @@ pub struct PacketWrapper {
     pub email: ::std::string::String,
     // @@protoc_insertion_point(field:PacketWrapper.data)
     pub data: ::std::vec::Vec<u8>,
+    // @@protoc_insertion_point(field:PacketWrapper.media_kind)
+    pub media_kind: ::protobuf::EnumOrUnknown<packet_wrapper::MediaKind>,
     // special fields
     // @@protoc_insertion_point(special_field:PacketWrapper.special_fields)
     pub special_fields: ::protobuf::SpecialFields,
@@ -52,7 +54,7 @@ impl PacketWrapper {
     }
 
     fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
-        let mut fields = ::std::vec::Vec::with_capacity(3);
+        let mut fields = ::std::vec::Vec::with_capacity(4);
         let mut oneofs = ::std::vec::Vec::with_capacity(0);
         fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
             "packet_type",
@@ -69,6 +71,11 @@ impl PacketWrapper {
             |m: &PacketWrapper| { &m.data },
             |m: &mut PacketWrapper| { &mut m.data },
         ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "media_kind",
+            |m: &PacketWrapper| { &m.media_kind },
+            |m: &mut PacketWrapper| { &mut m.media_kind },
+        ));
         ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<PacketWrapper>(
             "PacketWrapper",
             fields,
@@ -96,6 +103,9 @@ impl ::protobuf::Message for PacketWrapper {
                 26 => {
                     self.data = is.read_bytes()?;
                 },
+                32 => {
+                    self.media_kind = is.read_enum_or_unknown()?;
+                },
                 tag => {
                     ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
                 },
@@ -117,6 +127,9 @@ impl ::protobuf::Message for PacketWrapper {
         if !self.data.is_empty() {
             my_size += ::protobuf::rt::bytes_size(3, &self.data);
         }
+        if self.media_kind != ::protobuf::EnumOrUnknown::new(packet_wrapper::MediaKind::UNKNOWN) {
+            my_size += ::protobuf::rt::int32_size(4, self.media_kind.value());
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
         self.special_fields.cached_size().set(my_size as u32);
         my_size
@@ -132,6 +145,9 @@ impl ::protobuf::Message for PacketWrapper {
         if !self.data.is_empty() {
             os.write_bytes(3, &self.data)?;
         }
+        if self.media_kind != ::protobuf::EnumOrUnknown::new(packet_wrapper::MediaKind::UNKNOWN) {
+            os.write_enum(4, ::protobuf::EnumOrUnknown::value(&self.media_kind))?;
+        }
         os.write_unknown_fields(self.special_fields.unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -152,6 +168,7 @@ impl ::protobuf::Message for PacketWrapper {
         self.packet_type = ::protobuf::EnumOrUnknown::new(packet_wrapper::PacketType::RSA_PUB_KEY);
         self.email.clear();
         self.data.clear();
+        self.media_kind = ::protobuf::EnumOrUnknown::new(packet_wrapper::MediaKind::UNKNOWN);
         self.special_fields.clear();
     }
 
@@ -160,6 +177,7 @@ impl ::protobuf::Message for PacketWrapper {
             packet_type: ::protobuf::EnumOrUnknown::from_i32(0),
             email: ::std::string::String::new(),
             data: ::std::vec::Vec::new(),
+            media_kind: ::protobuf::EnumOrUnknown::from_i32(0),
             special_fields: ::protobuf::SpecialFields::new(),
         };
         &instance
@@ -196,6 +214,24 @@ pub mod packet_wrapper {
         MEDIA = 2,
         // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.CONNECTION)
         CONNECTION = 3,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.ROSTER)
+        ROSTER = 4,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.REJECTION)
+        REJECTION = 5,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.GOING_AWAY)
+        GOING_AWAY = 6,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.NEGOTIATION)
+        NEGOTIATION = 7,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.CHAT)
+        CHAT = 8,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.PING)
+        PING = 9,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.PONG)
+        PONG = 10,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.TRANSPORT)
+        TRANSPORT = 11,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.PacketType.MODERATION)
+        MODERATION = 12,
     }
 
     impl ::protobuf::Enum for PacketType {
@@ -211,6 +247,15 @@ pub mod packet_wrapper {
                 1 => ::std::option::Option::Some(PacketType::AES_KEY),
                 2 => ::std::option::Option::Some(PacketType::MEDIA),
                 3 => ::std::option::Option::Some(PacketType::CONNECTION),
+                4 => ::std::option::Option::Some(PacketType::ROSTER),
+                5 => ::std::option::Option::Some(PacketType::REJECTION),
+                6 => ::std::option::Option::Some(PacketType::GOING_AWAY),
+                7 => ::std::option::Option::Some(PacketType::NEGOTIATION),
+                8 => ::std::option::Option::Some(PacketType::CHAT),
+                9 => ::std::option::Option::Some(PacketType::PING),
+                10 => ::std::option::Option::Some(PacketType::PONG),
+                11 => ::std::option::Option::Some(PacketType::TRANSPORT),
+                12 => ::std::option::Option::Some(PacketType::MODERATION),
                 _ => ::std::option::Option::None
             }
         }
@@ -221,6 +266,15 @@ pub mod packet_wrapper {
                 "AES_KEY" => ::std::option::Option::Some(PacketType::AES_KEY),
                 "MEDIA" => ::std::option::Option::Some(PacketType::MEDIA),
                 "CONNECTION" => ::std::option::Option::Some(PacketType::CONNECTION),
+                "ROSTER" => ::std::option::Option::Some(PacketType::ROSTER),
+                "REJECTION" => ::std::option::Option::Some(PacketType::REJECTION),
+                "GOING_AWAY" => ::std::option::Option::Some(PacketType::GOING_AWAY),
+                "NEGOTIATION" => ::std::option::Option::Some(PacketType::NEGOTIATION),
+                "CHAT" => ::std::option::Option::Some(PacketType::CHAT),
+                "PING" => ::std::option::Option::Some(PacketType::PING),
+                "PONG" => ::std::option::Option::Some(PacketType::PONG),
+                "TRANSPORT" => ::std::option::Option::Some(PacketType::TRANSPORT),
+                "MODERATION" => ::std::option::Option::Some(PacketType::MODERATION),
                 _ => ::std::option::Option::None
             }
         }
@@ -230,6 +284,15 @@ pub mod packet_wrapper {
             PacketType::AES_KEY,
             PacketType::MEDIA,
             PacketType::CONNECTION,
+            PacketType::ROSTER,
+            PacketType::REJECTION,
+            PacketType::GOING_AWAY,
+            PacketType::NEGOTIATION,
+            PacketType::CHAT,
+            PacketType::PING,
+            PacketType::PONG,
+            PacketType::TRANSPORT,
+            PacketType::MODERATION,
         ];
     }
 
@@ -256,35 +319,93 @@ pub mod packet_wrapper {
             ::protobuf::reflect::GeneratedEnumDescriptorData::new::<PacketType>("PacketWrapper.PacketType")
         }
     }
+
+    #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
+    // @@protoc_insertion_point(enum:PacketWrapper.MediaKind)
+    pub enum MediaKind {
+        // @@protoc_insertion_point(enum_value:PacketWrapper.MediaKind.UNKNOWN)
+        UNKNOWN = 0,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.MediaKind.AUDIO)
+        AUDIO = 1,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.MediaKind.VIDEO)
+        VIDEO = 2,
+        // @@protoc_insertion_point(enum_value:PacketWrapper.MediaKind.HEARTBEAT)
+        HEARTBEAT = 3,
+    }
+
+    impl ::protobuf::Enum for MediaKind {
+        const NAME: &'static str = "MediaKind";
+
+        fn value(&self) -> i32 {
+            *self as i32
+        }
+
+        fn from_i32(value: i32) -> ::std::option::Option<MediaKind> {
+            match value {
+                0 => ::std::option::Option::Some(MediaKind::UNKNOWN),
+                1 => ::std::option::Option::Some(MediaKind::AUDIO),
+                2 => ::std::option::Option::Some(MediaKind::VIDEO),
+                3 => ::std::option::Option::Some(MediaKind::HEARTBEAT),
+                _ => ::std::option::Option::None
+            }
+        }
+
+        fn from_str(str: &str) -> ::std::option::Option<MediaKind> {
+            match str {
+                "UNKNOWN" => ::std::option::Option::Some(MediaKind::UNKNOWN),
+                "AUDIO" => ::std::option::Option::Some(MediaKind::AUDIO),
+                "VIDEO" => ::std::option::Option::Some(MediaKind::VIDEO),
+                "HEARTBEAT" => ::std::option::Option::Some(MediaKind::HEARTBEAT),
+                _ => ::std::option::Option::None
+            }
+        }
+
+        const VALUES: &'static [MediaKind] = &[
+            MediaKind::UNKNOWN,
+            MediaKind::AUDIO,
+            MediaKind::VIDEO,
+            MediaKind::HEARTBEAT,
+        ];
+    }
+
+    impl ::protobuf::EnumFull for MediaKind {
+        fn enum_descriptor() -> ::protobuf::reflect::EnumDescriptor {
+            static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::Lazy::new();
+            descriptor.get(|| super::file_descriptor().enum_by_package_relative_name("PacketWrapper.MediaKind").unwrap()).clone()
+        }
+
+        fn descriptor(&self) -> ::protobuf::reflect::EnumValueDescriptor {
+            let index = *self as usize;
+            Self::enum_descriptor().value_by_index(index)
+        }
+    }
+
+    impl ::std::default::Default for MediaKind {
+        fn default() -> Self {
+            MediaKind::UNKNOWN
+        }
+    }
+
+    impl MediaKind {
+        pub(in super) fn generated_enum_descriptor_data() -> ::protobuf::reflect::GeneratedEnumDescriptorData {
+            ::protobuf::reflect::GeneratedEnumDescriptorData::new::<MediaKind>("PacketWrapper.MediaKind")
+        }
+    }
 }
 
 static file_descriptor_proto_data: &'static [u8] = b"\
-    \n\x1atypes/packet_wrapper.proto\"\xbc\x01\n\rPacketWrapper\x12:\n\x0bpa\
+    \n\x1atypes/packet_wrapper.proto\"\xae\x03\n\rPacketWrapper\x12:\n\x0bpa\
     cket_type\x18\x01\x20\x01(\x0e2\x19.PacketWrapper.PacketTypeR\npacketTyp\
     e\x12\x14\n\x05email\x18\x02\x20\x01(\tR\x05email\x12\x12\n\x04data\x18\
-    \x03\x20\x01(\x0cR\x04data\"E\n\nPacketType\x12\x0f\n\x0bRSA_PUB_KEY\x10\
-    \0\x12\x0b\n\x07AES_KEY\x10\x01\x12\t\n\x05MEDIA\x10\x02\x12\x0e\n\nCONN\
-    ECTION\x10\x03J\xa7\x03\n\x06\x12\x04\0\0\x0c\x01\n\x08\n\x01\x0c\x12\
-    \x03\0\0\x12\n\n\n\x02\x04\0\x12\x04\x02\0\x0c\x01\n\n\n\x03\x04\0\x01\
-    \x12\x03\x02\x08\x15\n\x0c\n\x04\x04\0\x04\0\x12\x04\x03\x02\x08\x03\n\
-    \x0c\n\x05\x04\0\x04\0\x01\x12\x03\x03\x07\x11\n\r\n\x06\x04\0\x04\0\x02\
-    \0\x12\x03\x04\x04\x14\n\x0e\n\x07\x04\0\x04\0\x02\0\x01\x12\x03\x04\x04\
-    \x0f\n\x0e\n\x07\x04\0\x04\0\x02\0\x02\x12\x03\x04\x12\x13\n\r\n\x06\x04\
-    \0\x04\0\x02\x01\x12\x03\x05\x04\x10\n\x0e\n\x07\x04\0\x04\0\x02\x01\x01\
-    \x12\x03\x05\x04\x0b\n\x0e\n\x07\x04\0\x04\0\x02\x01\x02\x12\x03\x05\x0e\
-    \x0f\n\r\n\x06\x04\0\x04\0\x02\x02\x12\x03\x06\x04\x0e\n\x0e\n\x07\x04\0\
-    \x04\0\x02\x02\x01\x12\x03\x06\x04\t\n\x0e\n\x07\x04\0\x04\0\x02\x02\x02\
-    \x12\x03\x06\x0c\r\n\r\n\x06\x04\0\x04\0\x02\x03\x12\x03\x07\x04\x13\n\
-    \x0e\n\x07\x04\0\x04\0\x02\x03\x01\x12\x03\x07\x04\x0e\n\x0e\n\x07\x04\0\
-    \x04\0\x02\x03\x02\x12\x03\x07\x11\x12\n\x0b\n\x04\x04\0\x02\0\x12\x03\t\
-    \x02\x1d\n\x0c\n\x05\x04\0\x02\0\x06\x12\x03\t\x02\x0c\n\x0c\n\x05\x04\0\
-    \x02\0\x01\x12\x03\t\r\x18\n\x0c\n\x05\x04\0\x02\0\x03\x12\x03\t\x1b\x1c\
-    \n\x0b\n\x04\x04\0\x02\x01\x12\x03\n\x02\x13\n\x0c\n\x05\x04\0\x02\x01\
-    \x05\x12\x03\n\x02\x08\n\x0c\n\x05\x04\0\x02\x01\x01\x12\x03\n\t\x0e\n\
-    \x0c\n\x05\x04\0\x02\x01\x03\x12\x03\n\x11\x12\n\x0b\n\x04\x04\0\x02\x02\
-    \x12\x03\x0b\x02\x11\n\x0c\n\x05\x04\0\x02\x02\x05\x12\x03\x0b\x02\x07\n\
-    \x0c\n\x05\x04\0\x02\x02\x01\x12\x03\x0b\x08\x0c\n\x0c\n\x05\x04\0\x02\
-    \x02\x03\x12\x03\x0b\x0f\x10b\x06proto3\
+    \x03\x20\x01(\x0cR\x04data\x127\n\nmedia_kind\x18\x04\x20\x01(\x0e2\x18.\
+    PacketWrapper.MediaKindR\tmediaKind\"\xbe\x01\n\nPacketType\x12\x0f\n\
+    \x0bRSA_PUB_KEY\x10\0\x12\x0b\n\x07AES_KEY\x10\x01\x12\t\n\x05MEDIA\x10\
+    \x02\x12\x0e\n\nCONNECTION\x10\x03\x12\n\n\x06ROSTER\x10\x04\x12\r\n\tRE\
+    JECTION\x10\x05\x12\x0e\n\nGOING_AWAY\x10\x06\x12\x0f\n\x0bNEGOTIATION\
+    \x10\x07\x12\x08\n\x04CHAT\x10\x08\x12\x08\n\x04PING\x10\t\x12\x08\n\x04\
+    PONG\x10\n\x12\r\n\tTRANSPORT\x10\x0b\x12\x0e\n\nMODERATION\x10\x0c\"=\n\
+    \tMediaKind\x12\x0b\n\x07UNKNOWN\x10\0\x12\t\n\x05AUDIO\x10\x01\x12\t\n\
+    \x05VIDEO\x10\x02\x12\r\n\tHEARTBEAT\x10\x03b\x06proto3\
 ";
 
 /// `FileDescriptorProto` object which was a source for this generated file
@@ -304,8 +425,9 @@ pub fn file_descriptor() -> &'static ::protobuf::reflect::FileDescriptor {
             let mut deps = ::std::vec::Vec::with_capacity(0);
             let mut messages = ::std::vec::Vec::with_capacity(1);
             messages.push(PacketWrapper::generated_message_descriptor_data());
-            let mut enums = ::std::vec::Vec::with_capacity(1);
+            let mut enums = ::std::vec::Vec::with_capacity(2);
             enums.push(packet_wrapper::PacketType::generated_enum_descriptor_data());
+            enums.push(packet_wrapper::MediaKind::generated_enum_descriptor_data());
             ::protobuf::reflect::GeneratedFileDescriptor::new_generated(
                 file_descriptor_proto(),
                 deps,
@@ -0,0 +1,276 @@
+// This file is generated by rust-protobuf 3.4.0. Do not edit
+// .proto file is parsed by protoc --rust-out=...
+// @generated
+
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unused_results)]
+#![allow(unused_mut)]
+
+//! Generated file from `types/ping_packet.proto`
+
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_3_4_0;
+
+// @@protoc_insertion_point(message:PingPacket)
+#[derive(PartialEq,Clone,Default,Debug)]
+pub struct PingPacket {
+    // message fields
+    // @@protoc_insertion_point(field:PingPacket.sequence)
+    pub sequence: u64,
+    // @@protoc_insertion_point(field:PingPacket.sent_at)
+    pub sent_at: f64,
+    // @@protoc_insertion_point(field:PingPacket.peers)
+    pub peers: bool,
+    // @@protoc_insertion_point(field:PingPacket.to)
+    pub to: ::std::string::String,
+    // @@protoc_insertion_point(field:PingPacket.received_at)
+    pub received_at: f64,
+    // @@protoc_insertion_point(field:PingPacket.answered_at)
+    pub answered_at: f64,
+    // special fields
+    // @@protoc_insertion_point(special_field:PingPacket.special_fields)
+    pub special_fields: ::protobuf::SpecialFields,
+}
+
+impl<'a> ::std::default::Default for &'a PingPacket {
+    fn default() -> &'a PingPacket {
+        <PingPacket as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl PingPacket {
+    pub fn new() -> PingPacket {
+        ::std::default::Default::default()
+    }
+
+    fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
+        let mut fields = ::std::vec::Vec::with_capacity(6);
+        let mut oneofs = ::std::vec::Vec::with_capacity(0);
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "sequence",
+            |m: &PingPacket| { &m.sequence },
+            |m: &mut PingPacket| { &mut m.sequence },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "sent_at",
+            |m: &PingPacket| { &m.sent_at },
+            |m: &mut PingPacket| { &mut m.sent_at },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "peers",
+            |m: &PingPacket| { &m.peers },
+            |m: &mut PingPacket| { &mut m.peers },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "to",
+            |m: &PingPacket| { &m.to },
+            |m: &mut PingPacket| { &mut m.to },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "received_at",
+            |m: &PingPacket| { &m.received_at },
+            |m: &mut PingPacket| { &mut m.received_at },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "answered_at",
+            |m: &PingPacket| { &m.answered_at },
+            |m: &mut PingPacket| { &mut m.answered_at },
+        ));
+        ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<PingPacket>(
+            "PingPacket",
+            fields,
+            oneofs,
+        )
+    }
+}
+
+impl ::protobuf::Message for PingPacket {
+    const NAME: &'static str = "PingPacket";
+
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+        while let Some(tag) = is.read_raw_tag_or_eof()? {
+            match tag {
+                8 => {
+                    self.sequence = is.read_uint64()?;
+                },
+                17 => {
+                    self.sent_at = is.read_double()?;
+                },
+                24 => {
+                    self.peers = is.read_bool()?;
+                },
+                34 => {
+                    self.to = is.read_string()?;
+                },
+                41 => {
+                    self.received_at = is.read_double()?;
+                },
+                49 => {
+                    self.answered_at = is.read_double()?;
+                },
+                tag => {
+                    ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u64 {
+        let mut my_size = 0;
+        if self.sequence != 0 {
+            my_size += ::protobuf::rt::uint64_size(1, self.sequence);
+        }
+        if self.sent_at != 0. {
+            my_size += 1 + 8;
+        }
+        if self.peers != false {
+            my_size += 1 + 1;
+        }
+        if !self.to.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.to);
+        }
+        if self.received_at != 0. {
+            my_size += 1 + 8;
+        }
+        if self.answered_at != 0. {
+            my_size += 1 + 8;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+        self.special_fields.cached_size().set(my_size as u32);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+        if self.sequence != 0 {
+            os.write_uint64(1, self.sequence)?;
+        }
+        if self.sent_at != 0. {
+            os.write_double(2, self.sent_at)?;
+        }
+        if self.peers != false {
+            os.write_bool(3, self.peers)?;
+        }
+        if !self.to.is_empty() {
+            os.write_string(4, &self.to)?;
+        }
+        if self.received_at != 0. {
+            os.write_double(5, self.received_at)?;
+        }
+        if self.answered_at != 0. {
+            os.write_double(6, self.answered_at)?;
+        }
+        os.write_unknown_fields(self.special_fields.unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn special_fields(&self) -> &::protobuf::SpecialFields {
+        &self.special_fields
+    }
+
+    fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+        &mut self.special_fields
+    }
+
+    fn new() -> PingPacket {
+        PingPacket::new()
+    }
+
+    fn clear(&mut self) {
+        self.sequence = 0;
+        self.sent_at = 0.;
+        self.peers = false;
+        self.to.clear();
+        self.received_at = 0.;
+        self.answered_at = 0.;
+        self.special_fields.clear();
+    }
+
+    fn default_instance() -> &'static PingPacket {
+        static instance: PingPacket = PingPacket {
+            sequence: 0,
+            sent_at: 0.,
+            peers: false,
+            to: ::std::string::String::new(),
+            received_at: 0.,
+            answered_at: 0.,
+            special_fields: ::protobuf::SpecialFields::new(),
+        };
+        &instance
+    }
+}
+
+impl ::protobuf::MessageFull for PingPacket {
+    fn descriptor() -> ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::Lazy::new();
+        descriptor.get(|| file_descriptor().message_by_package_relative_name("PingPacket").unwrap()).clone()
+    }
+}
+
+impl ::std::fmt::Display for PingPacket {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for PingPacket {
+    type RuntimeType = ::protobuf::reflect::rt::RuntimeTypeMessage<Self>;
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n\x17types/ping_packet.proto\"\xa9\x01\n\nPingPacket\x12\x1a\n\x08seque\
+    nce\x18\x01\x20\x01(\x04R\x08sequence\x12\x17\n\x07sent_at\x18\x02\x20\
+    \x01(\x01R\x06sentAt\x12\x14\n\x05peers\x18\x03\x20\x01(\x08R\x05peers\
+    \x12\x0e\n\x02to\x18\x04\x20\x01(\tR\x02to\x12\x1f\n\x0breceived_at\x18\
+    \x05\x20\x01(\x01R\nreceivedAt\x12\x1f\n\x0banswered_at\x18\x06\x20\x01(\
+    \x01R\nansweredAtb\x06proto3\
+";
+
+/// `FileDescriptorProto` object which was a source for this generated file
+fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
+    static file_descriptor_proto_lazy: ::protobuf::rt::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::Lazy::new();
+    file_descriptor_proto_lazy.get(|| {
+        ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
+    })
+}
+
+/// `FileDescriptor` object which allows dynamic access to files
+pub fn file_descriptor() -> &'static ::protobuf::reflect::FileDescriptor {
+    static generated_file_descriptor_lazy: ::protobuf::rt::Lazy<::protobuf::reflect::GeneratedFileDescriptor> = ::protobuf::rt::Lazy::new();
+    static file_descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::FileDescriptor> = ::protobuf::rt::Lazy::new();
+    file_descriptor.get(|| {
+        let generated_file_descriptor = generated_file_descriptor_lazy.get(|| {
+            let mut deps = ::std::vec::Vec::with_capacity(0);
+            let mut messages = ::std::vec::Vec::with_capacity(1);
+            messages.push(PingPacket::generated_message_descriptor_data());
+            let mut enums = ::std::vec::Vec::with_capacity(0);
+            ::protobuf::reflect::GeneratedFileDescriptor::new_generated(
+                file_descriptor_proto(),
+                deps,
+                messages,
+                enums,
+            )
+        });
+        ::protobuf::reflect::FileDescriptor::new_generated_2(generated_file_descriptor)
+    })
+}
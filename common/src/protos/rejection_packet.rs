@@ -0,0 +1,290 @@
+// This file is generated by rust-protobuf 3.4.0. Do not edit
+// .proto file is parsed by protoc --rust-out=...
+// @generated
+
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unused_results)]
+#![allow(unused_mut)]
+
+//! Generated file from `types/rejection_packet.proto`
+
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_3_4_0;
+
+// @@protoc_insertion_point(message:RejectionPacket)
+#[derive(PartialEq,Clone,Default,Debug)]
+pub struct RejectionPacket {
+    // message fields
+    // @@protoc_insertion_point(field:RejectionPacket.reason)
+    pub reason: ::protobuf::EnumOrUnknown<rejection_packet::Reason>,
+    // @@protoc_insertion_point(field:RejectionPacket.message)
+    pub message: ::std::string::String,
+    // special fields
+    // @@protoc_insertion_point(special_field:RejectionPacket.special_fields)
+    pub special_fields: ::protobuf::SpecialFields,
+}
+
+impl<'a> ::std::default::Default for &'a RejectionPacket {
+    fn default() -> &'a RejectionPacket {
+        <RejectionPacket as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RejectionPacket {
+    pub fn new() -> RejectionPacket {
+        ::std::default::Default::default()
+    }
+
+    fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
+        let mut fields = ::std::vec::Vec::with_capacity(2);
+        let mut oneofs = ::std::vec::Vec::with_capacity(0);
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "reason",
+            |m: &RejectionPacket| { &m.reason },
+            |m: &mut RejectionPacket| { &mut m.reason },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "message",
+            |m: &RejectionPacket| { &m.message },
+            |m: &mut RejectionPacket| { &mut m.message },
+        ));
+        ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<RejectionPacket>(
+            "RejectionPacket",
+            fields,
+            oneofs,
+        )
+    }
+}
+
+impl ::protobuf::Message for RejectionPacket {
+    const NAME: &'static str = "RejectionPacket";
+
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+        while let Some(tag) = is.read_raw_tag_or_eof()? {
+            match tag {
+                8 => {
+                    self.reason = is.read_enum_or_unknown()?;
+                },
+                18 => {
+                    self.message = is.read_string()?;
+                },
+                tag => {
+                    ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u64 {
+        let mut my_size = 0;
+        if self.reason != ::protobuf::EnumOrUnknown::new(rejection_packet::Reason::ROOM_CLOSED) {
+            my_size += ::protobuf::rt::int32_size(1, self.reason.value());
+        }
+        if !self.message.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.message);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+        self.special_fields.cached_size().set(my_size as u32);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+        if self.reason != ::protobuf::EnumOrUnknown::new(rejection_packet::Reason::ROOM_CLOSED) {
+            os.write_enum(1, ::protobuf::EnumOrUnknown::value(&self.reason))?;
+        }
+        if !self.message.is_empty() {
+            os.write_string(2, &self.message)?;
+        }
+        os.write_unknown_fields(self.special_fields.unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn special_fields(&self) -> &::protobuf::SpecialFields {
+        &self.special_fields
+    }
+
+    fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+        &mut self.special_fields
+    }
+
+    fn new() -> RejectionPacket {
+        RejectionPacket::new()
+    }
+
+    fn clear(&mut self) {
+        self.reason = ::protobuf::EnumOrUnknown::new(rejection_packet::Reason::ROOM_CLOSED);
+        self.message.clear();
+        self.special_fields.clear();
+    }
+
+    fn default_instance() -> &'static RejectionPacket {
+        static instance: RejectionPacket = RejectionPacket {
+            reason: ::protobuf::EnumOrUnknown::from_i32(0),
+            message: ::std::string::String::new(),
+            special_fields: ::protobuf::SpecialFields::new(),
+        };
+        &instance
+    }
+}
+
+impl ::protobuf::MessageFull for RejectionPacket {
+    fn descriptor() -> ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::Lazy::new();
+        descriptor.get(|| file_descriptor().message_by_package_relative_name("RejectionPacket").unwrap()).clone()
+    }
+}
+
+impl ::std::fmt::Display for RejectionPacket {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RejectionPacket {
+    type RuntimeType = ::protobuf::reflect::rt::RuntimeTypeMessage<Self>;
+}
+
+/// Nested message and enums of message `RejectionPacket`
+pub mod rejection_packet {
+    #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
+    // @@protoc_insertion_point(enum:RejectionPacket.Reason)
+    pub enum Reason {
+        // @@protoc_insertion_point(enum_value:RejectionPacket.Reason.ROOM_CLOSED)
+        ROOM_CLOSED = 0,
+        // @@protoc_insertion_point(enum_value:RejectionPacket.Reason.ROOM_FULL)
+        ROOM_FULL = 1,
+        // @@protoc_insertion_point(enum_value:RejectionPacket.Reason.REPLACED)
+        REPLACED = 2,
+        // @@protoc_insertion_point(enum_value:RejectionPacket.Reason.UNSUPPORTED_VERSION)
+        UNSUPPORTED_VERSION = 3,
+        // @@protoc_insertion_point(enum_value:RejectionPacket.Reason.UNSUPPORTED_CAPABILITIES)
+        UNSUPPORTED_CAPABILITIES = 4,
+        // @@protoc_insertion_point(enum_value:RejectionPacket.Reason.KICKED)
+        KICKED = 5,
+    }
+
+    impl ::protobuf::Enum for Reason {
+        const NAME: &'static str = "Reason";
+
+        fn value(&self) -> i32 {
+            *self as i32
+        }
+
+        fn from_i32(value: i32) -> ::std::option::Option<Reason> {
+            match value {
+                0 => ::std::option::Option::Some(Reason::ROOM_CLOSED),
+                1 => ::std::option::Option::Some(Reason::ROOM_FULL),
+                2 => ::std::option::Option::Some(Reason::REPLACED),
+                3 => ::std::option::Option::Some(Reason::UNSUPPORTED_VERSION),
+                4 => ::std::option::Option::Some(Reason::UNSUPPORTED_CAPABILITIES),
+                5 => ::std::option::Option::Some(Reason::KICKED),
+                _ => ::std::option::Option::None
+            }
+        }
+
+        fn from_str(str: &str) -> ::std::option::Option<Reason> {
+            match str {
+                "ROOM_CLOSED" => ::std::option::Option::Some(Reason::ROOM_CLOSED),
+                "ROOM_FULL" => ::std::option::Option::Some(Reason::ROOM_FULL),
+                "REPLACED" => ::std::option::Option::Some(Reason::REPLACED),
+                "UNSUPPORTED_VERSION" => ::std::option::Option::Some(Reason::UNSUPPORTED_VERSION),
+                "UNSUPPORTED_CAPABILITIES" => ::std::option::Option::Some(Reason::UNSUPPORTED_CAPABILITIES),
+                "KICKED" => ::std::option::Option::Some(Reason::KICKED),
+                _ => ::std::option::Option::None
+            }
+        }
+
+        const VALUES: &'static [Reason] = &[
+            Reason::ROOM_CLOSED,
+            Reason::ROOM_FULL,
+            Reason::REPLACED,
+            Reason::UNSUPPORTED_VERSION,
+            Reason::UNSUPPORTED_CAPABILITIES,
+            Reason::KICKED,
+        ];
+    }
+
+    impl ::protobuf::EnumFull for Reason {
+        fn enum_descriptor() -> ::protobuf::reflect::EnumDescriptor {
+            static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::Lazy::new();
+            descriptor.get(|| super::file_descriptor().enum_by_package_relative_name("RejectionPacket.Reason").unwrap()).clone()
+        }
+
+        fn descriptor(&self) -> ::protobuf::reflect::EnumValueDescriptor {
+            let index = *self as usize;
+            Self::enum_descriptor().value_by_index(index)
+        }
+    }
+
+    impl ::std::default::Default for Reason {
+        fn default() -> Self {
+            Reason::ROOM_CLOSED
+        }
+    }
+
+    impl Reason {
+        pub(in super) fn generated_enum_descriptor_data() -> ::protobuf::reflect::GeneratedEnumDescriptorData {
+            ::protobuf::reflect::GeneratedEnumDescriptorData::new::<Reason>("RejectionPacket.Reason")
+        }
+    }
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n\x1ctypes/rejection_packet.proto\"\xd7\x01\n\x0fRejectionPacket\x12/\n\
+    \x06reason\x18\x01\x20\x01(\x0e2\x17.RejectionPacket.ReasonR\x06reason\
+    \x12\x18\n\x07message\x18\x02\x20\x01(\tR\x07message\"y\n\x06Reason\x12\
+    \x0f\n\x0bROOM_CLOSED\x10\0\x12\r\n\tROOM_FULL\x10\x01\x12\x0c\n\x08REPL\
+    ACED\x10\x02\x12\x17\n\x13UNSUPPORTED_VERSION\x10\x03\x12\x1c\n\x18UNSUP\
+    PORTED_CAPABILITIES\x10\x04\x12\n\n\x06KICKED\x10\x05b\x06proto3\
+";
+
+/// `FileDescriptorProto` object which was a source for this generated file
+fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
+    static file_descriptor_proto_lazy: ::protobuf::rt::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::Lazy::new();
+    file_descriptor_proto_lazy.get(|| {
+        ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
+    })
+}
+
+/// `FileDescriptor` object which allows dynamic access to files
+pub fn file_descriptor() -> &'static ::protobuf::reflect::FileDescriptor {
+    static generated_file_descriptor_lazy: ::protobuf::rt::Lazy<::protobuf::reflect::GeneratedFileDescriptor> = ::protobuf::rt::Lazy::new();
+    static file_descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::FileDescriptor> = ::protobuf::rt::Lazy::new();
+    file_descriptor.get(|| {
+        let generated_file_descriptor = generated_file_descriptor_lazy.get(|| {
+            let mut deps = ::std::vec::Vec::with_capacity(0);
+            let mut messages = ::std::vec::Vec::with_capacity(1);
+            messages.push(RejectionPacket::generated_message_descriptor_data());
+            let mut enums = ::std::vec::Vec::with_capacity(1);
+            enums.push(rejection_packet::Reason::generated_enum_descriptor_data());
+            ::protobuf::reflect::GeneratedFileDescriptor::new_generated(
+                file_descriptor_proto(),
+                deps,
+                messages,
+                enums,
+            )
+        });
+        ::protobuf::reflect::FileDescriptor::new_generated_2(generated_file_descriptor)
+    })
+}
@@ -0,0 +1,353 @@
+// This file is generated by rust-protobuf 3.4.0. Do not edit
+// .proto file is parsed by protoc --rust-out=...
+// @generated
+
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unused_results)]
+#![allow(unused_mut)]
+
+//! Generated file from `types/roster_packet.proto`
+
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_3_4_0;
+
+// @@protoc_insertion_point(message:RosterPacket)
+#[derive(PartialEq,Clone,Default,Debug)]
+pub struct RosterPacket {
+    // message fields
+    // @@protoc_insertion_point(field:RosterPacket.event_type)
+    pub event_type: ::protobuf::EnumOrUnknown<roster_packet::EventType>,
+    // @@protoc_insertion_point(field:RosterPacket.username)
+    pub username: ::std::string::String,
+    // @@protoc_insertion_point(field:RosterPacket.members)
+    pub members: ::std::vec::Vec<::std::string::String>,
+    // @@protoc_insertion_point(field:RosterPacket.role)
+    pub role: ::protobuf::EnumOrUnknown<super::moderation_packet::Role>,
+    // @@protoc_insertion_point(field:RosterPacket.roles)
+    pub roles: ::std::collections::HashMap<::std::string::String, ::protobuf::EnumOrUnknown<super::moderation_packet::Role>>,
+    // special fields
+    // @@protoc_insertion_point(special_field:RosterPacket.special_fields)
+    pub special_fields: ::protobuf::SpecialFields,
+}
+
+impl<'a> ::std::default::Default for &'a RosterPacket {
+    fn default() -> &'a RosterPacket {
+        <RosterPacket as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RosterPacket {
+    pub fn new() -> RosterPacket {
+        ::std::default::Default::default()
+    }
+
+    fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
+        let mut fields = ::std::vec::Vec::with_capacity(5);
+        let mut oneofs = ::std::vec::Vec::with_capacity(0);
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "event_type",
+            |m: &RosterPacket| { &m.event_type },
+            |m: &mut RosterPacket| { &mut m.event_type },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "username",
+            |m: &RosterPacket| { &m.username },
+            |m: &mut RosterPacket| { &mut m.username },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_vec_simpler_accessor::<_, _>(
+            "members",
+            |m: &RosterPacket| { &m.members },
+            |m: &mut RosterPacket| { &mut m.members },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "role",
+            |m: &RosterPacket| { &m.role },
+            |m: &mut RosterPacket| { &mut m.role },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_map_simpler_accessor::<_, _, _>(
+            "roles",
+            |m: &RosterPacket| { &m.roles },
+            |m: &mut RosterPacket| { &mut m.roles },
+        ));
+        ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<RosterPacket>(
+            "RosterPacket",
+            fields,
+            oneofs,
+        )
+    }
+}
+
+impl ::protobuf::Message for RosterPacket {
+    const NAME: &'static str = "RosterPacket";
+
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+        while let Some(tag) = is.read_raw_tag_or_eof()? {
+            match tag {
+                8 => {
+                    self.event_type = is.read_enum_or_unknown()?;
+                },
+                18 => {
+                    self.username = is.read_string()?;
+                },
+                26 => {
+                    self.members.push(is.read_string()?);
+                },
+                32 => {
+                    self.role = is.read_enum_or_unknown()?;
+                },
+                42 => {
+                    let len = is.read_raw_varint32()?;
+                    let old_limit = is.push_limit(len as u64)?;
+                    let mut key = ::std::default::Default::default();
+                    let mut value = ::std::default::Default::default();
+                    while let Some(tag) = is.read_raw_tag_or_eof()? {
+                        match tag {
+                            10 => key = is.read_string()?,
+                            16 => value = is.read_enum_or_unknown()?,
+                            _ => ::protobuf::rt::skip_field_for_tag(tag, is)?,
+                        };
+                    }
+                    is.pop_limit(old_limit);
+                    self.roles.insert(key, value);
+                },
+                tag => {
+                    ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u64 {
+        let mut my_size = 0;
+        if self.event_type != ::protobuf::EnumOrUnknown::new(roster_packet::EventType::JOINED) {
+            my_size += ::protobuf::rt::int32_size(1, self.event_type.value());
+        }
+        if !self.username.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.username);
+        }
+        for value in &self.members {
+            my_size += ::protobuf::rt::string_size(3, &value);
+        };
+        if self.role != ::protobuf::EnumOrUnknown::new(super::moderation_packet::Role::MUSICIAN) {
+            my_size += ::protobuf::rt::int32_size(4, self.role.value());
+        }
+        for (k, v) in &self.roles {
+            let mut entry_size = 0;
+            entry_size += ::protobuf::rt::string_size(1, &k);
+            entry_size += ::protobuf::rt::int32_size(2, v.value());
+            my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(entry_size) + entry_size
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+        self.special_fields.cached_size().set(my_size as u32);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+        if self.event_type != ::protobuf::EnumOrUnknown::new(roster_packet::EventType::JOINED) {
+            os.write_enum(1, ::protobuf::EnumOrUnknown::value(&self.event_type))?;
+        }
+        if !self.username.is_empty() {
+            os.write_string(2, &self.username)?;
+        }
+        for v in &self.members {
+            os.write_string(3, &v)?;
+        };
+        if self.role != ::protobuf::EnumOrUnknown::new(super::moderation_packet::Role::MUSICIAN) {
+            os.write_enum(4, ::protobuf::EnumOrUnknown::value(&self.role))?;
+        }
+        for (k, v) in &self.roles {
+            let mut entry_size = 0;
+            entry_size += ::protobuf::rt::string_size(1, &k);
+            entry_size += ::protobuf::rt::int32_size(2, v.value());
+            os.write_raw_varint32(42)?; // Tag.
+            os.write_raw_varint32(entry_size as u32)?;
+            os.write_string(1, &k)?;
+            os.write_enum(2, ::protobuf::EnumOrUnknown::value(v))?;
+        };
+        os.write_unknown_fields(self.special_fields.unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn special_fields(&self) -> &::protobuf::SpecialFields {
+        &self.special_fields
+    }
+
+    fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+        &mut self.special_fields
+    }
+
+    fn new() -> RosterPacket {
+        RosterPacket::new()
+    }
+
+    fn clear(&mut self) {
+        self.event_type = ::protobuf::EnumOrUnknown::new(roster_packet::EventType::JOINED);
+        self.username.clear();
+        self.members.clear();
+        self.role = ::protobuf::EnumOrUnknown::new(super::moderation_packet::Role::MUSICIAN);
+        self.roles.clear();
+        self.special_fields.clear();
+    }
+
+    fn default_instance() -> &'static RosterPacket {
+        static instance: ::protobuf::rt::Lazy<RosterPacket> = ::protobuf::rt::Lazy::new();
+        instance.get(RosterPacket::new)
+    }
+}
+
+impl ::protobuf::MessageFull for RosterPacket {
+    fn descriptor() -> ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::Lazy::new();
+        descriptor.get(|| file_descriptor().message_by_package_relative_name("RosterPacket").unwrap()).clone()
+    }
+}
+
+impl ::std::fmt::Display for RosterPacket {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RosterPacket {
+    type RuntimeType = ::protobuf::reflect::rt::RuntimeTypeMessage<Self>;
+}
+
+/// Nested message and enums of message `RosterPacket`
+pub mod roster_packet {
+    #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
+    // @@protoc_insertion_point(enum:RosterPacket.EventType)
+    pub enum EventType {
+        // @@protoc_insertion_point(enum_value:RosterPacket.EventType.JOINED)
+        JOINED = 0,
+        // @@protoc_insertion_point(enum_value:RosterPacket.EventType.LEFT)
+        LEFT = 1,
+        // @@protoc_insertion_point(enum_value:RosterPacket.EventType.SNAPSHOT)
+        SNAPSHOT = 2,
+        // @@protoc_insertion_point(enum_value:RosterPacket.EventType.PRESENT)
+        PRESENT = 3,
+    }
+
+    impl ::protobuf::Enum for EventType {
+        const NAME: &'static str = "EventType";
+
+        fn value(&self) -> i32 {
+            *self as i32
+        }
+
+        fn from_i32(value: i32) -> ::std::option::Option<EventType> {
+            match value {
+                0 => ::std::option::Option::Some(EventType::JOINED),
+                1 => ::std::option::Option::Some(EventType::LEFT),
+                2 => ::std::option::Option::Some(EventType::SNAPSHOT),
+                3 => ::std::option::Option::Some(EventType::PRESENT),
+                _ => ::std::option::Option::None
+            }
+        }
+
+        fn from_str(str: &str) -> ::std::option::Option<EventType> {
+            match str {
+                "JOINED" => ::std::option::Option::Some(EventType::JOINED),
+                "LEFT" => ::std::option::Option::Some(EventType::LEFT),
+                "SNAPSHOT" => ::std::option::Option::Some(EventType::SNAPSHOT),
+                "PRESENT" => ::std::option::Option::Some(EventType::PRESENT),
+                _ => ::std::option::Option::None
+            }
+        }
+
+        const VALUES: &'static [EventType] = &[
+            EventType::JOINED,
+            EventType::LEFT,
+            EventType::SNAPSHOT,
+            EventType::PRESENT,
+        ];
+    }
+
+    impl ::protobuf::EnumFull for EventType {
+        fn enum_descriptor() -> ::protobuf::reflect::EnumDescriptor {
+            static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::Lazy::new();
+            descriptor.get(|| super::file_descriptor().enum_by_package_relative_name("RosterPacket.EventType").unwrap()).clone()
+        }
+
+        fn descriptor(&self) -> ::protobuf::reflect::EnumValueDescriptor {
+            let index = *self as usize;
+            Self::enum_descriptor().value_by_index(index)
+        }
+    }
+
+    impl ::std::default::Default for EventType {
+        fn default() -> Self {
+            EventType::JOINED
+        }
+    }
+
+    impl EventType {
+        pub(in super) fn generated_enum_descriptor_data() -> ::protobuf::reflect::GeneratedEnumDescriptorData {
+            ::protobuf::reflect::GeneratedEnumDescriptorData::new::<EventType>("RosterPacket.EventType")
+        }
+    }
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n\x19types/roster_packet.proto\x1a\x1dtypes/moderation_packet.proto\"\
+    \xc6\x02\n\x0cRosterPacket\x126\n\nevent_type\x18\x01\x20\x01(\x0e2\x17.\
+    RosterPacket.EventTypeR\teventType\x12\x1a\n\x08username\x18\x02\x20\x01\
+    (\tR\x08username\x12\x18\n\x07members\x18\x03\x20\x03(\tR\x07members\x12\
+    \x19\n\x04role\x18\x04\x20\x01(\x0e2\x05.RoleR\x04role\x12.\n\x05roles\
+    \x18\x05\x20\x03(\x0b2\x18.RosterPacket.RolesEntryR\x05roles\x1a?\n\nRol\
+    esEntry\x12\x10\n\x03key\x18\x01\x20\x01(\tR\x03key\x12\x1b\n\x05value\
+    \x18\x02\x20\x01(\x0e2\x05.RoleR\x05value:\x028\x01\"<\n\tEventType\x12\
+    \n\n\x06JOINED\x10\0\x12\x08\n\x04LEFT\x10\x01\x12\x0c\n\x08SNAPSHOT\x10\
+    \x02\x12\x0b\n\x07PRESENT\x10\x03b\x06proto3\
+";
+
+/// `FileDescriptorProto` object which was a source for this generated file
+fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
+    static file_descriptor_proto_lazy: ::protobuf::rt::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::Lazy::new();
+    file_descriptor_proto_lazy.get(|| {
+        ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
+    })
+}
+
+/// `FileDescriptor` object which allows dynamic access to files
+pub fn file_descriptor() -> &'static ::protobuf::reflect::FileDescriptor {
+    static generated_file_descriptor_lazy: ::protobuf::rt::Lazy<::protobuf::reflect::GeneratedFileDescriptor> = ::protobuf::rt::Lazy::new();
+    static file_descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::FileDescriptor> = ::protobuf::rt::Lazy::new();
+    file_descriptor.get(|| {
+        let generated_file_descriptor = generated_file_descriptor_lazy.get(|| {
+            let mut deps = ::std::vec::Vec::with_capacity(1);
+            deps.push(super::moderation_packet::file_descriptor().clone());
+            let mut messages = ::std::vec::Vec::with_capacity(1);
+            messages.push(RosterPacket::generated_message_descriptor_data());
+            let mut enums = ::std::vec::Vec::with_capacity(1);
+            enums.push(roster_packet::EventType::generated_enum_descriptor_data());
+            ::protobuf::reflect::GeneratedFileDescriptor::new_generated(
+                file_descriptor_proto(),
+                deps,
+                messages,
+                enums,
+            )
+        });
+        ::protobuf::reflect::FileDescriptor::new_generated_2(generated_file_descriptor)
+    })
+}
@@ -0,0 +1,276 @@
+// This file is generated by rust-protobuf 3.4.0. Do not edit
+// .proto file is parsed by protoc --rust-out=...
+// @generated
+
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unused_results)]
+#![allow(unused_mut)]
+
+//! Generated file from `types/transport_packet.proto`
+
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_3_4_0;
+
+// @@protoc_insertion_point(message:TransportPacket)
+#[derive(PartialEq,Clone,Default,Debug)]
+pub struct TransportPacket {
+    // message fields
+    // @@protoc_insertion_point(field:TransportPacket.tempo)
+    pub tempo: f64,
+    // @@protoc_insertion_point(field:TransportPacket.beats_per_bar)
+    pub beats_per_bar: u32,
+    // @@protoc_insertion_point(field:TransportPacket.beat_unit)
+    pub beat_unit: u32,
+    // @@protoc_insertion_point(field:TransportPacket.running)
+    pub running: bool,
+    // @@protoc_insertion_point(field:TransportPacket.count_in)
+    pub count_in: u32,
+    // @@protoc_insertion_point(field:TransportPacket.started_at)
+    pub started_at: f64,
+    // special fields
+    // @@protoc_insertion_point(special_field:TransportPacket.special_fields)
+    pub special_fields: ::protobuf::SpecialFields,
+}
+
+impl<'a> ::std::default::Default for &'a TransportPacket {
+    fn default() -> &'a TransportPacket {
+        <TransportPacket as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl TransportPacket {
+    pub fn new() -> TransportPacket {
+        ::std::default::Default::default()
+    }
+
+    fn generated_message_descriptor_data() -> ::protobuf::reflect::GeneratedMessageDescriptorData {
+        let mut fields = ::std::vec::Vec::with_capacity(6);
+        let mut oneofs = ::std::vec::Vec::with_capacity(0);
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "tempo",
+            |m: &TransportPacket| { &m.tempo },
+            |m: &mut TransportPacket| { &mut m.tempo },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "beats_per_bar",
+            |m: &TransportPacket| { &m.beats_per_bar },
+            |m: &mut TransportPacket| { &mut m.beats_per_bar },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "beat_unit",
+            |m: &TransportPacket| { &m.beat_unit },
+            |m: &mut TransportPacket| { &mut m.beat_unit },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "running",
+            |m: &TransportPacket| { &m.running },
+            |m: &mut TransportPacket| { &mut m.running },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "count_in",
+            |m: &TransportPacket| { &m.count_in },
+            |m: &mut TransportPacket| { &mut m.count_in },
+        ));
+        fields.push(::protobuf::reflect::rt::v2::make_simpler_field_accessor::<_, _>(
+            "started_at",
+            |m: &TransportPacket| { &m.started_at },
+            |m: &mut TransportPacket| { &mut m.started_at },
+        ));
+        ::protobuf::reflect::GeneratedMessageDescriptorData::new_2::<TransportPacket>(
+            "TransportPacket",
+            fields,
+            oneofs,
+        )
+    }
+}
+
+impl ::protobuf::Message for TransportPacket {
+    const NAME: &'static str = "TransportPacket";
+
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+        while let Some(tag) = is.read_raw_tag_or_eof()? {
+            match tag {
+                9 => {
+                    self.tempo = is.read_double()?;
+                },
+                16 => {
+                    self.beats_per_bar = is.read_uint32()?;
+                },
+                24 => {
+                    self.beat_unit = is.read_uint32()?;
+                },
+                32 => {
+                    self.running = is.read_bool()?;
+                },
+                40 => {
+                    self.count_in = is.read_uint32()?;
+                },
+                49 => {
+                    self.started_at = is.read_double()?;
+                },
+                tag => {
+                    ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u64 {
+        let mut my_size = 0;
+        if self.tempo != 0. {
+            my_size += 1 + 8;
+        }
+        if self.beats_per_bar != 0 {
+            my_size += ::protobuf::rt::uint32_size(2, self.beats_per_bar);
+        }
+        if self.beat_unit != 0 {
+            my_size += ::protobuf::rt::uint32_size(3, self.beat_unit);
+        }
+        if self.running != false {
+            my_size += 1 + 1;
+        }
+        if self.count_in != 0 {
+            my_size += ::protobuf::rt::uint32_size(5, self.count_in);
+        }
+        if self.started_at != 0. {
+            my_size += 1 + 8;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+        self.special_fields.cached_size().set(my_size as u32);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+        if self.tempo != 0. {
+            os.write_double(1, self.tempo)?;
+        }
+        if self.beats_per_bar != 0 {
+            os.write_uint32(2, self.beats_per_bar)?;
+        }
+        if self.beat_unit != 0 {
+            os.write_uint32(3, self.beat_unit)?;
+        }
+        if self.running != false {
+            os.write_bool(4, self.running)?;
+        }
+        if self.count_in != 0 {
+            os.write_uint32(5, self.count_in)?;
+        }
+        if self.started_at != 0. {
+            os.write_double(6, self.started_at)?;
+        }
+        os.write_unknown_fields(self.special_fields.unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn special_fields(&self) -> &::protobuf::SpecialFields {
+        &self.special_fields
+    }
+
+    fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+        &mut self.special_fields
+    }
+
+    fn new() -> TransportPacket {
+        TransportPacket::new()
+    }
+
+    fn clear(&mut self) {
+        self.tempo = 0.;
+        self.beats_per_bar = 0;
+        self.beat_unit = 0;
+        self.running = false;
+        self.count_in = 0;
+        self.started_at = 0.;
+        self.special_fields.clear();
+    }
+
+    fn default_instance() -> &'static TransportPacket {
+        static instance: TransportPacket = TransportPacket {
+            tempo: 0.,
+            beats_per_bar: 0,
+            beat_unit: 0,
+            running: false,
+            count_in: 0,
+            started_at: 0.,
+            special_fields: ::protobuf::SpecialFields::new(),
+        };
+        &instance
+    }
+}
+
+impl ::protobuf::MessageFull for TransportPacket {
+    fn descriptor() -> ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::Lazy::new();
+        descriptor.get(|| file_descriptor().message_by_package_relative_name("TransportPacket").unwrap()).clone()
+    }
+}
+
+impl ::std::fmt::Display for TransportPacket {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for TransportPacket {
+    type RuntimeType = ::protobuf::reflect::rt::RuntimeTypeMessage<Self>;
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n\x1ctypes/transport_packet.proto\"\xbc\x01\n\x0fTransportPacket\x12\
+    \x14\n\x05tempo\x18\x01\x20\x01(\x01R\x05tempo\x12\"\n\rbeats_per_bar\
+    \x18\x02\x20\x01(\rR\x0bbeatsPerBar\x12\x1b\n\tbeat_unit\x18\x03\x20\x01\
+    (\rR\x08beatUnit\x12\x18\n\x07running\x18\x04\x20\x01(\x08R\x07running\
+    \x12\x19\n\x08count_in\x18\x05\x20\x01(\rR\x07countIn\x12\x1d\n\nstarted\
+    _at\x18\x06\x20\x01(\x01R\tstartedAtb\x06proto3\
+";
+
+/// `FileDescriptorProto` object which was a source for this generated file
+fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
+    static file_descriptor_proto_lazy: ::protobuf::rt::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::Lazy::new();
+    file_descriptor_proto_lazy.get(|| {
+        ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
+    })
+}
+
+/// `FileDescriptor` object which allows dynamic access to files
+pub fn file_descriptor() -> &'static ::protobuf::reflect::FileDescriptor {
+    static generated_file_descriptor_lazy: ::protobuf::rt::Lazy<::protobuf::reflect::GeneratedFileDescriptor> = ::protobuf::rt::Lazy::new();
+    static file_descriptor: ::protobuf::rt::Lazy<::protobuf::reflect::FileDescriptor> = ::protobuf::rt::Lazy::new();
+    file_descriptor.get(|| {
+        let generated_file_descriptor = generated_file_descriptor_lazy.get(|| {
+            let mut deps = ::std::vec::Vec::with_capacity(0);
+            let mut messages = ::std::vec::Vec::with_capacity(1);
+            messages.push(TransportPacket::generated_message_descriptor_data());
+            let mut enums = ::std::vec::Vec::with_capacity(0);
+            ::protobuf::reflect::GeneratedFileDescriptor::new_generated(
+                file_descriptor_proto(),
+                deps,
+                messages,
+                enums,
+            )
+        });
+        ::protobuf::reflect::FileDescriptor::new_generated_2(generated_file_descriptor)
+    })
+}
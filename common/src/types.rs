@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 
 #[derive(Serialize, Deserialize, Clone, Validate)]
@@ -12,6 +13,9 @@ pub struct LoginRequest {
     ))]
     pub username: String,
     pub password: String,
+    /// Keeps the session after the browser is closed, for longer without being used
+    #[serde(default)]
+    pub remember_me: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Validate)]
@@ -38,9 +42,22 @@ pub struct RegisterRequest {
         max = 50,
         message = "Username length must be between 3 and 50 characters"
     ))]
+    #[validate(custom = "validate_username")]
     pub username: String,
 }
 
+/// A username is also the name of its peer on the relay, where only these characters are allowed
+fn validate_username(username: &str) -> Result<(), ValidationError> {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+    if !username.chars().all(allowed) {
+        let mut error = ValidationError::new("username");
+        error.message =
+            Some("A username can only contain letters, digits, underscores and dashes".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -66,11 +83,482 @@ pub struct StartResetRequest{
 
 #[derive(Serialize, Deserialize, Clone, Validate)]
 pub struct CreateRoomRequest {
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Room name length must be between 3 and 50 characters"
+    ))]
     pub name: String,
+    #[validate(length(max = 500, message = "Description must be at most 500 characters"))]
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Private rooms are not listed to the other users
+    #[serde(default)]
+    pub private: bool,
+    /// Peers can join only while the room is open
+    #[serde(default = "default_open")]
+    pub open: bool,
+    #[validate(range(
+        min = 1,
+        max = 20,
+        message = "Between 1 and 20 people can play in a room"
+    ))]
+    #[serde(default = "default_max_people_playing")]
+    pub max_people_playing: u32,
+    /// The clients encrypt media end-to-end, rooms created this way cannot be recorded
+    #[serde(default)]
+    pub encrypted: bool,
 }
-#[derive(Serialize, Deserialize, Clone)]
+
+impl Default for CreateRoomRequest {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            description: None,
+            private: false,
+            open: default_open(),
+            max_people_playing: default_max_people_playing(),
+            encrypted: false,
+        }
+    }
+}
+
+fn default_open() -> bool {
+    true
+}
+
+fn default_max_people_playing() -> u32 {
+    5
+}
+
+/// Changes the settings of a room, the fields left out keep their value. An empty description
+/// removes it. Whether media is encrypted is chosen once, when the room is created.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Validate)]
+pub struct UpdateRoomRequest {
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Room name length must be between 3 and 50 characters"
+    ))]
+    pub name: Option<String>,
+    #[validate(length(max = 500, message = "Description must be at most 500 characters"))]
+    pub description: Option<String>,
+    pub private: Option<bool>,
+    pub open: Option<bool>,
+    #[validate(range(
+        min = 1,
+        max = 20,
+        message = "Between 1 and 20 people can play in a room"
+    ))]
+    pub max_people_playing: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RoomResponse {
     pub id: Uuid,
     pub name: String,
+    pub description: Option<String>,
     pub owner: String,
+    pub private: bool,
+    pub open: bool,
+    pub max_people_playing: u32,
+    #[serde(default)]
+    pub encrypted: bool,
+    #[serde(default)]
+    pub transport: TransportResponse,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// A page of the rooms listed, along with how many peers are in each
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RoomListResponse {
+    pub rooms: Vec<RoomListEntry>,
+    /// Passed as `after` to read the next page, `None` on the last page
+    pub next: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RoomListEntry {
+    #[serde(flatten)]
+    pub room: RoomResponse,
+    /// Peers in the room right now
+    pub participants: u32,
+}
+
+/// The metronome of a room
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TransportResponse {
+    /// Beats per minute, a beat is a 1/`beat_unit` note
+    pub tempo: f64,
+    pub beats_per_bar: u32,
+    pub beat_unit: u32,
+    /// Bars counted in whenever the transport starts
+    pub count_in: u32,
+    pub running: bool,
+    /// First downbeat after the count-in, on the clock of the server
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub started_at: Option<OffsetDateTime>,
+}
+
+impl Default for TransportResponse {
+    fn default() -> Self {
+        Self {
+            tempo: 120.0,
+            beats_per_bar: 4,
+            beat_unit: 4,
+            count_in: 1,
+            running: false,
+            started_at: None,
+        }
+    }
+}
+
+/// Changes the metronome of a room, the fields left out keep their value. Starting the transport,
+/// or changing the tempo or the time signature while it runs, counts in again.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Validate)]
+pub struct UpdateTransportRequest {
+    #[validate(range(
+        min = 20.0,
+        max = 400.0,
+        message = "Tempo must be between 20 and 400 bpm"
+    ))]
+    pub tempo: Option<f64>,
+    #[validate(range(min = 1, max = 16, message = "A bar has between 1 and 16 beats"))]
+    pub beats_per_bar: Option<u32>,
+    #[validate(custom = "validate_beat_unit")]
+    pub beat_unit: Option<u32>,
+    #[validate(range(max = 4, message = "At most 4 bars can be counted in"))]
+    pub count_in: Option<u32>,
+    pub running: Option<bool>,
+}
+
+fn validate_beat_unit(beat_unit: u32) -> Result<(), ValidationError> {
+    if ![1, 2, 4, 8, 16].contains(&beat_unit) {
+        let mut error = ValidationError::new("beat_unit");
+        error.message =
+            Some("The beat unit must be a whole, half, quarter, 8th or 16th note".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Lets a user into a private room: a user is found by username or email and becomes a member
+/// right away, an email that belongs to no user gets an invite link
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Validate)]
+#[validate(schema(function = "validate_invitation"))]
+pub struct InviteMemberRequest {
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Username length must be between 3 and 50 characters"
+    ))]
+    pub username: Option<String>,
+    #[validate(email(message = "Invalid email"))]
+    pub email: Option<String>,
+}
+
+fn validate_invitation(request: &InviteMemberRequest) -> Result<(), ValidationError> {
+    if request.username.is_none() == request.email.is_none() {
+        let mut error = ValidationError::new("invitation");
+        error.message = Some("Invite either a username or an email".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RoomMemberResponse {
+    pub username: String,
+    pub added_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub added_at: OffsetDateTime,
+}
+
+/// Either the member added or, for an email that belongs to no user, the invite sent to it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InvitationResponse {
+    pub member: Option<RoomMemberResponse>,
+    pub invite: Option<InviteResponse>,
+}
+
+/// Creates a link that lets whoever opens it into a private room
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Validate)]
+pub struct CreateInviteRequest {
+    /// Unlimited when missing
+    #[validate(range(min = 1, max = 1000, message = "A link can be used 1 to 1000 times"))]
+    pub max_uses: Option<u32>,
+    /// A week when missing
+    #[validate(range(min = 1, max = 720, message = "A link lasts 1 to 720 hours"))]
+    pub expires_in_hours: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InviteResponse {
+    pub id: Uuid,
+    /// Accepted with `POST /api/invites/:token`
+    pub token: String,
+    pub created_by: String,
+    pub email: Option<String>,
+    pub max_uses: Option<u32>,
+    pub uses: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JoinTicketResponse {
+    pub ticket: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecordingResponse {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub started_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub stopped_at: Option<OffsetDateTime>,
+    pub tracks: Vec<RecordingTrackResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecordingTrackResponse {
+    pub id: Uuid,
+    pub username: String,
+    /// `audio` (Ogg/Opus) or `video` (WebM/VP9)
+    pub kind: String,
+    /// Time of the first packet of the track since the start of the recording
+    pub offset_ms: i64,
+    pub size: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChatMessageResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub text: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub sent_at: OffsetDateTime,
+}
+
+/// Plans a session in a room, a recurring one repeats with the same length until `repeat_until`
+/// or forever
+#[derive(Serialize, Deserialize, Clone, Debug, Validate)]
+#[validate(schema(function = "validate_schedule"))]
+pub struct CreateScheduleRequest {
+    #[validate(length(
+        min = 3,
+        max = 100,
+        message = "Title length must be between 3 and 100 characters"
+    ))]
+    pub title: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub starts_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub ends_at: OffsetDateTime,
+    /// `daily` or `weekly`, missing for a session that does not repeat
+    #[validate(custom = "validate_recurrence")]
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    /// Last time an occurrence can start
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub repeat_until: Option<OffsetDateTime>,
+    /// The room is open only while one of its sessions with the flag is in progress
+    #[serde(default)]
+    pub opens_room: bool,
+}
+
+fn validate_recurrence(recurrence: &str) -> Result<(), ValidationError> {
+    if !["daily", "weekly"].contains(&recurrence) {
+        let mut error = ValidationError::new("recurrence");
+        error.message = Some("A session repeats daily or weekly".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+fn validate_schedule(request: &CreateScheduleRequest) -> Result<(), ValidationError> {
+    let length = request.ends_at - request.starts_at;
+    if length <= time::Duration::ZERO || length > time::Duration::days(1) {
+        let mut error = ValidationError::new("schedule");
+        error.message = Some("A session ends after it starts and lasts at most a day".into());
+        return Err(error);
+    }
+    match request.repeat_until {
+        Some(_) if request.recurrence.is_none() => {
+            let mut error = ValidationError::new("schedule");
+            error.message = Some("Only a recurring session repeats until a time".into());
+            Err(error)
+        }
+        Some(until) if until < request.starts_at => {
+            let mut error = ValidationError::new("schedule");
+            error.message = Some("A session repeats until a time after its start".into());
+            Err(error)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScheduleResponse {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub title: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub starts_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub ends_at: OffsetDateTime,
+    pub recurrence: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub repeat_until: Option<OffsetDateTime>,
+    pub opens_room: bool,
+    /// Start of the first occurrence that has not ended, missing once the schedule is over
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub next_starts_at: Option<OffsetDateTime>,
+    pub created_by: String,
+    pub rsvps: Vec<RsvpResponse>,
+}
+
+/// Answers whether the user will attend a planned session, all of its occurrences alike
+#[derive(Serialize, Deserialize, Clone, Debug, Validate)]
+pub struct RsvpRequest {
+    /// `yes`, `no` or `maybe`
+    #[validate(custom = "validate_rsvp")]
+    pub response: String,
+}
+
+fn validate_rsvp(response: &str) -> Result<(), ValidationError> {
+    if !["yes", "no", "maybe"].contains(&response) {
+        let mut error = ValidationError::new("response");
+        error.message = Some("Answer yes, no or maybe".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RsvpResponse {
+    pub username: String,
+    pub response: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// Link of the calendar feed of a user, calendar apps subscribe to it without signing in
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CalendarFeedResponse {
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Validate)]
+pub struct UpdateProfileRequest {
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "First name length must be between 3 and 50 characters"
+    ))]
+    pub first_name: String,
+
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Last name length must be between 3 and 50 characters"
+    ))]
+    pub last_name: String,
+}
+
+/// Changes the password of the user signed in, who proves it knows the current one
+#[derive(Serialize, Deserialize, Clone, Validate)]
+pub struct UpdatePasswordRequest {
+    pub current_password: String,
+    #[validate(length(min = 6, message = "Password must be at least 6 characters"))]
+    pub password: String,
+    pub confirm_password: String,
+}
+
+/// Deletes the account of the user signed in, the password confirms it
+#[derive(Serialize, Deserialize, Clone, Validate)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+}
+
+/// Everything stored about a user
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AccountExportResponse {
+    pub profile: ProfileExport,
+    /// The rooms the user owns
+    pub rooms: Vec<RoomResponse>,
+    pub memberships: Vec<MembershipExport>,
+    pub sessions: Vec<SessionResponse>,
+    pub messages: Vec<MessageExport>,
+    pub rsvps: Vec<RsvpExport>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileExport {
+    pub id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// A room the user was let into or given a role in
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MembershipExport {
+    pub room_id: Uuid,
+    pub room_name: String,
+    /// Missing in the public rooms, which have no members
+    pub added_by: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub added_at: Option<OffsetDateTime>,
+    /// `listener`, `musician` or `moderator`
+    pub role: String,
+}
+
+/// A session of the user, the token in its cookie is never shown
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    /// Kept after the browser is closed
+    pub remember: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_seen_at: OffsetDateTime,
+    /// Pushed back whenever the session is used
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    /// The session the request was made with
+    pub current: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MessageExport {
+    pub room_id: Uuid,
+    pub text: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub sent_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RsvpExport {
+    pub schedule_id: Uuid,
+    pub response: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
 }
@@ -7,6 +7,15 @@ impl std::fmt::Display for PacketType {
             PacketType::AES_KEY => f.write_str("AES_KEY"), 
             PacketType::MEDIA => f.write_str("MEDIA"),
             PacketType::CONNECTION => f.write_str("CONNECTION"),
+            PacketType::ROSTER => f.write_str("ROSTER"),
+            PacketType::REJECTION => f.write_str("REJECTION"),
+            PacketType::GOING_AWAY => f.write_str("GOING_AWAY"),
+            PacketType::NEGOTIATION => f.write_str("NEGOTIATION"),
+            PacketType::CHAT => f.write_str("CHAT"),
+            PacketType::PING => f.write_str("PING"),
+            PacketType::PONG => f.write_str("PONG"),
+            PacketType::TRANSPORT => f.write_str("TRANSPORT"),
+            PacketType::MODERATION => f.write_str("MODERATION"),
         }
     }
 }
@@ -17,6 +17,10 @@ pub struct Props {
     #[prop_or(true)]
     pub required: bool,
 
+    /// Initial value, when the form edits something that exists
+    #[prop_or_default]
+    pub value: Option<AttrValue>,
+
     pub handle_onchange: Callback<String>,
     pub handle_on_input_blur: Callback<(String, String)>,
     pub errors: Rc<RefCell<ValidationErrors>>,
@@ -29,6 +33,7 @@ pub fn text_input(props: &Props) -> Html {
         name,
         label,
         required,
+        value,
         handle_on_input_blur,
         handle_onchange,
         errors,
@@ -68,6 +73,7 @@ pub fn text_input(props: &Props) -> Html {
             <PasswordInput name={name} handle_onchange={handle_onchange.clone()} handle_on_input_blur={handle_on_input_blur.clone()} errors={errors.clone()}/>
         }else {
             <input type={t} required={*required} {name} id={name} class={text_input_classes()} placeholder={label}
+                value={value.clone()}
                 onchange={onchange}
                 onblur={onblur}
 
@@ -0,0 +1,126 @@
+use common::types::SessionResponse;
+use gloo_net::http::Request;
+use time::OffsetDateTime;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::console::log_1;
+use yew::prelude::*;
+use yew_router::hooks::use_navigator;
+
+use crate::components::{
+    atoms::{spinner::Spinner, text_error::TextError},
+    router::Route,
+};
+
+fn format_local(at: OffsetDateTime) -> String {
+    js_sys::Date::new(&JsValue::from_f64(at.unix_timestamp() as f64 * 1000.0))
+        .to_locale_string("default", &JsValue::UNDEFINED)
+        .into()
+}
+
+/// Where the user is signed in: each session but the current one can be signed out, or all of
+/// them at once
+#[function_component(AccountSessions)]
+pub fn account_sessions() -> Html {
+    let navigator = use_navigator().unwrap();
+    let sessions = use_state(Vec::<SessionResponse>::new);
+    let is_loading = use_state(|| true);
+    let error = use_state(|| None::<AttrValue>);
+    // bumped to list the sessions again
+    let version = use_state(|| 0u32);
+
+    {
+        let sessions = sessions.clone();
+        let is_loading = is_loading.clone();
+        use_effect_with(*version, move |_| {
+            spawn_local(async move {
+                match Request::get("/api/auth/sessions").send().await {
+                    Ok(res) if res.ok() => match res.json::<Vec<SessionResponse>>().await {
+                        Ok(listed) => sessions.set(listed),
+                        Err(e) => log_1(&e.to_string().into()),
+                    },
+                    Ok(res) => log_1(&format!("Cannot list sessions: {}", res.status()).into()),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+                is_loading.set(false);
+            });
+        });
+    }
+
+    let sign_out = {
+        let error = error.clone();
+        let version = version.clone();
+        Callback::from(move |id: String| {
+            let error = error.clone();
+            let version = version.clone();
+            spawn_local(async move {
+                match Request::delete(&format!("/api/auth/sessions/{}", id))
+                    .send()
+                    .await
+                {
+                    Ok(res) if res.ok() => version.set(*version + 1),
+                    Ok(_) => error.set(Some("Cannot sign out the session".into())),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let sign_out_everywhere = {
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let error = error.clone();
+            let navigator = navigator.clone();
+            spawn_local(async move {
+                match Request::post("/api/auth/sign-out-everywhere").send().await {
+                    Ok(res) if res.ok() => navigator.replace(&Route::Login),
+                    Ok(_) => error.set(Some("Cannot sign out".into())),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let button_classes = "text-sm text-primary-600 hover:underline dark:text-primary-500";
+    html! {
+        <div class="space-y-2">
+            <h2 class="text-lg font-bold text-gray-900 dark:text-white">{"Sessions"}</h2>
+            if *is_loading {
+                <Spinner />
+            }
+            <ul class="divide-y divide-gray-200 dark:divide-gray-700">
+                { for sessions.iter().map(|session| {
+                    let on_sign_out = {
+                        let sign_out = sign_out.clone();
+                        let id = session.id.to_string();
+                        Callback::from(move |_: MouseEvent| sign_out.emit(id.clone()))
+                    };
+                    html! {
+                        <li key={session.id.to_string()} class="py-2 px-1 text-sm text-gray-900 dark:text-white">
+                            <div class="flex justify-between">
+                                <span class="truncate">{ session.user_agent.clone().unwrap_or_else(|| "Unknown device".into()) }</span>
+                                if session.current {
+                                    <span class="ms-2 text-gray-500 dark:text-gray-400">{"This device"}</span>
+                                } else {
+                                    <button onclick={on_sign_out} type="button" class={classes!("ms-2", button_classes)}>{"Sign out"}</button>
+                                }
+                            </div>
+                            <div class="text-gray-500 dark:text-gray-400">
+                                { format!(
+                                    "{} · last seen {}{}",
+                                    session.ip.as_deref().unwrap_or("unknown address"),
+                                    format_local(session.last_seen_at),
+                                    if session.remember { " · remembered" } else { "" },
+                                ) }
+                            </div>
+                        </li>
+                    }
+                }) }
+            </ul>
+            if let Some(error) = &*error {
+                <TextError error={error.clone()}/>
+            }
+            <button onclick={sign_out_everywhere} type="button" class={button_classes}>{"Sign out everywhere"}</button>
+        </div>
+    }
+}
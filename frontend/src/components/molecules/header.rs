@@ -17,6 +17,13 @@ pub fn header() -> Html {
             show_logout.set(!(*show_logout));
         })
     };
+    let account = {
+        let navigator = navigator.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            navigator.push(&Route::Account);
+        })
+    };
     let logout = {
         Callback::from(move |e: MouseEvent|  {
             e.prevent_default();
@@ -47,6 +54,7 @@ pub fn header() -> Html {
                     if *show_logout {
                         <div id="dropdownNavbar" class="z-10 font-normal bg-white divide-y divide-gray-100 rounded-lg shadow w-44 dark:bg-gray-700 dark:divide-gray-600">
                             <div class="py-">
+                                <a onclick={account} class="block cursor-pointer px-4 py-2 text-sm text-gray-700 hover:bg-gray-100 dark:hover:bg-gray-600 dark:text-gray-200 dark:hover:text-white">{"Settings"}</a>
                                 <a onclick={logout} class="block cursor-pointer px-4 py-2 text-sm text-gray-700 hover:bg-gray-100 dark:hover:bg-gray-600 dark:text-gray-200 dark:hover:text-white">{"Sign out"}</a>
                             </div>
                         </div>
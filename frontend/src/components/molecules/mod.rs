@@ -1,2 +1,6 @@
+pub mod account_sessions;
 pub mod host;
 pub mod header;
+pub mod room_list;
+pub mod room_members;
+pub mod room_schedules;
@@ -0,0 +1,161 @@
+use common::types::{RoomListEntry, RoomListResponse};
+use gloo_net::http::Request;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{console::log_1, HtmlInputElement};
+use yew::prelude::*;
+use yew_router::hooks::use_navigator;
+
+use crate::components::{
+    atoms::{class::text_input_classes, spinner::Spinner},
+    router::Route,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct RoomListProps {
+    pub title: AttrValue,
+    /// Lists the rooms of the user, otherwise the public rooms
+    #[prop_or_default]
+    pub mine: bool,
+    /// Shows a field to search the rooms by name
+    #[prop_or_default]
+    pub searchable: bool,
+}
+
+/// Rooms listed by `GET /api/rooms`, a page at a time. Clicking a room joins it.
+#[function_component(RoomList)]
+pub fn room_list(props: &RoomListProps) -> Html {
+    let navigator = use_navigator().unwrap();
+    let rooms = use_state(Vec::<RoomListEntry>::new);
+    let next = use_state(|| None::<String>);
+    let is_loading = use_state(|| false);
+    let search = use_state(String::new);
+
+    let fetch = {
+        let rooms = rooms.clone();
+        let next = next.clone();
+        let is_loading = is_loading.clone();
+        let filter = if props.mine { "mine=true" } else { "public=true" };
+        Callback::from(move |(q, after): (String, Option<String>)| {
+            let rooms = rooms.clone();
+            let next = next.clone();
+            let is_loading = is_loading.clone();
+            let mut url = format!("/api/rooms?{}", filter);
+            if !q.is_empty() {
+                url.push_str(&format!("&q={}", js_sys::encode_uri_component(&q)));
+            }
+            if let Some(after) = &after {
+                url.push_str(&format!("&after={}", after));
+            }
+            spawn_local(async move {
+                is_loading.set(true);
+                match Request::get(&url).send().await {
+                    Ok(res) if res.ok() => match res.json::<RoomListResponse>().await {
+                        Ok(page) => {
+                            // a page after the first one is appended to the rooms listed
+                            let mut listed = if after.is_some() {
+                                (*rooms).clone()
+                            } else {
+                                Vec::new()
+                            };
+                            listed.extend(page.rooms);
+                            rooms.set(listed);
+                            next.set(page.next.map(|id| id.to_string()));
+                        }
+                        Err(e) => log_1(&e.to_string().into()),
+                    },
+                    Ok(res) => log_1(&format!("Cannot list rooms: {}", res.status()).into()),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    {
+        let fetch = fetch.clone();
+        use_effect_with((*search).clone(), move |q| {
+            fetch.emit((q.clone(), None));
+        });
+    }
+
+    let on_search = {
+        let search = search.clone();
+        Callback::from(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlInputElement>().value();
+            search.set(value.trim().to_owned());
+        })
+    };
+
+    let load_more = {
+        let search = search.clone();
+        let next = next.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            if let Some(after) = (*next).clone() {
+                fetch.emit(((*search).clone(), Some(after)));
+            }
+        })
+    };
+
+    html! {
+        <div class="space-y-2">
+            <h2 class="text-lg font-bold text-gray-900 dark:text-white">{props.title.clone()}</h2>
+            if props.searchable {
+                <input type="search" class={text_input_classes()} placeholder="Search by name" onchange={on_search} />
+            }
+            <ul class="divide-y divide-gray-200 dark:divide-gray-700">
+                { for rooms.iter().map(|entry| {
+                    let id = entry.room.id.to_string();
+                    let onclick = {
+                        let navigator = navigator.clone();
+                        let id = id.clone();
+                        Callback::from(move |e: MouseEvent| {
+                            e.prevent_default();
+                            navigator.push(&Route::Session { id: id.clone() });
+                        })
+                    };
+                    let on_schedule = {
+                        let navigator = navigator.clone();
+                        let id = id.clone();
+                        Callback::from(move |e: MouseEvent| {
+                            e.prevent_default();
+                            e.stop_propagation();
+                            navigator.push(&Route::RoomSchedule { id: id.clone() });
+                        })
+                    };
+                    let on_settings = {
+                        let navigator = navigator.clone();
+                        Callback::from(move |e: MouseEvent| {
+                            e.prevent_default();
+                            e.stop_propagation();
+                            navigator.push(&Route::RoomSettings { id: id.clone() });
+                        })
+                    };
+                    html! {
+                        <li key={entry.room.id.to_string()}>
+                            <a {onclick} class="flex justify-between cursor-pointer py-2 px-1 text-sm text-gray-900 hover:bg-gray-100 dark:text-white dark:hover:bg-gray-700">
+                                <span>{&entry.room.name}</span>
+                                <span class="text-gray-500 dark:text-gray-400">
+                                    { if props.mine { String::new() } else { format!("{} · ", entry.room.owner) } }
+                                    { format!("{} playing", entry.participants) }
+                                    <button onclick={on_schedule} type="button" class="ms-2 text-primary-600 hover:underline dark:text-primary-500">{"Schedule"}</button>
+                                    if props.mine {
+                                        <button onclick={on_settings} type="button" class="ms-2 text-primary-600 hover:underline dark:text-primary-500">{"Settings"}</button>
+                                    }
+                                </span>
+                            </a>
+                        </li>
+                    }
+                }) }
+            </ul>
+            if rooms.is_empty() && !*is_loading {
+                <p class="text-sm text-gray-500 dark:text-gray-400">{"No rooms"}</p>
+            }
+            if *is_loading {
+                <Spinner />
+            } else if next.is_some() {
+                <button onclick={load_more} type="button" class="text-sm text-primary-600 hover:underline dark:text-primary-500">{"Load more"}</button>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,216 @@
+use common::types::{
+    CreateInviteRequest, InvitationResponse, InviteMemberRequest, InviteResponse,
+    RoomMemberResponse,
+};
+use gloo_net::http::Request;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{console::log_1, HtmlInputElement};
+use yew::prelude::*;
+
+use crate::components::atoms::{
+    class::text_input_classes, spinner::Spinner, text_error::TextError,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct RoomMembersProps {
+    pub id: AttrValue,
+}
+
+fn invite_link(token: &str) -> String {
+    let origin = web_sys::window()
+        .and_then(|window| window.location().origin().ok())
+        .unwrap_or_default();
+    format!("{}/invite/{}", origin, token)
+}
+
+/// Members and invite links of a private room, managed by its owner
+#[function_component(RoomMembers)]
+pub fn room_members(RoomMembersProps { id }: &RoomMembersProps) -> Html {
+    let members = use_state(Vec::<RoomMemberResponse>::new);
+    let invites = use_state(Vec::<InviteResponse>::new);
+    let is_loading = use_state(|| true);
+    let error = use_state(|| None::<AttrValue>);
+    let invitee = use_state(String::new);
+    // bumped to list the members and the invites again
+    let version = use_state(|| 0u32);
+
+    {
+        let members = members.clone();
+        let invites = invites.clone();
+        let is_loading = is_loading.clone();
+        use_effect_with((id.clone(), *version), move |(id, _)| {
+            let id = id.clone();
+            spawn_local(async move {
+                match Request::get(&format!("/api/rooms/{}/members", id))
+                    .send()
+                    .await
+                {
+                    Ok(res) if res.ok() => match res.json::<Vec<RoomMemberResponse>>().await {
+                        Ok(listed) => members.set(listed),
+                        Err(e) => log_1(&e.to_string().into()),
+                    },
+                    Ok(res) => log_1(&format!("Cannot list members: {}", res.status()).into()),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+                match Request::get(&format!("/api/rooms/{}/invites", id))
+                    .send()
+                    .await
+                {
+                    Ok(res) if res.ok() => match res.json::<Vec<InviteResponse>>().await {
+                        Ok(listed) => invites.set(listed),
+                        Err(e) => log_1(&e.to_string().into()),
+                    },
+                    Ok(res) => log_1(&format!("Cannot list invites: {}", res.status()).into()),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+                is_loading.set(false);
+            });
+        });
+    }
+
+    let on_invitee = {
+        let invitee = invitee.clone();
+        Callback::from(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlInputElement>().value();
+            invitee.set(value.trim().to_owned());
+        })
+    };
+
+    let invite = {
+        let id = id.clone();
+        let invitee = invitee.clone();
+        let error = error.clone();
+        let version = version.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            // an address invites whoever owns it, anything else is a username
+            let request = if invitee.contains('@') {
+                InviteMemberRequest {
+                    username: None,
+                    email: Some((*invitee).clone()),
+                }
+            } else {
+                InviteMemberRequest {
+                    username: Some((*invitee).clone()),
+                    email: None,
+                }
+            };
+            let url = format!("/api/rooms/{}/members", id);
+            let error = error.clone();
+            let version = version.clone();
+            spawn_local(async move {
+                let body = serde_json::to_string(&request).unwrap();
+                let request = Request::post(&url)
+                    .header("Content-Type", "application/json")
+                    .body(Some(body));
+                match request.send().await {
+                    Ok(res) if res.ok() => {
+                        if let Err(e) = res.json::<InvitationResponse>().await {
+                            log_1(&e.to_string().into());
+                        }
+                        error.set(None);
+                        version.set(*version + 1);
+                    }
+                    Ok(res) if res.status() == 404 => error.set(Some("No such user".into())),
+                    Ok(_) => error.set(Some("Cannot invite".into())),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let create_link = {
+        let id = id.clone();
+        let error = error.clone();
+        let version = version.clone();
+        Callback::from(move |_: MouseEvent| {
+            let url = format!("/api/rooms/{}/invites", id);
+            let error = error.clone();
+            let version = version.clone();
+            spawn_local(async move {
+                let body = serde_json::to_string(&CreateInviteRequest::default()).unwrap();
+                let request = Request::post(&url)
+                    .header("Content-Type", "application/json")
+                    .body(Some(body));
+                match request.send().await {
+                    Ok(res) if res.ok() => version.set(*version + 1),
+                    Ok(_) => error.set(Some("Cannot create the link".into())),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+            });
+        })
+    };
+
+    // removes a member or revokes an invite
+    let delete = {
+        let error = error.clone();
+        let version = version.clone();
+        Callback::from(move |url: String| {
+            let error = error.clone();
+            let version = version.clone();
+            spawn_local(async move {
+                match Request::delete(&url).send().await {
+                    Ok(res) if res.ok() => version.set(*version + 1),
+                    Ok(_) => error.set(Some("Cannot remove".into())),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let button_classes = "text-sm text-primary-600 hover:underline dark:text-primary-500";
+    html! {
+        <div class="space-y-2">
+            <h2 class="text-lg font-bold text-gray-900 dark:text-white">{"Members"}</h2>
+            <form onsubmit={invite} class="flex space-x-2">
+                <input type="text" required={true} class={text_input_classes()} placeholder="Username or email" onchange={on_invitee} />
+                <button type="submit" class={button_classes}>{"Invite"}</button>
+            </form>
+            if let Some(error) = &*error {
+                <TextError error={error.clone()}/>
+            }
+            if *is_loading {
+                <Spinner />
+            }
+            <ul class="divide-y divide-gray-200 dark:divide-gray-700">
+                { for members.iter().map(|member| {
+                    let onclick = {
+                        let delete = delete.clone();
+                        let url = format!("/api/rooms/{}/members/{}", id, member.username);
+                        Callback::from(move |_: MouseEvent| delete.emit(url.clone()))
+                    };
+                    html! {
+                        <li key={member.username.clone()} class="flex justify-between py-2 px-1 text-sm text-gray-900 dark:text-white">
+                            <span>{&member.username}</span>
+                            <button {onclick} type="button" class={button_classes}>{"Remove"}</button>
+                        </li>
+                    }
+                }) }
+            </ul>
+            <h2 class="text-lg font-bold text-gray-900 dark:text-white">{"Invite links"}</h2>
+            <ul class="divide-y divide-gray-200 dark:divide-gray-700">
+                { for invites.iter().map(|invite| {
+                    let onclick = {
+                        let delete = delete.clone();
+                        let url = format!("/api/rooms/{}/invites/{}", id, invite.id);
+                        Callback::from(move |_: MouseEvent| delete.emit(url.clone()))
+                    };
+                    let uses = match invite.max_uses {
+                        Some(max_uses) => format!("{}/{} used", invite.uses, max_uses),
+                        None => format!("{} used", invite.uses),
+                    };
+                    html! {
+                        <li key={invite.id.to_string()} class="flex justify-between py-2 px-1 text-sm text-gray-900 dark:text-white">
+                            <span class="truncate">{ invite.email.clone().unwrap_or_else(|| invite_link(&invite.token)) }</span>
+                            <span class="text-gray-500 dark:text-gray-400">
+                                {uses}
+                                <button {onclick} type="button" class={classes!("ms-2", button_classes)}>{"Revoke"}</button>
+                            </span>
+                        </li>
+                    }
+                }) }
+            </ul>
+            <button onclick={create_link} type="button" class={button_classes}>{"Create a link"}</button>
+        </div>
+    }
+}
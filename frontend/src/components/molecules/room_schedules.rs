@@ -0,0 +1,292 @@
+use common::types::{CreateScheduleRequest, RsvpRequest, RsvpResponse, ScheduleResponse};
+use gloo_net::http::Request;
+use time::OffsetDateTime;
+use validator::{Validate, ValidationErrors};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{console::log_1, HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::use_store;
+
+use crate::{
+    components::atoms::{
+        class::{label_classes, text_input_classes},
+        spinner::Spinner,
+        text_error::TextError,
+    },
+    store::Store,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct RoomSchedulesProps {
+    pub id: AttrValue,
+    /// Shows the form to plan a session and lets the sessions be cancelled
+    #[prop_or_default]
+    pub can_plan: bool,
+}
+
+/// Reads the value of a `datetime-local` input, a time of the user
+fn parse_local(value: &str) -> Option<OffsetDateTime> {
+    let millis = js_sys::Date::new(&JsValue::from_str(value)).get_time();
+    if millis.is_nan() {
+        return None;
+    }
+    OffsetDateTime::from_unix_timestamp((millis / 1000.0) as i64).ok()
+}
+
+fn format_local(at: OffsetDateTime) -> String {
+    js_sys::Date::new(&JsValue::from_f64(at.unix_timestamp() as f64 * 1000.0))
+        .to_locale_string("default", &JsValue::UNDEFINED)
+        .into()
+}
+
+fn first_message(errors: &ValidationErrors) -> AttrValue {
+    errors
+        .field_errors()
+        .values()
+        .flat_map(|errors| errors.iter())
+        .find_map(|error| error.message.clone())
+        .map_or("Cannot plan the session".into(), |message| {
+            AttrValue::from(message.into_owned())
+        })
+}
+
+/// Sessions planned in a room: anyone who can join it answers whether it will attend, and the
+/// owner plans and cancels them
+#[function_component(RoomSchedules)]
+pub fn room_schedules(RoomSchedulesProps { id, can_plan }: &RoomSchedulesProps) -> Html {
+    let (store, _) = use_store::<Store>();
+    let username = store
+        .auth_user
+        .as_ref()
+        .map(|user| user.username.clone())
+        .unwrap_or_default();
+    let schedules = use_state(Vec::<ScheduleResponse>::new);
+    let is_loading = use_state(|| true);
+    let error = use_state(|| None::<AttrValue>);
+    let title = use_state(String::new);
+    let starts_at = use_state(String::new);
+    let ends_at = use_state(String::new);
+    let recurrence = use_state(String::new);
+    let opens_room = use_state(|| false);
+    // bumped to list the schedules again
+    let version = use_state(|| 0u32);
+
+    {
+        let schedules = schedules.clone();
+        let is_loading = is_loading.clone();
+        use_effect_with((id.clone(), *version), move |(id, _)| {
+            let id = id.clone();
+            spawn_local(async move {
+                match Request::get(&format!("/api/rooms/{}/schedules", id))
+                    .send()
+                    .await
+                {
+                    Ok(res) if res.ok() => match res.json::<Vec<ScheduleResponse>>().await {
+                        Ok(listed) => schedules.set(listed),
+                        Err(e) => log_1(&e.to_string().into()),
+                    },
+                    Ok(res) => log_1(&format!("Cannot list schedules: {}", res.status()).into()),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+                is_loading.set(false);
+            });
+        });
+    }
+
+    let on_text = |state: UseStateHandle<String>| {
+        Callback::from(move |e: Event| {
+            state.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+    let on_recurrence = {
+        let recurrence = recurrence.clone();
+        Callback::from(move |e: Event| {
+            recurrence.set(e.target_unchecked_into::<HtmlSelectElement>().value());
+        })
+    };
+    let on_opens_room = {
+        let opens_room = opens_room.clone();
+        Callback::from(move |e: Event| {
+            opens_room.set(e.target_unchecked_into::<HtmlInputElement>().checked());
+        })
+    };
+
+    let plan = {
+        let id = id.clone();
+        let error = error.clone();
+        let version = version.clone();
+        let (title, starts_at, ends_at, recurrence, opens_room) = (
+            title.clone(),
+            starts_at.clone(),
+            ends_at.clone(),
+            recurrence.clone(),
+            opens_room.clone(),
+        );
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let (Some(start), Some(end)) = (parse_local(&starts_at), parse_local(&ends_at)) else {
+                error.set(Some("Set when the session starts and ends".into()));
+                return;
+            };
+            let request = CreateScheduleRequest {
+                title: (*title).clone(),
+                starts_at: start,
+                ends_at: end,
+                recurrence: (!recurrence.is_empty()).then(|| (*recurrence).clone()),
+                repeat_until: None,
+                opens_room: *opens_room,
+            };
+            if let Err(e) = request.validate() {
+                error.set(Some(first_message(&e)));
+                return;
+            }
+            let url = format!("/api/rooms/{}/schedules", id);
+            let error = error.clone();
+            let version = version.clone();
+            spawn_local(async move {
+                let body = serde_json::to_string(&request).unwrap();
+                let request = Request::post(&url)
+                    .header("Content-Type", "application/json")
+                    .body(Some(body));
+                match request.send().await {
+                    Ok(res) if res.ok() => {
+                        error.set(None);
+                        version.set(*version + 1);
+                    }
+                    Ok(res) if res.status() == 403 => error.set(Some(
+                        "Only the owner and the moderators plan sessions".into(),
+                    )),
+                    Ok(_) => error.set(Some("Cannot plan the session".into())),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let respond = {
+        let error = error.clone();
+        let version = version.clone();
+        Callback::from(move |(url, response): (String, &'static str)| {
+            let error = error.clone();
+            let version = version.clone();
+            spawn_local(async move {
+                let body = serde_json::to_string(&RsvpRequest {
+                    response: response.to_owned(),
+                })
+                .unwrap();
+                let request = Request::put(&url)
+                    .header("Content-Type", "application/json")
+                    .body(Some(body));
+                match request.send().await {
+                    Ok(res) if res.ok() => version.set(*version + 1),
+                    Ok(_) => error.set(Some("Cannot answer".into())),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let cancel = {
+        let error = error.clone();
+        let version = version.clone();
+        Callback::from(move |url: String| {
+            let error = error.clone();
+            let version = version.clone();
+            spawn_local(async move {
+                match Request::delete(&url).send().await {
+                    Ok(res) if res.ok() => version.set(*version + 1),
+                    Ok(_) => error.set(Some("Cannot cancel".into())),
+                    Err(e) => log_1(&e.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let button_classes = "text-sm text-primary-600 hover:underline dark:text-primary-500";
+    html! {
+        <div class="space-y-2">
+            <h2 class="text-lg font-bold text-gray-900 dark:text-white">{"Planned sessions"}</h2>
+            if *is_loading {
+                <Spinner />
+            }
+            <ul class="divide-y divide-gray-200 dark:divide-gray-700">
+                { for schedules.iter().map(|schedule| {
+                    let url = format!("/api/rooms/{}/schedules/{}", id, schedule.id);
+                    let own = schedule
+                        .rsvps
+                        .iter()
+                        .find(|rsvp| rsvp.username == username)
+                        .map(|rsvp| rsvp.response.clone());
+                    let count = |response: &str| {
+                        schedule.rsvps.iter().filter(|rsvp: &&RsvpResponse| rsvp.response == response).count()
+                    };
+                    let when = match (schedule.next_starts_at, schedule.recurrence.as_deref()) {
+                        (None, _) => String::from("over"),
+                        (Some(at), None) => format_local(at),
+                        (Some(at), Some(recurrence)) => format!("{}, then {}", format_local(at), recurrence),
+                    };
+                    let answers = ["yes", "maybe", "no"].map(|response| {
+                        let onclick = {
+                            let respond = respond.clone();
+                            let url = format!("{}/rsvp", url);
+                            Callback::from(move |_: MouseEvent| respond.emit((url.clone(), response)))
+                        };
+                        let chosen = own.as_deref() == Some(response);
+                        html! {
+                            <button {onclick} type="button" class={classes!("ms-2", button_classes, chosen.then_some("font-bold"))}>{response}</button>
+                        }
+                    });
+                    let on_cancel = {
+                        let cancel = cancel.clone();
+                        let url = url.clone();
+                        Callback::from(move |_: MouseEvent| cancel.emit(url.clone()))
+                    };
+                    html! {
+                        <li key={schedule.id.to_string()} class="py-2 px-1 text-sm text-gray-900 dark:text-white">
+                            <div class="flex justify-between">
+                                <span>{&schedule.title}</span>
+                                <span class="text-gray-500 dark:text-gray-400">{when}</span>
+                            </div>
+                            <div class="flex justify-between text-gray-500 dark:text-gray-400">
+                                <span>{ format!("{} yes · {} maybe", count("yes"), count("maybe")) }</span>
+                                <span>
+                                    { for answers }
+                                    <a href={format!("{}/ics", url)} class={classes!("ms-2", button_classes)}>{"Add to calendar"}</a>
+                                    if *can_plan {
+                                        <button onclick={on_cancel} type="button" class={classes!("ms-2", button_classes)}>{"Cancel"}</button>
+                                    }
+                                </span>
+                            </div>
+                        </li>
+                    }
+                }) }
+            </ul>
+            if schedules.is_empty() && !*is_loading {
+                <p class="text-sm text-gray-500 dark:text-gray-400">{"No sessions planned"}</p>
+            }
+            if let Some(error) = &*error {
+                <TextError error={error.clone()}/>
+            }
+            if *can_plan {
+                <form onsubmit={plan} class="space-y-2">
+                    <input type="text" required={true} class={text_input_classes()} placeholder="Title" onchange={on_text(title.clone())} />
+                    <div class="flex space-x-2">
+                        <input type="datetime-local" required={true} class={text_input_classes()} onchange={on_text(starts_at.clone())} />
+                        <input type="datetime-local" required={true} class={text_input_classes()} onchange={on_text(ends_at.clone())} />
+                    </div>
+                    <select class={text_input_classes()} onchange={on_recurrence}>
+                        <option value="" selected={recurrence.is_empty()}>{"Once"}</option>
+                        <option value="daily">{"Every day"}</option>
+                        <option value="weekly">{"Every week"}</option>
+                    </select>
+                    <div class="flex items-center">
+                        <input type="checkbox" id="opens_room" checked={*opens_room} onchange={on_opens_room} />
+                        <label for="opens_room" class={classes!("ms-2", label_classes())}>{"Open the room only during its sessions"}</label>
+                    </div>
+                    <button type="submit" class={button_classes}>{"Plan"}</button>
+                </form>
+            }
+        </div>
+    }
+}
@@ -3,12 +3,22 @@ use crate::components::pages::icons::push_pin::PushPinIcon;
 use crate::utils::animation;
 use crate::utils::animation::request_animation_frame;
 use crate::WEBTRANSPORT_HOST;
+use common::protos::chat_packet::ChatPacket;
 use common::protos::media_packet::media_packet::MediaType;
+use common::protos::moderation_packet::{moderation_packet::Action, ModerationPacket, Role};
+use common::protos::rejection_packet::rejection_packet::Reason;
+use common::types::{
+    ChatMessageResponse, JoinTicketResponse, RoomResponse, UpdateTransportRequest,
+};
+use gloo_net::http::Request;
 use log::warn;
 use std::borrow::BorrowMut;
 use std::cell::RefCell;
 use std::rc::Rc;
-use videocall_client::{MediaDeviceAccess, VideoCallClient, VideoCallClientOptions};
+use videocall_client::{
+    Beat, LatencyReport, LatencyStats, MediaDeviceAccess, Transport, VideoCallClient,
+    VideoCallClientOptions,
+};
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
@@ -23,6 +33,7 @@ use yew_hooks::use_effect_update_with_deps;
 #[derive(Debug)]
 pub enum WsAction {
     Connect,
+    TicketIssued(String),
     Connected,
     Lost(Option<JsValue>),
     RequestMediaPermissions,
@@ -36,14 +47,43 @@ pub enum WsAction {
 pub enum MeetingAction {
     ToggleMicMute,
     ToggleVideoOnOff,
+    Moderate(Action, String),
+}
+
+#[derive(Debug)]
+pub enum ChatAction {
+    Received(ChatPacket),
+    Input(String),
+    Send,
+    LoadOlder,
+    Older(Vec<ChatMessageResponse>),
+}
+
+#[derive(Debug)]
+pub enum MetronomeAction {
+    Load,
+    Loaded(RoomResponse),
+    Changed(Transport),
+    Beat(Beat),
+    ToggleClick,
+    TempoInput(String),
+    Update(UpdateTransportRequest),
 }
 
 pub enum Msg {
     WsAction(WsAction),
     MeetingAction(MeetingAction),
+    ChatAction(ChatAction),
+    MetronomeAction(MetronomeAction),
     OnPeerAdded(String),
+    OnPeerJoined(String),
+    OnPeerLeft(String),
+    OnRejected((Reason, String)),
+    OnGoingAway(String),
+    OnModeration((String, ModerationPacket)),
     OnFirstFrame((String, MediaType)),
     OnChangeMic(String),
+    OnLatency(LatencyReport),
 }
 
 impl From<WsAction> for Msg {
@@ -58,6 +98,26 @@ impl From<MeetingAction> for Msg {
     }
 }
 
+impl From<ChatAction> for Msg {
+    fn from(action: ChatAction) -> Self {
+        Msg::ChatAction(action)
+    }
+}
+
+impl From<MetronomeAction> for Msg {
+    fn from(action: MetronomeAction) -> Self {
+        Msg::MetronomeAction(action)
+    }
+}
+
+/// A message of the chat panel, either received live or loaded from the history of the room
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatMessage {
+    pub id: String,
+    pub username: String,
+    pub text: String,
+}
+
 #[derive(Properties, Debug, PartialEq)]
 pub struct AttendantsComponentProps {
     pub id: String,
@@ -72,6 +132,21 @@ pub struct Client {
     pub video_enabled: bool,
     pub error: Option<String>,
     pub audio_id: Option<String>,
+    pub rejected: bool,
+    pub going_away: bool,
+    pub latency: LatencyReport,
+    pub chat: Vec<ChatMessage>,
+    pub chat_draft: String,
+    /// `false` once the history of the room has been loaded up to the first message
+    pub older_messages: bool,
+    pub transport: Option<Transport>,
+    /// Last beat of the metronome, until it stops
+    pub beat: Option<Beat>,
+    /// Only the owner of the room changes the metronome
+    pub owner: bool,
+    pub tempo_draft: String,
+    /// Last moderation action taken in the room
+    pub moderation_notice: Option<String>,
 }
 
 impl Client {
@@ -80,7 +155,7 @@ impl Client {
         let id = ctx.props().id.clone();
         let opts = VideoCallClientOptions {
             userid: username.clone(),
-            webtransport_url: format!("{WEBTRANSPORT_HOST}/{username}/{id}"),
+            webtransport_url: format!("{WEBTRANSPORT_HOST}/{id}"),
             enable_e2ee: false,
             on_connected: {
                 let link = ctx.link().clone();
@@ -94,6 +169,42 @@ impl Client {
                 let link = ctx.link().clone();
                 Callback::from(move |email| link.send_message(Msg::OnPeerAdded(email)))
             },
+            on_peer_joined: {
+                let link = ctx.link().clone();
+                Callback::from(move |email| link.send_message(Msg::OnPeerJoined(email)))
+            },
+            on_peer_left: {
+                let link = ctx.link().clone();
+                Callback::from(move |email| link.send_message(Msg::OnPeerLeft(email)))
+            },
+            on_rejected: {
+                let link = ctx.link().clone();
+                Callback::from(move |rejection| link.send_message(Msg::OnRejected(rejection)))
+            },
+            on_going_away: {
+                let link = ctx.link().clone();
+                Callback::from(move |message| link.send_message(Msg::OnGoingAway(message)))
+            },
+            on_chat_message: {
+                let link = ctx.link().clone();
+                Callback::from(move |message| link.send_message(ChatAction::Received(message)))
+            },
+            on_latency: {
+                let link = ctx.link().clone();
+                Callback::from(move |report| link.send_message(Msg::OnLatency(report)))
+            },
+            on_transport: {
+                let link = ctx.link().clone();
+                Callback::from(move |transport| link.send_message(MetronomeAction::Changed(transport)))
+            },
+            on_beat: {
+                let link = ctx.link().clone();
+                Callback::from(move |beat| link.send_message(MetronomeAction::Beat(beat)))
+            },
+            on_moderation: {
+                let link = ctx.link().clone();
+                Callback::from(move |moderation| link.send_message(Msg::OnModeration(moderation)))
+            },
             on_peer_first_frame: {
                 let link = ctx.link().clone();
                 Callback::from(move |(email, media_type)| {
@@ -133,12 +244,24 @@ impl Component for Client {
             video_enabled: false,
             error: None,
             audio_id: None,
+            rejected: false,
+            going_away: false,
+            latency: LatencyReport::default(),
+            chat: Vec::new(),
+            chat_draft: String::new(),
+            older_messages: true,
+            transport: None,
+            beat: None,
+            owner: false,
+            tempo_draft: String::new(),
+            moderation_notice: None,
         }
     }
 
     fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
         if first_render {
             ctx.link().send_message(WsAction::RequestMediaPermissions);
+            ctx.link().send_message(MetronomeAction::Load);
         }
     }
 
@@ -146,16 +269,53 @@ impl Component for Client {
         match msg {
             Msg::WsAction(action) => match action {
                 WsAction::Connect => {
-                    if self.client.is_connected() {
+                    if (self.client.is_connected() && !self.going_away) || self.rejected {
+                        return false;
+                    }
+                    // the relay cannot read our session cookie, so ask for a fresh join ticket
+                    // before every connection attempt
+                    let id = ctx.props().id.clone();
+                    let link = ctx.link().clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let url = format!("/api/rooms/{id}/ticket");
+                        match Request::post(url.as_ref()).send().await {
+                            Ok(res) if res.ok() => match res.json::<JoinTicketResponse>().await {
+                                Ok(JoinTicketResponse { ticket, .. }) => {
+                                    link.send_message(WsAction::TicketIssued(ticket))
+                                }
+                                Err(e) => link.send_message(WsAction::Log(format!(
+                                    "Invalid join ticket: {e}"
+                                ))),
+                            },
+                            Ok(res) => link.send_message(WsAction::Log(format!(
+                                "Cannot get a join ticket: {}",
+                                res.status()
+                            ))),
+                            Err(e) => link
+                                .send_message(WsAction::Log(format!("Connection failed: {e}"))),
+                        }
+                    });
+                    false
+                }
+                WsAction::TicketIssued(ticket) => {
+                    if self.client.is_connected() && !self.going_away {
                         return false;
                     }
+                    // connecting again replaces the connection to the server going away
+                    self.going_away = false;
+                    let id = ctx.props().id.clone();
+                    self.client
+                        .set_webtransport_url(format!("{WEBTRANSPORT_HOST}/{id}?ticket={ticket}"));
                     if let Err(e) = self.client.connect() {
                         ctx.link()
                             .send_message(WsAction::Log(format!("Connection failed: {e}")));
                     }
                     true
                 }
-                WsAction::Connected => true,
+                WsAction::Connected => {
+                    self.follow_role();
+                    true
+                }
                 WsAction::Log(msg) => {
                     warn!("{}", msg);
                     false
@@ -181,6 +341,59 @@ impl Component for Client {
                 }
             },
             Msg::OnPeerAdded(_email) => true,
+            Msg::OnPeerJoined(_email) => true,
+            Msg::OnPeerLeft(_email) => true,
+            Msg::OnRejected((reason, message)) => {
+                // retrying would be refused again, or would take the room back from the newer
+                // session of the same user
+                self.rejected = true;
+                self.error = Some(match reason {
+                    Reason::REPLACED => {
+                        "You joined this room from another tab or device".to_owned()
+                    }
+                    Reason::UNSUPPORTED_VERSION => {
+                        "This version of the application is outdated, reload the page".to_owned()
+                    }
+                    Reason::KICKED => "You were removed from the room by a moderator".to_owned(),
+                    _ => message,
+                });
+                true
+            }
+            Msg::OnGoingAway(message) => {
+                warn!("{}", message);
+                self.going_away = true;
+                ctx.link().send_message(WsAction::Connect);
+                false
+            }
+            Msg::OnModeration((sender, packet)) => {
+                let username = &ctx.props().username;
+                let role = packet.role.enum_value_or_default();
+                let target = if packet.username == *username {
+                    "you".to_owned()
+                } else {
+                    packet.username.clone()
+                };
+                let notice = match packet.action.enum_value_or_default() {
+                    Action::MUTE => {
+                        // the server does not stop the microphone, the muted peer does
+                        if packet.username == *username {
+                            self.mic_enabled = false;
+                        }
+                        format!("{sender} muted {target}")
+                    }
+                    Action::PROMOTE => {
+                        format!("{sender} promoted {target} to {}", role_label(role))
+                    }
+                    Action::DEMOTE => format!("{sender} demoted {target} to {}", role_label(role)),
+                    Action::KICK => format!("{sender} removed {target} from the room"),
+                    Action::TRANSFER_OWNERSHIP => {
+                        format!("{sender} made {target} the owner of the room")
+                    }
+                };
+                self.moderation_notice = Some(notice);
+                self.follow_role();
+                true
+            }
             Msg::OnFirstFrame((_email, media_type)) => matches!(media_type, MediaType::VIDEO),
             Msg::MeetingAction(action) => {
                 match action {
@@ -190,6 +403,11 @@ impl Component for Client {
                     MeetingAction::ToggleVideoOnOff => {
                         self.video_enabled = !self.video_enabled;
                     }
+                    MeetingAction::Moderate(action, username) => {
+                        // the server sends the action back to every peer once applied
+                        self.client.moderate(action, username);
+                        return false;
+                    }
                 }
                 true
             }
@@ -197,25 +415,186 @@ impl Component for Client {
                 self.audio_id = Some(id);
                 true
             }
+            Msg::OnLatency(report) => {
+                let changed = self.latency != report;
+                self.latency = report;
+                changed
+            }
+            Msg::ChatAction(action) => match action {
+                ChatAction::Received(packet) => {
+                    // the latest messages are replayed on every connection
+                    if self.chat.iter().any(|message| message.id == packet.id) {
+                        return false;
+                    }
+                    self.chat.push(ChatMessage {
+                        id: packet.id,
+                        username: packet.username,
+                        text: packet.text,
+                    });
+                    true
+                }
+                ChatAction::Input(draft) => {
+                    self.chat_draft = draft;
+                    false
+                }
+                ChatAction::Send => {
+                    let text = self.chat_draft.trim().to_owned();
+                    if text.is_empty() || !self.client.is_connected() {
+                        return false;
+                    }
+                    self.client.send_chat(text);
+                    self.chat_draft.clear();
+                    true
+                }
+                ChatAction::LoadOlder => {
+                    let id = ctx.props().id.clone();
+                    let url = match self.chat.first() {
+                        Some(oldest) => format!("/api/rooms/{id}/messages?before={}", oldest.id),
+                        None => format!("/api/rooms/{id}/messages"),
+                    };
+                    let link = ctx.link().clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match Request::get(url.as_ref()).send().await {
+                            Ok(res) if res.ok() => {
+                                match res.json::<Vec<ChatMessageResponse>>().await {
+                                    Ok(messages) => link.send_message(ChatAction::Older(messages)),
+                                    Err(e) => link.send_message(WsAction::Log(format!(
+                                        "Invalid chat history: {e}"
+                                    ))),
+                                }
+                            }
+                            Ok(res) => link.send_message(WsAction::Log(format!(
+                                "Cannot load the chat history: {}",
+                                res.status()
+                            ))),
+                            Err(e) => link.send_message(WsAction::Log(format!(
+                                "Cannot load the chat history: {e}"
+                            ))),
+                        }
+                    });
+                    false
+                }
+                ChatAction::Older(messages) => {
+                    self.older_messages = !messages.is_empty();
+                    // the history comes newest first
+                    let older = messages
+                        .into_iter()
+                        .rev()
+                        .map(|message| ChatMessage {
+                            id: message.id.to_string(),
+                            username: message.username,
+                            text: message.text,
+                        })
+                        .filter(|message| !self.chat.contains(message))
+                        .collect::<Vec<_>>();
+                    self.chat.splice(0..0, older);
+                    true
+                }
+            },
+            Msg::MetronomeAction(action) => match action {
+                MetronomeAction::Load => {
+                    let id = ctx.props().id.clone();
+                    let link = ctx.link().clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let url = format!("/api/rooms/{id}");
+                        match Request::get(url.as_ref()).send().await {
+                            Ok(res) if res.ok() => match res.json::<RoomResponse>().await {
+                                Ok(room) => link.send_message(MetronomeAction::Loaded(room)),
+                                Err(e) => {
+                                    link.send_message(WsAction::Log(format!("Invalid room: {e}")))
+                                }
+                            },
+                            Ok(res) => link.send_message(WsAction::Log(format!(
+                                "Cannot load the room: {}",
+                                res.status()
+                            ))),
+                            Err(e) => link
+                                .send_message(WsAction::Log(format!("Cannot load the room: {e}"))),
+                        }
+                    });
+                    false
+                }
+                MetronomeAction::Loaded(room) => {
+                    self.owner = room.owner == ctx.props().username;
+                    true
+                }
+                MetronomeAction::Changed(transport) => {
+                    self.tempo_draft = transport.tempo.to_string();
+                    self.transport = Some(transport);
+                    if !transport.running {
+                        self.beat = None;
+                    }
+                    true
+                }
+                MetronomeAction::Beat(beat) => {
+                    // a beat scheduled before the metronome stopped may still fall
+                    if !self.transport.is_some_and(|transport| transport.running) {
+                        return false;
+                    }
+                    self.beat = Some(beat);
+                    true
+                }
+                MetronomeAction::ToggleClick => {
+                    self.client.set_click(!self.client.click_enabled());
+                    true
+                }
+                MetronomeAction::TempoInput(tempo) => {
+                    self.tempo_draft = tempo;
+                    false
+                }
+                MetronomeAction::Update(request) => {
+                    // the server sends the new state to every peer, this one included
+                    let id = ctx.props().id.clone();
+                    let link = ctx.link().clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let url = format!("/api/rooms/{id}/transport");
+                        let body = serde_json::to_string(&request).unwrap();
+                        match Request::put(url.as_ref())
+                            .header("Content-Type", "application/json")
+                            .body(Some(body))
+                            .send()
+                            .await
+                        {
+                            Ok(res) if res.ok() => {}
+                            Ok(res) => link.send_message(WsAction::Log(format!(
+                                "Cannot change the metronome: {}",
+                                res.status()
+                            ))),
+                            Err(e) => link.send_message(WsAction::Log(format!(
+                                "Cannot change the metronome: {e}"
+                            ))),
+                        }
+                    });
+                    false
+                }
+            },
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let username = ctx.props().username.clone();
         let media_access_granted = self.media_device_access.is_granted();
+        let role = self.client.role();
+        let listener = role == Role::LISTENER;
         let rows: Vec<VNode> = self
             .client
             .sorted_peer_keys()
             .iter()
             .map(|key| {
                 let peer_video_div_id = Rc::new(format!("peer-video-{}-div", &key));
+                let latency = self.latency.peers.get(key).map(latency_label);
+                let peer_role = self.client.role_of(key);
                 html! {
                     <>
                         <div class="grid-item" id={(*peer_video_div_id).clone()}>
                             // One canvas for the User Video
                             <div class="canvas-container">
                                 <UserVideo id={key.clone()}></UserVideo>
-                                <h4 class="floating-name">{key.clone()}</h4>
+                                <h4 class="floating-name">{ format!("{} ({})", key, role_label(peer_role)) }</h4>
+                                { latency.map(|latency| html! { <span class="floating-latency">{ latency }</span> }) }
+                                if self.client.can_moderate(key) {
+                                    { self.view_moderation(ctx, key, role, peer_role) }
+                                }
                                 <button onclick={
                                     Callback::from(move |_| {
                                     toggle_pinned_div(&(*peer_video_div_id).clone());
@@ -238,11 +617,13 @@ impl Component for Client {
                                 <div class="">
                                     <button
                                         class=" p-2 rounded-md "
+                                        disabled={listener}
                                         onclick={ctx.link().callback(|_| MeetingAction::ToggleVideoOnOff)}>
                                         { if !self.video_enabled { "Start Video"} else { "Stop Video"} }
                                     </button>
                                     <button
                                         class="bg-yew-blue p-2 rounded-md text-white"
+                                        disabled={listener}
                                         onclick={ctx.link().callback(|_| MeetingAction::ToggleMicMute)}>
                                         { if !self.mic_enabled { "Start playing"} else { "Stop playing"} }
                                         </button>
@@ -254,18 +635,54 @@ impl Component for Client {
                                         html! {<></>}
                                     }
                                 }
-                                <h4 class="">{username}</h4>
+                                <h4 class="">{ format!("{} ({})", username, role_label(role)) }</h4>
+                                if listener {
+                                    <p>{"You are listening: a moderator can let you play"}</p>
+                                }
+                                { self.moderation_notice.as_ref().map(|notice| html! { <p>{ notice }</p> }) }
 
                                 {if !self.client.is_connected() {
                                     html! {<h4>{"Connecting"}</h4>}
                                 } else {
                                     html! {<h4>{"Connected"}</h4>}
                                 }}
+                                { self.latency.server.as_ref().map(|server| html! {
+                                    <h4>{ format!("Server: {}", latency_label(server)) }</h4>
+                                }) }
+                                { self.transport.map(|transport| self.view_metronome(ctx, transport)) }
 
                             </nav>
                         }
 
                 }
+                <aside class="chat">
+                    if self.older_messages {
+                        <button onclick={ctx.link().callback(|_| ChatAction::LoadOlder)}>
+                            {"Load older messages"}
+                        </button>
+                    }
+                    <ul>
+                        { for self.chat.iter().map(|message| html! {
+                            <li key={message.id.clone()}>
+                                <strong>{&message.username}</strong>{": "}{&message.text}
+                            </li>
+                        }) }
+                    </ul>
+                    <form onsubmit={ctx.link().callback(|e: SubmitEvent| {
+                        e.prevent_default();
+                        ChatAction::Send
+                    })}>
+                        <input
+                            type="text"
+                            placeholder="Write a message"
+                            value={self.chat_draft.clone()}
+                            oninput={ctx.link().callback(|e: InputEvent| {
+                                ChatAction::Input(e.target_unchecked_into::<HtmlInputElement>().value())
+                            })}
+                        />
+                        <button type="submit" class="bg-yew-blue p-2 rounded-md text-white">{"Send"}</button>
+                    </form>
+                </aside>
                 if let Some(id) = &self.audio_id {
                     <AudioVisualizer audio_id={id.clone()}/>
                 } else {
@@ -276,6 +693,106 @@ impl Component for Client {
     }
 }
 
+impl Client {
+    /// Follows the role of this client: a listener publishes no media and only the owner
+    /// changes the metronome
+    fn follow_role(&mut self) {
+        let role = self.client.role();
+        if role == Role::LISTENER {
+            self.mic_enabled = false;
+            self.video_enabled = false;
+        }
+        self.owner = role == Role::OWNER;
+    }
+
+    /// Actions this client may take on a peer with a lower role
+    fn view_moderation(
+        &self,
+        ctx: &Context<Self>,
+        peer: &str,
+        role: Role,
+        peer_role: Role,
+    ) -> Html {
+        let moderate = |label: &'static str, action: Action| {
+            let peer = peer.to_owned();
+            html! {
+                <button onclick={ctx.link().callback(move |_| MeetingAction::Moderate(action, peer.clone()))}>
+                    { label }
+                </button>
+            }
+        };
+        // a moderator only raises a listener, the owner also makes moderators
+        let promote =
+            peer_role == Role::LISTENER || (peer_role == Role::MUSICIAN && role == Role::OWNER);
+        html! {
+            <div class="moderation">
+                { moderate("Mute", Action::MUTE) }
+                if promote {
+                    { moderate("Promote", Action::PROMOTE) }
+                }
+                if peer_role != Role::LISTENER {
+                    { moderate("Demote", Action::DEMOTE) }
+                }
+                { moderate("Kick", Action::KICK) }
+                if role == Role::OWNER {
+                    { moderate("Make owner", Action::TRANSFER_OWNERSHIP) }
+                }
+            </div>
+        }
+    }
+
+    fn view_metronome(&self, ctx: &Context<Self>, transport: Transport) -> Html {
+        let running = transport.running;
+        let tempo = self.tempo_draft.parse::<f64>().ok();
+        html! {
+            <div class="metronome">
+                <span>{ format!("{} bpm, {}/{}", transport.tempo, transport.beats_per_bar, transport.beat_unit) }</span>
+                <span class="beats">
+                    { for (0..transport.beats_per_bar).map(|index| {
+                        let class = match self.beat.filter(|beat| beat.beat == index) {
+                            Some(beat) if beat.is_count_in() => "beat count-in",
+                            Some(_) => "beat active",
+                            None => "beat",
+                        };
+                        html! { <span class={class}>{"●"}</span> }
+                    }) }
+                </span>
+                <button onclick={ctx.link().callback(|_| MetronomeAction::ToggleClick)}>
+                    { if self.client.click_enabled() { "Mute click" } else { "Play click" } }
+                </button>
+                if self.owner {
+                    <form onsubmit={ctx.link().callback(move |e: SubmitEvent| {
+                        e.prevent_default();
+                        MetronomeAction::Update(UpdateTransportRequest {
+                            tempo,
+                            ..Default::default()
+                        })
+                    })}>
+                        <input
+                            type="number"
+                            min="20"
+                            max="400"
+                            value={self.tempo_draft.clone()}
+                            oninput={ctx.link().callback(|e: InputEvent| {
+                                MetronomeAction::TempoInput(e.target_unchecked_into::<HtmlInputElement>().value())
+                            })}
+                        />
+                        <button type="submit">{"Set tempo"}</button>
+                    </form>
+                    <button
+                        class="bg-yew-blue p-2 rounded-md text-white"
+                        onclick={ctx.link().callback(move |_| MetronomeAction::Update(UpdateTransportRequest {
+                            running: Some(!running),
+                            ..Default::default()
+                        }))}>
+                        { if running { "Stop" } else { "Start" } }
+                    </button>
+                }
+            </div>
+        }
+    }
+}
+
 // props for the video component
 #[derive(Properties, Debug, PartialEq)]
 pub struct UserVideoProps {
@@ -309,6 +826,25 @@ fn user_video(props: &UserVideoProps) -> Html {
     }
 }
 
+fn role_label(role: Role) -> &'static str {
+    match role {
+        Role::LISTENER => "listener",
+        Role::MUSICIAN => "musician",
+        Role::MODERATOR => "moderator",
+        Role::OWNER => "owner",
+    }
+}
+
+/// Round trip time, jitter and loss of the pings, in a line
+fn latency_label(stats: &LatencyStats) -> String {
+    format!(
+        "{:.0} ms ±{:.0} ms, {:.0}% lost",
+        stats.rtt_ms,
+        stats.jitter_ms,
+        stats.loss * 100.0
+    )
+}
+
 fn toggle_pinned_div(div_id: &str) {
     if let Some(div) = window()
         .and_then(|w| w.document())
@@ -0,0 +1,258 @@
+use common::types::{
+    DeleteAccountRequest, UpdatePasswordRequest, UpdateProfileRequest, UserResponse,
+};
+use gloo_net::http::Request;
+use validator::{Validate, ValidationErrors};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{console::log_1, HtmlInputElement};
+use yew::prelude::*;
+use yew_router::hooks::use_navigator;
+use yewdux::use_store;
+
+use crate::{
+    components::{
+        atoms::{
+            class::{label_classes, text_input_classes},
+            form_title::TextTitle,
+            logo::Logo,
+            spinner::Spinner,
+            text_error::TextError,
+            text_success::TextSuccess,
+        },
+        molecules::account_sessions::AccountSessions,
+        pages::classes::{box_div_classes, main_div_classes, submit_button_classes},
+        router::Route,
+    },
+    store::Store,
+};
+
+fn first_message(errors: &ValidationErrors) -> AttrValue {
+    errors
+        .field_errors()
+        .values()
+        .flat_map(|errors| errors.iter())
+        .find_map(|error| error.message.clone())
+        .map_or("Invalid values".into(), |message| {
+            AttrValue::from(message.into_owned())
+        })
+}
+
+/// Lets the user signed in change its name and password, download what is stored about it and
+/// delete its account
+#[function_component(Account)]
+pub fn account() -> Html {
+    let (_, dispatch) = use_store::<Store>();
+    let navigator = use_navigator().unwrap();
+    let user = use_state(|| None::<UserResponse>);
+    let first_name = use_state(String::new);
+    let last_name = use_state(String::new);
+    let current_password = use_state(String::new);
+    let password = use_state(String::new);
+    let confirm_password = use_state(String::new);
+    let delete_password = use_state(String::new);
+    let error = use_state(|| None::<AttrValue>);
+    let message = use_state(|| None::<AttrValue>);
+
+    {
+        let navigator = navigator.clone();
+        let user = user.clone();
+        let (first_name, last_name) = (first_name.clone(), last_name.clone());
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                match Request::get("/api/account").send().await {
+                    Ok(res) if res.ok() => match res.json::<UserResponse>().await {
+                        Ok(found) => {
+                            first_name.set(found.first_name.clone());
+                            last_name.set(found.last_name.clone());
+                            user.set(Some(found));
+                        }
+                        Err(err) => log_1(&err.to_string().into()),
+                    },
+                    Ok(_) => navigator.replace(&Route::Login),
+                    // network error
+                    Err(err) => log_1(&err.to_string().into()),
+                }
+            });
+        });
+    }
+
+    let on_text = |state: UseStateHandle<String>| {
+        Callback::from(move |e: Event| {
+            state.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let update_profile = {
+        let (error, message) = (error.clone(), message.clone());
+        let (first_name, last_name) = (first_name.clone(), last_name.clone());
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let request = UpdateProfileRequest {
+                first_name: (*first_name).clone(),
+                last_name: (*last_name).clone(),
+            };
+            if let Err(e) = request.validate() {
+                error.set(Some(first_message(&e)));
+                return;
+            }
+            let (error, message) = (error.clone(), message.clone());
+            let dispatch = dispatch.clone();
+            spawn_local(async move {
+                let body = serde_json::to_string(&request).unwrap();
+                let request = Request::patch("/api/account")
+                    .header("Content-Type", "application/json")
+                    .body(Some(body));
+                match request.send().await {
+                    Ok(res) if res.ok() => match res.json::<UserResponse>().await {
+                        Ok(updated) => {
+                            dispatch.reduce_mut(move |s| s.auth_user = Some(updated.into()));
+                            error.set(None);
+                            message.set(Some("Your name was changed".into()));
+                        }
+                        Err(err) => log_1(&err.to_string().into()),
+                    },
+                    Ok(_) => error.set(Some("Cannot change your name".into())),
+                    Err(err) => log_1(&err.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let update_password = {
+        let (error, message) = (error.clone(), message.clone());
+        let (current_password, password, confirm_password) = (
+            current_password.clone(),
+            password.clone(),
+            confirm_password.clone(),
+        );
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let request = UpdatePasswordRequest {
+                current_password: (*current_password).clone(),
+                password: (*password).clone(),
+                confirm_password: (*confirm_password).clone(),
+            };
+            if let Err(e) = request.validate() {
+                error.set(Some(first_message(&e)));
+                return;
+            }
+            if request.password != request.confirm_password {
+                error.set(Some("The passwords do not match".into()));
+                return;
+            }
+            let (error, message) = (error.clone(), message.clone());
+            spawn_local(async move {
+                let body = serde_json::to_string(&request).unwrap();
+                let request = Request::put("/api/account/password")
+                    .header("Content-Type", "application/json")
+                    .body(Some(body));
+                match request.send().await {
+                    Ok(res) if res.ok() => {
+                        error.set(None);
+                        message.set(Some(
+                            "Your password was changed, the other devices were signed out".into(),
+                        ));
+                    }
+                    Ok(res) if res.status() == 403 => {
+                        error.set(Some("The current password is wrong".into()))
+                    }
+                    Ok(_) => error.set(Some("Cannot change your password".into())),
+                    Err(err) => log_1(&err.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let delete_account = {
+        let (error, message) = (error.clone(), message.clone());
+        let delete_password = delete_password.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let confirmed = web_sys::window()
+                .and_then(|window| {
+                    window
+                        .confirm_with_message(
+                            "Delete your account? The rooms nobody else is in are deleted too.",
+                        )
+                        .ok()
+                })
+                .unwrap_or(false);
+            if !confirmed {
+                return;
+            }
+            let request = DeleteAccountRequest {
+                password: (*delete_password).clone(),
+            };
+            let navigator = navigator.clone();
+            let (error, message) = (error.clone(), message.clone());
+            spawn_local(async move {
+                let body = serde_json::to_string(&request).unwrap();
+                let request = Request::delete("/api/account")
+                    .header("Content-Type", "application/json")
+                    .body(Some(body));
+                match request.send().await {
+                    Ok(res) if res.ok() => navigator.replace(&Route::Login),
+                    Ok(res) if res.status() == 403 => {
+                        message.set(None);
+                        error.set(Some("The password is wrong".into()))
+                    }
+                    Ok(_) => error.set(Some("Cannot delete your account".into())),
+                    Err(err) => log_1(&err.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let section_classes = "text-lg font-bold text-gray-900 dark:text-white";
+    html! {
+    <div class={main_div_classes()}>
+        <Logo/>
+        <div class={box_div_classes()}>
+            <div class={"p-6 space-y-4 md:space-y-6 sm:p-8"}>
+                <TextTitle message={"Account"} />
+                if let Some(user) = &*user {
+                    <p class="text-sm text-gray-500 dark:text-gray-400">
+                        { format!("Signed in as {} ({})", user.username, user.email) }
+                    </p>
+                    if let Some(error) = &*error {
+                        <TextError error={error.clone()}/>
+                    }
+                    <TextSuccess message={(*message).clone()} />
+                    <form onsubmit={update_profile} class="space-y-2">
+                        <h2 class={section_classes}>{"Name"}</h2>
+                        <label for="first_name" class={label_classes()}>{"First name"}</label>
+                        <input type="text" id="first_name" required={true} class={text_input_classes()} value={(*first_name).clone()} onchange={on_text(first_name.clone())} />
+                        <label for="last_name" class={label_classes()}>{"Last name"}</label>
+                        <input type="text" id="last_name" required={true} class={text_input_classes()} value={(*last_name).clone()} onchange={on_text(last_name.clone())} />
+                        <button type="submit" class={submit_button_classes()}>{"Save"}</button>
+                    </form>
+                    <form onsubmit={update_password} class="space-y-2">
+                        <h2 class={section_classes}>{"Password"}</h2>
+                        <input type="password" required={true} class={text_input_classes()} placeholder="Current password" onchange={on_text(current_password.clone())} />
+                        <input type="password" required={true} class={text_input_classes()} placeholder="New password" onchange={on_text(password.clone())} />
+                        <input type="password" required={true} class={text_input_classes()} placeholder="Confirm the new password" onchange={on_text(confirm_password.clone())} />
+                        <button type="submit" class={submit_button_classes()}>{"Change password"}</button>
+                    </form>
+                    <AccountSessions />
+                    <div class="space-y-2">
+                        <h2 class={section_classes}>{"Your data"}</h2>
+                        <a href="/api/account/export" download="" class="text-sm text-primary-600 hover:underline dark:text-primary-500">
+                            {"Download everything stored about you"}
+                        </a>
+                    </div>
+                    <form onsubmit={delete_account} class="space-y-2">
+                        <h2 class={section_classes}>{"Delete the account"}</h2>
+                        <p class="text-sm text-gray-500 dark:text-gray-400">
+                            {"Each room you own goes to one of its moderators or members, the ones nobody else is in are deleted with their recordings"}
+                        </p>
+                        <input type="password" required={true} class={text_input_classes()} placeholder="Password" onchange={on_text(delete_password.clone())} />
+                        <button type="submit" class="w-full text-white bg-red-600 hover:bg-red-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center">{"Delete the account"}</button>
+                    </form>
+                } else {
+                    <Spinner />
+                }
+            </div>
+        </div>
+    </div>
+    }
+}
@@ -1,34 +1,59 @@
 use std::{cell::RefCell, ops::Deref, rc::Rc};
 
-use common::types::{CreateRoomRequest, LoginRequest, RoomResponse, UserResponse};
+use common::types::{CreateRoomRequest, RoomResponse, UpdateRoomRequest, UserResponse};
 use gloo_net::http::Request;
 use validator::{Validate, ValidationErrors};
 use wasm_bindgen_futures::spawn_local;
-use web_sys::console::log_1;
+use web_sys::{console::log_1, HtmlInputElement};
 use yew::prelude::*;
-use yew_router::{components::Link, hooks::use_navigator, navigator};
+use yew_router::hooks::use_navigator;
 use yewdux::use_store;
 
 use crate::{
     components::{
         atoms::{
-            form_title::TextTitle, logo::Logo, spinner::Spinner, text_error::TextError,
-            text_input::TextInput,
+            class::label_classes, form_title::TextTitle, logo::Logo, spinner::Spinner,
+            text_error::TextError, text_input::TextInput,
         },
+        molecules::room_members::RoomMembers,
         pages::classes::{box_div_classes, main_div_classes, submit_button_classes},
         router::Route,
     },
     store::Store,
 };
 
+fn set_field(data: &mut CreateRoomRequest, name: &str, value: String) {
+    match name {
+        "name" => data.name = value,
+        "description" => data.description = Some(value),
+        // a number that does not parse fails the validation
+        "max_people_playing" => data.max_people_playing = value.trim().parse().unwrap_or_default(),
+        _ => (),
+    }
+}
+
 fn get_input_callback(
     name: &'static str,
     cloned_form: UseStateHandle<CreateRoomRequest>,
 ) -> Callback<String> {
     Callback::from(move |value| {
+        let mut data = cloned_form.deref().clone();
+        set_field(&mut data, name, value);
+        cloned_form.set(data);
+    })
+}
+
+fn get_checkbox_callback(
+    name: &'static str,
+    cloned_form: UseStateHandle<CreateRoomRequest>,
+) -> Callback<Event> {
+    Callback::from(move |e: Event| {
+        let checked = e.target_unchecked_into::<HtmlInputElement>().checked();
         let mut data = cloned_form.deref().clone();
         match name {
-            "name" => data.name = value,
+            "private" => data.private = checked,
+            "open" => data.open = checked,
+            "encrypted" => data.encrypted = checked,
             _ => (),
         }
         cloned_form.set(data);
@@ -41,11 +66,20 @@ struct FormState {
     pub message: Option<AttrValue>,
 }
 
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Room whose settings are edited, a new room is created when missing
+    #[prop_or_default]
+    pub id: Option<AttrValue>,
+}
+
 #[function_component(CreateRoom)]
-pub fn create_room() -> Html {
+pub fn create_room(Props { id }: &Props) -> Html {
     let (store, dispatch) = use_store::<Store>();
     let navigator = use_navigator().unwrap();
-    let form = use_state(|| CreateRoomRequest { name: "".into() });
+    let form = use_state(CreateRoomRequest::default);
+    // the form is shown once the room to edit is loaded
+    let is_loaded = use_state(|| id.is_none());
     let form_state = use_state(|| FormState {
         is_loading: false,
         is_error: false,
@@ -76,11 +110,49 @@ pub fn create_room() -> Html {
         });
     }
 
+    {
+        let form = form.clone();
+        let is_loaded = is_loaded.clone();
+        let form_state = form_state.clone();
+        use_effect_with(id.clone(), move |id| {
+            if let Some(id) = id.clone() {
+                spawn_local(async move {
+                    match Request::get(&format!("/api/rooms/{}", id)).send().await {
+                        Ok(res) if res.ok() => match res.json::<RoomResponse>().await {
+                            Ok(room) => {
+                                form.set(CreateRoomRequest {
+                                    name: room.name,
+                                    description: room.description,
+                                    private: room.private,
+                                    open: room.open,
+                                    max_people_playing: room.max_people_playing,
+                                    encrypted: room.encrypted,
+                                });
+                                is_loaded.set(true);
+                            }
+                            Err(err) => log_1(&err.to_string().into()),
+                        },
+                        Ok(_) => form_state.set(FormState {
+                            is_error: true,
+                            message: Some("Room does not exists".into()),
+                            is_loading: false,
+                        }),
+                        // network error
+                        Err(err) => {
+                            log_1(&err.to_string().into());
+                        }
+                    }
+                });
+            }
+        });
+    }
+
     let create_session = {
         let form = form.clone();
         let validation_errors = validation_errors.clone();
         let form_state = form_state.clone();
         let navigator = navigator.clone();
+        let id = id.clone();
         Callback::from(move |e: SubmitEvent| {
             e.prevent_default();
 
@@ -89,25 +161,46 @@ pub fn create_room() -> Html {
                     let form = form.deref().clone();
                     let navigator = navigator.clone();
                     let form_state = form_state.clone();
+                    let id = id.clone();
                     spawn_local(async move {
                         form_state.set(FormState {
                             is_error: false,
                             message: None,
                             is_loading: true,
                         });
-                        let body = serde_json::to_string(&form).unwrap();
-                        match Request::post("/api/rooms")
-                            .header("Content-Type", "application/json")
-                            .body(Some(body))
-                            .send()
-                            .await
-                        {
+                        let request = match &id {
+                            Some(id) => {
+                                let update = UpdateRoomRequest {
+                                    name: Some(form.name),
+                                    // an empty description removes it
+                                    description: Some(form.description.unwrap_or_default()),
+                                    private: Some(form.private),
+                                    open: Some(form.open),
+                                    max_people_playing: Some(form.max_people_playing),
+                                };
+                                let body = serde_json::to_string(&update).unwrap();
+                                Request::patch(&format!("/api/rooms/{}", id))
+                                    .header("Content-Type", "application/json")
+                                    .body(Some(body))
+                            }
+                            None => {
+                                let body = serde_json::to_string(&form).unwrap();
+                                Request::post("/api/rooms")
+                                    .header("Content-Type", "application/json")
+                                    .body(Some(body))
+                            }
+                        };
+                        match request.send().await {
                             Ok(res) => {
                                 if res.ok() {
                                     let response = res.json::<RoomResponse>().await.unwrap();
-                                    navigator.push(&Route::Session {
-                                        id: response.id.into(),
-                                    });
+                                    if id.is_some() {
+                                        navigator.push(&Route::Home);
+                                    } else {
+                                        navigator.push(&Route::Session {
+                                            id: response.id.into(),
+                                        });
+                                    }
                                     form_state.set(FormState {
                                         is_error: false,
                                         message: None,
@@ -116,7 +209,11 @@ pub fn create_room() -> Html {
                                 } else {
                                     form_state.set(FormState {
                                         is_error: true,
-                                        message: Some("Cannot create room".into()),
+                                        message: Some(if id.is_some() {
+                                            "Cannot save room".into()
+                                        } else {
+                                            "Cannot create room".into()
+                                        }),
                                         is_loading: false,
                                     });
                                 }
@@ -139,13 +236,10 @@ pub fn create_room() -> Html {
         let cloned_validation_errors = validation_errors.clone();
         Callback::from(move |(name, value): (String, String)| {
             let mut data = cloned_form.deref().clone();
-            match name.as_str() {
-                "name" => data.name = value,
-                _ => (),
-            }
-            cloned_form.set(data);
+            set_field(&mut data, name.as_str(), value);
+            cloned_form.set(data.clone());
 
-            match cloned_form.validate() {
+            match data.validate() {
                 Ok(_) => {
                     cloned_validation_errors
                         .borrow_mut()
@@ -172,15 +266,39 @@ pub fn create_room() -> Html {
 
     let name_change = get_input_callback("name", form.clone());
     let description_change = get_input_callback("description", form.clone());
+    let max_people_playing_change = get_input_callback("max_people_playing", form.clone());
+    let private_change = get_checkbox_callback("private", form.clone());
+    let open_change = get_checkbox_callback("open", form.clone());
+    let encrypted_change = get_checkbox_callback("encrypted", form.clone());
+    let checkbox_classes = "w-4 h-4 me-2 border border-gray-300 rounded bg-gray-50 focus:ring-3 focus:ring-primary-300 dark:bg-gray-700 dark:border-gray-600 dark:focus:ring-primary-600";
+    let (title, submit) = if id.is_some() {
+        ("Room settings", "Save")
+    } else {
+        ("Create a new session", "Create session")
+    };
     html! {
     <div class={main_div_classes()}>
         <Logo/>
         <div class={box_div_classes()}>
             <div class={"p-6 space-y-4 md:space-y-6 sm:p-8"}>
-                <TextTitle message={"Create a new session"} />
+                <TextTitle message={title} />
+                if *is_loaded {
                 <form onsubmit={create_session} class={"space-y-4 md:space-y-6"}>
-                    <TextInput label={"Session name"} name={"name"} handle_onchange={name_change} handle_on_input_blur={onblur.clone()} errors={&*validation_errors}/>
-                    <TextInput label={"Description"} t={"textarea"} name={"description"} handle_onchange={description_change} handle_on_input_blur={onblur.clone()} errors={&*validation_errors}/>
+                    <TextInput label={"Session name"} name={"name"} value={AttrValue::from(form.name.clone())} handle_onchange={name_change} handle_on_input_blur={onblur.clone()} errors={&*validation_errors}/>
+                    <TextInput label={"Description"} t={"textarea"} name={"description"} required={false} value={form.description.clone().map(AttrValue::from)} handle_onchange={description_change} handle_on_input_blur={onblur.clone()} errors={&*validation_errors}/>
+                    <TextInput label={"People playing"} t={"number"} name={"max_people_playing"} value={AttrValue::from(form.max_people_playing.to_string())} handle_onchange={max_people_playing_change} handle_on_input_blur={onblur.clone()} errors={&*validation_errors}/>
+                    <div class={"flex items-center"}>
+                        <input type={"checkbox"} id={"private"} name={"private"} class={checkbox_classes} checked={form.private} onchange={private_change} />
+                        <label for={"private"} class={label_classes()}>{"Private: only the invited members can join"}</label>
+                    </div>
+                    <div class={"flex items-center"}>
+                        <input type={"checkbox"} id={"open"} name={"open"} class={checkbox_classes} checked={form.open} onchange={open_change} />
+                        <label for={"open"} class={label_classes()}>{"Open: people can join"}</label>
+                    </div>
+                    <div class={"flex items-center"}>
+                        <input type={"checkbox"} id={"encrypted"} name={"encrypted"} class={checkbox_classes} checked={form.encrypted} disabled={id.is_some()} onchange={encrypted_change} />
+                        <label for={"encrypted"} class={label_classes()}>{"End-to-end encrypted: cannot be recorded"}</label>
+                    </div>
                     if let Some(res) = &form_state.deref().message {
                         if form_state.is_error {
                             <TextError error={res.clone()}/>
@@ -188,13 +306,23 @@ pub fn create_room() -> Html {
                     }
                     <button type={"submit"} class={submit_button_classes()}>
                         <div class={"flex justify-center"}>
-                            <span>{"Create session"}</span>
+                            <span>{submit}</span>
                             if form_state.clone().deref().is_loading {
                                 <Spinner />
                             }
                         </div>
                        </button>
                 </form>
+                if let Some(id) = id {
+                    if form.private {
+                        <RoomMembers id={id.clone()} />
+                    }
+                }
+                } else if let Some(res) = &form_state.deref().message {
+                    <TextError error={res.clone()}/>
+                } else {
+                    <Spinner />
+                }
             </div>
         </div>
     </div>
@@ -16,7 +16,7 @@ use crate::{
             spinner::Spinner,
             text_error::TextError,
         },
-        molecules::header::Header,
+        molecules::{header::Header, room_list::RoomList},
         pages::classes::{box_div_classes, main_div_classes, submit_button_classes},
         router::Route,
     },
@@ -155,6 +155,8 @@ pub fn home() -> Html {
                                     }
                                 </div>
                                 </button>
+                            <RoomList title={"My rooms"} mine={true} />
+                            <RoomList title={"Public rooms"} searchable={true} />
                         </div>
                     </div>
                 </div>
@@ -0,0 +1,75 @@
+use common::types::RoomResponse;
+use gloo_net::http::Request;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::console::log_1;
+use yew::prelude::*;
+use yew_router::hooks::use_navigator;
+
+use crate::components::{
+    atoms::{form_title::TextTitle, logo::Logo, spinner::Spinner, text_error::TextError},
+    pages::classes::{box_div_classes, main_div_classes},
+    router::Route,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub token: AttrValue,
+}
+
+/// Opened from an invite link, makes the user a member of the room and joins it
+#[function_component(Invite)]
+pub fn invite(Props { token }: &Props) -> Html {
+    let navigator = use_navigator().unwrap();
+    let error = use_state(|| None::<AttrValue>);
+
+    {
+        let error = error.clone();
+        use_effect_with(token.clone(), move |token| {
+            let token = token.clone();
+            spawn_local(async move {
+                match Request::post(&format!("/api/invites/{}", token))
+                    .send()
+                    .await
+                {
+                    Ok(res) if res.ok() => match res.json::<RoomResponse>().await {
+                        Ok(room) => navigator.replace(&Route::Session {
+                            id: room.id.to_string(),
+                        }),
+                        Err(err) => log_1(&err.to_string().into()),
+                    },
+                    Ok(res) if res.status() == 403 => match res.json::<serde_json::Value>().await {
+                        Ok(body) if body["error"]["message"] == "NOT_ALLOWED" => {
+                            error.set(Some("The invite was sent to another email address".into()))
+                        }
+                        Ok(body) if body["error"]["message"] == "BANNED_FROM_ROOM" => error.set(
+                            Some("You were kicked out of the room, try again later".into()),
+                        ),
+                        // not logged in
+                        _ => navigator.replace(&Route::Login),
+                    },
+                    Ok(_) => error.set(Some("The invite is expired or was used up".into())),
+                    // network error
+                    Err(err) => {
+                        log_1(&err.to_string().into());
+                    }
+                }
+            });
+        });
+    }
+
+    html! {
+    <div class={main_div_classes()}>
+        <Logo/>
+        <div class={box_div_classes()}>
+            <div class={"p-6 space-y-4 md:space-y-6 sm:p-8"}>
+                <TextTitle message={"Joining the room"} />
+                if let Some(error) = &*error {
+                    <TextError error={error.clone()}/>
+                } else {
+                    <Spinner />
+                }
+            </div>
+        </div>
+    </div>
+    }
+}
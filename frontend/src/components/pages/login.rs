@@ -4,14 +4,14 @@ use common::types::LoginRequest;
 use gloo_net::http::Request;
 use validator::{Validate, ValidationErrors};
 use wasm_bindgen_futures::spawn_local;
-use web_sys::console::log_1;
+use web_sys::{console::log_1, HtmlInputElement};
 use yew::prelude::*;
 use yew_router::{components::Link, hooks::use_navigator};
 
 use crate::components::{
     atoms::{
-        form_title::TextTitle, logo::Logo, spinner::Spinner, text_error::TextError,
-        text_input::TextInput,
+        class::label_classes, form_title::TextTitle, logo::Logo, spinner::Spinner,
+        text_error::TextError, text_input::TextInput,
     },
     pages::classes::{box_div_classes, main_div_classes, submit_button_classes},
     router::Route,
@@ -44,6 +44,7 @@ pub fn login() -> Html {
     let form = use_state(|| LoginRequest {
         username: "".into(),
         password: "".into(),
+        remember_me: false,
     });
     let form_state = use_state(|| FormState {
         is_loading: false,
@@ -143,6 +144,14 @@ pub fn login() -> Html {
         })
     };
 
+    let remember_me_change = {
+        let form = form.clone();
+        Callback::from(move |e: Event| {
+            let mut data = form.deref().clone();
+            data.remember_me = e.target_unchecked_into::<HtmlInputElement>().checked();
+            form.set(data);
+        })
+    };
     let username_change = get_input_callback("username", form.clone());
     let password_change = get_input_callback("password", form.clone());
     html! {
@@ -155,6 +164,10 @@ pub fn login() -> Html {
                     <TextInput label={"Username"} name={"username"} handle_onchange={username_change} handle_on_input_blur={onblur.clone()} errors={&*validation_errors}/>
                     <TextInput t={"password"} label={"Password"} name={"password"} handle_onchange={password_change} handle_on_input_blur={onblur.clone()} errors={&*validation_errors}/>
                     <div class={"flex items-center justify-between"}>
+                        <div class={"flex items-center"}>
+                            <input type="checkbox" id="remember_me" checked={form.remember_me} onchange={remember_me_change} />
+                            <label for="remember_me" class={classes!("ms-2", label_classes())}>{"Remember me"}</label>
+                        </div>
                         <Link<Route> to={Route::StartReset} classes={"text-sm font-medium text-primary-600 hover:underline dark:text-primary-500"}>{"Forgot password?"}</Link<Route>>
                     </div>
                     if let Some(res) = &form_state.deref().message {
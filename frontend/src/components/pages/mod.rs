@@ -1,9 +1,12 @@
+pub mod account;
 pub mod change_password;
 pub mod classes;
 pub mod home;
+pub mod invite;
 pub mod icons;
 pub mod create_session;
 pub mod login;
 pub mod register;
 pub mod reset_password;
+pub mod schedule;
 pub mod session;
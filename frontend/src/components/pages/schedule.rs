@@ -0,0 +1,125 @@
+use common::types::{CalendarFeedResponse, RoomResponse, UserResponse};
+use gloo_net::http::Request;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::console::log_1;
+use yew::prelude::*;
+use yew_router::hooks::use_navigator;
+use yewdux::use_store;
+
+use crate::{
+    components::{
+        atoms::{
+            class::text_input_classes, form_title::TextTitle, logo::Logo, spinner::Spinner,
+            text_error::TextError,
+        },
+        molecules::room_schedules::RoomSchedules,
+        pages::classes::{box_div_classes, main_div_classes},
+        router::Route,
+    },
+    store::Store,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub id: AttrValue,
+}
+
+/// Sessions planned in a room, and the link of the calendar feed of the user
+#[function_component(RoomSchedule)]
+pub fn room_schedule(Props { id }: &Props) -> Html {
+    let (store, dispatch) = use_store::<Store>();
+    let navigator = use_navigator().unwrap();
+    let room = use_state(|| None::<RoomResponse>);
+    let error = use_state(|| None::<AttrValue>);
+    let feed = use_state(|| None::<String>);
+
+    {
+        let navigator = navigator.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                match Request::get("/api/auth/me").send().await {
+                    Ok(res) if res.ok() => {
+                        let user = res.json::<UserResponse>().await.unwrap();
+                        dispatch.reduce_mut(move |s| s.auth_user = Some(user.into()));
+                    }
+                    Ok(_) => navigator.replace(&Route::Login),
+                    // network error
+                    Err(err) => log_1(&err.to_string().into()),
+                }
+            });
+        });
+    }
+
+    {
+        let room = room.clone();
+        let error = error.clone();
+        use_effect_with(id.clone(), move |id| {
+            let id = id.clone();
+            spawn_local(async move {
+                match Request::get(&format!("/api/rooms/{}", id)).send().await {
+                    Ok(res) if res.ok() => match res.json::<RoomResponse>().await {
+                        Ok(found) => room.set(Some(found)),
+                        Err(err) => log_1(&err.to_string().into()),
+                    },
+                    Ok(_) => error.set(Some("Room does not exists".into())),
+                    Err(err) => log_1(&err.to_string().into()),
+                }
+            });
+        });
+    }
+
+    // shows the link of the feed, or replaces it with a new one
+    let show_feed = {
+        let feed = feed.clone();
+        Callback::from(move |rotate: bool| {
+            let feed = feed.clone();
+            spawn_local(async move {
+                let request = if rotate {
+                    Request::post("/api/calendar")
+                } else {
+                    Request::get("/api/calendar")
+                };
+                match request.send().await {
+                    Ok(res) if res.ok() => match res.json::<CalendarFeedResponse>().await {
+                        Ok(response) => feed.set(Some(response.url)),
+                        Err(err) => log_1(&err.to_string().into()),
+                    },
+                    Ok(res) => log_1(&format!("Cannot get the feed: {}", res.status()).into()),
+                    Err(err) => log_1(&err.to_string().into()),
+                }
+            });
+        })
+    };
+
+    let username = store.auth_user.as_ref().map(|user| user.username.clone());
+    let button_classes = "text-sm text-primary-600 hover:underline dark:text-primary-500";
+    html! {
+    <div class={main_div_classes()}>
+        <Logo/>
+        <div class={box_div_classes()}>
+            <div class={"p-6 space-y-4 md:space-y-6 sm:p-8"}>
+                if let Some(room) = &*room {
+                    <TextTitle message={room.name.clone()} />
+                    <RoomSchedules id={id.clone()} can_plan={username.as_ref() == Some(&room.owner)} />
+                    <div class="space-y-2">
+                        <h2 class="text-lg font-bold text-gray-900 dark:text-white">{"Calendar feed"}</h2>
+                        <p class="text-sm text-gray-500 dark:text-gray-400">
+                            {"Subscribe to it from a calendar app to follow the sessions of your rooms and the ones you attend"}
+                        </p>
+                        if let Some(url) = &*feed {
+                            <input type="text" readonly={true} class={text_input_classes()} value={url.clone()} />
+                            <button onclick={show_feed.reform(|_| true)} type="button" class={button_classes}>{"Replace the link"}</button>
+                        } else {
+                            <button onclick={show_feed.reform(|_| false)} type="button" class={button_classes}>{"Show the link"}</button>
+                        }
+                    </div>
+                } else if let Some(error) = &*error {
+                    <TextError error={error.clone()}/>
+                } else {
+                    <Spinner />
+                }
+            </div>
+        </div>
+    </div>
+    }
+}
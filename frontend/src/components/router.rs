@@ -1,11 +1,12 @@
 use yew::prelude::*;
 use yew_router::prelude::*;
 
-use crate::components::pages::{home::Home, login::Login, register::Register};
+use crate::components::pages::{home::Home, invite::Invite, login::Login, register::Register};
 
 use super::{
     layouts::simple::SimpleLayout, pages::{
-        change_password::ChangePassword, create_session::CreateRoom, reset_password::StartReset, session::Session,
+        account::Account, change_password::ChangePassword, create_session::CreateRoom, reset_password::StartReset,
+        schedule::RoomSchedule, session::Session,
     }
 };
 
@@ -26,12 +27,24 @@ pub enum Route {
     #[at("/start-reset")]
     StartReset,
 
+    #[at("/account")]
+    Account,
+
     #[at("/session/:id")]
     Session { id: String },
 
     #[at("/create-room")]
     CreateRoom,
 
+    #[at("/room/:id/settings")]
+    RoomSettings { id: String },
+
+    #[at("/room/:id/schedule")]
+    RoomSchedule { id: String },
+
+    #[at("/invite/:token")]
+    Invite { token: String },
+
     #[not_found]
     #[at("/not-found")]
     NotFound,
@@ -59,6 +72,21 @@ pub fn switch(routes: Route) -> Html {
                 <CreateRoom/>
             </SimpleLayout>
         },
+        Route::RoomSettings { id } => html! {
+            <SimpleLayout>
+                <CreateRoom id={AttrValue::from(id)}/>
+            </SimpleLayout>
+        },
+        Route::RoomSchedule { id } => html! {
+            <SimpleLayout>
+                <RoomSchedule id={AttrValue::from(id)}/>
+            </SimpleLayout>
+        },
+        Route::Invite { token } => html! {
+            <SimpleLayout>
+                <Invite token={AttrValue::from(token)}/>
+            </SimpleLayout>
+        },
         Route::Register => html! {
             <SimpleLayout>
                 <Register/>
@@ -74,6 +102,11 @@ pub fn switch(routes: Route) -> Html {
                 <StartReset/>
             </SimpleLayout>
         },
+        Route::Account => html! {
+            <SimpleLayout>
+                <Account/>
+            </SimpleLayout>
+        },
         Route::NotFound => {
             html! {
                 <SimpleLayout>
@@ -0,0 +1,157 @@
+//
+// Offset and drift of the local clock against the clock of the server, estimated like NTP from
+// the PONGs the server sends back to the pings of the client
+//
+use common::protos::ping_packet::PingPacket;
+use std::collections::VecDeque;
+
+/// Exchanges remembered, about a minute with a ping every second
+const WINDOW: usize = 64;
+
+/// Exchanges slower than the fastest one by more than this are left out of the estimate: the
+/// extra time is queueing, likely on one way only, and skews their offset
+const DELAY_TOLERANCE_MS: f64 = 5.0;
+
+/// The drift is estimated once the exchanges kept span at least this long, before it is too
+/// noisy to be of any use
+const MIN_DRIFT_SPAN_MS: f64 = 10_000.0;
+
+/// Estimate of the local clock against the clock of the server
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ClockEstimate {
+    /// Milliseconds to add to the local clock to read the clock of the server
+    pub offset_ms: f64,
+    /// How much faster the clock of the server runs, in parts per million
+    pub drift_ppm: f64,
+    /// Round trip of the fastest exchange, the offset is off by at most half of it
+    pub delay_ms: f64,
+}
+
+#[derive(Debug)]
+struct Sample {
+    /// Local time halfway through the exchange
+    local: f64,
+    offset: f64,
+    delay: f64,
+}
+
+/// Keeps the last exchanges with the server.  Times are milliseconds since the epoch, as
+/// returned by `Date.now()`.
+#[derive(Debug, Default)]
+pub(super) struct Clock {
+    samples: VecDeque<Sample>,
+}
+
+impl Clock {
+    /// Accounts a pong of the server received at `now`
+    pub fn pong(&mut self, pong: &PingPacket, now: f64) {
+        if pong.received_at == 0.0 {
+            return;
+        }
+        let answered_at = if pong.answered_at == 0.0 {
+            pong.received_at
+        } else {
+            pong.answered_at
+        };
+        let delay = (now - pong.sent_at) - (answered_at - pong.received_at);
+        if delay < 0.0 {
+            return;
+        }
+        self.samples.push_back(Sample {
+            local: (pong.sent_at + now) / 2.0,
+            offset: ((pong.received_at - pong.sent_at) + (answered_at - now)) / 2.0,
+            delay,
+        });
+        if self.samples.len() > WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Fits a line through the offsets of the fastest exchanges, its slope is the drift
+    pub fn estimate(&self, now: f64) -> Option<ClockEstimate> {
+        let delay_ms = self
+            .samples
+            .iter()
+            .map(|sample| sample.delay)
+            .min_by(f64::total_cmp)?;
+        let kept: Vec<&Sample> = self
+            .samples
+            .iter()
+            .filter(|sample| sample.delay <= delay_ms + DELAY_TOLERANCE_MS)
+            .collect();
+        let n = kept.len() as f64;
+        let mean_local = kept.iter().map(|sample| sample.local).sum::<f64>() / n;
+        let mean_offset = kept.iter().map(|sample| sample.offset).sum::<f64>() / n;
+        let (first, last) = (kept[0].local, kept[kept.len() - 1].local);
+        let drift = if last - first < MIN_DRIFT_SPAN_MS {
+            0.0
+        } else {
+            let covariance: f64 = kept
+                .iter()
+                .map(|sample| (sample.local - mean_local) * (sample.offset - mean_offset))
+                .sum();
+            let variance: f64 = kept
+                .iter()
+                .map(|sample| (sample.local - mean_local).powi(2))
+                .sum();
+            covariance / variance
+        };
+        Some(ClockEstimate {
+            offset_ms: mean_offset + drift * (now - mean_local),
+            drift_ppm: drift * 1_000_000.0,
+            delay_ms,
+        })
+    }
+
+    /// Reads the clock of the server at local time `now`, `None` until the server answered
+    pub fn server_time(&self, now: f64) -> Option<f64> {
+        self.estimate(now).map(|estimate| now + estimate.offset_ms)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn pong(sent_at: f64, received_at: f64, answered_at: f64) -> PingPacket {
+        PingPacket {
+            sent_at,
+            received_at,
+            answered_at,
+            ..Default::default()
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_offset() {
+        let mut clock = Clock::default();
+        assert_eq!(clock.server_time(1000.0), None);
+        // the clock of the server is 5 seconds ahead, 10ms each way
+        clock.pong(&pong(1000.0, 6010.0, 6011.0), 1021.0);
+        // queued for 90ms on the way to the server only
+        clock.pong(&pong(2000.0, 7100.0, 7100.0), 2110.0);
+
+        let estimate = clock.estimate(3000.0).unwrap();
+        assert_eq!(estimate.offset_ms, 5000.0);
+        assert_eq!(estimate.drift_ppm, 0.0);
+        assert_eq!(estimate.delay_ms, 20.0);
+        assert_eq!(clock.server_time(3000.0), Some(8000.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_drift() {
+        let mut clock = Clock::default();
+        // the clock of the server runs 100 ppm faster
+        let server = |local: f64| local + 5000.0 + local / 10_000.0;
+        for i in 0..=20 {
+            let sent_at = i as f64 * 1000.0;
+            let at_server = server(sent_at + 10.0);
+            clock.pong(&pong(sent_at, at_server, at_server), sent_at + 20.0);
+        }
+
+        let estimate = clock.estimate(30_000.0).unwrap();
+        assert!((estimate.drift_ppm - 100.0).abs() < 1e-6);
+        assert!((estimate.offset_ms - 5003.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,203 @@
+//
+// Round trip time, jitter and loss measured with the PING and PONG packets, towards the server
+// and towards each peer of the room
+//
+use common::protos::ping_packet::PingPacket;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Pings remembered for each target, the loss is computed on them
+const WINDOW: usize = 20;
+
+/// A ping still unanswered after this long is counted as lost
+const PONG_TIMEOUT_MS: f64 = 2000.0;
+
+/// Latency towards the server or a peer
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LatencyStats {
+    /// Smoothed round trip time, in milliseconds
+    pub rtt_ms: f64,
+    /// Variation of the one-way transit time of the pings (the interarrival jitter of RFC 3550),
+    /// in milliseconds
+    pub jitter_ms: f64,
+    /// Fraction of the last pings left unanswered, between 0 and 1
+    pub loss: f64,
+}
+
+/// Latency towards the server and each peer that answered at least one ping
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LatencyReport {
+    pub server: Option<LatencyStats>,
+    pub peers: BTreeMap<String, LatencyStats>,
+}
+
+#[derive(Debug, Default)]
+struct Ping {
+    sequence: u64,
+    sent_at: f64,
+    answered: bool,
+}
+
+#[derive(Debug, Default)]
+struct Target {
+    pings: VecDeque<Ping>,
+    rtt: Option<f64>,
+    jitter: f64,
+    /// One-way transit of the last answered ping, off by the difference between the clocks
+    transit: Option<f64>,
+}
+
+impl Target {
+    fn sent(&mut self, sequence: u64, sent_at: f64) {
+        self.pings.push_back(Ping {
+            sequence,
+            sent_at,
+            answered: false,
+        });
+        if self.pings.len() > WINDOW {
+            self.pings.pop_front();
+        }
+    }
+
+    fn answered(&mut self, pong: &PingPacket, now: f64) {
+        let Some(ping) = self
+            .pings
+            .iter_mut()
+            .find(|ping| ping.sequence == pong.sequence && !ping.answered)
+        else {
+            return;
+        };
+        ping.answered = true;
+
+        let rtt = now - ping.sent_at;
+        self.rtt = Some(match self.rtt {
+            Some(srtt) => srtt + (rtt - srtt) / 8.0,
+            None => rtt,
+        });
+        // the offset between the clocks cancels out in the difference of two transits
+        let transit = pong.received_at - ping.sent_at;
+        if let Some(previous) = self.transit {
+            self.jitter += ((transit - previous).abs() - self.jitter) / 16.0;
+        }
+        self.transit = Some(transit);
+    }
+
+    fn stats(&self, now: f64) -> Option<LatencyStats> {
+        let rtt_ms = self.rtt?;
+        let settled = self
+            .pings
+            .iter()
+            .filter(|ping| ping.answered || now - ping.sent_at > PONG_TIMEOUT_MS);
+        let (lost, total) = settled.fold((0, 0), |(lost, total), ping| {
+            (lost + usize::from(!ping.answered), total + 1)
+        });
+        Some(LatencyStats {
+            rtt_ms,
+            jitter_ms: self.jitter,
+            loss: if total == 0 {
+                0.0
+            } else {
+                lost as f64 / total as f64
+            },
+        })
+    }
+}
+
+/// Keeps track of the pings sent and of the pongs received.  Times are milliseconds since the
+/// epoch, as returned by `Date.now()`.
+#[derive(Debug, Default)]
+pub(super) struct Latency {
+    sequence: u64,
+    server: Target,
+    peers: BTreeMap<String, Target>,
+}
+
+impl Latency {
+    /// Accounts a ping sent to the server and to `peers`, returns its sequence number
+    pub fn ping(&mut self, peers: &[String], now: f64) -> u64 {
+        self.sequence += 1;
+        self.server.sent(self.sequence, now);
+        for peer in peers {
+            self.peers
+                .entry(peer.clone())
+                .or_default()
+                .sent(self.sequence, now);
+        }
+        self.sequence
+    }
+
+    /// Accounts a pong of the server, when `peer` is `None`, or of a peer
+    pub fn pong(&mut self, peer: Option<&str>, pong: &PingPacket, now: f64) {
+        let target = match peer {
+            Some(peer) => match self.peers.get_mut(peer) {
+                Some(target) => target,
+                None => return,
+            },
+            None => &mut self.server,
+        };
+        target.answered(pong, now);
+    }
+
+    pub fn remove_peer(&mut self, peer: &str) {
+        self.peers.remove(peer);
+    }
+
+    pub fn report(&self, now: f64) -> LatencyReport {
+        LatencyReport {
+            server: self.server.stats(now),
+            peers: self
+                .peers
+                .iter()
+                .filter_map(|(peer, target)| Some((peer.clone(), target.stats(now)?)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn pong(sequence: u64, sent_at: f64, received_at: f64) -> PingPacket {
+        PingPacket {
+            sequence,
+            sent_at,
+            received_at,
+            ..Default::default()
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_rtt_and_jitter() {
+        let mut latency = Latency::default();
+        let peers = vec!["bob".to_owned()];
+        latency.ping(&peers, 1000.0);
+        // the clock of bob is 5 seconds ahead
+        latency.pong(Some("bob"), &pong(1, 1000.0, 6010.0), 1020.0);
+        latency.ping(&peers, 2000.0);
+        latency.pong(Some("bob"), &pong(2, 2000.0, 7026.0), 2040.0);
+
+        let report = latency.report(2040.0);
+        assert_eq!(report.server, None);
+        let bob = report.peers["bob"];
+        assert_eq!(bob.rtt_ms, 20.0 + (40.0 - 20.0) / 8.0);
+        assert_eq!(bob.jitter_ms, 16.0 / 16.0);
+        assert_eq!(bob.loss, 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_loss() {
+        let mut latency = Latency::default();
+        for sequence in 1..=4 {
+            latency.ping(&[], sequence as f64 * 1000.0);
+        }
+        latency.pong(None, &pong(1, 1000.0, 0.0), 1010.0);
+        latency.pong(None, &pong(3, 3000.0, 0.0), 3010.0);
+
+        // the fourth ping is not late yet
+        let server = latency.report(4500.0).server.unwrap();
+        assert_eq!(server.loss, 1.0 / 3.0);
+        let server = latency.report(6500.0).server.unwrap();
+        assert_eq!(server.loss, 2.0 / 4.0);
+    }
+}
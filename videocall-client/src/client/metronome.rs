@@ -0,0 +1,239 @@
+//
+// Metronome of the room: the beat grid sent by the relay, laid on the clock of the server, and
+// the click played on the beats
+//
+use common::protos::transport_packet::TransportPacket;
+use log::error;
+use wasm_bindgen::JsValue;
+use web_sys::AudioContext;
+
+/// How often the beats about to fall are scheduled, in milliseconds
+pub(super) const SCHEDULE_PERIOD_MS: u32 = 25;
+
+/// Beats falling sooner than this are scheduled, long enough to cover a late timer
+const LOOKAHEAD_MS: f64 = 100.0;
+
+/// Length of a click, in seconds
+const CLICK_DURATION: f64 = 0.03;
+
+/// State of the metronome of the room.  The owner of the room changes it through the REST API,
+/// the server sends it to every peer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transport {
+    /// Beats per minute, a beat is a 1/`beat_unit` note
+    pub tempo: f64,
+    pub beats_per_bar: u32,
+    pub beat_unit: u32,
+    pub running: bool,
+    /// Bars counted in before [`started_at`](Self::started_at)
+    pub count_in: u32,
+    /// Time of the server, in milliseconds since the epoch, of the first downbeat after the
+    /// count-in
+    pub started_at: f64,
+}
+
+/// A beat of the metronome
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Beat {
+    /// Bar of the beat from the end of the count-in, negative during the count-in
+    pub bar: i64,
+    /// Beat in the bar, 0 is the downbeat
+    pub beat: u32,
+    /// Time of the server, in milliseconds since the epoch, when the beat falls
+    pub at: f64,
+}
+
+impl Beat {
+    pub fn is_count_in(&self) -> bool {
+        self.bar < 0
+    }
+}
+
+impl From<&TransportPacket> for Transport {
+    fn from(packet: &TransportPacket) -> Self {
+        Self {
+            tempo: packet.tempo,
+            beats_per_bar: packet.beats_per_bar.max(1),
+            beat_unit: packet.beat_unit,
+            running: packet.running,
+            count_in: packet.count_in,
+            started_at: packet.started_at,
+        }
+    }
+}
+
+impl Transport {
+    /// Length of a beat, in milliseconds
+    pub fn beat_ms(&self) -> f64 {
+        60_000.0 / self.tempo
+    }
+
+    /// Index of the first beat at or after the time `server_time`, counting from the first
+    /// downbeat after the count-in
+    fn next_index(&self, server_time: f64) -> i64 {
+        let first = -i64::from(self.count_in * self.beats_per_bar);
+        let index = ((server_time - self.started_at) / self.beat_ms()).ceil() as i64;
+        index.max(first)
+    }
+
+    fn beat(&self, index: i64) -> Beat {
+        let beats_per_bar = i64::from(self.beats_per_bar);
+        Beat {
+            bar: index.div_euclid(beats_per_bar),
+            beat: index.rem_euclid(beats_per_bar) as u32,
+            at: self.started_at + index as f64 * self.beat_ms(),
+        }
+    }
+}
+
+/// Schedules the beats of the transport, and plays them when the click is enabled
+#[derive(Debug, Default)]
+pub(super) struct Metronome {
+    transport: Option<Transport>,
+    /// Index of the first beat not scheduled yet
+    next: Option<i64>,
+    audio: Option<AudioContext>,
+}
+
+impl Metronome {
+    pub fn transport(&self) -> Option<Transport> {
+        self.transport
+    }
+
+    pub fn set_transport(&mut self, transport: Transport) {
+        self.transport = Some(transport);
+        self.next = None;
+    }
+
+    pub fn click_enabled(&self) -> bool {
+        self.audio.is_some()
+    }
+
+    /// Browsers start an audio context only in response to a gesture of the user, so this must
+    /// be called from an event handler
+    pub fn set_click(&mut self, enabled: bool) {
+        if enabled == self.audio.is_some() {
+            return;
+        }
+        if !enabled {
+            if let Some(audio) = self.audio.take() {
+                let _ = audio.close();
+            }
+            return;
+        }
+        match AudioContext::new() {
+            Ok(audio) => self.audio = Some(audio),
+            Err(e) => error!("Failed to create the audio context of the click: {:?}", e),
+        }
+    }
+
+    /// Plays the click of the beats falling within the lookahead, returns them along with how
+    /// long until they fall, in milliseconds
+    pub fn schedule(&mut self, server_time: f64) -> Vec<(Beat, f64)> {
+        let Some(transport) = self.transport.filter(|transport| transport.running) else {
+            return Vec::new();
+        };
+        // beats already past are skipped, after a throttled timer or a change of the clock
+        let mut index = transport.next_index(server_time);
+        if let Some(next) = self.next {
+            index = index.max(next);
+        }
+        let mut due = Vec::new();
+        loop {
+            let beat = transport.beat(index);
+            let delay = beat.at - server_time;
+            if delay > LOOKAHEAD_MS {
+                break;
+            }
+            if let Some(audio) = &self.audio {
+                if let Err(e) = click(audio, &beat, delay) {
+                    error!("Failed to schedule the click: {:?}", e);
+                }
+            }
+            due.push((beat, delay));
+            index += 1;
+        }
+        self.next = Some(index);
+        due
+    }
+}
+
+/// Plays a short beep, higher on the downbeats and lower during the count-in
+fn click(audio: &AudioContext, beat: &Beat, delay_ms: f64) -> Result<(), JsValue> {
+    let at = audio.current_time() + delay_ms.max(0.0) / 1000.0;
+    let frequency = match (beat.is_count_in(), beat.beat) {
+        (true, _) => 800.0,
+        (false, 0) => 1600.0,
+        (false, _) => 1000.0,
+    };
+    let oscillator = audio.create_oscillator()?;
+    oscillator.frequency().set_value(frequency);
+    let gain = audio.create_gain()?;
+    gain.gain().set_value_at_time(0.5, at)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(0.001, at + CLICK_DURATION)?;
+    oscillator.connect_with_audio_node(&gain)?;
+    gain.connect_with_audio_node(&audio.destination())?;
+    oscillator.start_with_when(at)?;
+    oscillator.stop_with_when(at + CLICK_DURATION)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn transport() -> Transport {
+        Transport {
+            tempo: 120.0,
+            beats_per_bar: 3,
+            beat_unit: 4,
+            running: true,
+            count_in: 1,
+            started_at: 10_000.0,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_beat_grid() {
+        let transport = transport();
+        // the count-in starts a bar, 3 beats of 500ms, before the anchor
+        assert_eq!(transport.next_index(0.0), -3);
+        assert_eq!(
+            transport.beat(-3),
+            Beat {
+                bar: -1,
+                beat: 0,
+                at: 8500.0
+            }
+        );
+        assert_eq!(transport.next_index(10_000.0), 0);
+        assert_eq!(transport.next_index(10_001.0), 1);
+        assert_eq!(
+            transport.beat(4),
+            Beat {
+                bar: 1,
+                beat: 1,
+                at: 12_000.0
+            }
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_schedule() {
+        let mut metronome = Metronome::default();
+        metronome.set_transport(transport());
+        let due = metronome.schedule(9950.0);
+        assert_eq!(due, vec![(transport().beat(0), 50.0)]);
+        // a beat is scheduled once
+        assert_eq!(metronome.schedule(9960.0), vec![]);
+        assert_eq!(metronome.schedule(10_450.0).len(), 1);
+
+        metronome.set_transport(Transport {
+            running: false,
+            ..transport()
+        });
+        assert_eq!(metronome.schedule(10_950.0), vec![]);
+    }
+}
@@ -1,3 +1,10 @@
+mod clock;
+mod latency;
+mod metronome;
+mod moderation;
 mod video_call_client;
 
+pub use clock::ClockEstimate;
+pub use latency::{LatencyReport, LatencyStats};
+pub use metronome::{Beat, Transport};
 pub use video_call_client::{VideoCallClient, VideoCallClientOptions};
@@ -0,0 +1,74 @@
+//
+// Roles of this client and of the peers in the room, as told by the server when connecting, in
+// the roster and in the moderation actions it relays
+//
+use common::protos::moderation_packet::{moderation_packet::Action, ModerationPacket, Role};
+use std::collections::HashMap;
+
+/// Orders the roles by the power they give, the values of the protocol do not
+fn rank(role: Role) -> u8 {
+    match role {
+        Role::LISTENER => 0,
+        Role::MUSICIAN => 1,
+        Role::MODERATOR => 2,
+        Role::OWNER => 3,
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Roles {
+    own: Role,
+    peers: HashMap<String, Role>,
+}
+
+impl Roles {
+    pub fn own(&self) -> Role {
+        self.own
+    }
+
+    pub fn set_own(&mut self, role: Role) {
+        self.own = role;
+    }
+
+    /// Returns the role of a peer, [`Role::MUSICIAN`] when the server did not tell
+    pub fn of(&self, peer: &str) -> Role {
+        self.peers.get(peer).copied().unwrap_or_default()
+    }
+
+    pub fn joined(&mut self, peer: &str, role: Role) {
+        self.peers.insert(peer.to_owned(), role);
+    }
+
+    pub fn left(&mut self, peer: &str) {
+        self.peers.remove(peer);
+    }
+
+    /// Takes the roles of a snapshot of the room in place of the known ones
+    pub fn snapshot(&mut self, roles: &HashMap<String, Role>) {
+        self.peers = roles.clone();
+    }
+
+    /// Follows an action taken by `sender`, `userid` being this client
+    pub fn follow(&mut self, userid: &str, sender: &str, packet: &ModerationPacket) {
+        let role = packet.role.enum_value_or_default();
+        if packet.username == userid {
+            self.own = role;
+        } else if packet.action != Action::KICK.into() {
+            self.peers.insert(packet.username.clone(), role);
+        }
+        // the former owner stays as a moderator
+        if packet.action == Action::TRANSFER_OWNERSHIP.into() {
+            if sender == userid {
+                self.own = Role::MODERATOR;
+            } else {
+                self.peers.insert(sender.to_owned(), Role::MODERATOR);
+            }
+        }
+    }
+
+    /// Whether this client may act on `peer`: only moderators and the owner moderate, and only
+    /// the participants with a lower role
+    pub fn can_moderate(&self, peer: &str) -> bool {
+        rank(self.own) >= rank(Role::MODERATOR) && rank(self.of(peer)) < rank(self.own)
+    }
+}
@@ -1,13 +1,31 @@
 use super::super::connection::{ConnectOptions, Connection};
 use super::super::decode::{PeerDecodeManager, PeerStatus};
+use super::clock::{Clock, ClockEstimate};
+use super::latency::{Latency, LatencyReport};
+use super::metronome::{self, Beat, Metronome, Transport};
+use super::moderation::Roles;
+use crate::constants::{AUDIO_CODEC, VIDEO_CODEC};
 use crate::crypto::aes::Aes128State;
 use crate::crypto::rsa::RsaWrapper;
 use anyhow::{anyhow, Result};
+use gloo::timers::callback::{Interval, Timeout};
+use common::protocol::{E2EE_NONE, E2EE_RSA_AES128_CBC, PROTOCOL_VERSION};
 use common::protos::aes_packet::AesPacket;
+use common::protos::chat_packet::ChatPacket;
+use common::protos::connection_packet::{Capabilities, ConnectionPacket};
+use common::protos::going_away_packet::GoingAwayPacket;
 use common::protos::media_packet::media_packet::MediaType;
+use common::protos::moderation_packet::{moderation_packet::Action, ModerationPacket, Role};
+use common::protos::negotiation_packet::NegotiationPacket;
 use common::protos::packet_wrapper::packet_wrapper::PacketType;
 use common::protos::packet_wrapper::PacketWrapper;
+use common::protos::ping_packet::PingPacket;
+use common::protos::rejection_packet::rejection_packet::Reason;
+use common::protos::rejection_packet::RejectionPacket;
+use common::protos::roster_packet::roster_packet::EventType;
+use common::protos::roster_packet::RosterPacket;
 use common::protos::rsa_packet::RsaPacket;
+use common::protos::transport_packet::TransportPacket;
 use log::{debug, error, info};
 use protobuf::Message;
 use rsa::pkcs8::{DecodePublicKey, EncodePublicKey};
@@ -16,6 +34,9 @@ use std::cell::RefCell;
 use std::rc::{Rc, Weak};
 use yew::prelude::Callback;
 
+/// Interval between two pings to the server and to the peers, in milliseconds
+const PING_PERIOD_MS: u32 = 1000;
+
 /// Options struct for constructing a client via [VideoCallClient::new(options)][VideoCallClient::new]
 #[derive(Clone, Debug, PartialEq)]
 pub struct VideoCallClientOptions {
@@ -25,6 +46,15 @@ pub struct VideoCallClientOptions {
     /// Callback will be called as `callback(peer_userid)` when a new peer is added
     pub on_peer_added: Callback<String>,
 
+    /// Callback will be called as `callback(peer_userid)` when the server reports that a peer is
+    /// in the room, either because it just joined or because it was already there when this
+    /// client connected
+    pub on_peer_joined: Callback<String>,
+
+    /// Callback will be called as `callback(peer_userid)` when the server reports that a peer
+    /// left the room.  The decoders of the peer are dropped before the callback is invoked.
+    pub on_peer_left: Callback<String>,
+
     /// Callback will be called as `callback(peer_userid, media_type)` immediately after the first frame of a given peer & media type is decoded
     pub on_peer_first_frame: Callback<(String, MediaType)>,
 
@@ -39,11 +69,49 @@ pub struct VideoCallClientOptions {
     /// The url to which WebTransport connections should be made
     pub webtransport_url: String,
 
-    /// Callback will be called as `callback(())` after a new connection is made
+    /// Callback will be called as `callback(())` after a new connection is made and the server
+    /// agreed on the protocol version and the capabilities of this client.  A server that cannot
+    /// serve this client calls [`on_rejected`](Self::on_rejected) instead.
     pub on_connected: Callback<()>,
 
     /// Callback will be called as `callback(())` if a connection gets dropped
     pub on_connection_lost: Callback<()>,
+
+    /// Callback will be called as `callback(reason, message)` if the server refuses to let this
+    /// client into the room, with [`Reason::REPLACED`] once the same user opens another session
+    /// in the room, or with [`Reason::KICKED`] once a moderator kicks this client.  The server
+    /// closes the connection right after, so [`on_connection_lost`](Self::on_connection_lost)
+    /// follows.
+    pub on_rejected: Callback<(Reason, String)>,
+
+    /// Callback will be called as `callback(message)` when the server is shutting down.  The
+    /// server keeps relaying for a while before closing the connection, so the application can
+    /// [connect()][VideoCallClient::connect] again to reach another server in the meantime.
+    pub on_going_away: Callback<String>,
+
+    /// Callback will be called as `callback(message)` for each chat message of the room,
+    /// including the ones sent by this client.  The latest messages are replayed right after
+    /// connecting, oldest first.
+    pub on_chat_message: Callback<ChatPacket>,
+
+    /// Callback will be called as `callback(report)` every second with the latency towards the
+    /// server and towards each peer, see [`latency()`](VideoCallClient::latency)
+    pub on_latency: Callback<LatencyReport>,
+
+    /// Callback will be called as `callback(transport)` when this client connects and whenever
+    /// the owner of the room changes the metronome
+    pub on_transport: Callback<Transport>,
+
+    /// Callback will be called as `callback(beat)` on every beat of the metronome while it runs,
+    /// count-in included, timed on the clock of the server so that every peer counts together
+    pub on_beat: Callback<Beat>,
+
+    /// Callback will be called as `callback((sender, action))` for each moderation action taken
+    /// in the room, including the ones of this client, once the server applied it.  `role` is the
+    /// role of the target afterwards, see [`role()`](VideoCallClient::role) and
+    /// [`role_of()`](VideoCallClient::role_of).  The application must stop the microphone when
+    /// this client is muted, the server does not.
+    pub on_moderation: Callback<(String, ModerationPacket)>,
 }
 
 #[derive(Debug)]
@@ -51,12 +119,31 @@ struct InnerOptions {
     enable_e2ee: bool,
     userid: String,
     on_peer_added: Callback<String>,
+    on_peer_joined: Callback<String>,
+    on_peer_left: Callback<String>,
+    on_connected: Callback<()>,
+    on_rejected: Callback<(Reason, String)>,
+    on_going_away: Callback<String>,
+    on_chat_message: Callback<ChatPacket>,
+    on_latency: Callback<LatencyReport>,
+    on_transport: Callback<Transport>,
+    on_beat: Callback<Beat>,
+    on_moderation: Callback<(String, ModerationPacket)>,
 }
 
 #[derive(Debug)]
 struct Inner {
     options: InnerOptions,
     connection: Option<Connection>,
+    /// Set once the server accepted the connection, what is sent before is ignored
+    admitted: bool,
+    latency: Latency,
+    /// Kept across reconnections, the server and its clock stay the same
+    clock: Clock,
+    pinger: Option<Interval>,
+    metronome: Metronome,
+    beat_scheduler: Option<Interval>,
+    roles: Roles,
     aes: Rc<Aes128State>,
     rsa: Rc<RsaWrapper>,
     peer_decode_manager: PeerDecodeManager,
@@ -93,8 +180,25 @@ impl VideoCallClient {
                 enable_e2ee: options.enable_e2ee,
                 userid: options.userid.clone(),
                 on_peer_added: options.on_peer_added.clone(),
+                on_peer_joined: options.on_peer_joined.clone(),
+                on_peer_left: options.on_peer_left.clone(),
+                on_connected: options.on_connected.clone(),
+                on_rejected: options.on_rejected.clone(),
+                on_going_away: options.on_going_away.clone(),
+                on_chat_message: options.on_chat_message.clone(),
+                on_latency: options.on_latency.clone(),
+                on_transport: options.on_transport.clone(),
+                on_beat: options.on_beat.clone(),
+                on_moderation: options.on_moderation.clone(),
             },
             connection: None,
+            admitted: false,
+            latency: Latency::default(),
+            clock: Clock::default(),
+            pinger: None,
+            metronome: Metronome::default(),
+            beat_scheduler: None,
+            roles: Roles::default(),
             aes: aes.clone(),
             rsa: Rc::new(RsaWrapper::new(options.enable_e2ee)),
             peer_decode_manager: Self::create_peer_decoder_manager(&options),
@@ -143,17 +247,15 @@ impl VideoCallClient {
             },
             on_connected: {
                 let inner = Rc::downgrade(&self.inner);
-                let callback = self.options.on_connected.clone();
                 Callback::from(move |_| {
                     if let Some(inner) = Weak::upgrade(&inner) {
                         match inner.try_borrow() {
-                            Ok(inner) => inner.send_public_key(),
+                            Ok(inner) => inner.send_connection(),
                             Err(_) => {
-                                error!("Unable to borrow inner -- not sending public key");
+                                error!("Unable to borrow inner -- not sending connection packet");
                             }
                         }
                     }
-                    callback.emit(());
                 })
             },
             on_connection_lost: self.options.on_connection_lost.clone(),
@@ -168,9 +270,42 @@ impl VideoCallClient {
             options,
             self.aes.clone(),
         )?);
+        borrowed.admitted = false;
+        borrowed.latency = Latency::default();
+        borrowed.pinger = Some({
+            let inner = Rc::downgrade(&self.inner);
+            Interval::new(PING_PERIOD_MS, move || {
+                if let Some(inner) = Weak::upgrade(&inner) {
+                    match inner.try_borrow_mut() {
+                        Ok(mut inner) => inner.ping(),
+                        Err(_) => error!("Unable to borrow inner -- skipping ping"),
+                    }
+                }
+            })
+        });
+        borrowed.beat_scheduler = Some({
+            let inner = Rc::downgrade(&self.inner);
+            Interval::new(metronome::SCHEDULE_PERIOD_MS, move || {
+                if let Some(inner) = Weak::upgrade(&inner) {
+                    match inner.try_borrow_mut() {
+                        Ok(mut inner) => inner.schedule_beats(),
+                        Err(_) => error!("Unable to borrow inner -- skipping beats"),
+                    }
+                }
+            })
+        });
         Ok(())
     }
 
+    /// Replaces the url used by the next call to [connect()][Self::connect].
+    ///
+    /// Useful when the url carries a short-lived credential, such as a join ticket, that must be
+    /// refreshed before each connection attempt.
+    ///
+    pub fn set_webtransport_url(&mut self, webtransport_url: String) {
+        self.options.webtransport_url = webtransport_url;
+    }
+
     fn create_peer_decoder_manager(opts: &VideoCallClientOptions) -> PeerDecodeManager {
         let mut peer_decode_manager = PeerDecodeManager::new();
         peer_decode_manager.on_first_frame = opts.on_peer_first_frame.clone();
@@ -187,6 +322,138 @@ impl VideoCallClient {
         }
     }
 
+    /// Sends a chat message to the room.
+    ///
+    /// The server assigns the id, the author and the time of the message, which comes back to
+    /// this client through [`options.on_chat_message`](VideoCallClientOptions::on_chat_message)
+    /// like any other.
+    pub fn send_chat(&self, text: String) {
+        let packet = ChatPacket {
+            text,
+            ..Default::default()
+        };
+        match packet.write_to_bytes() {
+            Ok(data) => self.send_packet(PacketWrapper {
+                packet_type: PacketType::CHAT.into(),
+                email: self.options.userid.clone(),
+                data,
+                ..Default::default()
+            }),
+            Err(e) => {
+                error!("Failed to serialize chat packet: {}", e.to_string());
+            }
+        }
+    }
+
+    /// Returns the round trip time, the jitter and the loss towards the server and towards each
+    /// peer, measured with a ping every second.
+    pub fn latency(&self) -> LatencyReport {
+        match self.inner.try_borrow() {
+            Ok(inner) => inner.latency.report(js_sys::Date::now()),
+            Err(_) => LatencyReport::default(),
+        }
+    }
+
+    /// Returns the time of the clock of the server, in milliseconds since the epoch, or `None`
+    /// until the server answered a ping.
+    ///
+    /// The offset and the drift of the local clock are estimated from the pings of the last
+    /// minute, see [`clock()`](Self::clock), so every client reads the same timeline.
+    pub fn server_time(&self) -> Option<f64> {
+        match self.inner.try_borrow() {
+            Ok(inner) => inner.clock.server_time(js_sys::Date::now()),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the offset and the drift of the local clock against the clock of the server
+    pub fn clock(&self) -> Option<ClockEstimate> {
+        match self.inner.try_borrow() {
+            Ok(inner) => inner.clock.estimate(js_sys::Date::now()),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the metronome of the room, `None` until the server sent it.
+    ///
+    /// The owner of the room changes it through the REST API, see
+    /// [`options.on_transport`](VideoCallClientOptions::on_transport).
+    pub fn transport(&self) -> Option<Transport> {
+        match self.inner.try_borrow() {
+            Ok(inner) => inner.metronome.transport(),
+            Err(_) => None,
+        }
+    }
+
+    /// Plays, or stops playing, a click on the beats of the metronome.
+    ///
+    /// Browsers let a page play audio only after a gesture of the user, so the click must be
+    /// enabled from an event handler.
+    pub fn set_click(&self, enabled: bool) {
+        match self.inner.try_borrow_mut() {
+            Ok(mut inner) => inner.metronome.set_click(enabled),
+            Err(_) => error!("Unable to borrow inner -- not changing the click"),
+        }
+    }
+
+    /// Returns `true` if a click is played on the beats of the metronome
+    pub fn click_enabled(&self) -> bool {
+        match self.inner.try_borrow() {
+            Ok(inner) => inner.metronome.click_enabled(),
+            Err(_) => false,
+        }
+    }
+
+    /// Asks the server to take a moderation action on a participant.
+    ///
+    /// Moderators and the owner act on the participants with a lower role, see
+    /// [`can_moderate()`](Self::can_moderate), and only the owner transfers the ownership.  The
+    /// server ignores the other actions, the applied ones come back through
+    /// [`options.on_moderation`](VideoCallClientOptions::on_moderation).
+    pub fn moderate(&self, action: Action, username: String) {
+        let packet = ModerationPacket {
+            action: action.into(),
+            username,
+            ..Default::default()
+        };
+        match packet.write_to_bytes() {
+            Ok(data) => self.send_packet(PacketWrapper {
+                packet_type: PacketType::MODERATION.into(),
+                email: self.options.userid.clone(),
+                data,
+                ..Default::default()
+            }),
+            Err(e) => {
+                error!("Failed to serialize moderation packet: {}", e.to_string());
+            }
+        }
+    }
+
+    /// Returns the role of this client in the room.  A listener publishes no media: the client
+    /// drops it before sending.
+    pub fn role(&self) -> Role {
+        match self.inner.try_borrow() {
+            Ok(inner) => inner.roles.own(),
+            Err(_) => Role::default(),
+        }
+    }
+
+    /// Returns the role of a peer in the room
+    pub fn role_of(&self, peer_userid: &str) -> Role {
+        match self.inner.try_borrow() {
+            Ok(inner) => inner.roles.of(peer_userid),
+            Err(_) => Role::default(),
+        }
+    }
+
+    /// Returns `true` if this client may mute, promote, demote or kick a peer
+    pub fn can_moderate(&self, peer_userid: &str) -> bool {
+        match self.inner.try_borrow() {
+            Ok(inner) => inner.roles.can_moderate(peer_userid),
+            Err(_) => false,
+        }
+    }
+
     /// Returns `true` if the client is currently connected to a server.
     pub fn is_connected(&self) -> bool {
         if let Ok(inner) = self.inner.try_borrow() {
@@ -218,6 +485,10 @@ impl VideoCallClient {
 
 impl Inner {
     fn send_packet(&self, media: PacketWrapper) {
+        // the server would drop it anyway
+        if media.packet_type == PacketType::MEDIA.into() && self.roles.own() == Role::LISTENER {
+            return;
+        }
         if let Some(connection) = &self.connection {
             connection.send_packet(media);
         }
@@ -229,6 +500,51 @@ impl Inner {
             response.packet_type.enum_value(),
             response.email
         );
+        // roster, rejection, going away and negotiation packets come from the server and describe
+        // other peers, not the sender; chat messages are replayed from the history, their authors
+        // may not be in the room; pings only measure the latency; the metronome belongs to the
+        // room; moderation actions are relayed by the server
+        match response.packet_type.enum_value() {
+            Ok(PacketType::NEGOTIATION) => {
+                self.on_negotiation(&response.data);
+                return;
+            }
+            Ok(PacketType::ROSTER) => {
+                self.on_roster(&response.data);
+                return;
+            }
+            Ok(PacketType::REJECTION) => {
+                self.on_rejection(&response.data);
+                return;
+            }
+            Ok(PacketType::GOING_AWAY) => {
+                self.on_going_away(&response.data);
+                return;
+            }
+            Ok(PacketType::CHAT) => {
+                self.on_chat(&response.data);
+                return;
+            }
+            Ok(PacketType::PING) => {
+                self.on_ping(&response);
+                return;
+            }
+            Ok(PacketType::PONG) => {
+                self.on_pong(&response);
+                return;
+            }
+            Ok(PacketType::TRANSPORT) => {
+                self.on_transport(&response.data);
+                return;
+            }
+            Ok(PacketType::MODERATION) => {
+                self.on_moderation(&response);
+                return;
+            }
+            _ => {}
+        }
+        // the relay stamps `email` with the authenticated sender, unlike the `email` of the media
+        // packets which is set by the sender itself
         let peer_status = self.peer_decode_manager.ensure_peer(&response.email);
         match response.packet_type.enum_value() {
             Ok(PacketType::AES_KEY) => {
@@ -295,8 +611,21 @@ impl Inner {
             Ok(PacketType::CONNECTION) => {
                 error!("Not implemented: CONNECTION packet type");
             }
+            Ok(PacketType::ROSTER)
+            | Ok(PacketType::REJECTION)
+            | Ok(PacketType::GOING_AWAY)
+            | Ok(PacketType::NEGOTIATION)
+            | Ok(PacketType::CHAT)
+            | Ok(PacketType::PING)
+            | Ok(PacketType::PONG)
+            | Ok(PacketType::TRANSPORT)
+            | Ok(PacketType::MODERATION) => {}
             Err(_) => {}
         }
+        self.on_peer_status(peer_status);
+    }
+
+    fn on_peer_status(&self, peer_status: PeerStatus) {
         if let PeerStatus::Added(peer_userid) = peer_status {
             debug!("added peer {}", peer_userid);
             self.send_public_key();
@@ -304,6 +633,298 @@ impl Inner {
         }
     }
 
+    fn on_roster(&mut self, data: &[u8]) {
+        let roster = match RosterPacket::parse_from_bytes(data) {
+            Ok(roster) => roster,
+            Err(e) => {
+                error!("Failed to parse roster packet: {}", e.to_string());
+                return;
+            }
+        };
+        match roster.event_type.enum_value() {
+            Ok(EventType::JOINED) => {
+                self.roles
+                    .joined(&roster.username, roster.role.enum_value_or_default());
+                self.on_peer_joined(&roster.username);
+            }
+            Ok(EventType::LEFT) => self.on_peer_left(&roster.username),
+            Ok(EventType::SNAPSHOT) => {
+                let roles = roster
+                    .roles
+                    .iter()
+                    .map(|(member, role)| (member.clone(), role.enum_value_or_default()))
+                    .collect();
+                self.roles.snapshot(&roles);
+                // peers that left while we were not connected
+                let departed = self
+                    .peer_decode_manager
+                    .sorted_keys()
+                    .iter()
+                    .filter(|peer| !roster.members.contains(peer))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for peer in departed {
+                    self.on_peer_left(&peer);
+                }
+                for member in &roster.members {
+                    self.on_peer_joined(member);
+                }
+            }
+            Ok(EventType::PRESENT) | Err(_) => {}
+        }
+    }
+
+    fn on_rejection(&self, data: &[u8]) {
+        match RejectionPacket::parse_from_bytes(data) {
+            Ok(rejection) => {
+                let reason = rejection.reason.enum_value_or_default();
+                info!("Rejected by the server: {:?} {}", reason, rejection.message);
+                self.options.on_rejected.emit((reason, rejection.message));
+            }
+            Err(e) => {
+                error!("Failed to parse rejection packet: {}", e.to_string());
+            }
+        }
+    }
+
+    fn on_negotiation(&mut self, data: &[u8]) {
+        let negotiated = match NegotiationPacket::parse_from_bytes(data) {
+            Ok(negotiated) => negotiated,
+            Err(e) => {
+                error!("Failed to parse negotiation packet: {}", e.to_string());
+                return;
+            }
+        };
+        info!(
+            "Connected to {} with protocol version {}: {} {} e2ee {} datagrams {}",
+            negotiated.server_version,
+            negotiated.protocol_version,
+            negotiated.audio_codec,
+            negotiated.video_codec,
+            negotiated.e2ee_scheme,
+            negotiated.datagrams
+        );
+        // only the codecs this client offered can be chosen
+        if negotiated.audio_codec != AUDIO_CODEC || negotiated.video_codec != VIDEO_CODEC {
+            error!("The server chose codecs this client did not offer");
+        }
+        self.admitted = true;
+        self.roles.set_own(negotiated.role.enum_value_or_default());
+        self.send_public_key();
+        self.options.on_connected.emit(());
+    }
+
+    fn on_going_away(&self, data: &[u8]) {
+        match GoingAwayPacket::parse_from_bytes(data) {
+            Ok(going_away) => {
+                info!("Server going away: {}", going_away.message);
+                self.options.on_going_away.emit(going_away.message);
+            }
+            Err(e) => {
+                error!("Failed to parse going away packet: {}", e.to_string());
+            }
+        }
+    }
+
+    fn on_chat(&self, data: &[u8]) {
+        match ChatPacket::parse_from_bytes(data) {
+            Ok(message) => self.options.on_chat_message.emit(message),
+            Err(e) => {
+                error!("Failed to parse chat packet: {}", e.to_string());
+            }
+        }
+    }
+
+    /// Sends a ping to the server and one to the peers, then reports the latency measured so far
+    fn ping(&mut self) {
+        let connected = self
+            .connection
+            .as_ref()
+            .is_some_and(|connection| connection.is_connected());
+        if !connected || !self.admitted {
+            return;
+        }
+        let now = js_sys::Date::now();
+        let peers = self.peer_decode_manager.sorted_keys().to_vec();
+        let sequence = self.latency.ping(&peers, now);
+        self.send_ping(sequence, now, false);
+        if !peers.is_empty() {
+            self.send_ping(sequence, now, true);
+        }
+        self.options.on_latency.emit(self.latency.report(now));
+    }
+
+    fn send_ping(&self, sequence: u64, sent_at: f64, peers: bool) {
+        let packet = PingPacket {
+            sequence,
+            sent_at,
+            peers,
+            ..Default::default()
+        };
+        match packet.write_to_bytes() {
+            Ok(data) => self.send_packet(PacketWrapper {
+                packet_type: PacketType::PING.into(),
+                email: self.options.userid.clone(),
+                data,
+                ..Default::default()
+            }),
+            Err(e) => {
+                error!("Failed to serialize ping packet: {}", e.to_string());
+            }
+        }
+    }
+
+    /// Answers the ping of a peer, the pong reaches the whole room and is addressed to the peer
+    fn on_ping(&self, response: &PacketWrapper) {
+        let ping = match PingPacket::parse_from_bytes(&response.data) {
+            Ok(ping) => ping,
+            Err(e) => {
+                error!("Failed to parse ping packet: {}", e.to_string());
+                return;
+            }
+        };
+        if !ping.peers || response.email == self.options.userid {
+            return;
+        }
+        let pong = PingPacket {
+            sequence: ping.sequence,
+            sent_at: ping.sent_at,
+            to: response.email.clone(),
+            received_at: js_sys::Date::now(),
+            ..Default::default()
+        };
+        match pong.write_to_bytes() {
+            Ok(data) => self.send_packet(PacketWrapper {
+                packet_type: PacketType::PONG.into(),
+                email: self.options.userid.clone(),
+                data,
+                ..Default::default()
+            }),
+            Err(e) => {
+                error!("Failed to serialize pong packet: {}", e.to_string());
+            }
+        }
+    }
+
+    fn on_pong(&mut self, response: &PacketWrapper) {
+        let pong = match PingPacket::parse_from_bytes(&response.data) {
+            Ok(pong) => pong,
+            Err(e) => {
+                error!("Failed to parse pong packet: {}", e.to_string());
+                return;
+            }
+        };
+        // the server answers without a sender, the peers answer to the whole room
+        if response.email.is_empty() {
+            let now = js_sys::Date::now();
+            self.latency.pong(None, &pong, now);
+            self.clock.pong(&pong, now);
+        } else if pong.to == self.options.userid {
+            self.latency
+                .pong(Some(&response.email), &pong, js_sys::Date::now());
+        }
+    }
+
+    fn on_transport(&mut self, data: &[u8]) {
+        match TransportPacket::parse_from_bytes(data) {
+            Ok(packet) => {
+                let transport = Transport::from(&packet);
+                self.metronome.set_transport(transport);
+                self.options.on_transport.emit(transport);
+            }
+            Err(e) => {
+                error!("Failed to parse transport packet: {}", e.to_string());
+            }
+        }
+    }
+
+    fn on_moderation(&mut self, response: &PacketWrapper) {
+        match ModerationPacket::parse_from_bytes(&response.data) {
+            Ok(packet) => {
+                info!(
+                    "{} took {:?} on {}",
+                    response.email,
+                    packet.action.enum_value_or_default(),
+                    packet.username
+                );
+                self.roles
+                    .follow(&self.options.userid, &response.email, &packet);
+                self.options
+                    .on_moderation
+                    .emit((response.email.clone(), packet));
+            }
+            Err(e) => {
+                error!("Failed to parse moderation packet: {}", e.to_string());
+            }
+        }
+    }
+
+    /// Schedules the beats about to fall, once the clock of the server is known
+    fn schedule_beats(&mut self) {
+        let Some(server_time) = self.clock.server_time(js_sys::Date::now()) else {
+            return;
+        };
+        for (beat, delay) in self.metronome.schedule(server_time) {
+            let on_beat = self.options.on_beat.clone();
+            Timeout::new(delay.max(0.0) as u32, move || on_beat.emit(beat)).forget();
+        }
+    }
+
+    fn on_peer_joined(&mut self, peer_userid: &String) {
+        if *peer_userid == self.options.userid {
+            return;
+        }
+        debug!("peer {} joined", peer_userid);
+        let peer_status = self.peer_decode_manager.ensure_peer(peer_userid);
+        self.on_peer_status(peer_status);
+        self.options.on_peer_joined.emit(peer_userid.clone());
+    }
+
+    fn on_peer_left(&mut self, peer_userid: &String) {
+        debug!("peer {} left", peer_userid);
+        self.roles.left(peer_userid);
+        self.peer_decode_manager.delete_peer(peer_userid);
+        self.latency.remove_peer(peer_userid);
+        self.options.on_peer_left.emit(peer_userid.clone());
+    }
+
+    /// Introduces the client to the server, which answers with a NEGOTIATION packet or refuses
+    /// the client with a REJECTION packet
+    fn send_connection(&self) {
+        let e2ee_scheme = if self.options.enable_e2ee {
+            E2EE_RSA_AES128_CBC
+        } else {
+            E2EE_NONE
+        };
+        let packet = ConnectionPacket {
+            protocol_version: PROTOCOL_VERSION,
+            client_version: format!("videocall-client/{}", env!("CARGO_PKG_VERSION")),
+            capabilities: Some(Capabilities {
+                audio_codecs: vec![AUDIO_CODEC.to_owned()],
+                video_codecs: vec![VIDEO_CODEC.to_owned()],
+                e2ee_schemes: vec![e2ee_scheme.to_owned()],
+                datagrams: true,
+                ..Default::default()
+            })
+            .into(),
+            ..Default::default()
+        };
+        match packet.write_to_bytes() {
+            Ok(data) => {
+                debug!(">> {} sending connection packet", self.options.userid);
+                self.send_packet(PacketWrapper {
+                    packet_type: PacketType::CONNECTION.into(),
+                    email: self.options.userid.clone(),
+                    data,
+                    ..Default::default()
+                });
+            }
+            Err(e) => {
+                error!("Failed to serialize connection packet: {}", e.to_string());
+            }
+        }
+    }
+
     fn send_public_key(&self) {
         if !self.options.enable_e2ee {
             return;
@@ -11,7 +11,7 @@ use std::cell::Cell;
 use std::rc::Rc;
 use common::protos::media_packet::media_packet::MediaType;
 use common::protos::media_packet::MediaPacket;
-use common::protos::packet_wrapper::packet_wrapper::PacketType;
+use common::protos::packet_wrapper::packet_wrapper::{MediaKind, PacketType};
 use common::protos::packet_wrapper::PacketWrapper;
 use yew::prelude::Callback;
 
@@ -83,6 +83,7 @@ impl Connection {
                 data,
                 email: userid.clone(),
                 packet_type: PacketType::MEDIA.into(),
+                media_kind: MediaKind::HEARTBEAT.into(),
                 ..Default::default()
             };
             if let Status::Connected = status.get() {
@@ -187,6 +187,7 @@ mod test {
             data,
             frame_type: EncodedVideoChunkTypeWrapper(chunk_type).to_string(),
             timestamp: 0.0,
+            capture_time: 0.0,
             duration: 0.0,
             audio_metadata: Default::default(), // Put an appropriate default or value here
             video_metadata: Some(video_metadata).into(), // Assuming sequence is a field in VideoMetadata
@@ -122,6 +122,7 @@ impl CameraEncoder {
                     sequence_number,
                     &mut buffer,
                     &userid,
+                    client.server_time().unwrap_or_default(),
                     aes.clone(),
                 );
                 client.send_packet(packet);
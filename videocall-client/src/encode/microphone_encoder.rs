@@ -103,8 +103,14 @@ impl MicrophoneEncoder {
             let mut sequence = 0;
             Box::new(move |chunk: JsValue| {
                 let chunk = web_sys::EncodedAudioChunk::from(chunk);
-                let packet: PacketWrapper =
-                    transform_audio_chunk(&chunk, &mut buffer, &userid, sequence, aes.clone());
+                let packet: PacketWrapper = transform_audio_chunk(
+                    &chunk,
+                    &mut buffer,
+                    &userid,
+                    sequence,
+                    client.server_time().unwrap_or_default(),
+                    aes.clone(),
+                );
                 client.send_packet(packet);
                 sequence += 1;
             })
@@ -4,7 +4,10 @@ use protobuf::Message;
 use std::rc::Rc;
 use common::protos::{
     media_packet::{media_packet::MediaType, MediaPacket, VideoMetadata},
-    packet_wrapper::{packet_wrapper::PacketType, PacketWrapper},
+    packet_wrapper::{
+        packet_wrapper::{MediaKind, PacketType},
+        PacketWrapper,
+    },
 };
 use web_sys::{EncodedAudioChunk, EncodedVideoChunk};
 
@@ -13,6 +16,7 @@ pub fn transform_video_chunk(
     sequence: u64,
     buffer: &mut [u8],
     email: &str,
+    capture_time: f64,
     aes: Rc<Aes128State>,
 ) -> PacketWrapper {
     let byte_length = chunk.byte_length() as usize;
@@ -23,6 +27,7 @@ pub fn transform_video_chunk(
         email: email.to_owned(),
         media_type: MediaType::VIDEO.into(),
         timestamp: chunk.timestamp(),
+        capture_time,
         video_metadata: Some(VideoMetadata {
             sequence,
             ..Default::default()
@@ -39,6 +44,7 @@ pub fn transform_video_chunk(
         data,
         email: media_packet.email,
         packet_type: PacketType::MEDIA.into(),
+        media_kind: MediaKind::VIDEO.into(),
         ..Default::default()
     }
 }
@@ -49,6 +55,7 @@ pub fn transform_audio_chunk(
     buffer: &mut [u8],
     email: &str,
     sequence: u64,
+    capture_time: f64,
     aes: Rc<Aes128State>,
 ) -> PacketWrapper {
     chunk.copy_to_with_u8_array(buffer);
@@ -58,6 +65,7 @@ pub fn transform_audio_chunk(
         data: buffer[0..chunk.byte_length() as usize].to_vec(),
         frame_type: EncodedAudioChunkTypeWrapper(chunk.type_()).to_string(),
         timestamp: chunk.timestamp(),
+        capture_time,
         video_metadata: Some(VideoMetadata {
             sequence,
             ..Default::default()
@@ -74,6 +82,7 @@ pub fn transform_audio_chunk(
         data,
         email: media_packet.email,
         packet_type: PacketType::MEDIA.into(),
+        media_kind: MediaKind::AUDIO.into(),
         ..Default::default()
     }
 }
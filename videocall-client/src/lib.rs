@@ -77,6 +77,9 @@ mod encode;
 mod media_devices;
 mod wrappers;
 
-pub use client::{VideoCallClient, VideoCallClientOptions};
+pub use client::{
+    Beat, ClockEstimate, LatencyReport, LatencyStats, Transport, VideoCallClient,
+    VideoCallClientOptions,
+};
 pub use encode::{CameraEncoder, MicrophoneEncoder};
 pub use media_devices::{MediaDeviceAccess, MediaDeviceList, SelectableDevices};